@@ -124,6 +124,31 @@ void TestHcDna::canGetCapabilityNames() {
 
 
 
+void TestHcDna::canGetCapabilityDescription() {
+    Dna *dna = holochain_dna_create_from_json("{\"name\":\"test\","
+                                              "\"zomes\":{"
+                                              "\"zome1\":{\"description\":\"lorem\",\"config\":{},\"capabilities\":{"
+                                              "\"test_cap\":{"
+                                              "    \"capability\": {"
+                                              "        \"membrane\": \"public\""
+                                              "    },"
+                                              "    \"functions\": [],"
+                                              "    \"description\": \"read posts\""
+                                              "}"
+                                              "}}"
+                                              "}}");
+    QVERIFY(dna != 0);
+
+    char *description = holochain_dna_get_capability_description(dna, "zome1", "test_cap");
+    QCOMPARE(QString(description), QString("read posts"));
+    holochain_dna_string_free(description);
+
+    QVERIFY(holochain_dna_get_capability_description(dna, "zome1", "no_cap") == 0);
+
+    holochain_dna_free(dna);
+}
+
+
 void TestHcDna::canGetFunctionNames() {
     Dna *dna = holochain_dna_create_from_json("{\"name\":\"test\","
                                               "\"zomes\":{"
@@ -11,6 +11,7 @@ private slots:
   void canSetName();
   void canGetZomeNames();
   void canGetCapabilityNames();
+  void canGetCapabilityDescription();
   void canGetFunctionNames();
   void canGetFunctionParameters();
 };
@@ -3,10 +3,11 @@ use holochain_core_types::{
         content::{Address, AddressableContent, Content},
         storage::ContentAddressableStorage,
     },
-    error::HolochainError,
+    error::{HolochainError, StorageError},
 };
 use std::{
-    fs::{create_dir_all, read_to_string, write},
+    collections::HashSet,
+    fs::{create_dir_all, read_dir, read_to_string, remove_file, write},
     path::{Path, MAIN_SEPARATOR},
     sync::{Arc, RwLock},
 };
@@ -49,12 +50,13 @@ impl ContentAddressableStorage for FilesystemStorage {
         let _guard = self.lock.write()?;
         // @TODO be more efficient here
         // @see https://github.com/holochain/holochain-rust/issues/248
-        create_dir_all(&self.dir_path)?;
+        create_dir_all(&self.dir_path).map_err(StorageError::from)?;
 
         write(
             self.address_to_path(&content.address()),
             content.content().to_string(),
-        )?;
+        )
+        .map_err(StorageError::from)?;
 
         Ok(())
     }
@@ -67,12 +69,42 @@ impl ContentAddressableStorage for FilesystemStorage {
     fn fetch(&self, address: &Address) -> Result<Option<Content>, HolochainError> {
         let _guard = self.lock.read()?;
         if self.contains(&address)? {
-            Ok(Some(read_to_string(self.address_to_path(address))?.into()))
+            Ok(Some(
+                read_to_string(self.address_to_path(address))
+                    .map_err(StorageError::from)?
+                    .into(),
+            ))
         } else {
             Ok(None)
         }
     }
 
+    fn addresses(&self) -> Result<HashSet<Address>, HolochainError> {
+        let _guard = self.lock.read()?;
+        if !Path::new(&self.dir_path).is_dir() {
+            return Ok(HashSet::new());
+        }
+        let mut addresses = HashSet::new();
+        for dir_entry in read_dir(&self.dir_path).map_err(StorageError::from)? {
+            let path = dir_entry.map_err(StorageError::from)?.path();
+            if path.extension().map(|ext| ext == "txt").unwrap_or(false) {
+                if let Some(stem) = path.file_stem() {
+                    addresses.insert(Address::from(stem.to_string_lossy().to_string()));
+                }
+            }
+        }
+        Ok(addresses)
+    }
+
+    fn remove(&mut self, address: &Address) -> Result<(), HolochainError> {
+        let _guard = self.lock.write()?;
+        let path = self.address_to_path(address);
+        if Path::new(&path).is_file() {
+            remove_file(path).map_err(StorageError::from)?;
+        }
+        Ok(())
+    }
+
     fn get_id(&self) -> Uuid {
         self.id
     }
@@ -87,11 +119,15 @@ pub mod tests {
     use crate::cas::file::FilesystemStorage;
     use holochain_core_types::{
         cas::{
-            content::{ExampleAddressableContent, OtherExampleAddressableContent},
-            storage::StorageTestSuite,
+            content::{
+                AddressableContent, ExampleAddressableContent, OtherExampleAddressableContent,
+            },
+            storage::{ContentAddressableStorage, StorageTestSuite},
         },
+        error::{HolochainError, StorageError},
         json::RawString,
     };
+    use std::fs::File;
 
     pub fn test_file_cas() -> (FilesystemStorage, TempDir) {
         let dir = tempdir().expect("Could not create a tempdir for CAS testing");
@@ -113,4 +149,20 @@ pub mod tests {
         );
     }
 
+    #[test]
+    /// show that IO failures surface as typed storage errors
+    fn file_storage_error_test() {
+        let dir = tempdir().unwrap();
+        // a file where the storage expects its directory makes every write fail
+        let blocking_file = dir.path().join("not_a_dir");
+        File::create(&blocking_file).unwrap();
+        let mut cas = FilesystemStorage::new(&blocking_file.to_string_lossy()).unwrap();
+
+        let content =
+            ExampleAddressableContent::try_from_content(&RawString::from("foo").into()).unwrap();
+        match cas.add(&content) {
+            Err(HolochainError::Storage(StorageError::Io(_))) => (),
+            other => panic!("expected a storage io error, got {:?}", other),
+        }
+    }
 }
@@ -6,7 +6,7 @@ use holochain_core_types::{
     error::HolochainError,
 };
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     sync::{Arc, RwLock},
 };
 use uuid::Uuid;
@@ -49,6 +49,17 @@ impl ContentAddressableStorage for MemoryStorage {
         Ok(map.get(address).cloned())
     }
 
+    fn addresses(&self) -> Result<HashSet<Address>, HolochainError> {
+        let map = self.storage.read()?;
+        Ok(map.keys().cloned().collect())
+    }
+
+    fn remove(&mut self, address: &Address) -> Result<(), HolochainError> {
+        let mut map = self.storage.write()?;
+        map.remove(address);
+        Ok(())
+    }
+
     fn get_id(&self) -> Uuid {
         self.id
     }
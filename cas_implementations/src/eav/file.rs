@@ -1,7 +1,7 @@
 use holochain_core_types::{
     cas::content::{AddressableContent, Content},
     eav::{Attribute, Entity, EntityAttributeValue, EntityAttributeValueStorage, Value},
-    error::{HcResult, HolochainError},
+    error::{HcResult, HolochainError, StorageError},
 };
 use std::{
     collections::HashSet,
@@ -83,10 +83,10 @@ impl EavFileStorage {
         };
         let path =
             vec![self.dir_path.clone(), subscript, address].join(&MAIN_SEPARATOR.to_string());
-        create_dir_all(path.clone())?;
+        create_dir_all(path.clone()).map_err(StorageError::from)?;
         let address_path = vec![path, eav.address().to_string()].join(&MAIN_SEPARATOR.to_string());
-        let mut f = File::create(address_path)?;
-        writeln!(f, "{}", eav.content())?;
+        let mut f = File::create(address_path).map_err(StorageError::from)?;
+        writeln!(f, "{}", eav.content()).map_err(StorageError::from)?;
         Ok(())
     }
 
@@ -125,7 +125,7 @@ impl EavFileStorage {
 impl EntityAttributeValueStorage for EavFileStorage {
     fn add_eav(&mut self, eav: &EntityAttributeValue) -> Result<(), HolochainError> {
         let _guard = self.lock.write()?;
-        create_dir_all(self.dir_path.clone())?;
+        create_dir_all(self.dir_path.clone()).map_err(StorageError::from)?;
         self.write_to_file(ENTITY_DIR.to_string(), eav)
             .and_then(|_| self.write_to_file(ATTRIBUTE_DIR.to_string(), eav))
             .and_then(|_| self.write_to_file(VALUE_DIR.to_string(), eav))
@@ -430,7 +430,7 @@ pub mod tests {
         writeln!(tmp_file, "{}", example_dna_string()).unwrap();
         match Container::load_dna(&file_path.into_os_string().into_string().unwrap()) {
             Ok(dna) => {
-                assert_eq!(dna.name, "my dna");
+                assert_eq!(dna.name(), "my dna");
             }
             Err(_) => assert!(false),
         }
@@ -85,7 +85,7 @@ impl Holochain {
     /// create a new Holochain instance
     pub fn new(dna: Dna, context: Arc<Context>) -> HolochainResult<Self> {
         let mut instance = Instance::new(context.clone());
-        let name = dna.name.clone();
+        let name = dna.name().to_string();
         instance.start_action_loop(context.clone());
         let context = instance.initialize_context(context.clone());
         let context2 = context.clone();
@@ -230,7 +230,7 @@ mod tests {
     #[test]
     fn can_instantiate() {
         let mut dna = Dna::new();
-        dna.name = "TestApp".to_string();
+        dna.set_name("TestApp".to_string());
         let (context, test_logger) = test_context("bob");
         let result = Holochain::new(dna.clone(), context.clone());
 
@@ -65,7 +65,7 @@ impl ContainerApiDispatcher {
             let dna = nucleus.dna();
             match dna {
                 Some(dna) => {
-                    for (zome_name, zome) in dna.zomes {
+                    for (zome_name, zome) in dna.zomes().clone() {
                         for (cap_name, cap) in zome.capabilities {
                             for func in cap.functions {
                                 let func_name = func.name;
@@ -0,0 +1,66 @@
+//! Measures what running zome code on a parsed module saves compared to parsing the WASM
+//! for every call, using the validation package callback of the nucleus actions test zome,
+//! which validation runs for every entry it checks.
+//!
+//! Build the test zome with `make wasm_build` first, then run `cargo bench` in core/.
+#![feature(test)]
+extern crate holochain_core;
+extern crate holochain_core_types;
+extern crate test;
+extern crate test_utils;
+extern crate wasmi;
+
+use holochain_core::{
+    context::Context,
+    nucleus::{
+        ribosome::{run_parsed_dna, wasm_cache::ParsedModule},
+        ZomeFnCall,
+    },
+};
+use holochain_core_types::dna::wasm::DnaWasm;
+use std::sync::Arc;
+use test::Bencher;
+use wasmi::Module;
+
+const TEST_ZOME_WASM: &str =
+    "src/nucleus/actions/wasm-test/target/wasm32-unknown-unknown/release/nucleus_actions_tests.wasm";
+
+fn test_zome_wasm() -> DnaWasm {
+    DnaWasm {
+        code: test_utils::create_wasm_from_file(TEST_ZOME_WASM),
+    }
+}
+
+fn run_validation_package_callback(context: Arc<Context>, module: &Module) {
+    let call = ZomeFnCall::new(
+        "test_zome",
+        "no capability, since this is an entry validation call",
+        "__hdk_get_validation_package_for_entry_type",
+        "package_entry",
+    );
+    run_parsed_dna(
+        "bench",
+        context,
+        module,
+        &call,
+        Some(String::from("package_entry").into_bytes()),
+    )
+    .expect("Could not run validation package callback");
+}
+
+#[bench]
+fn validation_callback_parsing_wasm_every_call(b: &mut Bencher) {
+    let context = test_utils::test_context("bench");
+    let wasm = test_zome_wasm();
+    b.iter(|| {
+        let module = wasm.parsed_module().unwrap();
+        run_validation_package_callback(context.clone(), &module)
+    });
+}
+
+#[bench]
+fn validation_callback_on_parsed_module(b: &mut Bencher) {
+    let context = test_utils::test_context("bench");
+    let module = test_zome_wasm().parsed_module().unwrap();
+    b.iter(|| run_validation_package_callback(context.clone(), &module));
+}
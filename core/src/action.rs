@@ -85,6 +85,12 @@ pub enum Action {
     /// Does not validate, assumes entry is valid.
     Commit((Entry, Option<Address>)),
 
+    /// Writes an entry to the source chain with its header linking to the given previous
+    /// header (None starts a new chain) instead of the current top of the chain.
+    /// Only reduced if chain repair is enabled in the context.
+    /// Does not validate, assumes entry is valid.
+    CommitAt((Entry, Option<Address>)),
+
     // -------------
     // DHT actions:
     // -------------
@@ -92,6 +98,11 @@ pub enum Action {
     /// Does not validate, assumes entry is valid.
     Hold(Entry),
 
+    /// Sets aside the address of an entry received from the network that failed validation
+    /// permanently, together with the reason. Quarantined entries are not held and not
+    /// validated again.
+    Quarantine((Address, String)),
+
     /// Adds a link to the local DHT shard's meta/EAV storage
     /// Does not validate, assumes link is valid.
     AddLink(Link),
@@ -108,8 +119,8 @@ pub enum Action {
     /// (only publish for AppEntryType, publish and publish_meta for links etc)
     Publish(Address),
 
-    /// GetEntry by address
-    GetEntry(Address),
+    /// GetEntry by address, asking the given peer if there is one
+    GetEntry((Address, Option<String>)),
     ///
     UpdateEntry((Address, Address)),
     ///
@@ -140,13 +151,28 @@ pub enum Action {
     ResolveDirectConnection(String),
 
     /// Makes the network module DM the source of the given entry
-    /// and prepare for receiveing an answer
-    GetValidationPackage(ChainHeader),
+    /// and prepare for receiveing an answer.
+    /// The message ID is used for the DM, so the response can be told apart from the
+    /// responses to other requests for the same entry.
+    GetValidationPackage((String, ChainHeader)),
 
     /// Updates the state to hold the response that we got for
-    /// our previous request for a validation package.
+    /// our previous request (given by its message ID) for a validation package.
     /// Triggered from the network handler when we got the response.
-    HandleGetValidationPackage((Address, Option<ValidationPackage>)),
+    HandleGetValidationPackage((String, Option<ValidationPackage>)),
+
+    /// Makes the network module forget the validation package request with the given
+    /// message ID, together with its result.
+    /// Triggered when the future waiting for the result gets dropped.
+    ClearValidationPackageResult(String),
+
+    /// Makes the network module send the messages it deferred while the network was paused.
+    /// Triggered from Context::resume_network().
+    ResumeNetwork,
+
+    /// Makes the network module send queued gossip as far as the bandwidth budget allows.
+    /// Triggered periodically while gossip is queued, see bandwidth::spawn_gossip_flusher().
+    FlushGossip,
 
     // ----------------
     // Nucleus actions:
@@ -159,6 +185,10 @@ pub enum Action {
     /// the result is Some arbitrary string
     ReturnInitializationResult(Option<String>),
 
+    /// Replaces the DNA of a running instance.
+    /// Triggered from Context::reload_dna(), which checks compatibility first.
+    ReloadDna(Dna),
+
     /// execute a function in a zome WASM
     ExecuteZomeFunction(ZomeFnCall),
 
@@ -238,7 +268,7 @@ pub mod tests {
 
     /// dummy action
     pub fn test_action() -> Action {
-        Action::GetEntry(expected_entry_address())
+        Action::GetEntry((expected_entry_address(), None))
     }
 
     /// dummy action wrapper with test_action()
@@ -253,7 +283,7 @@ pub mod tests {
 
     /// dummy action for a get of test_hash()
     pub fn test_action_wrapper_get() -> ActionWrapper {
-        ActionWrapper::new(Action::GetEntry(expected_entry_address()))
+        ActionWrapper::new(Action::GetEntry((expected_entry_address(), None)))
     }
 
     pub fn test_action_wrapper_rzfr() -> ActionWrapper {
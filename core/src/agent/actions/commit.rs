@@ -1,15 +1,28 @@
 extern crate futures;
 use crate::{
     action::{Action, ActionWrapper},
-    agent::state::ActionResponse,
+    agent::{
+        entry_crypto::encrypt_entry,
+        state::{chain_repair_disabled_error, ActionResponse},
+        unique_fields::{index_unique_fields, reserve_unique_fields},
+    },
     context::Context,
     instance::dispatch_action,
+    nucleus::actions::{
+        build_validation_package::build_validation_package, validate::validate_entry,
+    },
 };
 use futures::{
     future::Future,
     task::{LocalWaker, Poll},
 };
-use holochain_core_types::{cas::content::Address, entry::Entry, error::HolochainError};
+use holochain_core_types::{
+    cas::content::{Address, AddressableContent},
+    chain_header::ChainHeader,
+    entry::Entry,
+    error::HolochainError,
+    validation::{EntryAction, EntryLifecycle, ValidationData},
+};
 use std::{
     pin::{Pin, Unpin},
     sync::Arc,
@@ -21,12 +34,71 @@ use std::{
 /// be called from zome api functions and other contexts that don't care about implementation details.
 ///
 /// Returns a future that resolves to an ActionResponse.
+/// Fails with HolochainError::ValidationFailed if a live entry already holds the value of
+/// one of the unique fields of the entry's type (see agent::unique_fields).
+/// Entries of encrypted types get committed as ciphertext (see agent::entry_crypto),
+/// so the returned address is the one of the ciphertext.
 pub async fn commit_entry(
     entry: Entry,
     maybe_crud_link: Option<Address>,
     context: &Arc<Context>,
 ) -> Result<Address, HolochainError> {
-    let action_wrapper = ActionWrapper::new(Action::Commit((entry, maybe_crud_link)));
+    let (address, _) = await!(commit_entry_with_header(entry, maybe_crud_link, context))?;
+    Ok(address)
+}
+
+/// Commits the entry like commit_entry and also returns the chain header that was created
+/// for it, so callers don't have to look it up in the chain themselves.
+pub async fn commit_entry_with_header(
+    entry: Entry,
+    maybe_crud_link: Option<Address>,
+    context: &Arc<Context>,
+) -> Result<(Address, ChainHeader), HolochainError> {
+    // Unique values stay reserved until they are indexed,
+    // so that concurrent commits can't both take the same value.
+    let _reservation = reserve_unique_fields(&entry, maybe_crud_link.as_ref(), context)?;
+    let stored_entry = encrypt_entry(entry.clone(), context);
+    let action_wrapper = ActionWrapper::new(Action::Commit((stored_entry, maybe_crud_link)));
+    dispatch_action(&context.action_channel, action_wrapper.clone());
+    let address = await!(CommitFuture {
+        context: context.clone(),
+        action: action_wrapper.clone(),
+    })?;
+    index_unique_fields(&entry, &address, context)?;
+    let chain_header = context
+        .state()
+        .ok_or_else(|| HolochainError::ErrorGeneric("State not initialized".to_string()))?
+        .agent()
+        .committed_header(&action_wrapper)
+        .ok_or_else(|| {
+            HolochainError::ErrorGeneric(format!("No chain header found for entry {}", address))
+        })?;
+    Ok((address, chain_header))
+}
+
+/// Commit Action Creator for chain repair and migration tools.
+/// Validates the entry and commits it with its header linking to the given previous header
+/// (None starts a new chain) instead of the current top of the chain.
+/// Fails unless chain repair was enabled with Context::set_chain_repair_enabled,
+/// so it can not be used from zome calls.
+pub async fn commit_entry_at(
+    entry: Entry,
+    previous_header: Option<Address>,
+    context: &Arc<Context>,
+) -> Result<Address, HolochainError> {
+    if !context.chain_repair_enabled() {
+        return Err(chain_repair_disabled_error());
+    }
+    let validation_package = await!(build_validation_package(&entry, &context))?;
+    let validation_data = ValidationData {
+        package: validation_package,
+        sources: vec![context.agent_id.address()],
+        lifecycle: EntryLifecycle::Chain,
+        action: EntryAction::Create,
+    };
+    await!(validate_entry(entry.clone(), validation_data, &context))?;
+
+    let action_wrapper = ActionWrapper::new(Action::CommitAt((entry, previous_header)));
     dispatch_action(&context.action_channel, action_wrapper.clone());
     await!(CommitFuture {
         context: context.clone(),
@@ -69,3 +141,196 @@ impl Future for CommitFuture {
         }
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::{commit_entry, commit_entry_at, commit_entry_with_header};
+    use crate::{
+        agent::{state::chain_repair_disabled_error, unique_fields::reserve_unique_fields},
+        dht::actions::remove_entry::remove_entry,
+        instance::tests::test_context,
+        nucleus::actions::tests::{instance_by_name, test_dna},
+        workflows::author_entry::author_entry,
+    };
+    use futures::executor::block_on;
+    use holochain_core_types::{
+        cas::content::AddressableContent,
+        dna::Dna,
+        entry::{
+            deletion_entry::DeletionEntry,
+            entry_type::{test_app_entry_type, EntryType},
+            test_entry, test_entry_b, Entry,
+        },
+        error::HolochainError,
+        json::JsonString,
+    };
+    use std::{thread, time};
+
+    #[test]
+    fn commit_entry_at_requires_chain_repair() {
+        let context = test_context("bob");
+        assert_eq!(
+            Err(chain_repair_disabled_error()),
+            block_on(commit_entry_at(test_entry(), None, &context)),
+        );
+    }
+
+    #[test]
+    fn commit_entry_rejected_beyond_max_chain_length() {
+        let mut dna = test_dna();
+        dna.set_uuid(String::from(
+            "commit_entry_rejected_beyond_max_chain_length",
+        ));
+        // genesis commits the DNA and the agent entry
+        dna.set_max_chain_length(Some(3));
+        let (_instance, context) = instance_by_name("jane", dna);
+
+        assert_eq!(
+            Ok(test_entry().address()),
+            block_on(commit_entry(test_entry(), None, &context)),
+        );
+        assert_eq!(
+            Err(HolochainError::ChainFull),
+            block_on(commit_entry(test_entry_b(), None, &context)),
+        );
+    }
+
+    #[test]
+    fn commit_entry_with_header_returns_header_of_entry() {
+        let (_instance, context) = instance_by_name("jane", test_dna());
+
+        let (address, chain_header) =
+            block_on(commit_entry_with_header(test_entry(), None, &context)).unwrap();
+        assert_eq!(test_entry().address(), address);
+        assert_eq!(&address, chain_header.entry_address());
+        assert_eq!(
+            Some(chain_header),
+            context.state().unwrap().agent().top_chain_header()
+        );
+    }
+
+    #[test]
+    fn commit_entry_with_header_returns_header_of_each_commit() {
+        let (_instance, context) = instance_by_name("jane", test_dna());
+
+        let (_, first_header) =
+            block_on(commit_entry_with_header(test_entry(), None, &context)).unwrap();
+        let (address, second_header) =
+            block_on(commit_entry_with_header(test_entry(), None, &context)).unwrap();
+        assert_eq!(&address, second_header.entry_address());
+        assert_ne!(first_header, second_header);
+        assert_eq!(Some(first_header.address()), second_header.link());
+    }
+
+    fn dna_with_unique_name() -> Dna {
+        let mut dna = test_dna();
+        dna.zomes_mut()
+            .get_mut("test_zome")
+            .unwrap()
+            .entry_types
+            .get_mut(&EntryType::App(test_app_entry_type()))
+            .unwrap()
+            .unique_fields = vec![String::from("name")];
+        dna
+    }
+
+    fn named_entry(name: &str, bio: &str) -> Entry {
+        Entry::App(
+            test_app_entry_type(),
+            JsonString::from(format!("{{\"name\":\"{}\",\"bio\":\"{}\"}}", name, bio)),
+        )
+    }
+
+    #[test]
+    fn commit_entry_rejects_duplicate_unique_value() {
+        let (_instance, context) = instance_by_name("jane", dna_with_unique_name());
+
+        let first = named_entry("alice", "first");
+        assert_eq!(
+            Ok(first.address()),
+            block_on(commit_entry(first.clone(), None, &context)),
+        );
+        assert!(block_on(commit_entry(named_entry("bob", "first"), None, &context)).is_ok());
+
+        match block_on(commit_entry(named_entry("alice", "second"), None, &context)) {
+            Err(HolochainError::ValidationFailed(_)) => (),
+            other => panic!("duplicate unique value should be rejected, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn concurrent_commits_of_unique_value_take_it_once() {
+        let (_instance, context) = instance_by_name("jane", dna_with_unique_name());
+
+        let handles: Vec<_> = vec!["first", "second", "third"]
+            .into_iter()
+            .map(|bio| {
+                let context = context.clone();
+                thread::spawn(move || {
+                    block_on(commit_entry(named_entry("alice", bio), None, &context))
+                })
+            })
+            .collect();
+        let results: Vec<_> = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect();
+
+        assert_eq!(1, results.iter().filter(|result| result.is_ok()).count());
+        for result in results.into_iter().filter(|result| result.is_err()) {
+            match result {
+                Err(HolochainError::ValidationFailed(_)) => (),
+                other => panic!("duplicate unique value should be rejected, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn reserved_unique_value_is_released_when_dropped() {
+        let (_instance, context) = instance_by_name("jane", dna_with_unique_name());
+
+        // a commit of the value is in progress
+        let reservation =
+            reserve_unique_fields(&named_entry("alice", "first"), None, &context).unwrap();
+        match block_on(commit_entry(named_entry("alice", "second"), None, &context)) {
+            Err(HolochainError::ValidationFailed(_)) => (),
+            other => panic!("reserved unique value should be rejected, got {:?}", other),
+        }
+
+        // ... and failed without indexing it
+        drop(reservation);
+        assert!(block_on(commit_entry(named_entry("alice", "second"), None, &context)).is_ok());
+        assert!(context.reserved_unique_values().lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn commit_entry_accepts_unique_value_of_deleted_entry() {
+        let (_instance, context) = instance_by_name("jane", dna_with_unique_name());
+
+        let first = named_entry("alice", "first");
+        block_on(author_entry(&first, None, &context)).unwrap();
+        // wait for the entry to be held in our own DHT shard
+        thread::sleep(time::Duration::from_millis(500));
+
+        let deletion_entry = Entry::Deletion(DeletionEntry::new(first.address()));
+        block_on(commit_entry(
+            deletion_entry.clone(),
+            Some(first.address()),
+            &context,
+        ))
+        .unwrap();
+        block_on(remove_entry(
+            &context,
+            &context.action_channel,
+            first.address(),
+            deletion_entry.address(),
+        ))
+        .unwrap();
+
+        let second = named_entry("alice", "second");
+        assert_eq!(
+            Ok(second.address()),
+            block_on(commit_entry(second.clone(), None, &context)),
+        );
+    }
+}
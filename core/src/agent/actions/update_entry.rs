@@ -3,7 +3,7 @@ extern crate serde_json;
 use crate::{
     action::{Action, ActionWrapper},
     context::Context,
-    instance::dispatch_action,
+    instance::{dispatch_action, ActionSender},
 };
 
 use futures::{
@@ -13,7 +13,7 @@ use futures::{
 use holochain_core_types::{cas::content::Address, error::HolochainError};
 use std::{
     pin::{Pin, Unpin},
-    sync::{mpsc::SyncSender, Arc},
+    sync::Arc,
 };
 
 /// Update Entry Action Creator
@@ -21,7 +21,7 @@ use std::{
 /// Returns a future that resolves to an Ok(ActionWrapper) or an Err(HolochainError).
 pub fn update_entry(
     context: &Arc<Context>,
-    action_channel: &SyncSender<ActionWrapper>,
+    action_channel: &ActionSender,
     old_address: Address,
     new_address: Address,
 ) -> UpdateEntryFuture {
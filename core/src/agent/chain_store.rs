@@ -5,7 +5,9 @@ use holochain_core_types::{
     },
     chain_header::ChainHeader,
     entry::entry_type::EntryType,
+    error::HolochainError,
 };
+use multihash::Hash;
 use std::sync::{Arc, RwLock};
 
 #[derive(Debug, Clone)]
@@ -67,6 +69,175 @@ impl ChainStore {
             base_iter.collect()
         }
     }
+
+    /// Number of headers in the source chain from the given header downwards.
+    pub fn chain_length(&self, top_header: &Option<ChainHeader>) -> u64 {
+        self.iter(top_header).count() as u64
+    }
+
+    /// Number of bytes the source chain from the given header downwards takes up in storage,
+    /// counting the content of every header and of the entry it points to.
+    pub fn storage_bytes(&self, top_header: &Option<ChainHeader>) -> Result<u64, HolochainError> {
+        let mut bytes = 0;
+        for chain_header in self.iter(top_header) {
+            bytes += String::from(chain_header.content()).len() as u64;
+            let maybe_entry_content = self
+                .content_storage
+                .read()
+                .unwrap()
+                .fetch(chain_header.entry_address())?;
+            if let Some(entry_content) = maybe_entry_content {
+                bytes += String::from(entry_content).len() as u64;
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Walks the chain from the given header and returns the addresses of headers that are
+    /// referenced (as previous or previous-of-same-type header) but missing from storage,
+    /// so that sync code knows what to fetch. The walk stops at the first missing
+    /// previous header since the chain behind it can not be followed.
+    pub fn find_gaps(&self, top_header: &Option<ChainHeader>) -> Vec<Address> {
+        let storage = self.content_storage.read().unwrap();
+        let is_missing = |address: &Address| !storage.contains(address).unwrap_or(false);
+        let mut gaps = Vec::new();
+        let mut current = top_header.clone();
+        while let Some(chain_header) = current.take() {
+            if let Some(address) = chain_header.link_same_type() {
+                if is_missing(&address) && !gaps.contains(&address) {
+                    gaps.push(address);
+                }
+            }
+            if let Some(address) = chain_header.link() {
+                match storage.fetch(&address) {
+                    Ok(Some(content)) => {
+                        current = ChainHeader::try_from_content(&content).ok();
+                    }
+                    _ => {
+                        if !gaps.contains(&address) {
+                            gaps.push(address);
+                        }
+                    }
+                }
+            }
+        }
+        gaps
+    }
+
+    /// Addresses of all entries in the chain from the given header downwards, oldest first.
+    /// These are the leaves of the chain's Merkle tree.
+    fn entry_addresses_oldest_first(&self, top_header: &Option<ChainHeader>) -> Vec<Address> {
+        let mut addresses: Vec<Address> = self
+            .iter(top_header)
+            .map(|chain_header| chain_header.entry_address().clone())
+            .collect();
+        addresses.reverse();
+        addresses
+    }
+
+    /// Root of a Merkle tree over the addresses of all entries in the chain, oldest first.
+    /// Serves as a succinct commitment to the chain's content (e.g. to anchor it in an
+    /// external ledger) that, unlike the chain head, supports inclusion proofs.
+    /// See merkle_proof() and verify_merkle_proof().
+    pub fn merkle_root(&self, top_header: &Option<ChainHeader>) -> Address {
+        merkle_levels(self.entry_addresses_oldest_first(top_header))
+            .last()
+            .and_then(|level| level.first().cloned())
+            .unwrap_or_else(|| Address::encode_from_str("", Hash::SHA2256))
+    }
+
+    /// Sibling hashes from the leaf of the given entry up to the Merkle root, or None if the
+    /// entry is not part of the chain.
+    pub fn merkle_proof(
+        &self,
+        top_header: &Option<ChainHeader>,
+        entry_address: &Address,
+    ) -> Option<Vec<MerkleSibling>> {
+        let leaves = self.entry_addresses_oldest_first(top_header);
+        let mut index = leaves.iter().position(|address| address == entry_address)?;
+        let mut proof = Vec::new();
+        for level in merkle_levels(leaves).iter() {
+            // the last node of a level with odd length has no sibling and moves up as is
+            if index % 2 == 1 {
+                proof.push(MerkleSibling::Left(level[index - 1].clone()));
+            } else if let Some(address) = level.get(index + 1) {
+                proof.push(MerkleSibling::Right(address.clone()));
+            }
+            index /= 2;
+        }
+        Some(proof)
+    }
+}
+
+/// A step of a Merkle proof: the sibling to combine the current node with and its side.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MerkleSibling {
+    Left(Address),
+    Right(Address),
+}
+
+// Leaves and inner nodes are hashed with different prefixes (as in RFC 6962) so that an
+// inner node can't be passed off as an entry address.
+const MERKLE_LEAF_TAG: &str = "\u{0}";
+const MERKLE_NODE_TAG: &str = "\u{1}";
+
+/// Hash of the Merkle tree leaf of an entry address.
+fn merkle_leaf(entry_address: &Address) -> Address {
+    Address::encode_from_str(
+        &format!("{}{}", MERKLE_LEAF_TAG, String::from(entry_address.clone())),
+        Hash::SHA2256,
+    )
+}
+
+/// Hash of an inner Merkle tree node over its children in order.
+fn merkle_node(left: &Address, right: &Address) -> Address {
+    Address::encode_from_str(
+        &format!(
+            "{}{}{}",
+            MERKLE_NODE_TAG,
+            String::from(left.clone()),
+            String::from(right.clone())
+        ),
+        Hash::SHA2256,
+    )
+}
+
+/// All levels of the Merkle tree over the given entry addresses, from the leaves up to the root.
+fn merkle_levels(entry_addresses: Vec<Address>) -> Vec<Vec<Address>> {
+    if entry_addresses.is_empty() {
+        return Vec::new();
+    }
+    let mut levels = vec![entry_addresses.iter().map(merkle_leaf).collect()];
+    while levels.last().unwrap().len() > 1 {
+        let next = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => merkle_node(a, b),
+                _ => pair[0].clone(),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+/// Checks that the given proof (as returned by ChainStore::merkle_proof()) links the
+/// entry address to the Merkle root.
+pub fn verify_merkle_proof(
+    entry_address: &Address,
+    proof: &[MerkleSibling],
+    root: &Address,
+) -> bool {
+    let mut node = merkle_leaf(entry_address);
+    for sibling in proof {
+        node = match sibling {
+            MerkleSibling::Left(left) => merkle_node(left, &node),
+            MerkleSibling::Right(right) => merkle_node(&node, right),
+        };
+    }
+    node == *root
 }
 
 pub struct ChainStoreIterator {
@@ -161,12 +332,15 @@ impl Iterator for ChainStoreTypeIterator {
 pub mod tests {
     extern crate tempfile;
     use self::tempfile::tempdir;
-    use crate::agent::chain_store::ChainStore;
+    use crate::agent::chain_store::{
+        merkle_leaf, merkle_node, verify_merkle_proof, ChainStore, MerkleSibling,
+    };
     use holochain_cas_implementations::cas::file::FilesystemStorage;
     use holochain_core_types::{
         cas::content::AddressableContent,
         chain_header::{test_chain_header, test_sources, ChainHeader},
-        entry::{test_entry, test_entry_b, test_entry_c},
+        entry::{entry_type::test_app_entry_type, test_entry, test_entry_b, test_entry_c, Entry},
+        json::JsonString,
         signature::{test_signature_b, test_signature_c, test_signatures},
         time::test_iso_8601,
     };
@@ -295,6 +469,100 @@ pub mod tests {
         assert_eq!(expected, found);
     }
 
+    #[test]
+    /// show that storage_bytes() grows with every entry added to the chain
+    fn storage_bytes_test() {
+        let chain_store = test_chain_store();
+        let storage = chain_store.content_storage.clone();
+        assert_eq!(Ok(0), chain_store.storage_bytes(&None));
+
+        let mut top_header = None;
+        let mut last_bytes = 0;
+        for entry in vec![test_entry(), test_entry_b(), test_entry_c()] {
+            let chain_header = ChainHeader::new(
+                &entry.entry_type(),
+                &entry.address(),
+                &test_sources(),
+                &test_signatures(),
+                &top_header
+                    .as_ref()
+                    .map(|header: &ChainHeader| header.address()),
+                &None,
+                &None,
+                &test_iso_8601(),
+            );
+            (*storage.write().unwrap())
+                .add(&entry)
+                .expect("could not add entry to cas");
+            (*storage.write().unwrap())
+                .add(&chain_header)
+                .expect("could not add header to cas");
+
+            let bytes = chain_store
+                .storage_bytes(&Some(chain_header.clone()))
+                .unwrap();
+            assert_eq!(
+                last_bytes
+                    + String::from(entry.content()).len() as u64
+                    + String::from(chain_header.content()).len() as u64,
+                bytes
+            );
+            last_bytes = bytes;
+            top_header = Some(chain_header);
+        }
+    }
+
+    #[test]
+    /// show that find_gaps() reports missing previous headers
+    fn find_gaps_test() {
+        let chain_store = test_chain_store();
+
+        let chain_header_a = test_chain_header();
+        let entry_b = test_entry_b();
+        let chain_header_b = ChainHeader::new(
+            &entry_b.entry_type(),
+            &entry_b.address(),
+            &test_sources(),
+            &test_signatures(),
+            &Some(chain_header_a.address()),
+            &None,
+            &None,
+            &test_iso_8601(),
+        );
+        let entry_c = test_entry_c();
+        let chain_header_c = ChainHeader::new(
+            &entry_c.entry_type(),
+            &entry_c.address(),
+            &test_sources(),
+            &test_signatures(),
+            &Some(chain_header_b.address()),
+            &Some(chain_header_b.address()),
+            &None,
+            &test_iso_8601(),
+        );
+
+        let storage = chain_store.content_storage.clone();
+        (*storage.write().unwrap())
+            .add(&chain_header_a)
+            .expect("could not add header to cas");
+        (*storage.write().unwrap())
+            .add(&chain_header_c)
+            .expect("could not add header to cas");
+
+        assert_eq!(
+            vec![chain_header_b.address()],
+            chain_store.find_gaps(&Some(chain_header_c.clone()))
+        );
+
+        (*storage.write().unwrap())
+            .add(&chain_header_b)
+            .expect("could not add header to cas");
+        assert!(chain_store
+            .find_gaps(&Some(chain_header_c.clone()))
+            .is_empty());
+        assert!(chain_store.find_gaps(&None).is_empty());
+    }
+
     #[test]
     /// show query() implementation
     fn query_test() {
@@ -347,4 +615,155 @@ pub mod tests {
         assert_eq!(expected, found);
     }
 
+    /// builds a chain of the given entries in a fresh chain store and returns its top header
+    fn chain_of(chain_store: &ChainStore, entries: Vec<Entry>) -> Option<ChainHeader> {
+        let storage = chain_store.content_storage.clone();
+        let mut top_header = None;
+        for entry in entries {
+            let chain_header = ChainHeader::new(
+                &entry.entry_type(),
+                &entry.address(),
+                &test_sources(),
+                &test_signatures(),
+                &top_header
+                    .as_ref()
+                    .map(|header: &ChainHeader| header.address()),
+                &None,
+                &None,
+                &test_iso_8601(),
+            );
+            (*storage.write().unwrap())
+                .add(&chain_header)
+                .expect("could not add header to cas");
+            top_header = Some(chain_header);
+        }
+        top_header
+    }
+
+    #[test]
+    /// show that the Merkle root only depends on the entries of the chain
+    fn merkle_root_test() {
+        let entries = vec![
+            test_entry(),
+            test_entry_b(),
+            test_entry_c(),
+            Entry::App(test_app_entry_type(), JsonString::from("fourth")),
+            Entry::App(test_app_entry_type(), JsonString::from("fifth")),
+        ];
+        let chain_store = test_chain_store();
+        let top_header = chain_of(&chain_store, entries.clone());
+        let root = chain_store.merkle_root(&top_header);
+        assert_eq!(root, chain_store.merkle_root(&top_header));
+
+        let other_chain_store = test_chain_store();
+        let other_top_header = chain_of(&other_chain_store, entries.clone());
+        assert_eq!(root, other_chain_store.merkle_root(&other_top_header));
+
+        let shorter_top_header = chain_of(&other_chain_store, entries[..4].to_vec());
+        assert_ne!(root, other_chain_store.merkle_root(&shorter_top_header));
+
+        // the order of the entries matters
+        let mut reordered = entries.clone();
+        reordered.swap(0, 1);
+        let reordered_top_header = chain_of(&other_chain_store, reordered);
+        assert_ne!(root, other_chain_store.merkle_root(&reordered_top_header));
+
+        assert_eq!(
+            merkle_leaf(&test_entry().address()),
+            chain_store.merkle_root(&chain_of(&test_chain_store(), vec![test_entry()]))
+        );
+        assert_eq!(
+            chain_store.merkle_root(&None),
+            test_chain_store().merkle_root(&None)
+        );
+    }
+
+    #[test]
+    /// show that proofs verify against the Merkle root and tampered ones don't
+    fn merkle_proof_test() {
+        let entries = vec![
+            test_entry(),
+            test_entry_b(),
+            test_entry_c(),
+            Entry::App(test_app_entry_type(), JsonString::from("fourth")),
+            Entry::App(test_app_entry_type(), JsonString::from("fifth")),
+        ];
+        let chain_store = test_chain_store();
+        let top_header = chain_of(&chain_store, entries.clone());
+        let root = chain_store.merkle_root(&top_header);
+
+        for entry in entries.iter() {
+            let proof = chain_store
+                .merkle_proof(&top_header, &entry.address())
+                .expect("entry should be in the chain");
+            assert!(verify_merkle_proof(&entry.address(), &proof, &root));
+        }
+
+        let proof = chain_store
+            .merkle_proof(&top_header, &test_entry_b().address())
+            .unwrap();
+        let mut tampered_proof = proof.clone();
+        tampered_proof[0] = MerkleSibling::Left(merkle_leaf(&test_entry_c().address()));
+        assert!(!verify_merkle_proof(
+            &test_entry_b().address(),
+            &tampered_proof,
+            &root
+        ));
+        // the sibling has to be on the recorded side
+        let mut flipped_proof = proof.clone();
+        flipped_proof[0] = match proof[0].clone() {
+            MerkleSibling::Left(address) => MerkleSibling::Right(address),
+            MerkleSibling::Right(address) => MerkleSibling::Left(address),
+        };
+        assert!(!verify_merkle_proof(
+            &test_entry_b().address(),
+            &flipped_proof,
+            &root
+        ));
+        assert!(!verify_merkle_proof(
+            &test_entry_b().address(),
+            &tampered_proof,
+            &root
+        ));
+        assert!(!verify_merkle_proof(
+            &test_entry_c().address(),
+            &proof,
+            &root
+        ));
+
+        let foreign_entry = Entry::App(test_app_entry_type(), JsonString::from("foreign"));
+        assert_eq!(
+            None,
+            chain_store.merkle_proof(&top_header, &foreign_entry.address())
+        );
+    }
+
+    #[test]
+    /// show that an inner node of the tree can't be proven as an entry of the chain
+    fn merkle_proof_rejects_inner_nodes() {
+        let entries = vec![
+            test_entry(),
+            test_entry_b(),
+            test_entry_c(),
+            Entry::App(test_app_entry_type(), JsonString::from("fourth")),
+        ];
+        let chain_store = test_chain_store();
+        let top_header = chain_of(&chain_store, entries.clone());
+        let root = chain_store.merkle_root(&top_header);
+
+        let leaves: Vec<_> = entries
+            .iter()
+            .map(|entry| merkle_leaf(&entry.address()))
+            .collect();
+        let left_node = merkle_node(&leaves[0], &leaves[1]);
+        let right_node = merkle_node(&leaves[2], &leaves[3]);
+        assert_eq!(root, merkle_node(&left_node, &right_node));
+
+        assert!(!verify_merkle_proof(
+            &left_node,
+            &[MerkleSibling::Right(right_node)],
+            &root
+        ));
+    }
+
 }
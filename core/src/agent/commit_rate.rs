@@ -0,0 +1,109 @@
+//! Tracks how many entries each zome committed recently so zomes stuck in a runaway loop
+//! can't bloat the source chain.
+use holochain_core_types::error::HolochainError;
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+pub struct CommitRateLimiter {
+    window: Duration,
+    commits: HashMap<String, VecDeque<Instant>>,
+}
+
+impl Default for CommitRateLimiter {
+    fn default() -> Self {
+        CommitRateLimiter::new(Duration::from_secs(60))
+    }
+}
+
+impl CommitRateLimiter {
+    pub fn new(window: Duration) -> Self {
+        CommitRateLimiter {
+            window,
+            commits: HashMap::new(),
+        }
+    }
+
+    /// Reserves a commit slot for the given zome at `now` if it stays within `max_commits`
+    /// per window. Returns HolochainError::RateLimited otherwise, without reserving.
+    /// Checking and reserving happen in one step, so concurrent commits can not overshoot
+    /// the limit. A slot of a commit that failed gets handed back with release().
+    pub fn reserve(
+        &mut self,
+        zome_name: &str,
+        max_commits: u32,
+        now: Instant,
+    ) -> Result<(), HolochainError> {
+        let window = self.window;
+        let commits = self
+            .commits
+            .entry(zome_name.to_string())
+            .or_insert_with(VecDeque::new);
+        while commits
+            .front()
+            .map(|commit_time| now.duration_since(*commit_time) >= window)
+            .unwrap_or(false)
+        {
+            commits.pop_front();
+        }
+        if commits.len() >= max_commits as usize {
+            return Err(HolochainError::RateLimited(format!(
+                "Zome '{}' exceeded its limit of {} commits per {} seconds",
+                zome_name,
+                max_commits,
+                window.as_secs(),
+            )));
+        }
+        commits.push_back(now);
+        Ok(())
+    }
+
+    /// Hands back the slot the given zome reserved at `reserved_at`.
+    pub fn release(&mut self, zome_name: &str, reserved_at: Instant) {
+        if let Some(commits) = self.commits.get_mut(zome_name) {
+            if let Some(index) = commits
+                .iter()
+                .position(|commit_time| *commit_time == reserved_at)
+            {
+                commits.remove(index);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn commit_rate_limit_test() {
+        let mut limiter = CommitRateLimiter::new(Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert!(limiter.reserve("zome", 2, start).is_ok());
+        assert!(limiter.reserve("zome", 2, start).is_ok());
+        assert_eq!(
+            Err(HolochainError::RateLimited(String::from(
+                "Zome 'zome' exceeded its limit of 2 commits per 60 seconds"
+            ))),
+            limiter.reserve("zome", 2, start + Duration::from_secs(30)),
+        );
+        // other zomes are tracked separately
+        assert!(limiter.reserve("other zome", 2, start).is_ok());
+
+        // a released slot can be reserved again
+        limiter.release("zome", start);
+        assert!(limiter
+            .reserve("zome", 2, start + Duration::from_secs(30))
+            .is_ok());
+        assert!(limiter
+            .reserve("zome", 2, start + Duration::from_secs(30))
+            .is_err());
+
+        // commits are allowed again once the window has passed
+        assert!(limiter
+            .reserve("zome", 2, start + Duration::from_secs(90))
+            .is_ok());
+    }
+}
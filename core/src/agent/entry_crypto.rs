@@ -0,0 +1,309 @@
+//! Hooks for entry types with the "encrypted" sharing level.
+//! When an EntryCrypto is set on the Context, the content of such entries gets encrypted
+//! when they are committed, and decrypted when getting them.
+//! Authors validate the plaintext, while entries are stored, addressed and gossiped in
+//! their encrypted form only. DHT nodes therefore run the validation callbacks of
+//! encrypted types on the ciphertext and skip the checks of their content.
+use crate::context::Context;
+use holochain_core_types::{
+    cas::content::{Address, AddressableContent, Content},
+    dna::zome::entry_types::Sharing,
+    entry::{entry_type::EntryType, Entry},
+    error::HolochainError,
+    json::{JsonString, RawString},
+};
+use std::sync::Arc;
+
+/// Field of the (plaintext) JSON content of an entry of an encrypted type listing the
+/// addresses of the agents the entry gets encrypted for, besides the author.
+pub const RECIPIENTS_KEY: &str = "recipients";
+
+/// Pluggable encryption for the content of entries of encrypted types.
+pub trait EntryCrypto: Send + Sync {
+    /// Encrypts the content of an entry so that the given agents can decrypt it.
+    fn encrypt(&self, entry_content: &Content, recipients: &[Address]) -> Vec<u8>;
+
+    /// Recovers the content of an entry encrypted with encrypt().
+    fn decrypt(&self, bytes: &[u8]) -> Result<Content, HolochainError>;
+}
+
+/// Whether the DNA declares the given entry type with Sharing::Encrypted.
+pub fn is_encrypted_type(entry_type: &EntryType, context: &Arc<Context>) -> bool {
+    let app_entry_type = match entry_type {
+        EntryType::App(app_entry_type) => app_entry_type,
+        _ => return false,
+    };
+    context
+        .get_dna()
+        .and_then(|dna| {
+            dna.get_entry_type_def(&String::from(app_entry_type.clone()))
+                .unwrap_or(None)
+                .map(|entry_type_def| entry_type_def.sharing == Sharing::Encrypted)
+        })
+        .unwrap_or(false)
+}
+
+/// The agents an entry gets encrypted for: our own agent, so we can read back what we
+/// committed, and the agents listed in the content's RECIPIENTS_KEY field.
+pub fn recipients(content: &Content, context: &Arc<Context>) -> Vec<Address> {
+    let mut recipients = vec![context.agent_id.address()];
+    if let Ok(value) = serde_json::from_str::<serde_json::Value>(&String::from(content.clone())) {
+        let listed = value
+            .get(RECIPIENTS_KEY)
+            .and_then(|recipients| recipients.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|recipient| recipient.as_str())
+            .map(|recipient| Address::from(recipient.to_string()));
+        for recipient in listed {
+            if !recipients.contains(&recipient) {
+                recipients.push(recipient);
+            }
+        }
+    }
+    recipients
+}
+
+/// Replaces the content of an entry of an encrypted type with its ciphertext
+/// (base64 encoded), encrypted for its recipients (see recipients()).
+/// Other entries, and all entries if no EntryCrypto is set, are returned unchanged.
+pub fn encrypt_entry(entry: Entry, context: &Arc<Context>) -> Entry {
+    let crypto = match context.entry_crypto() {
+        Some(ref crypto) if is_encrypted_type(&entry.entry_type(), context) => crypto.clone(),
+        _ => return entry,
+    };
+    match entry {
+        Entry::App(app_entry_type, content) => {
+            let bytes = crypto.encrypt(&content, &recipients(&content, context));
+            Entry::App(
+                app_entry_type,
+                JsonString::from(RawString::from(base64::encode(&bytes))),
+            )
+        }
+        other => other,
+    }
+}
+
+/// Reverses encrypt_entry().
+/// Other entries, and all entries if no EntryCrypto is set, are returned unchanged.
+pub fn decrypt_entry(entry: Entry, context: &Arc<Context>) -> Result<Entry, HolochainError> {
+    let crypto = match context.entry_crypto() {
+        Some(ref crypto) if is_encrypted_type(&entry.entry_type(), context) => crypto.clone(),
+        _ => return Ok(entry),
+    };
+    match entry {
+        Entry::App(app_entry_type, ciphertext) => {
+            let encoded: String = serde_json::from_str(&String::from(ciphertext))?;
+            let bytes = base64::decode(&encoded).map_err(|error| {
+                HolochainError::SerializationError(format!("Invalid ciphertext: {}", error))
+            })?;
+            Ok(Entry::App(app_entry_type, crypto.decrypt(&bytes)?))
+        }
+        other => Ok(other),
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        agent::actions::commit::commit_entry,
+        nucleus::{
+            actions::{
+                tests::{instance_by_name, test_dna},
+                validate_and_commit::validate_and_commit,
+            },
+            state::ValidationResult,
+        },
+        workflows::get_entry_history::get_entry_with_meta_workflow,
+    };
+    use futures::executor::block_on;
+    use holochain_core_types::{
+        dna::Dna,
+        entry::{entry_type::test_app_entry_type, test_entry},
+        validation::{EntryAction, EntryLifecycle, ValidationData},
+    };
+    use std::sync::Mutex;
+
+    /// Not secure at all, just makes the ciphertext differ from the plaintext.
+    /// Remembers the recipients it encrypted for.
+    #[derive(Default)]
+    struct XorCrypto {
+        recipients: Mutex<Vec<Address>>,
+    }
+
+    impl EntryCrypto for XorCrypto {
+        fn encrypt(&self, entry_content: &Content, recipients: &[Address]) -> Vec<u8> {
+            *self.recipients.lock().unwrap() = recipients.to_vec();
+            String::from(entry_content.clone())
+                .into_bytes()
+                .into_iter()
+                .map(|byte| byte ^ 0x2a)
+                .collect()
+        }
+
+        fn decrypt(&self, bytes: &[u8]) -> Result<Content, HolochainError> {
+            let plain: Vec<u8> = bytes.iter().map(|byte| byte ^ 0x2a).collect();
+            String::from_utf8(plain)
+                .map(Content::from)
+                .map_err(|error| HolochainError::ErrorGeneric(error.to_string()))
+        }
+    }
+
+    fn dna_with_encrypted_test_entry() -> Dna {
+        let mut dna = test_dna();
+        dna.zomes_mut()
+            .get_mut("test_zome")
+            .unwrap()
+            .entry_types
+            .get_mut(&EntryType::App(test_app_entry_type()))
+            .unwrap()
+            .sharing = Sharing::Encrypted;
+        dna
+    }
+
+    fn commit(entry: &Entry, context: &Arc<Context>) -> Address {
+        block_on(validate_and_commit(
+            entry,
+            None,
+            EntryAction::Create,
+            EntryLifecycle::Chain,
+            context,
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn encrypted_entry_round_trip() {
+        let (_instance, context) = instance_by_name("jill", dna_with_encrypted_test_entry());
+        context.set_entry_crypto(Arc::new(XorCrypto::default()));
+        let validated = Arc::new(Mutex::new(Vec::new()));
+        {
+            let validated = validated.clone();
+            context.register_native_validator(
+                EntryType::App(test_app_entry_type()),
+                Box::new(move |entry: &Entry, _: &ValidationData| {
+                    validated.lock().unwrap().push(entry.address());
+                    ValidationResult::Pass
+                }),
+            );
+        }
+
+        let entry = test_entry();
+        let address = commit(&entry, &context);
+        assert_ne!(entry.address(), address);
+        // the author validates the plaintext
+        assert_eq!(vec![entry.address()], *validated.lock().unwrap());
+
+        let stored_content = context
+            .file_storage
+            .read()
+            .unwrap()
+            .fetch(&address)
+            .unwrap()
+            .expect("ciphertext should be stored under the returned address");
+        let stored_entry = Entry::try_from_content(&stored_content).unwrap();
+        assert_eq!(address, stored_entry.address());
+        assert_ne!(entry, stored_entry);
+        assert!(!String::from(stored_content).contains(&String::from(entry.content())));
+        assert!(!context
+            .file_storage
+            .read()
+            .unwrap()
+            .contains(&entry.address())
+            .unwrap());
+
+        let entry_with_meta = block_on(get_entry_with_meta_workflow(&context, &address))
+            .unwrap()
+            .expect("encrypted entry should be found");
+        assert_eq!(entry, entry_with_meta.entry);
+    }
+
+    #[test]
+    fn required_fields_are_checked_on_plaintext() {
+        let mut dna = dna_with_encrypted_test_entry();
+        dna.zomes_mut()
+            .get_mut("test_zome")
+            .unwrap()
+            .entry_types
+            .get_mut(&EntryType::App(test_app_entry_type()))
+            .unwrap()
+            .required_fields = vec![String::from("name")];
+        let (_instance, context) = instance_by_name("joy", dna);
+        context.set_entry_crypto(Arc::new(XorCrypto::default()));
+        context.register_native_validator(
+            EntryType::App(test_app_entry_type()),
+            Box::new(|_: &Entry, _: &ValidationData| ValidationResult::Pass),
+        );
+
+        let entry = Entry::App(
+            test_app_entry_type(),
+            JsonString::from("{\"name\":\"joy\"}"),
+        );
+        let address = commit(&entry, &context);
+        assert_ne!(entry.address(), address);
+
+        let unnamed = Entry::App(test_app_entry_type(), JsonString::from("{\"bio\":\"\"}"));
+        match block_on(validate_and_commit(
+            &unnamed,
+            None,
+            EntryAction::Create,
+            EntryLifecycle::Chain,
+            &context,
+        )) {
+            Err(HolochainError::ValidationFailed(_)) => (),
+            other => panic!("missing required field should be rejected, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn commit_entry_stores_ciphertext() {
+        let (_instance, context) = instance_by_name("jo", dna_with_encrypted_test_entry());
+        context.set_entry_crypto(Arc::new(XorCrypto::default()));
+
+        let entry = test_entry();
+        let address = block_on(commit_entry(entry.clone(), None, &context)).unwrap();
+        assert_ne!(entry.address(), address);
+        assert_eq!(
+            Some(address.clone()),
+            context
+                .state()
+                .unwrap()
+                .agent()
+                .top_chain_header()
+                .map(|header| header.entry_address().clone())
+        );
+        assert!(!context
+            .file_storage
+            .read()
+            .unwrap()
+            .contains(&entry.address())
+            .unwrap());
+    }
+
+    #[test]
+    fn entry_is_encrypted_for_its_recipients() {
+        let (_instance, context) = instance_by_name("jack", dna_with_encrypted_test_entry());
+        let crypto = Arc::new(XorCrypto::default());
+        context.set_entry_crypto(crypto.clone());
+        context.register_native_validator(
+            EntryType::App(test_app_entry_type()),
+            Box::new(|_: &Entry, _: &ValidationData| ValidationResult::Pass),
+        );
+
+        let entry = Entry::App(
+            test_app_entry_type(),
+            JsonString::from("{\"recipients\":[\"alice\",\"bob\"]}"),
+        );
+        commit(&entry, &context);
+
+        assert_eq!(
+            vec![
+                context.agent_id.address(),
+                Address::from("alice"),
+                Address::from("bob"),
+            ],
+            *crypto.recipients.lock().unwrap()
+        );
+    }
+}
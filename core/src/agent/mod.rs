@@ -2,13 +2,27 @@
 ///
 pub mod actions;
 pub mod chain_store;
+pub mod commit_rate;
+pub mod entry_crypto;
 pub mod state;
+pub mod unique_fields;
 
 use crate::context::Context;
 use holochain_core_types::{
-    cas::content::AddressableContent, chain_header::ChainHeader, entry::Entry,
+    capability_grant::{
+        create_capability_grant_eav, create_capability_revocation_eav, grant_and_sequence,
+        CapabilityGrant, GRANT_NAME, REVOKE_NAME,
+    },
+    cas::content::{Address, AddressableContent},
+    chain_header::ChainHeader,
+    eav::EntityAttributeValueStorage,
+    entry::{entry_type::EntryType, Entry},
+    error::HolochainError,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
 };
-use std::sync::Arc;
 
 pub fn find_chain_header(entry: &Entry, context: &Arc<Context>) -> Option<ChainHeader> {
     let chain = context.state().unwrap().agent().chain();
@@ -17,3 +31,217 @@ pub fn find_chain_header(entry: &Entry, context: &Arc<Context>) -> Option<ChainH
         .iter(&top_header)
         .find(|ref header| *header.entry_address() == entry.address())
 }
+
+/// The latest grant or revocation of every capability of the given agent, by capability.
+/// The value is the sequence number and whether it is a grant.
+/// A grant and a revocation with the same sequence number count as revoked.
+fn latest_grant_events(
+    agent: &Address,
+    storage: &EntityAttributeValueStorage,
+) -> Result<BTreeMap<CapabilityGrant, (u64, bool)>, HolochainError> {
+    let mut latest = BTreeMap::new();
+    for (attribute, is_grant) in vec![(GRANT_NAME, true), (REVOKE_NAME, false)] {
+        for eav in storage.fetch_eav(Some(agent.clone()), Some(attribute.to_string()), None)? {
+            let (grant, sequence) = grant_and_sequence(eav.value());
+            let event = latest.entry(grant).or_insert((sequence, is_grant));
+            if sequence > event.0 || (sequence == event.0 && !is_grant) {
+                *event = (sequence, is_grant);
+            }
+        }
+    }
+    Ok(latest)
+}
+
+/// Stores a grant or revocation of the capability after all earlier ones.
+fn add_grant_event(
+    agent: &Address,
+    grant: &CapabilityGrant,
+    is_grant: bool,
+    context: &Arc<Context>,
+) -> Result<(), HolochainError> {
+    let meta_storage = context.state().unwrap().dht().meta_storage();
+    // the write lock makes reading the last sequence number and adding the next one atomic
+    let mut storage = meta_storage.write().unwrap();
+    let sequence = latest_grant_events(agent, &*storage)?
+        .get(grant)
+        .map(|(sequence, _)| sequence + 1)
+        .unwrap_or(1);
+    let eav = if is_grant {
+        create_capability_grant_eav(agent, grant, sequence)
+    } else {
+        create_capability_revocation_eav(agent, grant, sequence)
+    };
+    storage.add_eav(&eav)
+}
+
+/// Grants the capability to the given agent, also if it was revoked before.
+pub fn grant_capability(
+    agent: &Address,
+    grant: &CapabilityGrant,
+    context: &Arc<Context>,
+) -> Result<(), HolochainError> {
+    add_grant_event(agent, grant, true, context)
+}
+
+/// Revokes the capability from the given agent until it gets granted again.
+pub fn revoke_capability(
+    agent: &Address,
+    grant: &CapabilityGrant,
+    context: &Arc<Context>,
+) -> Result<(), HolochainError> {
+    add_grant_event(agent, grant, false, context)
+}
+
+/// Returns all capability grants the given agent currently holds in this instance,
+/// i.e. every capability whose latest grant has not been revoked since.
+pub fn grants_for_agent(
+    agent: &Address,
+    context: &Arc<Context>,
+) -> Result<Vec<CapabilityGrant>, HolochainError> {
+    let meta_storage = context.state().unwrap().dht().meta_storage();
+    let storage = meta_storage.read().unwrap();
+    Ok(latest_grant_events(agent, &*storage)?
+        .into_iter()
+        .filter(|(_, (_, is_grant))| *is_grant)
+        .map(|(grant, _)| grant)
+        .collect())
+}
+
+/// Walks the source chain from its start and checks that every deletion entry deletes an
+/// entry committed before it that is not a deletion itself.
+/// Returns the first inconsistency as HolochainError::ValidationFailed.
+pub fn validate_deletion_graph(context: &Arc<Context>) -> Result<(), HolochainError> {
+    let agent_state = context
+        .state()
+        .ok_or_else(|| HolochainError::ErrorGeneric("State not initialized".to_string()))?
+        .agent();
+    let chain = agent_state.chain();
+    let mut chain_headers: Vec<ChainHeader> = chain.iter(&agent_state.top_chain_header()).collect();
+    chain_headers.reverse();
+
+    let mut committed: HashMap<Address, EntryType> = HashMap::new();
+    for chain_header in chain_headers {
+        let entry_address = chain_header.entry_address().clone();
+        if *chain_header.entry_type() == EntryType::Deletion {
+            let content = chain
+                .content_storage()
+                .read()
+                .unwrap()
+                .fetch(&entry_address)?
+                .ok_or_else(|| {
+                    HolochainError::ValidationFailed(format!(
+                        "Deletion entry {} is missing from storage",
+                        entry_address
+                    ))
+                })?;
+            let deleted_address = match Entry::try_from_content(&content)? {
+                Entry::Deletion(deletion_entry) => deletion_entry.deleted_entry_address(),
+                _ => Err(HolochainError::ValidationFailed(format!(
+                    "Entry {} is committed as deletion but is not one",
+                    entry_address
+                )))?,
+            };
+            let error = |reason: &str| {
+                Err(HolochainError::ValidationFailed(format!(
+                    "Deletion entry {} {}",
+                    entry_address, reason
+                )))
+            };
+            if deleted_address == entry_address {
+                return error("deletes itself");
+            }
+            match committed.get(&deleted_address) {
+                None => return error("deletes an entry that was not committed before it"),
+                Some(EntryType::Deletion) => return error("deletes another deletion"),
+                Some(_) => {}
+            }
+        }
+        committed.insert(entry_address, chain_header.entry_type().clone());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::{grant_capability, grants_for_agent, revoke_capability, validate_deletion_graph};
+    use crate::{
+        agent::actions::commit::commit_entry,
+        instance::tests::test_context_with_state,
+        nucleus::actions::tests::{instance_by_name, test_dna},
+    };
+    use futures::executor::block_on;
+    use holochain_core_types::{
+        capability_grant::CapabilityGrant,
+        cas::content::{Address, AddressableContent},
+        entry::{deletion_entry::DeletionEntry, test_entry, test_entry_b, Entry},
+    };
+
+    #[test]
+    fn grants_for_agent_test() {
+        let context = test_context_with_state();
+        let agent = Address::from("agent");
+        let other_agent = Address::from("other agent");
+        let grant_a = CapabilityGrant::new("blog", "main");
+        let grant_b = CapabilityGrant::new("blog", "admin");
+        let grant_c = CapabilityGrant::new("chat", "main");
+        grant_capability(&agent, &grant_a, &context).unwrap();
+        grant_capability(&agent, &grant_b, &context).unwrap();
+        grant_capability(&agent, &grant_c, &context).unwrap();
+        revoke_capability(&agent, &grant_c, &context).unwrap();
+        grant_capability(&other_agent, &grant_c, &context).unwrap();
+
+        let grants = grants_for_agent(&agent, &context).unwrap();
+        assert_eq!(vec![grant_b, grant_a], grants);
+        assert_eq!(
+            vec![grant_c],
+            grants_for_agent(&other_agent, &context).unwrap()
+        );
+    }
+
+    #[test]
+    fn capability_can_be_granted_again_after_revocation() {
+        let context = test_context_with_state();
+        let agent = Address::from("agent");
+        let grant = CapabilityGrant::new("blog", "main");
+
+        grant_capability(&agent, &grant, &context).unwrap();
+        assert_eq!(
+            vec![grant.clone()],
+            grants_for_agent(&agent, &context).unwrap()
+        );
+        revoke_capability(&agent, &grant, &context).unwrap();
+        assert!(grants_for_agent(&agent, &context).unwrap().is_empty());
+        grant_capability(&agent, &grant, &context).unwrap();
+        assert_eq!(
+            vec![grant.clone()],
+            grants_for_agent(&agent, &context).unwrap()
+        );
+        revoke_capability(&agent, &grant, &context).unwrap();
+        assert!(grants_for_agent(&agent, &context).unwrap().is_empty());
+    }
+
+    #[test]
+    fn validate_deletion_graph_test() {
+        let (_instance, context) = instance_by_name("jane", test_dna());
+        let commit = |entry: Entry| block_on(commit_entry(entry, None, &context)).unwrap();
+
+        commit(test_entry());
+        let deletion = Entry::Deletion(DeletionEntry::new(test_entry().address()));
+        commit(deletion.clone());
+        assert_eq!(Ok(()), validate_deletion_graph(&context));
+
+        commit(Entry::Deletion(DeletionEntry::new(deletion.address())));
+        assert!(validate_deletion_graph(&context).is_err());
+    }
+
+    #[test]
+    fn validate_deletion_graph_rejects_deleting_future_entries() {
+        let (_instance, context) = instance_by_name("jill", test_dna());
+        let commit = |entry: Entry| block_on(commit_entry(entry, None, &context)).unwrap();
+
+        let deletion = Entry::Deletion(DeletionEntry::new(test_entry_b().address()));
+        commit(deletion);
+        commit(test_entry_b());
+        assert!(validate_deletion_graph(&context).is_err());
+    }
+}
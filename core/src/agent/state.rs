@@ -9,13 +9,16 @@ use holochain_core_types::{
     agent::AgentId,
     cas::content::{Address, AddressableContent, Content},
     chain_header::ChainHeader,
+    dna::Dna,
     entry::{entry_type::EntryType, Entry},
     error::{HcResult, HolochainError},
+    hash::HashString,
     json::*,
     signature::Signature,
     time::Iso8601,
 };
 use holochain_wasm_utils::api_serialization::get_entry::*;
+use multihash::Hash as Multihash;
 use serde_json;
 use std::{collections::HashMap, convert::TryFrom, sync::Arc};
 
@@ -27,8 +30,16 @@ pub struct AgentState {
     // @TODO this will blow up memory, implement as some kind of dropping/FIFO with a limit?
     // @see https://github.com/holochain/holochain-rust/issues/166
     actions: HashMap<ActionWrapper, ActionResponse>,
+    /// the chain header created by every successful commit action
+    committed_headers: HashMap<ActionWrapper, ChainHeader>,
     chain: ChainStore,
     top_chain_header: Option<ChainHeader>,
+    /// hash of the committed DNA, set when the DNA gets committed or the chain gets loaded
+    /// so zome calls don't have to walk the chain for it
+    dna_hash: Option<HashString>,
+    /// number of headers in the chain below and including top_chain_header,
+    /// so commits don't have to walk the chain to check it against the DNA's maximum
+    chain_length: u64,
 }
 
 impl AgentState {
@@ -36,17 +47,26 @@ impl AgentState {
     pub fn new(chain: ChainStore) -> AgentState {
         AgentState {
             actions: HashMap::new(),
+            committed_headers: HashMap::new(),
             chain,
             top_chain_header: None,
+            dna_hash: None,
+            chain_length: 0,
         }
     }
 
     pub fn new_with_top_chain_header(chain: ChainStore, chain_header: ChainHeader) -> AgentState {
-        AgentState {
+        let mut state = AgentState {
             actions: HashMap::new(),
+            committed_headers: HashMap::new(),
             chain,
             top_chain_header: Some(chain_header),
-        }
+            dna_hash: None,
+            chain_length: 0,
+        };
+        state.dna_hash = state.get_dna().ok().map(|dna| dna_hash(&dna));
+        state.chain_length = state.chain.chain_length(&state.top_chain_header);
+        state
     }
 
     /// getter for a copy of self.actions
@@ -55,6 +75,11 @@ impl AgentState {
         self.actions.clone()
     }
 
+    /// The chain header that the given commit action created, if it succeeded.
+    pub fn committed_header(&self, action_wrapper: &ActionWrapper) -> Option<ChainHeader> {
+        self.committed_headers.get(action_wrapper).cloned()
+    }
+
     pub fn chain(&self) -> ChainStore {
         self.chain.clone()
     }
@@ -63,6 +88,11 @@ impl AgentState {
         self.top_chain_header.clone()
     }
 
+    /// Number of headers in the source chain, without walking it.
+    pub fn chain_length(&self) -> u64 {
+        self.chain_length
+    }
+
     pub fn get_agent_address(&self) -> HcResult<Address> {
         self.chain()
             .iter_type(&self.top_chain_header, &EntryType::AgentId)
@@ -73,6 +103,33 @@ impl AgentState {
             ))
     }
 
+    /// The DNA as it got committed to the source chain at genesis, i.e. without the
+    /// wildcard capabilities that get expanded in the running DNA.
+    /// This is the DNA that identifies the instance on the network.
+    pub fn get_dna(&self) -> HcResult<Dna> {
+        let dna_header = self
+            .chain()
+            .iter_type(&self.top_chain_header, &EntryType::Dna)
+            .last()
+            .ok_or(HolochainError::DnaMissing)?;
+        let content = self
+            .chain()
+            .content_storage()
+            .read()
+            .unwrap()
+            .fetch(dna_header.entry_address())?
+            .ok_or(HolochainError::DnaMissing)?;
+        match Entry::try_from_content(&content)? {
+            Entry::Dna(dna) => Ok(dna),
+            _ => Err(HolochainError::DnaMissing),
+        }
+    }
+
+    /// Hash of the DNA returned by get_dna(), as handed to zomes in their globals.
+    pub fn dna_hash(&self) -> Option<HashString> {
+        self.dna_hash.clone()
+    }
+
     pub async fn get_agent<'a>(&'a self, context: &'a Arc<Context>) -> HcResult<AgentId> {
         let agent_entry_address = self.get_agent_address()?;
         let entry_args = GetEntryArgs {
@@ -157,9 +214,25 @@ pub fn create_new_chain_header(
     context: Arc<Context>,
     crud_link: &Option<Address>,
 ) -> ChainHeader {
-    let agent_state = context
+    let top_chain_header = context
         .state()
         .expect("create_new_chain_header called without state")
+        .agent()
+        .top_chain_header();
+    create_chain_header_after(entry, context, crud_link, &top_chain_header)
+}
+
+/// Builds the header for an entry that links to the given previous header
+/// instead of the current top of the chain.
+pub fn create_chain_header_after(
+    entry: &Entry,
+    context: Arc<Context>,
+    crud_link: &Option<Address>,
+    previous_header: &Option<ChainHeader>,
+) -> ChainHeader {
+    let agent_state = context
+        .state()
+        .expect("create_chain_header_after called without state")
         .agent();
     let agent_address = agent_state
         .get_agent_address()
@@ -170,13 +243,12 @@ pub fn create_new_chain_header(
         &vec![agent_address],
         // @TODO signatures
         &vec![Signature::from("")],
-        &agent_state
-            .top_chain_header
+        &previous_header
             .clone()
             .and_then(|chain_header| Some(chain_header.address())),
         &agent_state
             .chain()
-            .iter_type(&agent_state.top_chain_header, &entry.entry_type())
+            .iter_type(previous_header, &entry.entry_type())
             .nth(0)
             .and_then(|chain_header| Some(chain_header.address())),
         crud_link,
@@ -185,6 +257,32 @@ pub fn create_new_chain_header(
     )
 }
 
+fn dna_hash(dna: &Dna) -> HashString {
+    HashString::encode_from_json_string(JsonString::from(dna), Multihash::SHA2256)
+}
+
+pub(crate) fn chain_repair_disabled_error() -> HolochainError {
+    HolochainError::ErrorGeneric(
+        "Committing at an explicit previous header requires chain repair to be enabled"
+            .to_string(),
+    )
+}
+
+/// Fails with HolochainError::ChainFull if the chain already holds the maximum number
+/// of entries the DNA allows.
+fn check_chain_length(context: &Arc<Context>, state: &AgentState) -> Result<(), HolochainError> {
+    let maybe_max_chain_length = context
+        .state()
+        .and_then(|global_state| global_state.nucleus().dna())
+        .and_then(|dna| dna.max_chain_length());
+    match maybe_max_chain_length {
+        Some(max_chain_length) if state.chain_length >= max_chain_length => {
+            Err(HolochainError::ChainFull)
+        }
+        _ => Ok(()),
+    }
+}
+
 /// Do a Commit Action against an agent state.
 /// Intended for use inside the reducer, isolated for unit testing.
 /// callback checks (e.g. validate_commit) happen elsewhere because callback functions cause
@@ -200,6 +298,12 @@ fn reduce_commit_entry(
 ) {
     let action = action_wrapper.action();
     let (entry, maybe_crud_link) = unwrap_to!(action => Action::Commit);
+    if let Err(error) = check_chain_length(&context, state) {
+        state
+            .actions
+            .insert(action_wrapper.clone(), ActionResponse::Commit(Err(error)));
+        return;
+    }
     let chain_header = create_new_chain_header(&entry, context.clone(), &maybe_crud_link);
 
     fn response(
@@ -213,6 +317,15 @@ fn reduce_commit_entry(
         Ok(entry.address())
     }
     let result = response(state, &entry, &chain_header);
+    if result.is_ok() {
+        state
+            .committed_headers
+            .insert(action_wrapper.clone(), chain_header.clone());
+        if let Entry::Dna(dna) = entry {
+            state.dna_hash = Some(dna_hash(dna));
+        }
+        state.chain_length += 1;
+    }
     state.top_chain_header = Some(chain_header);
     let con = context.clone();
 
@@ -228,10 +341,55 @@ fn reduce_commit_entry(
         .insert(action_wrapper.clone(), ActionResponse::Commit(result));
 }
 
+/// Commits an entry on top of an explicitly given previous header, for chain repair.
+/// The new header becomes the top of the chain.
+fn reduce_commit_entry_at(
+    context: Arc<Context>,
+    state: &mut AgentState,
+    action_wrapper: &ActionWrapper,
+) {
+    let action = action_wrapper.action();
+    let (entry, maybe_previous_header_address) = unwrap_to!(action => Action::CommitAt);
+
+    fn response(
+        context: Arc<Context>,
+        state: &mut AgentState,
+        entry: &Entry,
+        maybe_previous_header_address: &Option<Address>,
+    ) -> Result<Address, HolochainError> {
+        if !context.chain_repair_enabled() {
+            return Err(chain_repair_disabled_error());
+        }
+        let storage = &state.chain.content_storage().clone();
+        let previous_header = match maybe_previous_header_address {
+            Some(address) => {
+                let content = storage.read().unwrap().fetch(address)?.ok_or_else(|| {
+                    HolochainError::ErrorGeneric(format!("Chain header {} not found", address))
+                })?;
+                Some(ChainHeader::try_from_content(&content)?)
+            }
+            None => None,
+        };
+        let chain_header = create_chain_header_after(entry, context, &None, &previous_header);
+        storage.write().unwrap().add(entry)?;
+        storage.write().unwrap().add(&chain_header)?;
+        state.top_chain_header = Some(chain_header);
+        // the new top may be on another branch, so the length has to be counted again
+        state.chain_length = state.chain.chain_length(&state.top_chain_header);
+        Ok(entry.address())
+    }
+    let result = response(context, state, &entry, &maybe_previous_header_address);
+
+    state
+        .actions
+        .insert(action_wrapper.clone(), ActionResponse::Commit(result));
+}
+
 /// maps incoming action to the correct handler
 fn resolve_reducer(action_wrapper: &ActionWrapper) -> Option<AgentReduceFn> {
     match action_wrapper.action() {
         Action::Commit(_) => Some(reduce_commit_entry),
+        Action::CommitAt(_) => Some(reduce_commit_entry_at),
         _ => None,
     }
 }
@@ -256,18 +414,27 @@ pub fn reduce(
 #[cfg(test)]
 pub mod tests {
     extern crate tempfile;
-    use super::{reduce_commit_entry, ActionResponse, AgentState, AgentStateSnapshot};
+    use super::{
+        chain_repair_disabled_error, reduce_commit_entry, reduce_commit_entry_at, ActionResponse,
+        AgentState, AgentStateSnapshot,
+    };
     use crate::{
-        action::tests::test_action_wrapper_commit, agent::chain_store::tests::test_chain_store,
-        instance::tests::test_context, state::State,
+        action::{tests::test_action_wrapper_commit, Action, ActionWrapper},
+        agent::chain_store::tests::test_chain_store,
+        context::Context,
+        instance::tests::test_context,
+        state::State,
     };
     use holochain_core_types::{
-        cas::content::AddressableContent,
+        cas::content::{Address, AddressableContent},
         chain_header::test_chain_header,
-        entry::{expected_entry_address, test_entry, Entry},
+        dna::Dna,
+        entry::{expected_entry_address, test_entry, test_entry_b, test_entry_c, Entry},
         error::HolochainError,
+        hash::HashString,
         json::JsonString,
     };
+    use multihash::Hash as Multihash;
     use serde_json;
     use std::{
         collections::HashMap,
@@ -316,6 +483,117 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn dna_hash_is_kept_from_commit_and_chain() {
+        let mut agent_state = test_agent_state();
+        let context = test_context("bob");
+        assert_eq!(None, agent_state.dna_hash());
+
+        let dna = Dna::new();
+        let action_wrapper = ActionWrapper::new(Action::Commit((Entry::Dna(dna.clone()), None)));
+        reduce_commit_entry(context, &mut agent_state, &action_wrapper);
+        let expected = Some(HashString::encode_from_json_string(
+            JsonString::from(dna),
+            Multihash::SHA2256,
+        ));
+        assert_eq!(expected, agent_state.dna_hash());
+
+        let loaded_state = AgentState::new_with_top_chain_header(
+            agent_state.chain(),
+            agent_state.top_chain_header().unwrap(),
+        );
+        assert_eq!(expected, loaded_state.dna_hash());
+    }
+
+    fn commit_at(
+        context: &Arc<Context>,
+        agent_state: &mut AgentState,
+        entry: Entry,
+        previous_header: Option<Address>,
+    ) -> Result<Address, HolochainError> {
+        let action_wrapper = ActionWrapper::new(Action::CommitAt((entry, previous_header)));
+        reduce_commit_entry_at(context.clone(), agent_state, &action_wrapper);
+        match agent_state.actions().get(&action_wrapper) {
+            Some(ActionResponse::Commit(result)) => result.clone(),
+            other => panic!("unexpected response {:?}", other),
+        }
+    }
+
+    #[test]
+    /// test reconstructing a chain through explicit previous headers
+    fn test_reduce_commit_entry_at() {
+        let mut agent_state = test_agent_state();
+        let state = State::new_with_agent(test_context("bob"), Arc::new(agent_state.clone()));
+        let mut context = test_context("bob");
+        {
+            let context = Arc::get_mut(&mut context).unwrap();
+            context.set_state(Arc::new(RwLock::new(state)));
+            context.set_chain_repair_enabled(true);
+        }
+
+        let mut previous_header = None;
+        let mut headers = Vec::new();
+        for entry in vec![test_entry(), test_entry_b(), test_entry_c()] {
+            assert_eq!(
+                Ok(entry.address()),
+                commit_at(&context, &mut agent_state, entry.clone(), previous_header)
+            );
+            let top = agent_state.top_chain_header().unwrap();
+            assert_eq!(&entry.address(), top.entry_address());
+            previous_header = Some(top.address());
+            headers.insert(0, top);
+        }
+        let found: Vec<_> = agent_state
+            .chain()
+            .iter(&agent_state.top_chain_header())
+            .collect();
+        assert_eq!(headers, found);
+
+        // a header linking to the first one rather than the top of the chain
+        let first = headers.last().unwrap().clone();
+        commit_at(
+            &context,
+            &mut agent_state,
+            test_entry_b(),
+            Some(first.address()),
+        )
+        .unwrap();
+        let top = agent_state.top_chain_header().unwrap();
+        let found: Vec<_> = agent_state.chain().iter(&Some(top.clone())).collect();
+        assert_eq!(vec![top, first], found);
+        assert_eq!(2, agent_state.chain_length());
+    }
+
+    #[test]
+    fn chain_length_is_kept_from_commits_and_chain() {
+        let mut agent_state = test_agent_state();
+        let context = test_context("bob");
+        assert_eq!(0, agent_state.chain_length());
+
+        for entry in vec![test_entry(), test_entry_b(), test_entry_c()] {
+            let action_wrapper = ActionWrapper::new(Action::Commit((entry, None)));
+            reduce_commit_entry(context.clone(), &mut agent_state, &action_wrapper);
+        }
+        assert_eq!(3, agent_state.chain_length());
+
+        let loaded_state = AgentState::new_with_top_chain_header(
+            agent_state.chain(),
+            agent_state.top_chain_header().unwrap(),
+        );
+        assert_eq!(3, loaded_state.chain_length());
+    }
+
+    #[test]
+    fn test_reduce_commit_entry_at_requires_chain_repair() {
+        let mut agent_state = test_agent_state();
+        let context = test_context("bob");
+        assert_eq!(
+            Err(chain_repair_disabled_error()),
+            commit_at(&context, &mut agent_state, test_entry(), None)
+        );
+        assert_eq!(None, agent_state.top_chain_header());
+    }
+
     #[test]
     /// test response to json
     fn test_commit_response_to_json() {
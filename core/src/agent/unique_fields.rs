@@ -0,0 +1,187 @@
+//! Enforces the `unique_fields` of entry type definitions.
+//! Committed entries are indexed in the EAV storage under a key derived from their entry type,
+//! the unique field and its value. Only indexed entries that are still live block a commit
+//! of the same value, so deleting (or updating) an entry frees up its values.
+//! While a commit is in progress its values are reserved in the context, so concurrent
+//! commits can't take them before they are indexed.
+use crate::{context::Context, nucleus::actions::get_entry::get_entry_crud_status_from_dht};
+use holochain_core_types::{
+    cas::content::{Address, AddressableContent},
+    crud_status::CrudStatus,
+    eav::EntityAttributeValue,
+    entry::{entry_type::AppEntryType, Entry},
+    error::HolochainError,
+};
+use multihash::Hash;
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+/// Attribute linking the key of a unique value to the entries holding that value
+pub const UNIQUE_VALUE_NAME: &str = "unique-value";
+
+/// Key under which entries of the given type holding the given value in field are indexed.
+pub fn unique_value_key(
+    app_entry_type: &AppEntryType,
+    field: &str,
+    value: &serde_json::Value,
+) -> Address {
+    Address::encode_from_str(
+        &format!(
+            "{}/{}/{}",
+            String::from(app_entry_type.clone()),
+            field,
+            value
+        ),
+        Hash::SHA2256,
+    )
+}
+
+/// The unique fields the entry has a value for, with the key of that value.
+fn unique_value_keys(
+    entry: &Entry,
+    context: &Arc<Context>,
+) -> Result<Vec<(String, Address)>, HolochainError> {
+    let (app_entry_type, content) = match entry {
+        Entry::App(app_entry_type, content) => (app_entry_type, content),
+        _ => return Ok(Vec::new()),
+    };
+    let unique_fields = match context.get_dna() {
+        Some(dna) => dna
+            .get_entry_type_def(&String::from(app_entry_type.clone()))?
+            .map(|entry_type_def| entry_type_def.unique_fields.clone())
+            .unwrap_or_default(),
+        None => Vec::new(),
+    };
+    if unique_fields.is_empty() {
+        return Ok(Vec::new());
+    }
+    let value: serde_json::Value = serde_json::from_str(&String::from(content.clone()))?;
+    Ok(unique_fields
+        .into_iter()
+        .filter_map(|field| {
+            value.get(field.as_str()).map(|field_value| {
+                let key = unique_value_key(app_entry_type, &field, field_value);
+                (field, key)
+            })
+        })
+        .collect())
+}
+
+/// Entries that were committed but not held yet have no CRUD status and count as live.
+/// Only the status is read, so entries that were updated concurrently don't fail the check.
+fn is_live(address: &Address, context: &Arc<Context>) -> Result<bool, HolochainError> {
+    Ok(get_entry_crud_status_from_dht(context, address.clone())?
+        .map(|crud_status| crud_status == CrudStatus::LIVE)
+        .unwrap_or(true))
+}
+
+/// The address of the entry an update replaces, given the crud link it gets committed with
+/// (the address of the old entry or of its chain header).
+fn replaced_entry_address(crud_link: &Address, context: &Arc<Context>) -> Address {
+    let agent_state = context.state().unwrap().agent();
+    agent_state
+        .chain()
+        .iter(&agent_state.top_chain_header())
+        .find(|chain_header| chain_header.address() == *crud_link)
+        .map(|chain_header| chain_header.entry_address().clone())
+        .unwrap_or_else(|| crud_link.clone())
+}
+
+/// Fails with HolochainError::ValidationFailed if a live entry of the same type other than the
+/// one the entry replaces already holds the value of one of the type's unique fields.
+pub fn check_unique_fields(
+    entry: &Entry,
+    maybe_crud_link: Option<&Address>,
+    context: &Arc<Context>,
+) -> Result<(), HolochainError> {
+    let keys = unique_value_keys(entry, context)?;
+    if keys.is_empty() {
+        return Ok(());
+    }
+    let maybe_replaced =
+        maybe_crud_link.map(|crud_link| replaced_entry_address(crud_link, context));
+    for (field, key) in keys {
+        let holders = context.eav_storage.read().unwrap().fetch_eav(
+            Some(key),
+            Some(UNIQUE_VALUE_NAME.to_string()),
+            None,
+        )?;
+        for holder in holders.into_iter().map(|eav| eav.value()) {
+            if Some(&holder) != maybe_replaced.as_ref() && is_live(&holder, context)? {
+                return Err(HolochainError::ValidationFailed(format!(
+                    "Value of unique field {} of entry type {} is already taken by entry {}",
+                    field,
+                    entry.entry_type(),
+                    holder,
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Unique values reserved by a commit in progress, released when dropped.
+pub struct UniqueValuesReservation {
+    keys: Vec<Address>,
+    reserved: Arc<Mutex<HashSet<Address>>>,
+}
+
+impl Drop for UniqueValuesReservation {
+    fn drop(&mut self) {
+        let mut reserved = self.reserved.lock().unwrap();
+        for key in self.keys.iter() {
+            reserved.remove(key);
+        }
+    }
+}
+
+/// Reserves the values of the entry's unique fields and checks them with
+/// check_unique_fields(). The values should stay reserved until they got indexed.
+/// Fails with HolochainError::ValidationFailed if another commit reserved one of them.
+pub fn reserve_unique_fields(
+    entry: &Entry,
+    maybe_crud_link: Option<&Address>,
+    context: &Arc<Context>,
+) -> Result<UniqueValuesReservation, HolochainError> {
+    let keys = unique_value_keys(entry, context)?;
+    let reserved = context.reserved_unique_values();
+    {
+        let mut reserved_keys = reserved.lock().unwrap();
+        if let Some((field, _)) = keys.iter().find(|(_, key)| reserved_keys.contains(key)) {
+            return Err(HolochainError::ValidationFailed(format!(
+                "Value of unique field {} of entry type {} is being committed by another entry",
+                field,
+                entry.entry_type(),
+            )));
+        }
+        reserved_keys.extend(keys.iter().map(|(_, key)| key.clone()));
+    }
+    let reservation = UniqueValuesReservation {
+        keys: keys.into_iter().map(|(_, key)| key).collect(),
+        reserved,
+    };
+    check_unique_fields(entry, maybe_crud_link, context)?;
+    Ok(reservation)
+}
+
+/// Indexes the values of the entry's unique fields as held by the entry at address.
+pub fn index_unique_fields(
+    entry: &Entry,
+    address: &Address,
+    context: &Arc<Context>,
+) -> Result<(), HolochainError> {
+    for (_, key) in unique_value_keys(entry, context)? {
+        context
+            .eav_storage
+            .write()
+            .unwrap()
+            .add_eav(&EntityAttributeValue::new(
+                &key,
+                &UNIQUE_VALUE_NAME.to_string(),
+                address,
+            ))?;
+    }
+    Ok(())
+}
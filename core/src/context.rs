@@ -1,22 +1,62 @@
 use crate::{
-    action::ActionWrapper, instance::Observer, logger::Logger, persister::Persister, state::State,
+    action::{Action, ActionWrapper},
+    agent::{commit_rate::CommitRateLimiter, entry_crypto::EntryCrypto, state::AgentStateSnapshot},
+    instance::{dispatch_action, dispatch_action_and_wait, ActionSender, Observer},
+    logger::Logger,
+    metrics,
+    network::{
+        direct_message::{DirectMessage, DirectMessageStream},
+        handler::{handle_protocol_wrapper, replay::NetworkRecorder, store::StoreQueue},
+        pause::NetworkPause,
+        peer_rotation::PeerRotation,
+        request_signer::RequestSigner,
+        retry::RetryPolicy,
+    },
+    nucleus::{
+        ribosome::{
+            callback::validation_package::get_app_entry_validation_package_definition,
+            execution_stats::{Clock, ExecutionStats, FunctionStats},
+            wasm_cache::{expand_wildcard_capabilities, WasmLoadPolicy, WasmModuleCache},
+        },
+        state::ValidationResult,
+    },
+    persister::Persister,
+    state::{InstanceSnapshot, SnapshotContent, State},
+    tracer::Tracer,
 };
 use holochain_core_types::{
-    agent::AgentId,
-    cas::storage::ContentAddressableStorage,
+    agent::{AgentId, PublicKey},
+    cas::{content::Address, storage::ContentAddressableStorage},
     dna::{wasm::DnaWasm, Dna},
     eav::EntityAttributeValueStorage,
-    error::HolochainError,
+    entry::{
+        entry_type::{AppEntryType, EntryType},
+        Entry, EntryWithMeta,
+    },
+    error::{DnaError, HcResult, HolochainError},
     json::JsonString,
+    validation::{ValidationData, ValidationPackageDefinition},
 };
+use holochain_net_connection::protocol_wrapper::ProtocolWrapper;
 use std::{
+    collections::{HashMap, HashSet},
     sync::{
-        mpsc::{sync_channel, SyncSender},
+        mpsc::{sync_channel, SyncSender, TrySendError},
         Arc, Mutex, RwLock, RwLockReadGuard,
     },
     thread::sleep,
-    time::Duration,
+    time::{Duration, Instant},
 };
+use wasmi::Module;
+
+/// Callback that picks a winner among the live tips of a forked update chain.
+/// Returns None if it can not decide, in which case all tips are returned to the caller.
+pub type ConflictResolver = Fn(&Vec<EntryWithMeta>) -> Option<EntryWithMeta> + Send + Sync;
+
+/// In-process validation function for an entry type, used instead of the WASM validation
+/// callback. Returns ValidationResult::Fail for entries that are invalid (permanent) or
+/// can't be validated yet (transient).
+pub type NativeValidator = Fn(&Entry, &ValidationData) -> ValidationResult + Send + Sync;
 
 /// Context holds the components that parts of a Holochain instance need in order to operate.
 /// This includes components that are injected from the outside like logger and persister
@@ -28,11 +68,31 @@ pub struct Context {
     pub logger: Arc<Mutex<Logger>>,
     pub persister: Arc<Mutex<Persister>>,
     state: Option<Arc<RwLock<State>>>,
-    pub action_channel: SyncSender<ActionWrapper>,
+    pub action_channel: ActionSender,
     pub observer_channel: SyncSender<Observer>,
     pub file_storage: Arc<RwLock<ContentAddressableStorage>>,
     pub eav_storage: Arc<RwLock<EntityAttributeValueStorage>>,
     pub network_config: JsonString,
+    conflict_resolver: Arc<RwLock<Option<Arc<ConflictResolver>>>>,
+    entry_crypto: Arc<RwLock<Option<Arc<EntryCrypto>>>>,
+    request_signer: Arc<RwLock<Option<Arc<RequestSigner>>>>,
+    tracer: Arc<RwLock<Option<Arc<Tracer>>>>,
+    wasm_cache: Arc<RwLock<WasmModuleCache>>,
+    execution_stats: Arc<Mutex<ExecutionStats>>,
+    commit_rate_limiter: Arc<Mutex<CommitRateLimiter>>,
+    reserved_unique_values: Arc<Mutex<HashSet<Address>>>,
+    network_recorder: Arc<RwLock<Option<NetworkRecorder>>>,
+    network_pause: Arc<Mutex<NetworkPause>>,
+    peer_rotation: Arc<Mutex<PeerRotation>>,
+    store_queue: Arc<Mutex<StoreQueue>>,
+    direct_message_subscribers: Arc<Mutex<Vec<SyncSender<DirectMessage>>>>,
+    network_enabled: bool,
+    gossip_bandwidth_limit: Option<u64>,
+    retry_policy: RetryPolicy,
+    max_traversal_depth: Option<usize>,
+    chain_repair_enabled: bool,
+    validation_package_definitions: Arc<RwLock<HashMap<AppEntryType, ValidationPackageDefinition>>>,
+    native_validators: Arc<RwLock<HashMap<EntryType, Arc<NativeValidator>>>>,
 }
 
 impl Context {
@@ -55,11 +115,31 @@ impl Context {
             logger,
             persister,
             state: None,
-            action_channel: tx_action,
+            action_channel: ActionSender::new(tx_action),
             observer_channel: tx_observer,
             file_storage: cas,
             eav_storage: eav,
             network_config,
+            conflict_resolver: Arc::new(RwLock::new(None)),
+            entry_crypto: Arc::new(RwLock::new(None)),
+            request_signer: Arc::new(RwLock::new(None)),
+            tracer: Arc::new(RwLock::new(None)),
+            wasm_cache: Arc::new(RwLock::new(WasmModuleCache::default())),
+            execution_stats: Arc::new(Mutex::new(ExecutionStats::default())),
+            commit_rate_limiter: Arc::new(Mutex::new(CommitRateLimiter::default())),
+            reserved_unique_values: Arc::new(Mutex::new(HashSet::new())),
+            network_recorder: Arc::new(RwLock::new(None)),
+            network_pause: Arc::new(Mutex::new(NetworkPause::default())),
+            peer_rotation: Arc::new(Mutex::new(PeerRotation::new())),
+            store_queue: Arc::new(Mutex::new(StoreQueue::default())),
+            direct_message_subscribers: Arc::new(Mutex::new(Vec::new())),
+            network_enabled: true,
+            gossip_bandwidth_limit: None,
+            retry_policy: RetryPolicy::default(),
+            max_traversal_depth: None,
+            chain_repair_enabled: false,
+            validation_package_definitions: Arc::new(RwLock::new(HashMap::new())),
+            native_validators: Arc::new(RwLock::new(HashMap::new())),
         })
     }
 
@@ -78,11 +158,31 @@ impl Context {
             logger,
             persister,
             state: None,
-            action_channel,
+            action_channel: ActionSender::new(action_channel),
             observer_channel,
             file_storage: cas,
             eav_storage: eav,
             network_config,
+            conflict_resolver: Arc::new(RwLock::new(None)),
+            entry_crypto: Arc::new(RwLock::new(None)),
+            request_signer: Arc::new(RwLock::new(None)),
+            tracer: Arc::new(RwLock::new(None)),
+            wasm_cache: Arc::new(RwLock::new(WasmModuleCache::default())),
+            execution_stats: Arc::new(Mutex::new(ExecutionStats::default())),
+            commit_rate_limiter: Arc::new(Mutex::new(CommitRateLimiter::default())),
+            reserved_unique_values: Arc::new(Mutex::new(HashSet::new())),
+            network_recorder: Arc::new(RwLock::new(None)),
+            network_pause: Arc::new(Mutex::new(NetworkPause::default())),
+            peer_rotation: Arc::new(Mutex::new(PeerRotation::new())),
+            store_queue: Arc::new(Mutex::new(StoreQueue::default())),
+            direct_message_subscribers: Arc::new(Mutex::new(Vec::new())),
+            network_enabled: true,
+            gossip_bandwidth_limit: None,
+            retry_policy: RetryPolicy::default(),
+            max_traversal_depth: None,
+            chain_repair_enabled: false,
+            validation_package_definitions: Arc::new(RwLock::new(HashMap::new())),
+            native_validators: Arc::new(RwLock::new(HashMap::new())),
         })
     }
 
@@ -92,14 +192,333 @@ impl Context {
             .logger
             .lock()
             .or(Err(HolochainError::LoggingError))
-            .expect("Logger should work");;
+            .expect("Logger should work");
         logger.log(msg.into());
     }
 
+    /// The public key of the agent this instance runs for.
+    pub fn agent_public_key(&self) -> Result<PublicKey, HolochainError> {
+        self.agent_id.public_key()
+    }
+
     pub fn set_state(&mut self, state: Arc<RwLock<State>>) {
         self.state = Some(state);
     }
 
+    /// Declares the instance fully offline when set to false.
+    /// An offline instance never dispatches network actions: network look-ups are answered
+    /// from local storage only and publishing is a no-op. Networking is enabled by default.
+    pub fn set_network_enabled(&mut self, enabled: bool) {
+        self.network_enabled = enabled;
+    }
+
+    pub fn network_enabled(&self) -> bool {
+        self.network_enabled
+    }
+
+    /// Number of actions sent on the action channel that the action loop has not
+    /// processed yet. A steadily growing number points to a stuck reducer.
+    pub fn action_queue_depth(&self) -> usize {
+        self.action_channel.depth()
+    }
+
+    /// Caps outbound gossip to the given number of bytes per second, None for no limit.
+    /// Takes effect when the network gets initialized.
+    pub fn set_gossip_bandwidth_limit(&mut self, bytes_per_second: Option<u64>) {
+        self.gossip_bandwidth_limit = bytes_per_second;
+    }
+
+    pub fn gossip_bandwidth_limit(&self) -> Option<u64> {
+        self.gossip_bandwidth_limit
+    }
+
+    /// Sets how network look-ups (get_entry, get_validation_package) retry after a timeout.
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy.clone()
+    }
+
+    /// Limits how many source chain headers building a validation package may walk through.
+    /// Requests for packages covering a longer chain fail with
+    /// HolochainError::ValidationPackageTooLarge. None (the default) means no limit.
+    pub fn set_max_traversal_depth(&mut self, max_traversal_depth: Option<usize>) {
+        self.max_traversal_depth = max_traversal_depth;
+    }
+
+    pub fn max_traversal_depth(&self) -> Option<usize> {
+        self.max_traversal_depth
+    }
+
+    /// Allows committing entries at an explicit previous header (see commit_entry_at).
+    /// Only chain repair and migration tools should set this; instances running zome calls
+    /// keep it disabled, which is the default.
+    pub fn set_chain_repair_enabled(&mut self, enabled: bool) {
+        self.chain_repair_enabled = enabled;
+    }
+
+    pub fn chain_repair_enabled(&self) -> bool {
+        self.chain_repair_enabled
+    }
+
+    /// Registers the callback used to resolve concurrent updates of the same entry.
+    pub fn set_conflict_resolver(&self, resolver: Arc<ConflictResolver>) {
+        *self.conflict_resolver.write().unwrap() = Some(resolver);
+    }
+
+    pub fn conflict_resolver(&self) -> Option<Arc<ConflictResolver>> {
+        self.conflict_resolver.read().unwrap().clone()
+    }
+
+    /// Registers the encryption used for the content of entries whose type is shared
+    /// encrypted. Without it, such entries are stored in plaintext.
+    pub fn set_entry_crypto(&self, entry_crypto: Arc<EntryCrypto>) {
+        *self.entry_crypto.write().unwrap() = Some(entry_crypto);
+    }
+
+    pub fn entry_crypto(&self) -> Option<Arc<EntryCrypto>> {
+        self.entry_crypto.read().unwrap().clone()
+    }
+
+    /// Registers the signatures of our get requests, which lets peers grant us access
+    /// to entries whose access control list names us. Without it, our requests are
+    /// unsigned and we can't tell which agent sent the requests we receive.
+    pub fn set_request_signer(&self, request_signer: Arc<RequestSigner>) {
+        *self.request_signer.write().unwrap() = Some(request_signer);
+    }
+
+    pub fn request_signer(&self) -> Option<Arc<RequestSigner>> {
+        self.request_signer.read().unwrap().clone()
+    }
+
+    /// Registers the Tracer that receives the spans of the commit workflows.
+    pub fn set_tracer(&self, tracer: Arc<Tracer>) {
+        *self.tracer.write().unwrap() = Some(tracer);
+    }
+
+    pub fn tracer(&self) -> Option<Arc<Tracer>> {
+        self.tracer.read().unwrap().clone()
+    }
+
+    /// Registers a Rust function that validates entries of the given type in-process.
+    /// It gets consulted before the zome's WASM validation callback, which is then not run.
+    /// Meant for tests and embedded uses where loading WASM is not worth it.
+    pub fn register_native_validator(
+        &self,
+        entry_type: EntryType,
+        validator: Box<NativeValidator>,
+    ) {
+        self.native_validators
+            .write()
+            .unwrap()
+            .insert(entry_type, Arc::from(validator));
+    }
+
+    pub fn native_validator(&self, entry_type: &EntryType) -> Option<Arc<NativeValidator>> {
+        self.native_validators
+            .read()
+            .unwrap()
+            .get(entry_type)
+            .cloned()
+    }
+
+    /// Sets whether zome WASM gets parsed when the DNA is loaded or on first use.
+    /// Lazy by default.
+    pub fn set_wasm_load_policy(&self, policy: WasmLoadPolicy) {
+        self.wasm_cache.write().unwrap().set_policy(policy);
+    }
+
+    pub fn wasm_cache(&self) -> Arc<RwLock<WasmModuleCache>> {
+        self.wasm_cache.clone()
+    }
+
+    /// The parsed WASM module of the given zome, to be run with ribosome::run_parsed_dna().
+    /// Parses the zome's code on first use, later calls return the cached module without
+    /// touching the DNA.
+    pub fn get_parsed_module(&self, zome: &str) -> Result<Arc<Module>, HolochainError> {
+        if let Some(module) = self.wasm_cache.read().unwrap().get(zome) {
+            return Ok(module);
+        }
+        let dna = self.get_dna().ok_or(HolochainError::DnaMissing)?;
+        let wasm = dna.get_wasm_from_zome_name_checked(zome)?;
+        self.wasm_cache
+            .write()
+            .unwrap()
+            .get_or_load(zome, &wasm.code)
+    }
+
+    /// Replaces the clock zome function run times are measured with.
+    /// Meant for tests that need deterministic execution stats.
+    pub fn set_execution_clock(&self, clock: Box<Clock>) {
+        self.execution_stats.lock().unwrap().set_clock(clock);
+    }
+
+    /// The current instant according to the clock of the execution stats.
+    pub fn execution_clock_now(&self) -> Instant {
+        self.execution_stats.lock().unwrap().now()
+    }
+
+    /// Adds one call of a zome function that ran since `started` to the execution stats.
+    pub fn record_execution(&self, zome: &str, function: &str, started: Instant) {
+        let mut execution_stats = self.execution_stats.lock().unwrap();
+        let elapsed = execution_stats.now() - started;
+        execution_stats.record(zome, function, elapsed);
+    }
+
+    /// Number of calls and accumulated run time of every zome function
+    /// that has been run by this instance, sorted by zome and function name.
+    pub fn execution_stats(&self) -> Vec<FunctionStats> {
+        self.execution_stats.lock().unwrap().stats()
+    }
+
+    /// All metrics of this instance in the Prometheus text exposition format,
+    /// for a conductor to serve on a /metrics endpoint.
+    pub fn metrics_prometheus(&self) -> String {
+        metrics::prometheus_text(self)
+    }
+
+    pub fn cached_validation_package_definition(
+        &self,
+        app_entry_type: &AppEntryType,
+    ) -> Option<ValidationPackageDefinition> {
+        self.validation_package_definitions
+            .read()
+            .unwrap()
+            .get(app_entry_type)
+            .cloned()
+    }
+
+    pub fn cache_validation_package_definition(
+        &self,
+        app_entry_type: &AppEntryType,
+        definition: ValidationPackageDefinition,
+    ) {
+        self.validation_package_definitions
+            .write()
+            .unwrap()
+            .insert(app_entry_type.clone(), definition);
+    }
+
+    /// Parses every zome's WASM and asks the zomes for the validation package definitions
+    /// of all their app entry types, so that neither has to happen on the first request.
+    /// This makes start-up slower in exchange for a faster first zome call and validation.
+    pub fn warm_caches(context: &Arc<Context>) -> Result<(), HolochainError> {
+        let dna = context.get_dna().ok_or(HolochainError::DnaMissing)?;
+        for (zome_name, zome) in dna.zomes() {
+            if zome.code.code.is_empty() {
+                continue;
+            }
+            context
+                .wasm_cache
+                .write()
+                .unwrap()
+                .get_or_load(zome_name, &zome.code.code)?;
+            for entry_type in zome.entry_types.keys() {
+                if let EntryType::App(app_entry_type) = entry_type {
+                    get_app_entry_validation_package_definition(app_entry_type, context.clone())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn commit_rate_limiter(&self) -> Arc<Mutex<CommitRateLimiter>> {
+        self.commit_rate_limiter.clone()
+    }
+
+    /// Keys of the unique values reserved by commits in progress (see agent::unique_fields).
+    pub(crate) fn reserved_unique_values(&self) -> Arc<Mutex<HashSet<Address>>> {
+        self.reserved_unique_values.clone()
+    }
+
+    /// Starts or stops recording incoming network messages to a fixture file.
+    pub fn set_network_recorder(&self, recorder: Option<NetworkRecorder>) {
+        *self.network_recorder.write().unwrap() = recorder;
+    }
+
+    pub fn network_recorder(&self) -> Option<NetworkRecorder> {
+        self.network_recorder.read().unwrap().clone()
+    }
+
+    /// Stops syncing with the network: inbound messages get queued and outbound messages
+    /// deferred until resume_network() is called. Local operations continue as usual.
+    pub fn pause_network(&self) {
+        self.network_pause.lock().unwrap().pause();
+    }
+
+    /// Handles the inbound messages queued while paused and sends the deferred outbound ones.
+    pub fn resume_network(context: &Arc<Context>) {
+        let queued = context.network_pause.lock().unwrap().resume();
+        dispatch_action(
+            &context.action_channel,
+            ActionWrapper::new(Action::ResumeNetwork),
+        );
+        for protocol_wrapper in queued {
+            handle_protocol_wrapper(protocol_wrapper, context.clone());
+        }
+    }
+
+    pub fn network_paused(&self) -> bool {
+        self.network_pause.lock().unwrap().is_paused()
+    }
+
+    /// Queues the given inbound message if the network is paused.
+    /// Hands it back if not, so it can be handled right away.
+    pub fn hold_if_network_paused(
+        &self,
+        protocol_wrapper: ProtocolWrapper,
+    ) -> Option<ProtocolWrapper> {
+        self.network_pause.lock().unwrap().hold(protocol_wrapper)
+    }
+
+    /// Peers get_entry look-ups are sent to, see network::peer_rotation.
+    pub fn peer_rotation(&self) -> Arc<Mutex<PeerRotation>> {
+        self.peer_rotation.clone()
+    }
+
+    /// Gossiped entries waiting to be stored, see network::handler::store.
+    pub(crate) fn store_queue(&self) -> Arc<Mutex<StoreQueue>> {
+        self.store_queue.clone()
+    }
+
+    /// Returns a stream yielding every custom direct message this node receives from now on,
+    /// so request/response style handlers can consume them instead of polling.
+    /// Messages are buffered up to default_channel_buffer_size() per stream. Once a stream's
+    /// buffer is full, further messages can't be delivered to that stream until it gets
+    /// consumed (see publish_direct_message()).
+    pub fn direct_message_stream(&self) -> DirectMessageStream {
+        let (sender, receiver) = sync_channel(Self::default_channel_buffer_size());
+        self.direct_message_subscribers.lock().unwrap().push(sender);
+        DirectMessageStream::new(receiver)
+    }
+
+    /// Hands a received direct message to all open direct message streams without blocking
+    /// the network handler on slow consumers. Streams that got dropped are forgotten.
+    /// Fails if the message could not be delivered to a stream because its buffer is full.
+    pub(crate) fn publish_direct_message(&self, message: &DirectMessage) -> HcResult<()> {
+        let mut full_streams = 0;
+        self.direct_message_subscribers
+            .lock()
+            .unwrap()
+            .retain(|sender| match sender.try_send(message.clone()) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    full_streams += 1;
+                    true
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            });
+        if full_streams > 0 {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "{} direct message stream(s) full, message not delivered to them",
+                full_streams
+            )));
+        }
+        Ok(())
+    }
+
     pub fn state(&self) -> Option<RwLockReadGuard<State>> {
         match self.state {
             None => None,
@@ -138,6 +557,123 @@ impl Context {
         dna
     }
 
+    /// Swaps the DNA of the running instance, e.g. to pick up changed zome code during
+    /// development without a restart.
+    /// The new DNA has to keep the name, the uuid and every entry type of the current one so
+    /// existing chain data stays valid. Incompatible DNAs are rejected and the current DNA
+    /// stays active.
+    /// Clears the parsed WASM modules and cached validation package definitions.
+    pub fn reload_dna(&self, mut new_dna: Dna) -> Result<(), HolochainError> {
+        let old_dna = self
+            .state()
+            .and_then(|state| state.nucleus().dna())
+            .ok_or(HolochainError::DnaMissing)?;
+        new_dna
+            .check_function_names()
+            .map_err(HolochainError::Dna)?;
+        new_dna
+            .check_entry_type_names()
+            .map_err(HolochainError::Dna)?;
+        // A different name or uuid makes it a different app, not a new version of this one
+        if new_dna.name() != old_dna.name() || new_dna.uuid() != old_dna.uuid() {
+            let message = format!(
+                "Reloaded DNA '{}' ({}) is not a version of DNA '{}' ({})",
+                new_dna.name(),
+                new_dna.uuid(),
+                old_dna.name(),
+                old_dna.uuid()
+            );
+            return Err(HolochainError::Dna(DnaError::IncompatibleOverride(message)));
+        }
+        if let Some(entry_type) = old_dna
+            .zomes()
+            .values()
+            .flat_map(|zome| zome.entry_types.keys())
+            .find(|entry_type| {
+                !new_dna
+                    .zomes()
+                    .values()
+                    .any(|zome| zome.entry_types.contains_key(entry_type))
+            })
+        {
+            let message = format!(
+                "Reloaded DNA removes entry type '{}'",
+                String::from(entry_type.clone())
+            );
+            return Err(HolochainError::Dna(DnaError::IncompatibleOverride(message)));
+        }
+
+        expand_wildcard_capabilities(&mut new_dna);
+        dispatch_action_and_wait(
+            &self.action_channel,
+            &self.observer_channel,
+            ActionWrapper::new(Action::ReloadDna(new_dna)),
+        );
+        self.wasm_cache.write().unwrap().clear();
+        self.validation_package_definitions.write().unwrap().clear();
+        Ok(())
+    }
+
+    /// Captures the agent's source chain and the content of the CAS and EAV storage
+    /// in one serializable InstanceSnapshot, e.g. to set up tests without replaying commits
+    /// or to recover from a crash.
+    pub fn snapshot_state(&self) -> Result<InstanceSnapshot, HolochainError> {
+        let top_chain_header = self
+            .state()
+            .ok_or_else(|| HolochainError::ErrorGeneric(String::from("State not initialized")))?
+            .agent()
+            .top_chain_header();
+
+        let storage = self.file_storage.read()?;
+        let mut contents = Vec::new();
+        for address in storage.addresses()? {
+            if let Some(content) = storage.fetch(&address)? {
+                contents.push((address, String::from(content)));
+            }
+        }
+        contents.sort();
+
+        let eavs = self
+            .eav_storage
+            .read()?
+            .fetch_eav(None, None, None)?
+            .into_iter()
+            .collect();
+
+        Ok(InstanceSnapshot {
+            top_chain_header,
+            contents,
+            eavs,
+        })
+    }
+
+    /// Writes the content of the snapshot into this context's CAS and EAV storage and
+    /// returns the state to start an instance with (see Instance::from_state()).
+    pub fn restore_state(&self, snapshot: &InstanceSnapshot) -> Result<State, HolochainError> {
+        {
+            let mut storage = self.file_storage.write()?;
+            for (address, content) in snapshot.contents.iter() {
+                storage.add(&SnapshotContent {
+                    address: address.clone(),
+                    content: content.clone(),
+                })?;
+            }
+            let mut eav_storage = self.eav_storage.write()?;
+            for eav in snapshot.eavs.iter() {
+                eav_storage.add_eav(eav)?;
+            }
+        }
+
+        let context = Arc::new(self.clone());
+        match snapshot.top_chain_header {
+            Some(ref top_chain_header) => State::try_from_agent_snapshot(
+                context,
+                AgentStateSnapshot::new(top_chain_header.clone()),
+            ),
+            None => Ok(State::new(context)),
+        }
+    }
+
     pub fn get_wasm(&self, zome: &str) -> Option<DnaWasm> {
         let dna = self.get_dna().expect("Callback called without DNA set!");
         dna.get_wasm_from_zome_name(zome)
@@ -156,14 +692,35 @@ pub mod tests {
     extern crate tempfile;
     extern crate test_utils;
     use self::tempfile::tempdir;
+    use self::test_utils::create_test_dna_with_wat;
     use super::*;
     use crate::{
-        context::mock_network_config, instance::tests::test_logger, persister::SimplePersister,
-        state::State,
+        agent::actions::commit::commit_entry,
+        context::mock_network_config,
+        instance::tests::{test_context, test_instance_and_context_by_name, test_logger},
+        nucleus::{
+            actions::{
+                build_validation_package::build_validation_package,
+                tests::{instance_by_name, test_dna, test_entry_package_entry},
+            },
+            ribosome::callback::CallbackResult,
+        },
+        persister::SimplePersister,
+        state::{InstanceSnapshot, State},
     };
+    use futures::executor::block_on;
     use holochain_cas_implementations::{cas::file::FilesystemStorage, eav::file::EavFileStorage};
-    use holochain_core_types::agent::AgentId;
-    use std::sync::{Arc, Mutex, RwLock};
+    use holochain_core_types::{
+        agent::AgentId,
+        cas::content::{Address, AddressableContent},
+        chain_header::ChainHeader,
+        dna::zome::entry_types::EntryTypeDef,
+        entry::{entry_type::EntryType, test_entry},
+    };
+    use std::{
+        convert::TryFrom,
+        sync::{Arc, Mutex, RwLock},
+    };
 
     #[test]
     fn default_buffer_size_test() {
@@ -199,6 +756,29 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn agent_public_key_test() {
+        let dna = create_test_dna_with_wat("test_zome", "test_cap", None);
+        let (_, context) = test_instance_and_context_by_name(dna, "alice").unwrap();
+
+        let agent_id_header = {
+            let state = context.state().unwrap();
+            let agent = state.agent();
+            agent
+                .chain()
+                .iter_type(&agent.top_chain_header(), &EntryType::AgentId)
+                .next()
+                .expect("instance should have committed its agent id")
+        };
+
+        let public_key = context.agent_public_key().unwrap();
+        assert_eq!(
+            agent_id_header.entry_address(),
+            &Address::from(public_key.clone())
+        );
+        assert_eq!(context.agent_id.address(), Address::from(public_key));
+    }
+
     #[test]
     #[should_panic]
     #[cfg(not(windows))] // RwLock does not panic on windows since mutexes are recursive
@@ -228,4 +808,169 @@ pub mod tests {
             context.state();
         }
     }
+
+    #[test]
+    fn warm_caches_test() {
+        let (_instance, context) = instance_by_name("jill", test_dna());
+        Context::warm_caches(&context).expect("Could not warm caches");
+
+        let app_entry_type = AppEntryType::from("package_entry");
+        assert!(context.wasm_cache().read().unwrap().is_loaded("test_zome"));
+        assert_eq!(
+            Some(ValidationPackageDefinition::Entry),
+            context.cached_validation_package_definition(&app_entry_type)
+        );
+
+        let wasm = context.get_wasm("test_zome").unwrap();
+        let module = context
+            .wasm_cache()
+            .write()
+            .unwrap()
+            .get_or_load("test_zome", &wasm.code)
+            .unwrap();
+
+        block_on(build_validation_package(
+            &test_entry_package_entry(),
+            &context,
+        ))
+        .expect("Could not build validation package");
+
+        // the module parsed while warming up is still the one in use
+        assert!(Arc::ptr_eq(
+            &module,
+            &context
+                .wasm_cache()
+                .write()
+                .unwrap()
+                .get_or_load("test_zome", &wasm.code)
+                .unwrap()
+        ));
+    }
+
+    #[test]
+    fn get_parsed_module_test() {
+        let (_instance, context) = instance_by_name("jane", test_dna());
+        let module = context.get_parsed_module("test_zome").unwrap();
+        assert!(Arc::ptr_eq(
+            &module,
+            &context.get_parsed_module("test_zome").unwrap()
+        ));
+        assert_eq!(
+            Some(HolochainError::Dna(DnaError::ZomeNotFound(String::from(
+                "Zome 'no_zome' not found"
+            )))),
+            context.get_parsed_module("no_zome").err()
+        );
+
+        // validation callbacks run on the parsed module
+        let app_entry_type = AppEntryType::from("package_entry");
+        assert_eq!(
+            Ok(CallbackResult::ValidationPackageDefinition(
+                ValidationPackageDefinition::Entry
+            )),
+            get_app_entry_validation_package_definition(&app_entry_type, context.clone())
+        );
+        assert!(Arc::ptr_eq(
+            &module,
+            &context.get_parsed_module("test_zome").unwrap()
+        ));
+    }
+
+    #[test]
+    fn reload_dna_test() {
+        let (_instance, context) = instance_by_name("jack", test_dna());
+        Context::warm_caches(&context).expect("Could not warm caches");
+
+        let mut new_dna = test_dna();
+        new_dna
+            .zomes_mut()
+            .get_mut("test_zome")
+            .unwrap()
+            .entry_types
+            .insert("package_extra".into(), EntryTypeDef::new());
+        context
+            .reload_dna(new_dna.clone())
+            .expect("Could not reload compatible DNA");
+        assert_eq!(Some(new_dna.clone()), context.get_dna());
+        assert!(!context.wasm_cache().read().unwrap().is_loaded("test_zome"));
+        assert_eq!(
+            None,
+            context.cached_validation_package_definition(&AppEntryType::from("package_entry"))
+        );
+
+        let mut incompatible_dna = new_dna.clone();
+        incompatible_dna
+            .zomes_mut()
+            .get_mut("test_zome")
+            .unwrap()
+            .entry_types
+            .remove(&EntryType::from("package_entry"));
+        match context.reload_dna(incompatible_dna) {
+            Err(HolochainError::Dna(DnaError::IncompatibleOverride(message))) => {
+                assert!(message.contains("package_entry"))
+            }
+            other => panic!("expected IncompatibleOverride, got {:?}", other),
+        }
+        assert_eq!(Some(new_dna.clone()), context.get_dna());
+
+        let mut renamed_dna = new_dna.clone();
+        renamed_dna.set_name(String::from("other app"));
+        let mut other_uuid_dna = new_dna.clone();
+        other_uuid_dna.set_uuid(String::from("other uuid"));
+        for other_app_dna in vec![renamed_dna, other_uuid_dna] {
+            match context.reload_dna(other_app_dna) {
+                Err(HolochainError::Dna(DnaError::IncompatibleOverride(message))) => {
+                    assert!(message.contains("is not a version of"))
+                }
+                other => panic!("expected IncompatibleOverride, got {:?}", other),
+            }
+        }
+        assert_eq!(Some(new_dna), context.get_dna());
+    }
+
+    #[test]
+    fn snapshot_and_restore_state_test() {
+        let (_instance, context) = instance_by_name("jill", test_dna());
+        block_on(commit_entry(test_entry(), None, &context)).unwrap();
+        let snapshot = context.snapshot_state().unwrap();
+        assert!(snapshot
+            .contents
+            .iter()
+            .any(|(address, _)| *address == test_entry().address()));
+
+        let snapshot_json = JsonString::from(snapshot.clone());
+        let snapshot = InstanceSnapshot::try_from(snapshot_json).unwrap();
+
+        let fresh_context = test_context("jill");
+        let state = fresh_context.restore_state(&snapshot).unwrap();
+
+        let original_state = context.state().unwrap();
+        let original_top = original_state.agent().top_chain_header();
+        assert_eq!(original_top, state.agent().top_chain_header());
+        let chain: Vec<ChainHeader> = state.agent().chain().iter(&original_top).collect();
+        let original_chain: Vec<ChainHeader> =
+            original_state.agent().chain().iter(&original_top).collect();
+        assert_eq!(3, chain.len());
+        assert_eq!(original_chain, chain);
+        assert_eq!(context.get_dna(), state.nucleus().dna());
+
+        let storage = fresh_context.file_storage.read().unwrap();
+        let original_storage = context.file_storage.read().unwrap();
+        assert_eq!(original_storage.addresses(), storage.addresses());
+        for (address, _) in snapshot.contents.iter() {
+            assert_eq!(original_storage.fetch(address), storage.fetch(address));
+        }
+        assert_eq!(
+            context
+                .eav_storage
+                .read()
+                .unwrap()
+                .fetch_eav(None, None, None),
+            fresh_context
+                .eav_storage
+                .read()
+                .unwrap()
+                .fetch_eav(None, None, None)
+        );
+    }
 }
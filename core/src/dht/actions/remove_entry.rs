@@ -3,7 +3,7 @@ extern crate serde_json;
 use crate::{
     action::{Action, ActionWrapper},
     context::Context,
-    instance::dispatch_action,
+    instance::{dispatch_action, ActionSender},
 };
 use futures::{
     future::Future,
@@ -12,7 +12,7 @@ use futures::{
 use holochain_core_types::{cas::content::Address, error::HolochainError};
 use std::{
     pin::{Pin, Unpin},
-    sync::{mpsc::SyncSender, Arc},
+    sync::Arc,
 };
 
 /// Remove Entry Action Creator
@@ -20,7 +20,7 @@ use std::{
 /// Returns a future that resolves to an Ok(ActionWrapper) or an Err(HolochainError).
 pub fn remove_entry(
     context: &Arc<Context>,
-    action_channel: &SyncSender<ActionWrapper>,
+    action_channel: &ActionSender,
     deleted_address: Address,
     deletion_address: Address,
 ) -> RemoveEntryFuture {
@@ -7,7 +7,9 @@ use crate::{
 };
 use holochain_core_types::{
     cas::content::{Address, AddressableContent},
-    crud_status::{create_crud_link_eav, create_crud_status_eav, CrudStatus, STATUS_NAME},
+    crud_status::{
+        create_crud_link_eav, create_crud_status_eav, create_deletion_eav, CrudStatus, STATUS_NAME,
+    },
     eav::EntityAttributeValue,
     entry::Entry,
     error::HolochainError,
@@ -48,6 +50,7 @@ fn resolve_reducer(action_wrapper: &ActionWrapper) -> Option<DhtReducer> {
         Action::UpdateEntry(_) => Some(reduce_update_entry),
         Action::RemoveEntry(_) => Some(reduce_remove_entry),
         Action::AddLink(_) => Some(reduce_add_link),
+        Action::Quarantine(_) => Some(reduce_quarantine),
         _ => None,
     }
 }
@@ -89,10 +92,41 @@ pub(crate) fn reduce_hold_entry(
         return None;
     }
 
+    // Index deletions by the entry they delete
+    if let Entry::Deletion(deletion_entry) = entry {
+        let deletion_eav = create_deletion_eav(
+            &deletion_entry.clone().deleted_entry_address(),
+            &entry.address(),
+        );
+        let res = (*meta_storage.write().unwrap()).add_eav(&deletion_eav);
+        if res.is_err() {
+            // TODO #439 - Log the error. Once we have better logging.
+            println!(
+                "reduce_hold_entry: meta_storage write failed!: {:?}",
+                res.err().unwrap()
+            );
+            return None;
+        }
+    }
+
     // Done
     Some(new_store)
 }
 
+//
+pub(crate) fn reduce_quarantine(
+    _context: Arc<Context>,
+    old_store: &DhtStore,
+    action_wrapper: &ActionWrapper,
+) -> Option<DhtStore> {
+    let action = action_wrapper.action();
+    let (address, reason) = unwrap_to!(action => Action::Quarantine);
+
+    let mut new_store = (*old_store).clone();
+    new_store.add_to_quarantine(address.clone(), reason.clone());
+    Some(new_store)
+}
+
 //
 pub(crate) fn reduce_add_link(
     _context: Arc<Context>,
@@ -264,14 +298,14 @@ pub mod tests {
     use crate::{
         action::{Action, ActionWrapper},
         dht::{
-            dht_reducers::{reduce, reduce_hold_entry},
-            dht_store::DhtStore,
+            dht_reducers::{reduce, reduce_hold_entry, reduce_quarantine},
+            dht_store::{DhtStore, QUARANTINE_CAPACITY},
         },
         instance::tests::test_context,
         state::test_store,
     };
     use holochain_core_types::{
-        cas::content::AddressableContent,
+        cas::content::{Address, AddressableContent},
         entry::{test_entry, test_sys_entry, Entry},
         link::Link,
     };
@@ -410,4 +444,29 @@ pub mod tests {
         assert_eq!(&entry, &result_entry,);
     }
 
+    #[test]
+    fn reduce_quarantine_forgets_oldest_beyond_capacity() {
+        let context = test_context("bill");
+        let store = test_store(context.clone());
+
+        let mut dht_store = (*store.dht()).clone();
+        for index in 0..=QUARANTINE_CAPACITY {
+            let action_wrapper = ActionWrapper::new(Action::Quarantine((
+                Address::from(format!("entry {}", index)),
+                String::from("invalid"),
+            )));
+            dht_store = reduce_quarantine(context.clone(), &dht_store, &action_wrapper)
+                .expect("quarantining should give a new store");
+        }
+
+        assert_eq!(QUARANTINE_CAPACITY, dht_store.quarantine().len());
+        assert!(!dht_store.is_quarantined(&Address::from("entry 0")));
+        assert_eq!(
+            Some(&String::from("invalid")),
+            dht_store
+                .quarantine()
+                .get(&Address::from(format!("entry {}", QUARANTINE_CAPACITY)))
+        );
+    }
+
 }
@@ -6,10 +6,14 @@ use holochain_core_types::{
     link::Link,
 };
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     sync::{Arc, RwLock},
 };
 
+/// Maximum number of quarantined entries remembered. Beyond it the oldest ones get
+/// forgotten, and would be validated again if they got gossiped to us again.
+pub const QUARANTINE_CAPACITY: usize = 1024;
+
 /// The state-slice for the DHT.
 /// Holds the agent's local shard and interacts with the network module
 #[derive(Clone, Debug)]
@@ -19,6 +23,11 @@ pub struct DhtStore {
     meta_storage: Arc<RwLock<EntityAttributeValueStorage>>,
 
     actions: HashMap<ActionWrapper, Result<Address, HolochainError>>,
+
+    // Addresses of entries that failed validation permanently, with the failure reason
+    quarantine: HashMap<Address, String>,
+    // Quarantined addresses, oldest first
+    quarantine_order: VecDeque<Address>,
 }
 
 impl PartialEq for DhtStore {
@@ -29,6 +38,7 @@ impl PartialEq for DhtStore {
         let other_meta = &other.meta_storage.clone();
 
         self.actions == other.actions
+            && self.quarantine == other.quarantine
             && (*content.read().unwrap()).get_id() == (*other_content.read().unwrap()).get_id()
             && *meta.read().unwrap() == *other_meta.read().unwrap()
     }
@@ -45,6 +55,8 @@ impl DhtStore {
             content_storage,
             meta_storage,
             actions: HashMap::new(),
+            quarantine: HashMap::new(),
+            quarantine_order: VecDeque::new(),
         }
     }
 
@@ -85,4 +97,22 @@ impl DhtStore {
     ) -> &mut HashMap<ActionWrapper, Result<Address, HolochainError>> {
         &mut self.actions
     }
+    pub fn quarantine(&self) -> &HashMap<Address, String> {
+        &self.quarantine
+    }
+    pub fn is_quarantined(&self, address: &Address) -> bool {
+        self.quarantine.contains_key(address)
+    }
+    /// Quarantines the address, forgetting the oldest quarantined addresses beyond
+    /// QUARANTINE_CAPACITY.
+    pub(crate) fn add_to_quarantine(&mut self, address: Address, reason: String) {
+        if self.quarantine.insert(address.clone(), reason).is_none() {
+            self.quarantine_order.push_back(address);
+        }
+        while self.quarantine_order.len() > QUARANTINE_CAPACITY {
+            if let Some(oldest) = self.quarantine_order.pop_front() {
+                self.quarantine.remove(&oldest);
+            }
+        }
+    }
 }
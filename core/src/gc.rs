@@ -0,0 +1,192 @@
+//! Garbage collection for the content addressable storage.
+//!
+//! Content is kept if it can be reached from one of the given roots by following chain
+//! header links, header-to-entry references, link bases/targets and deletion targets.
+//! The source chain and the entries held for the DHT are always roots, since the agent
+//! chain, the DHT shard and the persister share one storage.
+//! Everything else is considered orphaned (e.g. artifacts of aborted commits) and removed.
+use crate::{agent::state::AGENT_SNAPSHOT_ADDRESS, context::Context};
+use holochain_core_types::{
+    cas::{
+        content::{Address, AddressableContent, Content},
+        storage::ContentAddressableStorage,
+    },
+    chain_header::ChainHeader,
+    crud_status::STATUS_NAME,
+    entry::Entry,
+    error::HolochainError,
+};
+use std::{
+    collections::{HashSet, VecDeque},
+    convert::TryFrom,
+};
+
+/// Counts of a garbage collection pass.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GcReport {
+    pub retained: usize,
+    pub removed: usize,
+}
+
+/// Addresses the given content refers to.
+fn referenced_addresses(content: &Content) -> Vec<Address> {
+    if let Ok(chain_header) = ChainHeader::try_from_content(content) {
+        return chain_header_references(&chain_header);
+    }
+    match Entry::try_from(content.clone()) {
+        Ok(Entry::ChainHeader(chain_header)) => chain_header_references(&chain_header),
+        Ok(Entry::LinkAdd(link_add)) => vec![
+            link_add.link().base().clone(),
+            link_add.link().target().clone(),
+        ],
+        Ok(Entry::LinkList(link_list)) => link_list
+            .links()
+            .iter()
+            .flat_map(|link| vec![link.base().clone(), link.target().clone()])
+            .collect(),
+        Ok(Entry::Deletion(deletion_entry)) => vec![deletion_entry.deleted_entry_address()],
+        _ => Vec::new(),
+    }
+}
+
+fn chain_header_references(chain_header: &ChainHeader) -> Vec<Address> {
+    vec![
+        Some(chain_header.entry_address().clone()),
+        chain_header.link(),
+        chain_header.link_same_type(),
+        chain_header.link_crud(),
+    ]
+    .into_iter()
+    .filter_map(|address| address)
+    .collect()
+}
+
+/// Marks everything reachable from the roots and removes all other content from the storage.
+pub fn collect_garbage(
+    storage: &mut ContentAddressableStorage,
+    roots: &[Address],
+) -> Result<GcReport, HolochainError> {
+    let mut reachable = HashSet::new();
+    let mut queue: VecDeque<Address> = roots.iter().cloned().collect();
+    while let Some(address) = queue.pop_front() {
+        if reachable.contains(&address) {
+            continue;
+        }
+        if let Some(content) = storage.fetch(&address)? {
+            queue.extend(referenced_addresses(&content));
+        }
+        reachable.insert(address);
+    }
+
+    let mut report = GcReport::default();
+    for address in storage.addresses()? {
+        if reachable.contains(&address) {
+            report.retained += 1;
+        } else {
+            storage.remove(&address)?;
+            report.removed += 1;
+        }
+    }
+    Ok(report)
+}
+
+impl Context {
+    /// Removes all content from the CAS that is not reachable from the given roots.
+    /// The head of the agent's source chain, the persisted agent state and every entry held
+    /// for the DHT (i.e. every entry with a CRUD status) are always added to the roots so
+    /// neither the source chain nor foreign entries this node holds get collected.
+    /// The storage stays locked from computing these roots until the collection is done,
+    /// so content committed in the meantime can't be taken for garbage.
+    pub fn gc_cas(&self, roots: &[Address]) -> Result<GcReport, HolochainError> {
+        let mut storage = self.file_storage.write()?;
+        let mut roots = roots.to_vec();
+        roots.push(Address::from(AGENT_SNAPSHOT_ADDRESS));
+        roots.extend(
+            self.eav_storage
+                .read()?
+                .fetch_eav(None, Some(STATUS_NAME.to_string()), None)?
+                .into_iter()
+                .map(|eav| eav.entity()),
+        );
+        if let Some(top_chain_header) = self
+            .state()
+            .and_then(|state| state.agent().top_chain_header())
+        {
+            roots.push(top_chain_header.address());
+        }
+        collect_garbage(&mut *storage, &roots)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        dht::actions::hold::hold_entry,
+        nucleus::actions::tests::{instance_by_name, test_dna},
+        workflows::author_entry::author_entry,
+    };
+    use futures::executor::block_on;
+    use holochain_core_types::{
+        entry::{entry_type::test_app_entry_type_b, test_entry, test_entry_b, test_entry_c},
+        json::RawString,
+        link::{link_add::LinkAdd, Link},
+    };
+
+    #[test]
+    fn gc_cas_collects_only_orphans() {
+        let (_instance, context) = instance_by_name("jill", test_dna());
+        let entry_address =
+            block_on(author_entry(&test_entry(), None, &context)).expect("commit should work");
+
+        // referenced through a link entry that is kept as a root
+        let target = test_entry_c();
+        let link_entry = Entry::LinkAdd(LinkAdd::from_link(&Link::new(
+            &entry_address,
+            &target.address(),
+            "test-tag",
+        )));
+        // never referenced by anything
+        let orphan = Entry::App(test_app_entry_type_b(), RawString::from("orphan").into());
+        {
+            let mut storage = context.file_storage.write().unwrap();
+            storage.add(&target).unwrap();
+            storage.add(&link_entry).unwrap();
+            storage.add(&orphan).unwrap();
+        }
+        let chain_addresses: Vec<Address> = {
+            let state = context.state().unwrap();
+            let agent = state.agent();
+            agent
+                .chain()
+                .iter(&agent.top_chain_header())
+                .flat_map(|header| vec![header.address(), header.entry_address().clone()])
+                .collect()
+        };
+
+        let report = context.gc_cas(&[link_entry.address()]).unwrap();
+        assert_eq!(1, report.removed);
+
+        let storage = context.file_storage.read().unwrap();
+        assert_eq!(Ok(false), storage.contains(&orphan.address()));
+        assert_eq!(Ok(true), storage.contains(&link_entry.address()));
+        assert_eq!(Ok(true), storage.contains(&target.address()));
+        for address in chain_addresses {
+            assert_eq!(Ok(true), storage.contains(&address));
+        }
+    }
+
+    #[test]
+    fn gc_cas_keeps_held_entries() {
+        let (_instance, context) = instance_by_name("jack", test_dna());
+        // held for the DHT on behalf of another agent, not part of the local chain
+        let foreign_entry = test_entry_b();
+        block_on(hold_entry(&foreign_entry, &context)).expect("hold should work");
+
+        let report = context.gc_cas(&[]).unwrap();
+        assert_eq!(0, report.removed);
+
+        let storage = context.file_storage.read().unwrap();
+        assert_eq!(Ok(true), storage.contains(&foreign_entry.address()));
+    }
+}
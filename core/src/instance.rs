@@ -1,7 +1,8 @@
 use crate::{action::ActionWrapper, context::Context, state::State};
 use std::{
     sync::{
-        mpsc::{sync_channel, Receiver, SyncSender},
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{sync_channel, Receiver, SendError, SyncSender},
         Arc, RwLock, RwLockReadGuard,
     },
     thread,
@@ -16,7 +17,7 @@ pub const RECV_DEFAULT_TIMEOUT_MS: Duration = Duration::from_millis(10000);
 pub struct Instance {
     /// The object holding the state. Actions go through the store sequentially.
     state: Arc<RwLock<State>>,
-    action_channel: SyncSender<ActionWrapper>,
+    action_channel: ActionSender,
     observer_channel: SyncSender<Observer>,
 }
 
@@ -29,13 +30,57 @@ pub struct Observer {
 
 pub static DISPATCH_WITHOUT_CHANNELS: &str = "dispatch called without channels open";
 
+/// Sending end of the action channel.
+/// Keeps count of the actions that were sent but not yet processed by the action loop,
+/// shared between all clones.
+#[derive(Clone)]
+pub struct ActionSender {
+    sender: SyncSender<ActionWrapper>,
+    depth: Arc<AtomicUsize>,
+}
+
+impl ActionSender {
+    pub fn new(sender: SyncSender<ActionWrapper>) -> Self {
+        ActionSender {
+            sender,
+            depth: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Sends the action, blocking while the channel buffer is full.
+    /// The action counts as pending from the moment this gets called.
+    pub fn send(&self, action_wrapper: ActionWrapper) -> Result<(), SendError<ActionWrapper>> {
+        self.depth.fetch_add(1, Ordering::SeqCst);
+        self.sender.send(action_wrapper).map_err(|error| {
+            self.depth.fetch_sub(1, Ordering::SeqCst);
+            error
+        })
+    }
+
+    /// Number of actions sent that have not been processed yet.
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::SeqCst)
+    }
+
+    /// Called by the action loop once it is done with an action.
+    fn processed(&self) {
+        self.depth.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl From<SyncSender<ActionWrapper>> for ActionSender {
+    fn from(sender: SyncSender<ActionWrapper>) -> Self {
+        ActionSender::new(sender)
+    }
+}
+
 impl Instance {
     pub fn default_channel_buffer_size() -> usize {
         100
     }
 
     /// get a clone of the action channel
-    pub fn action_channel(&self) -> SyncSender<ActionWrapper> {
+    pub fn action_channel(&self) -> ActionSender {
         self.action_channel.clone()
     }
 
@@ -85,7 +130,7 @@ impl Instance {
             sync_channel::<ActionWrapper>(Self::default_channel_buffer_size());
         let (tx_observer, rx_observer) =
             sync_channel::<Observer>(Self::default_channel_buffer_size());
-        self.action_channel = tx_action.clone();
+        self.action_channel = ActionSender::new(tx_action);
         self.observer_channel = tx_observer.clone();
 
         (rx_action, rx_observer)
@@ -115,6 +160,7 @@ impl Instance {
                     &rx_observer,
                     &sub_context,
                 );
+                sync_self.action_channel.processed();
             }
         });
     }
@@ -180,7 +226,7 @@ impl Instance {
         let (tx_observer, _) = sync_channel(1);
         Instance {
             state: Arc::new(RwLock::new(State::new(context))),
-            action_channel: tx_action,
+            action_channel: ActionSender::new(tx_action),
             observer_channel: tx_observer,
         }
     }
@@ -190,7 +236,7 @@ impl Instance {
         let (tx_observer, _) = sync_channel(1);
         Instance {
             state: Arc::new(RwLock::new(state)),
-            action_channel: tx_action,
+            action_channel: ActionSender::new(tx_action),
             observer_channel: tx_observer,
         }
     }
@@ -214,7 +260,7 @@ impl Instance {
 ///
 /// Panics if the channels passed are disconnected.
 pub fn dispatch_action_and_wait(
-    action_channel: &SyncSender<ActionWrapper>,
+    action_channel: &ActionSender,
     observer_channel: &SyncSender<Observer>,
     action_wrapper: ActionWrapper,
 ) {
@@ -248,7 +294,7 @@ pub fn dispatch_action_and_wait(
 ///
 /// Panics if the channels passed are disconnected.
 pub fn dispatch_action_with_observer<F>(
-    action_channel: &SyncSender<ActionWrapper>,
+    action_channel: &ActionSender,
     observer_channel: &SyncSender<Observer>,
     action_wrapper: ActionWrapper,
     closure: F,
@@ -270,7 +316,7 @@ pub fn dispatch_action_with_observer<F>(
 /// # Panics
 ///
 /// Panics if the channels passed are disconnected.
-pub fn dispatch_action(action_channel: &SyncSender<ActionWrapper>, action_wrapper: ActionWrapper) {
+pub fn dispatch_action(action_channel: &ActionSender, action_wrapper: ActionWrapper) {
     action_channel
         .send(action_wrapper)
         .expect(DISPATCH_WITHOUT_CHANNELS);
@@ -496,7 +542,7 @@ pub mod tests {
 
         /// fair warning... use test_instance_blank() if you want a minimal instance
         assert!(
-            !dna.zomes.clone().is_empty(),
+            !dna.zomes().clone().is_empty(),
             "Empty zomes = No genesis = infinite loops below!"
         );
 
@@ -556,8 +602,8 @@ pub mod tests {
     #[cfg_attr(tarpaulin, skip)]
     pub fn test_instance_blank() -> Instance {
         let mut dna = Dna::new();
-        dna.zomes.insert("".to_string(), Zome::default());
-        dna.uuid = "2297b5bc-ef75-4702-8e15-66e0545f3482".into();
+        dna.zomes_mut().insert("".to_string(), Zome::default());
+        dna.set_uuid("2297b5bc-ef75-4702-8e15-66e0545f3482".into());
         test_instance(dna).expect("Blank instance could not be initialized!")
     }
 
@@ -675,6 +721,28 @@ pub mod tests {
         );
     }
 
+    #[test]
+    /// tests that actions sent faster than they get processed show up as queue depth
+    fn action_queue_depth_reports_backlog() {
+        let mut instance = Instance::new(test_context("jason"));
+        let (rx_action, rx_observer) = instance.initialize_channels();
+        let context = instance.initialize_context(test_context("jane"));
+        assert_eq!(context.action_queue_depth(), 0);
+
+        for depth in 1..=3 {
+            dispatch_action(&context.action_channel, test_action_wrapper_commit());
+            assert_eq!(context.action_queue_depth(), depth);
+        }
+
+        let action_wrapper = rx_action.recv().unwrap();
+        instance.process_action(action_wrapper, Vec::new(), &rx_observer, &context);
+        instance.action_channel.processed();
+        assert_eq!(context.action_queue_depth(), 2);
+
+        dispatch_action(&context.action_channel, test_action_wrapper_commit());
+        assert_eq!(context.action_queue_depth(), 3);
+    }
+
     #[test]
     /// tests that an unimplemented genesis allows the nucleus to initialize
     /// @TODO is this right? should return unimplemented?
@@ -752,6 +820,64 @@ pub mod tests {
         );
     }
 
+    #[test]
+    /// tests that a succeeding __hdk_init allows the nucleus to initialize
+    fn test_init_ok() {
+        let dna = test_utils::create_test_dna_with_wat(
+            "test_zome",
+            Callback::Init.capability().as_str(),
+            Some(
+                r#"
+            (module
+                (memory (;0;) 17)
+                (func (export "__hdk_init") (param $p0 i32) (result i32)
+                    i32.const 0
+                )
+                (data (i32.const 0)
+                    ""
+                )
+                (export "memory" (memory 0))
+            )
+        "#,
+            ),
+        );
+
+        let maybe_instance = test_instance(dna);
+        assert!(maybe_instance.is_ok());
+
+        let instance = maybe_instance.unwrap();
+        assert!(instance.state().nucleus().has_initialized());
+    }
+
+    #[test]
+    /// tests that a failing __hdk_init aborts initialization with an error naming the zome
+    fn test_init_err() {
+        let dna = test_utils::create_test_dna_with_wat(
+            "test_zome",
+            Callback::Init.capability().as_str(),
+            Some(
+                r#"
+            (module
+                (memory (;0;) 17)
+                (func (export "__hdk_init") (param $p0 i32) (result i32)
+                    i32.const 9
+                )
+                (data (i32.const 0)
+                    "1337.0"
+                )
+                (export "memory" (memory 0))
+            )
+        "#,
+            ),
+        );
+
+        let instance = test_instance(dna);
+        assert!(instance.is_err());
+        let error = instance.err().unwrap();
+        assert!(error.contains("Initialization (__hdk_init) of zome"));
+        assert!(error.contains("test_zome"));
+    }
+
     /// Committing a DnaEntry to source chain should work
     #[test]
     fn can_commit_dna() {
@@ -44,12 +44,15 @@ pub mod action;
 pub mod agent;
 pub mod context;
 pub mod dht;
+pub mod gc;
 pub mod instance;
 #[cfg(test)]
 pub mod link_tests;
 pub mod logger;
+pub mod metrics;
 pub mod network;
 pub mod nucleus;
 pub mod persister;
 pub mod state;
+pub mod tracer;
 pub mod workflows;
@@ -0,0 +1,231 @@
+//! Renders the introspection counters of an instance (action queue, zome function execution
+//! stats, source chain and network state) in the Prometheus text exposition format,
+//! so a conductor can serve them on a /metrics endpoint.
+use crate::context::Context;
+use std::fmt::{self, Write};
+
+enum MetricType {
+    Counter,
+    Gauge,
+}
+
+impl MetricType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MetricType::Counter => "counter",
+            MetricType::Gauge => "gauge",
+        }
+    }
+}
+
+/// Collects metric families and renders them as text.
+#[derive(Default)]
+struct MetricsWriter {
+    output: String,
+}
+
+impl MetricsWriter {
+    fn family(&mut self, name: &str, metric_type: MetricType, help: &str) -> fmt::Result {
+        writeln!(self.output, "# HELP {} {}", name, help)?;
+        writeln!(self.output, "# TYPE {} {}", name, metric_type.as_str())
+    }
+
+    fn sample<V: fmt::Display>(
+        &mut self,
+        name: &str,
+        labels: &[(&str, &str)],
+        value: V,
+    ) -> fmt::Result {
+        write!(self.output, "{}", name)?;
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(label, label_value)| {
+                    format!("{}=\"{}\"", label, escape_label_value(label_value))
+                })
+                .collect();
+            write!(self.output, "{{{}}}", labels.join(","))?;
+        }
+        writeln!(self.output, " {}", value)
+    }
+
+    fn single<V: fmt::Display>(
+        &mut self,
+        name: &str,
+        metric_type: MetricType,
+        help: &str,
+        value: V,
+    ) -> fmt::Result {
+        self.family(name, metric_type, help)?;
+        self.sample(name, &[], value)
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn write_metrics(context: &Context, writer: &mut MetricsWriter) -> fmt::Result {
+    writer.single(
+        "holochain_action_queue_depth",
+        MetricType::Gauge,
+        "Actions dispatched but not yet processed by the action loop",
+        context.action_queue_depth(),
+    )?;
+
+    let execution_stats = context.execution_stats();
+    writer.family(
+        "holochain_zome_function_calls_total",
+        MetricType::Counter,
+        "Zome function calls run by the ribosome",
+    )?;
+    for stats in execution_stats.iter() {
+        writer.sample(
+            "holochain_zome_function_calls_total",
+            &[
+                ("zome", stats.zome.as_str()),
+                ("function", stats.function.as_str()),
+            ],
+            stats.calls,
+        )?;
+    }
+    writer.family(
+        "holochain_zome_function_duration_seconds_total",
+        MetricType::Counter,
+        "Time spent running zome functions",
+    )?;
+    for stats in execution_stats.iter() {
+        let seconds = stats.total_duration.as_secs() as f64
+            + f64::from(stats.total_duration.subsec_nanos()) / 1e9;
+        writer.sample(
+            "holochain_zome_function_duration_seconds_total",
+            &[
+                ("zome", stats.zome.as_str()),
+                ("function", stats.function.as_str()),
+            ],
+            seconds,
+        )?;
+    }
+
+    // The remaining metrics are read from the state, which is not there before the
+    // instance got initialized
+    let state = match context.state() {
+        Some(state) => state,
+        None => return Ok(()),
+    };
+    let agent_state = state.agent();
+    writer.single(
+        "holochain_source_chain_length",
+        MetricType::Gauge,
+        "Headers in the agent's source chain",
+        agent_state.chain_length(),
+    )?;
+
+    let network = state.network();
+    let network_snapshot = network.snapshot();
+    writer.single(
+        "holochain_network_pending_gets",
+        MetricType::Gauge,
+        "Network get_entry look-ups waiting for a response",
+        network_snapshot.pending_get_entries,
+    )?;
+    writer.single(
+        "holochain_network_pending_validation_packages",
+        MetricType::Gauge,
+        "Validation package requests waiting for a response",
+        network_snapshot.pending_validation_packages,
+    )?;
+    writer.single(
+        "holochain_network_open_direct_messages",
+        MetricType::Gauge,
+        "Direct messages sent and waiting for a response",
+        network_snapshot.open_direct_messages,
+    )?;
+    writer.single(
+        "holochain_network_deferred_messages",
+        MetricType::Gauge,
+        "Outbound messages held back while the network is paused",
+        network_snapshot.deferred_messages,
+    )?;
+    let bandwidth = network.bandwidth_usage();
+    writer.single(
+        "holochain_network_sent_bytes_per_second",
+        MetricType::Gauge,
+        "Bytes sent within the last second",
+        bandwidth.bytes_per_second,
+    )?;
+    writer.single(
+        "holochain_network_gossip_bytes_per_second",
+        MetricType::Gauge,
+        "Gossip bytes sent within the last second",
+        bandwidth.gossip_bytes_per_second,
+    )?;
+    writer.single(
+        "holochain_network_queued_gossip_messages",
+        MetricType::Gauge,
+        "Gossip messages waiting for bandwidth budget",
+        bandwidth.queued_messages,
+    )
+}
+
+/// All metrics of the instance in the Prometheus text exposition format.
+pub fn prometheus_text(context: &Context) -> String {
+    let mut writer = MetricsWriter::default();
+    write_metrics(context, &mut writer).expect("writing to a String can not fail");
+    writer.output
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        agent::actions::commit::commit_entry,
+        nucleus::actions::tests::{instance_by_name, test_dna},
+    };
+    use futures::executor::block_on;
+    use holochain_core_types::entry::test_entry;
+    use std::time::Duration;
+
+    #[test]
+    fn renders_well_formed_prometheus_text() {
+        let (_instance, context) = instance_by_name("jill", test_dna());
+        block_on(commit_entry(test_entry(), None, &context)).unwrap();
+        context.record_execution("test_zome", "main", Duration::from_millis(1500));
+        context.record_execution("test_zome", "main", Duration::from_millis(500));
+
+        let text = context.metrics_prometheus();
+
+        for name in vec![
+            "holochain_action_queue_depth",
+            "holochain_zome_function_calls_total",
+            "holochain_zome_function_duration_seconds_total",
+            "holochain_source_chain_length",
+            "holochain_network_pending_gets",
+        ] {
+            assert!(text.contains(&format!("# TYPE {} ", name)), "{}", name);
+        }
+        assert!(text.lines().any(|line| line
+            == "holochain_zome_function_calls_total{zome=\"test_zome\",function=\"main\"} 2"));
+        assert!(text.lines().any(|line| line
+            == "holochain_zome_function_duration_seconds_total{zome=\"test_zome\",function=\"main\"} 2"));
+        // genesis commits the DNA and the agent entry
+        assert!(text
+            .lines()
+            .any(|line| line == "holochain_source_chain_length 3"));
+
+        for line in text.lines().filter(|line| !line.starts_with('#')) {
+            let parts: Vec<&str> = line.rsplitn(2, ' ').collect();
+            assert_eq!(2, parts.len(), "{}", line);
+            assert!(parts[0].parse::<f64>().is_ok(), "{}", line);
+            assert!(parts[1].starts_with("holochain_"), "{}", line);
+        }
+    }
+
+    #[test]
+    fn escapes_label_values() {
+        assert_eq!("a\\\"b\\\\c\\n", escape_label_value("a\"b\\c\n"));
+    }
+}
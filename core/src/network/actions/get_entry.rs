@@ -3,46 +3,212 @@ use crate::{
     action::{Action, ActionWrapper},
     context::Context,
     instance::dispatch_action,
+    network::retry::{retry_future, Delay, RetryPolicy},
+    nucleus::actions::get_entry::{get_deletions_for, get_entry_with_meta},
 };
 use futures::{
     future::Future,
     task::{LocalWaker, Poll},
 };
-use holochain_core_types::{cas::content::Address, entry::EntryWithMeta, error::HcResult};
+use holochain_core_types::{
+    cas::content::Address,
+    entry::EntryWithMeta,
+    error::{HcResult, HolochainError},
+};
 use std::{
     pin::{Pin, Unpin},
     sync::Arc,
-    thread::sleep,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+/// How long a single network look-up may take and how often it gets repeated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GetEntryOptions {
+    /// Time to wait for a response before the look-up counts as timed out.
+    pub timeout: Duration,
+    /// Number of further look-ups after the first one timed out.
+    pub retries: u8,
+}
+
+impl Default for GetEntryOptions {
+    fn default() -> Self {
+        GetEntryOptions {
+            timeout: Duration::from_secs(60),
+            retries: 2,
+        }
+    }
+}
+
 /// GetEntry Action Creator
 /// This is the network version of get_entry that makes the network module start
 /// a look-up process.
 ///
 /// Returns a future that resolves to an ActionResponse.
+/// If the network is disabled, only the local DHT shard is looked at.
+/// Uses the default GetEntryOptions.
 pub async fn get_entry<'a>(
     context: &'a Arc<Context>,
     address: &'a Address,
 ) -> HcResult<Option<EntryWithMeta>> {
-    let action_wrapper = ActionWrapper::new(Action::GetEntry(address.clone()));
-    dispatch_action(&context.action_channel, action_wrapper.clone());
-    async {
-        sleep(Duration::from_secs(60));
-        let action_wrapper = ActionWrapper::new(Action::GetEntryTimeout(address.clone()));
-        dispatch_action(&context.action_channel, action_wrapper.clone());
+    await!(get_entry_with_options(
+        context,
+        address,
+        GetEntryOptions::default(),
+    ))
+}
+
+/// Like get_entry() but with the given timeout and number of retries.
+/// The delay between attempts follows the context's RetryPolicy.
+/// Every attempt goes to a known peer that was not asked before for this address,
+/// healthy peers first (see network::peer_rotation).
+/// Fails with HolochainError::Timeout if no attempt got a response in time.
+pub async fn get_entry_with_options<'a>(
+    context: &'a Arc<Context>,
+    address: &'a Address,
+    options: GetEntryOptions,
+) -> HcResult<Option<EntryWithMeta>> {
+    if !context.network_enabled() {
+        return get_entry_with_meta(context, address.clone());
+    }
+    let policy = RetryPolicy {
+        max_attempts: u32::from(options.retries) + 1,
+        ..context.retry_policy()
+    };
+    let result = await!(retry_future(
+        || request_entry(context, address, options.timeout),
+        policy,
+    ));
+    context.peer_rotation().lock().unwrap().finish(address);
+    result
+}
+
+/// Addresses of all deletion entries that target the given address, in address order.
+/// Looks at the deletion metadata of the EAV store, so only deletions that reached this
+/// node are found. Finding none is not an error but an empty vec, like get_entry()
+/// resolving to None.
+pub fn find_deletions_for(
+    context: &Arc<Context>,
+    target: &Address,
+) -> Result<Vec<Address>, HolochainError> {
+    get_deletions_for(target, context)
+}
+
+/// Gets all entries with concurrent network look-ups.
+///
+/// The results are in the order of the given addresses. An entry that could not be found
+/// is None in its slot and does not fail the batch.
+/// Uses the default GetEntryOptions.
+pub async fn get_entries<'a>(
+    context: &'a Arc<Context>,
+    addresses: &'a [Address],
+) -> HcResult<Vec<Option<EntryWithMeta>>> {
+    await!(get_entries_with_options(
+        context,
+        addresses,
+        GetEntryOptions::default(),
+    ))
+}
+
+/// Like get_entries() but with the given timeout and number of retries.
+/// All look-ups of a round are dispatched before awaiting the first one. The look-ups that
+/// timed out are dispatched again together in the next round, each to a peer that was not
+/// asked before for its address (see get_entry_with_options()). The delay between rounds
+/// follows the context's RetryPolicy.
+/// A look-up that still timed out in the last round fails the whole batch with
+/// HolochainError::Timeout, as does any other error.
+pub async fn get_entries_with_options<'a>(
+    context: &'a Arc<Context>,
+    addresses: &'a [Address],
+    options: GetEntryOptions,
+) -> HcResult<Vec<Option<EntryWithMeta>>> {
+    if !context.network_enabled() {
+        return addresses
+            .iter()
+            .map(|address| get_entry_with_meta(context, address.clone()))
+            .collect();
+    }
+    let policy = RetryPolicy {
+        max_attempts: u32::from(options.retries) + 1,
+        ..context.retry_policy()
     };
-    await!(GetEntryFuture {
+    let mut results: Vec<HcResult<Option<EntryWithMeta>>> = addresses
+        .iter()
+        .map(|_| Err(HolochainError::Timeout))
+        .collect();
+    let mut pending: Vec<usize> = (0..addresses.len()).collect();
+    let mut attempt = 1;
+    while !pending.is_empty() {
+        if attempt > 1 {
+            await!(Delay::new(policy.delay_after(attempt - 1)));
+        }
+        let requests: Vec<(usize, GetEntryFuture)> = pending
+            .iter()
+            .map(|index| {
+                let request = request_entry(context, &addresses[*index], options.timeout);
+                (*index, request)
+            })
+            .collect();
+        pending = Vec::new();
+        for (index, request) in requests {
+            let result = await!(request);
+            if let Err(ref error) = result {
+                if policy.should_retry(attempt, error) {
+                    pending.push(index);
+                }
+            }
+            results[index] = result;
+        }
+        attempt += 1;
+    }
+    let peer_rotation = context.peer_rotation();
+    let mut peer_rotation = peer_rotation.lock().unwrap();
+    for address in addresses {
+        peer_rotation.finish(address);
+    }
+    results.into_iter().collect()
+}
+
+/// Makes the network module start a look-up with the next peer of the context's
+/// PeerRotation and returns the future waiting for its result.
+fn request_entry(context: &Arc<Context>, address: &Address, timeout: Duration) -> GetEntryFuture {
+    let peer = context.peer_rotation().lock().unwrap().next_peer(address);
+    let action_wrapper = ActionWrapper::new(Action::GetEntry((address.clone(), peer.clone())));
+    dispatch_action(&context.action_channel, action_wrapper.clone());
+    GetEntryFuture {
         context: context.clone(),
+        action_wrapper,
         address: address.clone(),
-    })
+        peer,
+        deadline: Instant::now() + timeout,
+    }
 }
 
 /// GetEntryFuture resolves to a HcResult<Entry>.
 /// Tracks the state of the network module
 pub struct GetEntryFuture {
     context: Arc<Context>,
+    /// The GetEntry action this future waits for. Results in the network state are only
+    /// read once it got reduced, so a retry does not pick up the previous attempt's timeout.
+    action_wrapper: ActionWrapper,
     address: Address,
+    /// The peer that was asked, None if the network picked one.
+    peer: Option<String>,
+    deadline: Instant,
+}
+
+impl GetEntryFuture {
+    /// Keeps track of the asked peer's health before handing out the result.
+    fn ready(&self, result: HcResult<Option<EntryWithMeta>>) -> Poll<<Self as Future>::Output> {
+        if let Some(ref peer) = self.peer {
+            let peer_rotation = self.context.peer_rotation();
+            let mut peer_rotation = peer_rotation.lock().unwrap();
+            match result {
+                Ok(_) => peer_rotation.record_success(peer),
+                Err(_) => peer_rotation.record_failure(peer),
+            }
+        }
+        Poll::Ready(result)
+    }
 }
 
 impl Unpin for GetEntryFuture {}
@@ -51,7 +217,9 @@ impl Future for GetEntryFuture {
     type Output = HcResult<Option<EntryWithMeta>>;
 
     fn poll(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
-        let state = self.context.state().unwrap().network();
+        let state = self.context.state().unwrap();
+        let dispatched = state.history.contains(&self.action_wrapper);
+        let state = state.network();
         if let Err(error) = state.initialized() {
             return Poll::Ready(Err(error));
         }
@@ -61,8 +229,111 @@ impl Future for GetEntryFuture {
         //
         lw.wake();
         match state.get_entry_with_meta_results.get(&self.address) {
-            Some(Some(result)) => Poll::Ready(result.clone()),
+            Some(Some(result)) if dispatched => self.ready(result.clone()),
+            _ if Instant::now() >= self.deadline => {
+                // marks the look-up as timed out in the network state
+                let action_wrapper =
+                    ActionWrapper::new(Action::GetEntryTimeout(self.address.clone()));
+                dispatch_action(&self.context.action_channel, action_wrapper);
+                self.ready(Err(HolochainError::Timeout))
+            }
             _ => Poll::Pending,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        action::NetworkSettings,
+        agent::actions::commit::commit_entry,
+        context::mock_network_config,
+        instance::{tests::test_context_with_channels, Observer},
+        nucleus::actions::tests::{instance_by_name, test_dna},
+        state::test_store,
+    };
+    use futures::executor::block_on;
+    use holochain_core_types::{
+        cas::content::AddressableContent,
+        crud_status::CrudStatus,
+        entry::{deletion_entry::DeletionEntry, test_entry, test_entry_b, Entry},
+    };
+    use holochain_net_connection::protocol_wrapper::DhtData;
+    use std::sync::{mpsc::sync_channel, RwLock};
+
+    #[test]
+    fn get_entry_future_ignores_result_of_earlier_attempt() {
+        let (sender, _receiver) = sync_channel::<ActionWrapper>(10);
+        let (tx_observer, _observer) = sync_channel::<Observer>(10);
+        let mut context = test_context_with_channels("alice", &sender, &tx_observer);
+        let store = Arc::new(RwLock::new(test_store(context.clone())));
+        Arc::get_mut(&mut context).unwrap().set_state(store.clone());
+
+        let entry = test_entry();
+        let entry_with_meta = EntryWithMeta {
+            entry: entry.clone(),
+            crud_status: CrudStatus::LIVE,
+            maybe_crud_link: None,
+        };
+        let dht_data = DhtData {
+            msg_id: String::from(""),
+            dna_hash: String::from(""),
+            agent_id: String::from(""),
+            address: entry.address().to_string(),
+            content: serde_json::from_str(&serde_json::to_string(&Some(entry_with_meta)).unwrap())
+                .unwrap(),
+        };
+        // an earlier attempt left its result in the network state
+        for action in vec![
+            Action::InitNetwork(NetworkSettings {
+                config: mock_network_config(),
+                dna_hash: String::from("abcd"),
+                agent_id: String::from("abcd"),
+            }),
+            Action::GetEntry((entry.address(), None)),
+            Action::HandleGetResult(dht_data),
+        ] {
+            let mut new_store = store.write().unwrap();
+            *new_store = new_store.reduce(context.clone(), ActionWrapper::new(action));
+        }
+
+        // the action of the new attempt never gets reduced
+        let future = GetEntryFuture {
+            context: context.clone(),
+            action_wrapper: ActionWrapper::new(Action::GetEntry((entry.address(), None))),
+            address: entry.address(),
+            peer: None,
+            deadline: Instant::now() + Duration::from_millis(100),
+        };
+        assert_eq!(Err(HolochainError::Timeout), block_on(future));
+    }
+
+    #[test]
+    fn find_deletions_for_test() {
+        let (_instance, context) = instance_by_name("jill", test_dna());
+        let address = block_on(commit_entry(test_entry(), None, &context)).unwrap();
+        assert_eq!(Ok(vec![]), find_deletions_for(&context, &address));
+
+        let mut deletions = Vec::new();
+        for reason in vec!["outdated", "duplicate"] {
+            let deletion = Entry::Deletion(DeletionEntry::new_with_reason(
+                address.clone(),
+                reason.to_string(),
+            ));
+            block_on(commit_entry(
+                deletion.clone(),
+                Some(address.clone()),
+                &context,
+            ))
+            .unwrap();
+            deletions.push(deletion.address());
+        }
+        deletions.sort();
+        assert_eq!(Ok(deletions), find_deletions_for(&context, &address));
+        assert_eq!(
+            Ok(vec![]),
+            find_deletions_for(&context, &test_entry_b().address())
+        );
+    }
+}
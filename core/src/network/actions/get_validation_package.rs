@@ -3,45 +3,151 @@ use crate::{
     action::{Action, ActionWrapper},
     context::Context,
     instance::dispatch_action,
+    network::retry::retry_future,
+    nucleus::actions::build_validation_package::build_validation_package,
 };
 use futures::{
+    executor::block_on,
     future::Future,
     task::{LocalWaker, Poll},
 };
 use holochain_core_types::{
-    cas::content::Address, chain_header::ChainHeader, error::HcResult,
+    cas::content::AddressableContent,
+    chain_header::ChainHeader,
+    entry::Entry,
+    error::{HcResult, HolochainError},
     validation::ValidationPackage,
 };
 use std::{
     pin::{Pin, Unpin},
-    sync::Arc,
+    sync::{mpsc::channel, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
 };
 
+/// Maximum number of validation packages get_validation_packages() fetches at the same time.
+pub const VALIDATION_PACKAGE_WORKERS: usize = 4;
+
+/// Seconds to wait for the source's response before a request counts as timed out.
+pub const VALIDATION_PACKAGE_TIMEOUT_SECS: u64 = 60;
+
 /// GetValidationPackage Action Creator
 /// This triggers the network module to retrieve the validation package for the
 /// entry given by the header.
 ///
 /// Returns a future that resolves to Option<ValidationPackage> (or HolochainError).
-/// If that is None this means that we couldn't get a validation package from the source.
+/// If that is None this means that we couldn't get a validation package from the source,
+/// which is always the case if the network is disabled.
+/// Requests time out after VALIDATION_PACKAGE_TIMEOUT_SECS and are retried according to
+/// the context's RetryPolicy.
 pub async fn get_validation_package(
     header: ChainHeader,
     context: &Arc<Context>,
 ) -> HcResult<Option<ValidationPackage>> {
-    let entry_address = header.entry_address().clone();
-    let action_wrapper = ActionWrapper::new(Action::GetValidationPackage(header));
-    dispatch_action(&context.action_channel, action_wrapper.clone());
-    await!(GetValidationPackageFuture {
-        context: context.clone(),
-        address: entry_address,
-    })
+    if !context.network_enabled() {
+        return Ok(None);
+    }
+    await!(retry_future(
+        || {
+            // every attempt is a new request, so a late response to an earlier attempt
+            // can not be taken for the response to this one
+            let msg_id = snowflake::ProcessUniqueId::new().to_string();
+            let action_wrapper = ActionWrapper::new(Action::GetValidationPackage((
+                msg_id.clone(),
+                header.clone(),
+            )));
+            dispatch_action(&context.action_channel, action_wrapper.clone());
+            GetValidationPackageFuture {
+                context: context.clone(),
+                msg_id,
+                deadline: Instant::now() + Duration::from_secs(VALIDATION_PACKAGE_TIMEOUT_SECS),
+            }
+        },
+        context.retry_policy(),
+    ))
+}
+
+/// Gets the validation packages for all given headers, e.g. during chain sync.
+/// Up to VALIDATION_PACKAGE_WORKERS packages are fetched concurrently. Packages for entries
+/// of our own source chain are built locally instead of being requested over the network.
+///
+/// Returns the results in the order of the given headers.
+pub fn get_validation_packages(
+    headers: &[ChainHeader],
+    context: &Arc<Context>,
+) -> Vec<HcResult<Option<ValidationPackage>>> {
+    let context = context.clone();
+    map_bounded(
+        headers.to_vec(),
+        VALIDATION_PACKAGE_WORKERS,
+        move |header| match local_entry(&header, &context) {
+            Some(entry) => block_on(build_validation_package(&entry, &context)).map(Some),
+            None => block_on(get_validation_package(header, &context)),
+        },
+    )
+}
+
+/// Returns the entry of the given header if the header is part of our own source chain.
+fn local_entry(header: &ChainHeader, context: &Arc<Context>) -> Option<Entry> {
+    let storage = context.state()?.agent().chain().content_storage();
+    let storage = storage.read().unwrap();
+    if !storage.contains(&header.address()).unwrap_or(false) {
+        return None;
+    }
+    storage
+        .fetch(header.entry_address())
+        .ok()?
+        .and_then(|content| Entry::try_from_content(&content).ok())
+}
+
+/// Applies f to all items on up to the given number of threads.
+/// Returns the results in the order of the items.
+fn map_bounded<T, R, F>(items: Vec<T>, workers: usize, f: F) -> Vec<R>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+{
+    let count = items.len();
+    let queue = Arc::new(Mutex::new(items.into_iter().enumerate()));
+    let f = Arc::new(f);
+    let (sender, receiver) = channel();
+    for _ in 0..workers.min(count) {
+        let queue = queue.clone();
+        let f = f.clone();
+        let sender = sender.clone();
+        thread::spawn(move || loop {
+            let next = queue.lock().unwrap().next();
+            match next {
+                Some((index, item)) => {
+                    if sender.send((index, f(item))).is_err() {
+                        return;
+                    }
+                }
+                None => return,
+            }
+        });
+    }
+    drop(sender);
+
+    let mut results: Vec<Option<R>> = (0..count).map(|_| None).collect();
+    for (index, result) in receiver {
+        results[index] = Some(result);
+    }
+    results
+        .into_iter()
+        .map(|result| result.expect("worker thread for item panicked"))
+        .collect()
 }
 
 /// GetValidationPackageFuture resolves to an Option<ValidationPackage>
 /// which would be None if the source responded with None, indicating that it
 /// is not the source.
+/// Fails with HolochainError::Timeout if there was no response before the deadline.
 pub struct GetValidationPackageFuture {
     context: Arc<Context>,
-    address: Address,
+    msg_id: String,
+    deadline: Instant,
 }
 
 impl Unpin for GetValidationPackageFuture {}
@@ -59,9 +165,74 @@ impl Future for GetValidationPackageFuture {
         // See: https://github.com/holochain/holochain-rust/issues/314
         //
         lw.wake();
-        match state.get_validation_package_results.get(&self.address) {
+        match state.get_validation_package_results.get(&self.msg_id) {
             Some(Some(result)) => Poll::Ready(result.clone()),
+            _ if Instant::now() >= self.deadline => Poll::Ready(Err(HolochainError::Timeout)),
             _ => Poll::Pending,
         }
     }
 }
+
+impl Drop for GetValidationPackageFuture {
+    /// Removes the request and its result from the network state, whether the future
+    /// resolved or got abandoned.
+    fn drop(&mut self) {
+        let action_wrapper =
+            ActionWrapper::new(Action::ClearValidationPackageResult(self.msg_id.clone()));
+        // the instance might be gone already, in which case there is nothing to clean up
+        let _ = self.context.action_channel.send(action_wrapper);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::{map_bounded, GetValidationPackageFuture};
+    use crate::instance::tests::test_instance_and_context_by_name;
+    use futures::executor::block_on;
+    use holochain_core_types::error::HolochainError;
+    use std::{
+        sync::{Arc, Mutex},
+        thread,
+        time::{Duration, Instant},
+    };
+    use test_utils::create_test_dna_with_wat;
+
+    #[test]
+    fn unanswered_request_times_out() {
+        let mut dna = create_test_dna_with_wat("test_zome", "test_cap", None);
+        dna.set_uuid(String::from("unanswered_request_times_out"));
+        let (_, context) = test_instance_and_context_by_name(dna, "alice").unwrap();
+
+        let future = GetValidationPackageFuture {
+            context: context.clone(),
+            msg_id: String::from("never answered"),
+            deadline: Instant::now() + Duration::from_millis(10),
+        };
+        assert_eq!(Err(HolochainError::Timeout), block_on(future));
+    }
+
+    #[test]
+    fn map_bounded_preserves_order_and_limits_concurrency() {
+        // (currently running, most running at the same time)
+        let running = Arc::new(Mutex::new((0, 0)));
+        let results = {
+            let running = running.clone();
+            map_bounded((0..10).collect(), 3, move |i: u64| {
+                {
+                    let mut running = running.lock().unwrap();
+                    running.0 += 1;
+                    running.1 = running.1.max(running.0);
+                }
+                // later items finish first
+                thread::sleep(Duration::from_millis(50 - 5 * i));
+                running.lock().unwrap().0 -= 1;
+                i * 2
+            })
+        };
+
+        assert_eq!((0..10).map(|i| i * 2).collect::<Vec<u64>>(), results);
+        let (_, max_running) = *running.lock().unwrap();
+        assert!(max_running > 1);
+        assert!(max_running <= 3);
+    }
+}
@@ -4,6 +4,7 @@ use crate::{
     action::{Action, ActionWrapper, NetworkSettings},
     context::Context,
     instance::dispatch_action,
+    network::bandwidth::spawn_gossip_flusher,
 };
 use futures::{
     task::{LocalWaker, Poll},
@@ -24,15 +25,18 @@ async fn get_dna_and_agent(context: &Arc<Context>) -> Result<(String, String), H
     let agent = await!(agent_state.get_agent(&context))?;
     let agent_id = agent.key;
 
-    let dna = state
-        .nucleus()
-        .dna()
-        .ok_or("Network::start() called without DNA".to_string())?;
+    // The running DNA differs from the committed one (e.g. by expanded wildcard
+    // capabilities), all nodes need to agree on the hash of the committed DNA.
+    let dna = agent_state.get_dna()?;
     let dna_hash = base64::encode(&dna.multihash()?);
     Ok((dna_hash, agent_id))
 }
 /// Creates a network proxy object and stores DNA and agent hash in the network state.
+/// Does nothing if the network is disabled.
 pub async fn initialize_network(context: &Arc<Context>) -> Result<(), HolochainError> {
+    if !context.network_enabled() {
+        return Ok(());
+    }
     let (dna_hash, agent_id) = await!(get_dna_and_agent(context))?;
     let network_settings = NetworkSettings {
         config: context.network_config.clone(),
@@ -44,7 +48,12 @@ pub async fn initialize_network(context: &Arc<Context>) -> Result<(), HolochainE
 
     await!(InitNetworkFuture {
         context: context.clone(),
-    })
+    })?;
+
+    if context.gossip_bandwidth_limit().is_some() {
+        spawn_gossip_flusher(context);
+    }
+    Ok(())
 }
 
 pub struct InitNetworkFuture {
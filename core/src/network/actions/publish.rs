@@ -20,7 +20,11 @@ use std::{
 /// be called from zome api functions and other contexts that don't care about implementation details.
 ///
 /// Returns a future that resolves to an ActionResponse.
+/// If the network is disabled there is nobody to publish to and the address is returned as is.
 pub async fn publish(address: Address, context: &Arc<Context>) -> HcResult<Address> {
+    if !context.network_enabled() {
+        return Ok(address);
+    }
     let action_wrapper = ActionWrapper::new(Action::Publish(address));
     dispatch_action(&context.action_channel, action_wrapper.clone());
     await!(PublishFuture {
@@ -0,0 +1,260 @@
+//! Outbound bandwidth budget for gossip.
+//!
+//! Gossip (publishing entries and meta data to the DHT) can saturate constrained links.
+//! The throttle lets gossip messages go out only as long as the bytes sent within the last
+//! second stay under the configured cap and queues the rest. Interactive messages (gets,
+//! direct messages and their responses) are never held back but do count towards the usage.
+//! Queued gossip goes out with later network actions and, if there are none, through the
+//! periodic flush of spawn_gossip_flusher().
+use crate::{
+    action::{Action, ActionWrapper},
+    context::Context,
+};
+use holochain_core_types::json::JsonString;
+use holochain_net_connection::protocol_wrapper::ProtocolWrapper;
+use std::{
+    collections::VecDeque,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Interval in which spawn_gossip_flusher() checks for queued gossip.
+pub const GOSSIP_FLUSH_INTERVAL_MS: u64 = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MessagePriority {
+    /// Requests somebody is waiting for, bypass the throttle
+    Interactive,
+    /// Background DHT publishing, subject to the bandwidth budget
+    Gossip,
+}
+
+/// Snapshot of the outbound traffic within the last second.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BandwidthUsage {
+    /// all bytes sent within the last second
+    pub bytes_per_second: u64,
+    /// gossip bytes sent within the last second
+    pub gossip_bytes_per_second: u64,
+    /// gossip messages waiting for budget
+    pub queued_messages: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct BandwidthThrottle {
+    /// None means unlimited
+    gossip_bytes_per_second: Option<u64>,
+    window: Duration,
+    sent: VecDeque<(Instant, u64, MessagePriority)>,
+    queue: VecDeque<ProtocolWrapper>,
+}
+
+impl Default for BandwidthThrottle {
+    fn default() -> Self {
+        BandwidthThrottle::new(None)
+    }
+}
+
+fn message_size(message: &ProtocolWrapper) -> u64 {
+    String::from(JsonString::from(message.clone())).len() as u64
+}
+
+impl BandwidthThrottle {
+    pub fn new(gossip_bytes_per_second: Option<u64>) -> Self {
+        BandwidthThrottle {
+            gossip_bytes_per_second,
+            window: Duration::from_secs(1),
+            sent: VecDeque::new(),
+            queue: VecDeque::new(),
+        }
+    }
+
+    pub fn gossip_bytes_per_second(&self) -> Option<u64> {
+        self.gossip_bytes_per_second
+    }
+
+    fn forget_old(&mut self, now: Instant) {
+        while let Some((sent_at, _, _)) = self.sent.front() {
+            if now.duration_since(*sent_at) < self.window {
+                break;
+            }
+            self.sent.pop_front();
+        }
+    }
+
+    fn bytes_in_window(&self, priority: Option<MessagePriority>) -> u64 {
+        self.sent
+            .iter()
+            .filter(|(_, _, sent_priority)| priority.map_or(true, |p| p == *sent_priority))
+            .map(|(_, size, _)| size)
+            .sum()
+    }
+
+    /// Returns the messages that may be sent right now.
+    /// Interactive messages go out immediately, gossip messages get queued behind earlier
+    /// gossip and released as far as the budget allows.
+    pub fn admit(
+        &mut self,
+        message: ProtocolWrapper,
+        priority: MessagePriority,
+        now: Instant,
+    ) -> Vec<ProtocolWrapper> {
+        match priority {
+            MessagePriority::Interactive => {
+                self.forget_old(now);
+                self.sent
+                    .push_back((now, message_size(&message), MessagePriority::Interactive));
+                let mut released = vec![message];
+                released.append(&mut self.drain(now));
+                released
+            }
+            MessagePriority::Gossip => {
+                self.queue.push_back(message);
+                self.drain(now)
+            }
+        }
+    }
+
+    /// Releases queued gossip messages that fit into the budget.
+    /// A single message bigger than the whole budget is let through once the window is
+    /// free of gossip so it can not block the queue forever.
+    pub fn drain(&mut self, now: Instant) -> Vec<ProtocolWrapper> {
+        self.forget_old(now);
+        let mut released = Vec::new();
+        while let Some(size) = self.queue.front().map(message_size) {
+            let gossip_bytes = self.bytes_in_window(Some(MessagePriority::Gossip));
+            let fits = match self.gossip_bytes_per_second {
+                None => true,
+                Some(cap) => gossip_bytes + size <= cap || gossip_bytes == 0,
+            };
+            if !fits {
+                break;
+            }
+            self.sent.push_back((now, size, MessagePriority::Gossip));
+            released.push(self.queue.pop_front().unwrap());
+        }
+        released
+    }
+
+    pub fn usage(&self, now: Instant) -> BandwidthUsage {
+        let mut throttle = self.clone();
+        throttle.forget_old(now);
+        BandwidthUsage {
+            bytes_per_second: throttle.bytes_in_window(None),
+            gossip_bytes_per_second: throttle.bytes_in_window(Some(MessagePriority::Gossip)),
+            queued_messages: throttle.queue.len(),
+        }
+    }
+}
+
+/// Spawns a thread that dispatches Action::FlushGossip every GOSSIP_FLUSH_INTERVAL_MS while
+/// gossip is queued, so held back gossip goes out even if no other network action happens.
+/// The thread ends once the context is dropped or its action channel is closed.
+pub fn spawn_gossip_flusher(context: &Arc<Context>) {
+    let context = Arc::downgrade(context);
+    thread::spawn(move || loop {
+        thread::sleep(Duration::from_millis(GOSSIP_FLUSH_INTERVAL_MS));
+        let context = match context.upgrade() {
+            Some(context) => context,
+            None => return,
+        };
+        let gossip_queued = context
+            .state()
+            .map(|state| state.network().bandwidth_usage().queued_messages > 0)
+            .unwrap_or(false);
+        if gossip_queued
+            && context
+                .action_channel
+                .send(ActionWrapper::new(Action::FlushGossip))
+                .is_err()
+        {
+            return;
+        }
+    });
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use holochain_net_connection::protocol_wrapper::{DhtData, GetDhtData};
+
+    fn gossip_message(index: usize) -> ProtocolWrapper {
+        ProtocolWrapper::PublishDht(DhtData {
+            msg_id: format!("{}", index),
+            dna_hash: String::from("dna"),
+            agent_id: String::from("agent"),
+            address: format!("address{}", index),
+            content: json!({ "payload": "x".repeat(100) }),
+        })
+    }
+
+    fn interactive_message() -> ProtocolWrapper {
+        ProtocolWrapper::GetDht(GetDhtData {
+            msg_id: String::from("get"),
+            dna_hash: String::from("dna"),
+            from_agent_id: String::from("agent"),
+            address: String::from("address"),
+            signature: String::new(),
+            to_agent_id: String::new(),
+        })
+    }
+
+    #[test]
+    fn gossip_stays_under_cap() {
+        let size = message_size(&gossip_message(0));
+        let cap = size * 3;
+        let mut throttle = BandwidthThrottle::new(Some(cap));
+        let start = Instant::now();
+
+        // a burst of ten gossip messages only lets three through
+        let mut released = Vec::new();
+        for index in 0..10 {
+            released.append(&mut throttle.admit(
+                gossip_message(index),
+                MessagePriority::Gossip,
+                start,
+            ));
+        }
+        assert_eq!(3, released.len());
+        assert_eq!(7, throttle.usage(start).queued_messages);
+        assert!(throttle.usage(start).gossip_bytes_per_second <= cap);
+
+        // an interactive request goes out immediately despite the exhausted budget
+        let now = start + Duration::from_millis(100);
+        let sent = throttle.admit(interactive_message(), MessagePriority::Interactive, now);
+        assert_eq!(vec![interactive_message()], sent);
+        assert_eq!(7, throttle.usage(now).queued_messages);
+
+        // the queue drains in order at the configured rate
+        let mut drained = released;
+        for second in 1..5 {
+            let now = start + Duration::from_secs(second);
+            drained.append(&mut throttle.drain(now));
+            assert!(throttle.usage(now).gossip_bytes_per_second <= cap);
+        }
+        assert_eq!((0..10).map(gossip_message).collect::<Vec<_>>(), drained);
+        assert_eq!(
+            0,
+            throttle
+                .usage(start + Duration::from_secs(5))
+                .queued_messages
+        );
+    }
+
+    #[test]
+    fn unlimited_by_default() {
+        let mut throttle = BandwidthThrottle::default();
+        let now = Instant::now();
+        for index in 0..10 {
+            assert_eq!(
+                vec![gossip_message(index)],
+                throttle.admit(gossip_message(index), MessagePriority::Gossip, now)
+            );
+        }
+        assert_eq!(
+            10 * message_size(&gossip_message(0)),
+            throttle.usage(now).gossip_bytes_per_second
+        );
+    }
+}
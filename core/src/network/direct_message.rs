@@ -1,4 +1,12 @@
+use futures::{
+    stream::Stream,
+    task::{LocalWaker, Poll},
+};
 use holochain_core_types::{cas::content::Address, validation::ValidationPackage};
+use std::{
+    pin::{Pin, Unpin},
+    sync::mpsc::{Receiver, TryRecvError},
+};
 
 /// These are the different kinds of (low-level, i.e. non-app)
 /// node-to-node messages that can be send between Holochain nodes.
@@ -19,3 +27,36 @@ pub enum DirectMessage {
     /// "I can't"
     ValidationPackage(Option<ValidationPackage>),
 }
+
+/// Stream of the direct messages this node receives, in the order they arrive.
+/// Created with Context::direct_message_stream().
+pub struct DirectMessageStream {
+    receiver: Receiver<DirectMessage>,
+}
+
+impl DirectMessageStream {
+    pub(crate) fn new(receiver: Receiver<DirectMessage>) -> Self {
+        DirectMessageStream { receiver }
+    }
+}
+
+impl Unpin for DirectMessageStream {}
+
+impl Stream for DirectMessageStream {
+    type Item = DirectMessage;
+
+    fn poll_next(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Option<Self::Item>> {
+        match self.receiver.try_recv() {
+            Ok(message) => Poll::Ready(Some(message)),
+            Err(TryRecvError::Empty) => {
+                //
+                // TODO: connect the waker to incoming messages for performance reasons
+                // See: https://github.com/holochain/holochain-rust/issues/314
+                //
+                lw.wake();
+                Poll::Pending
+            }
+            Err(TryRecvError::Disconnected) => Poll::Ready(None),
+        }
+    }
+}
@@ -2,6 +2,7 @@ use crate::{
     action::{Action, ActionWrapper},
     context::Context,
     instance::dispatch_action,
+    network::request_signer::authenticated_requester,
     nucleus,
 };
 use holochain_core_types::cas::content::Address;
@@ -11,10 +12,14 @@ use holochain_net_connection::protocol_wrapper::{DhtData, GetDhtData};
 
 /// The network has requested a DHT entry from us.
 /// Lets try to get it and trigger a response.
+/// Entries with an access control list are only returned to requesters on the list that
+/// signed their request.
 pub fn handle_get_dht(get_dht_data: GetDhtData, context: Arc<Context>) {
-    let maybe_entry_with_meta = nucleus::actions::get_entry::get_entry_with_meta(
+    let requester = authenticated_requester(&get_dht_data, &context);
+    let maybe_entry_with_meta = nucleus::actions::get_entry::get_entry_with_meta_for(
         &context,
         Address::from(get_dht_data.address.clone()),
+        requester.as_ref(),
     )
     .unwrap_or_else(|error| {
         context.log(format!("Error trying to find entry {:?}", error));
@@ -1,4 +1,5 @@
 pub mod get;
+pub mod replay;
 pub mod send;
 pub mod store;
 
@@ -16,26 +17,45 @@ pub fn create_handler(c: &Arc<Context>) -> NetHandler {
     let context = c.clone();
     Box::new(move |message| {
         let message = message.unwrap();
-        let protocol_wrapper = ProtocolWrapper::try_from(message);
-        match protocol_wrapper {
-            Ok(ProtocolWrapper::StoreDht(dht_data)) => handle_store_dht(dht_data, context.clone()),
-            Ok(ProtocolWrapper::StoreDhtMeta(dht_meta_data)) => {
-                handle_store_dht_meta(dht_meta_data, context.clone())
+        if let Ok(protocol_wrapper) = ProtocolWrapper::try_from(message) {
+            if let Some(recorder) = context.network_recorder() {
+                if let Err(error) = recorder.record(&protocol_wrapper) {
+                    context.log(format!("Could not record network message: {:?}", error));
+                }
             }
-            Ok(ProtocolWrapper::GetDht(get_dht_data)) => {
-                handle_get_dht(get_dht_data, context.clone())
+            // while paused, messages wait for Context::resume_network()
+            if let Some(protocol_wrapper) = context.hold_if_network_paused(protocol_wrapper) {
+                handle_protocol_wrapper(protocol_wrapper, context.clone());
             }
-            Ok(ProtocolWrapper::GetDhtResult(dht_data)) => {
-                handle_get_dht_result(dht_data, context.clone())
-            }
-            Ok(ProtocolWrapper::HandleSend(message_data)) => {
-                handle_send(message_data, context.clone())
-            }
-            Ok(ProtocolWrapper::SendResult(message_data)) => {
-                handle_send_result(message_data, context.clone())
-            }
-            _ => {}
         }
         Ok(())
     })
 }
+
+/// Dispatches a single network message to the handler function for its type.
+pub fn handle_protocol_wrapper(protocol_wrapper: ProtocolWrapper, context: Arc<Context>) {
+    match protocol_wrapper {
+        ProtocolWrapper::StoreDht(dht_data) => handle_store_dht(dht_data, context),
+        ProtocolWrapper::StoreDhtMeta(dht_meta_data) => {
+            handle_store_dht_meta(dht_meta_data, context)
+        }
+        ProtocolWrapper::GetDht(get_dht_data) => {
+            add_peer(&get_dht_data.from_agent_id, &context);
+            handle_get_dht(get_dht_data, context)
+        }
+        ProtocolWrapper::GetDhtResult(dht_data) => handle_get_dht_result(dht_data, context),
+        ProtocolWrapper::HandleSend(message_data) => {
+            add_peer(&message_data.from_agent_id, &context);
+            handle_send(message_data, context)
+        }
+        ProtocolWrapper::SendResult(message_data) => handle_send_result(message_data, context),
+        _ => {}
+    }
+}
+
+/// Remembers an agent we heard from as a peer to send get_entry look-ups to.
+fn add_peer(agent_id: &str, context: &Arc<Context>) {
+    if agent_id != context.agent_id.key {
+        context.peer_rotation().lock().unwrap().add_peer(agent_id);
+    }
+}
@@ -0,0 +1,270 @@
+//! Record/replay of incoming network messages.
+//!
+//! A recorder attached to the context appends every message the network handler receives
+//! to a fixture file, one JSON message per line. Replaying such a fixture feeds the messages
+//! back through the handler so field bugs can be reproduced deterministically in tests.
+use crate::{context::Context, network::handler::handle_protocol_wrapper};
+use holochain_core_types::{error::HolochainError, json::JsonString};
+use holochain_net_connection::protocol_wrapper::ProtocolWrapper;
+use std::{
+    convert::TryFrom,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+#[derive(Clone, Debug)]
+pub struct NetworkRecorder {
+    fixture_path: PathBuf,
+}
+
+impl NetworkRecorder {
+    pub fn new<P: AsRef<Path>>(fixture_path: P) -> Self {
+        NetworkRecorder {
+            fixture_path: fixture_path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Appends the message to the fixture file.
+    pub fn record(&self, protocol_wrapper: &ProtocolWrapper) -> Result<(), HolochainError> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.fixture_path)?;
+        writeln!(file, "{}", JsonString::from(protocol_wrapper.clone()))?;
+        Ok(())
+    }
+}
+
+/// Parses one line of a fixture file. Blank lines hold no message.
+fn parse_fixture_line(line: String) -> Option<Result<ProtocolWrapper, HolochainError>> {
+    if line.trim().is_empty() {
+        return None;
+    }
+    Some(ProtocolWrapper::try_from(JsonString::from(line)))
+}
+
+/// Reads all messages recorded in a fixture file.
+pub fn load_fixture<P: AsRef<Path>>(
+    fixture_path: P,
+) -> Result<Vec<ProtocolWrapper>, HolochainError> {
+    let mut messages = Vec::new();
+    for line in BufReader::new(File::open(fixture_path)?).lines() {
+        if let Some(message) = parse_fixture_line(line?) {
+            messages.push(message?);
+        }
+    }
+    Ok(messages)
+}
+
+/// A line of a fixture file that could not be replayed:
+/// the index of the line (0 is the first) and why it failed.
+pub type ReplayFailure = (usize, HolochainError);
+
+/// Feeds the recorded messages of a fixture file through the network handler of the given
+/// context, in the order they were recorded.
+/// Lines that are not a valid message fail. With fail_fast, replaying stops at the first
+/// failing line. Otherwise all other messages still get replayed.
+///
+/// Returns the failures in the order of their lines, or an error if the fixture file
+/// could not be read.
+pub fn replay_fixture<P: AsRef<Path>>(
+    fixture_path: P,
+    context: &Arc<Context>,
+    fail_fast: bool,
+) -> Result<Vec<ReplayFailure>, HolochainError> {
+    let mut failures = Vec::new();
+    for (index, line) in BufReader::new(File::open(fixture_path)?)
+        .lines()
+        .enumerate()
+    {
+        match parse_fixture_line(line?) {
+            Some(Ok(protocol_wrapper)) => {
+                handle_protocol_wrapper(protocol_wrapper, context.clone())
+            }
+            Some(Err(error)) => {
+                failures.push((index, error));
+                if fail_fast {
+                    break;
+                }
+            }
+            None => (),
+        }
+    }
+    Ok(failures)
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate tempfile;
+    use self::tempfile::tempdir;
+    use super::*;
+    use crate::{
+        instance::tests::test_instance_and_context_by_name, network::actions::get_entry::get_entry,
+    };
+    use futures::executor::block_on;
+    use holochain_core_types::{
+        cas::content::{Address, AddressableContent},
+        crud_status::{create_crud_status_eav, CrudStatus},
+        entry::{test_entry, test_entry_b, Entry, EntryWithMeta},
+        error::HcResult,
+    };
+    use holochain_net_connection::protocol_wrapper::DhtData;
+    use std::{thread::sleep, time::Duration};
+    use test_utils::*;
+
+    #[test]
+    fn record_and_replay_get_entry() {
+        let mut dna = create_test_dna_with_wat("test_zome", "test_cap", None);
+        dna.set_uuid(String::from("record_and_replay_get_entry"));
+        let (_, context1) = test_instance_and_context_by_name(dna.clone(), "alice4").unwrap();
+        let (_, context2) = test_instance_and_context_by_name(dna.clone(), "bob4").unwrap();
+
+        let entry = test_entry();
+        context1.file_storage.write().unwrap().add(&entry).unwrap();
+        let status_eav = create_crud_status_eav(&entry.address(), CrudStatus::LIVE);
+        context1
+            .eav_storage
+            .write()
+            .unwrap()
+            .add_eav(&status_eav)
+            .unwrap();
+
+        // record bob's side of the exchange
+        let dir = tempdir().unwrap();
+        let fixture_path = dir.path().join("get_entry.fixture");
+        context2.set_network_recorder(Some(NetworkRecorder::new(&fixture_path)));
+        let recorded_result = block_on(get_entry(&context2, &entry.address()))
+            .unwrap()
+            .expect("entry should be found over the network");
+        context2.set_network_recorder(None);
+
+        let recorded = load_fixture(&fixture_path).unwrap();
+        assert!(recorded.iter().any(|message| match message {
+            ProtocolWrapper::GetDhtResult(_) => true,
+            _ => false,
+        }));
+
+        // replay into an instance that never asked for the entry
+        dna.set_uuid(String::from("record_and_replay_get_entry_replay"));
+        let (_, context3) = test_instance_and_context_by_name(dna, "carol4").unwrap();
+        assert!(replay_fixture(&fixture_path, &context3, false)
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(
+            Some(Some(Ok(Some(recorded_result)))),
+            wait_for_get_result(&context3, &entry.address())
+        );
+    }
+
+    /// Waits until the network state of the given context holds a get result for the address.
+    fn wait_for_get_result(
+        context: &Arc<Context>,
+        address: &Address,
+    ) -> Option<Option<HcResult<Option<EntryWithMeta>>>> {
+        let mut result = None;
+        for _ in 0..100 {
+            result = context
+                .state()
+                .unwrap()
+                .network()
+                .get_entry_with_meta_results
+                .get(address)
+                .cloned();
+            if result.is_some() {
+                break;
+            }
+            sleep(Duration::from_millis(10));
+        }
+        result
+    }
+
+    fn get_result_message(entry: &Entry) -> ProtocolWrapper {
+        let entry_with_meta = EntryWithMeta {
+            entry: entry.clone(),
+            crud_status: CrudStatus::LIVE,
+            maybe_crud_link: None,
+        };
+        ProtocolWrapper::GetDhtResult(DhtData {
+            msg_id: String::from(""),
+            dna_hash: String::from(""),
+            agent_id: String::from(""),
+            address: entry.address().to_string(),
+            content: serde_json::from_str(&serde_json::to_string(&Some(entry_with_meta)).unwrap())
+                .unwrap(),
+        })
+    }
+
+    #[test]
+    fn load_fixture_skips_blank_lines() {
+        let dir = tempdir().unwrap();
+        let fixture_path = dir.path().join("blank_lines.fixture");
+        let recorder = NetworkRecorder::new(&fixture_path);
+        recorder.record(&get_result_message(&test_entry())).unwrap();
+        let mut file = OpenOptions::new().append(true).open(&fixture_path).unwrap();
+        writeln!(file, "  ").unwrap();
+        recorder
+            .record(&get_result_message(&test_entry_b()))
+            .unwrap();
+        assert_eq!(
+            vec![
+                get_result_message(&test_entry()),
+                get_result_message(&test_entry_b())
+            ],
+            load_fixture(&fixture_path).unwrap()
+        );
+
+        writeln!(file, "not a message").unwrap();
+        assert!(load_fixture(&fixture_path).is_err());
+    }
+
+    #[test]
+    fn replay_fixture_reports_failures() {
+        let entry_a = test_entry();
+        let entry_b = test_entry_b();
+        let dir = tempdir().unwrap();
+        let fixture_path = dir.path().join("broken.fixture");
+        let recorder = NetworkRecorder::new(&fixture_path);
+        for line in vec![Some(&entry_a), None, Some(&entry_b), None] {
+            match line {
+                Some(entry) => recorder.record(&get_result_message(entry)).unwrap(),
+                None => {
+                    let mut file = OpenOptions::new().append(true).open(&fixture_path).unwrap();
+                    writeln!(file, "not a message").unwrap();
+                }
+            }
+        }
+
+        let mut dna = create_test_dna_with_wat("test_zome", "test_cap", None);
+        dna.set_uuid(String::from("replay_fixture_reports_failures"));
+        let (_, context1) = test_instance_and_context_by_name(dna.clone(), "alice5").unwrap();
+        let failures: Vec<usize> = replay_fixture(&fixture_path, &context1, false)
+            .unwrap()
+            .into_iter()
+            .map(|(index, _)| index)
+            .collect();
+        assert_eq!(vec![1, 3], failures);
+        for entry in vec![&entry_a, &entry_b] {
+            assert!(wait_for_get_result(&context1, &entry.address()).is_some());
+        }
+
+        let (_, context2) = test_instance_and_context_by_name(dna, "bob5").unwrap();
+        let failures: Vec<usize> = replay_fixture(&fixture_path, &context2, true)
+            .unwrap()
+            .into_iter()
+            .map(|(index, _)| index)
+            .collect();
+        assert_eq!(vec![1], failures);
+        // messages are handled in order, so the one after the failure would be there by now
+        assert!(wait_for_get_result(&context2, &entry_a.address()).is_some());
+        assert!(context2
+            .state()
+            .unwrap()
+            .network()
+            .get_entry_with_meta_results
+            .get(&entry_b.address())
+            .is_none());
+    }
+}
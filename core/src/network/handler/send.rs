@@ -18,7 +18,15 @@ pub fn handle_send(message_data: MessageData, context: Arc<Context>) {
         serde_json::from_str(&serde_json::to_string(&message_data.data).unwrap()).unwrap();
 
     match message {
-        DirectMessage::Custom(_) => context.log("DirectMessage::Custom not implemented"),
+        // custom messages are only handled by consumers of Context::direct_message_stream()
+        DirectMessage::Custom(_) => {
+            if let Err(error) = context.publish_direct_message(&message) {
+                context.log(format!(
+                    "warning: could not deliver direct message {}: {}",
+                    message_data.msg_id, error
+                ));
+            }
+        }
         DirectMessage::RequestValidationPackage(address) => {
             // Async functions only get executed when they are polled.
             // I don't want to wait for this workflow to finish here as it would block the
@@ -65,11 +73,16 @@ pub fn handle_send_result(message_data: MessageData, context: Arc<Context>) {
                 return;
             }
 
-            let initial_message = initial_message.unwrap();
-            let address = unwrap_to!(initial_message => DirectMessage::RequestValidationPackage);
+            match initial_message.unwrap() {
+                DirectMessage::RequestValidationPackage(_) => (),
+                _ => {
+                    context.log("Received a validation package in response to a message that did not request one. Not able to process.");
+                    return;
+                }
+            }
 
             let action_wrapper = ActionWrapper::new(Action::HandleGetValidationPackage((
-                address.clone(),
+                message_data.msg_id.clone(),
                 maybe_validation_package.clone(),
             )));
             dispatch_action(&context.action_channel, action_wrapper.clone());
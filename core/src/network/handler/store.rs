@@ -1,22 +1,290 @@
 use crate::{
+    action::{Action, ActionWrapper},
     context::Context,
     dht::actions::{add_link::add_link, hold::hold_entry},
-    network::entry_with_header::EntryWithHeader,
+    instance::dispatch_action_and_wait,
+    network::{
+        actions::get_validation_package::get_validation_packages,
+        entry_with_header::EntryWithHeader,
+    },
+    nucleus::{
+        actions::validate::validate_entry,
+        ribosome::callback::validation_package::validation_package_definition_for,
+    },
 };
 use futures::executor::block_on;
 use holochain_core_types::{
-    cas::content::Address,
+    cas::content::{Address, AddressableContent},
+    chain_header::ChainHeader,
     crud_status::{CrudStatus, LINK_NAME, STATUS_NAME},
-    entry::Entry,
+    entry::{entry_type::EntryType, verify_entry_address, Entry},
+    error::{HcResult, HolochainError},
+    validation::{
+        EntryAction, EntryLifecycle, ValidationData, ValidationPackage, ValidationPackageDefinition,
+    },
 };
 use holochain_net_connection::protocol_wrapper::{DhtData, DhtMetaData};
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    mem,
+    sync::Arc,
+    thread::{self, sleep},
+};
+
+/// Maximum number of gossiped entries waiting to be stored.
+pub const STORE_QUEUE_CAPACITY: usize = 1000;
+
+/// Gossiped entries waiting to be stored, see handle_store_dht().
+#[derive(Debug, Default)]
+pub struct StoreQueue {
+    pending: Vec<DhtData>,
+    draining: bool,
+}
+
+impl StoreQueue {
+    /// Queues the entry unless STORE_QUEUE_CAPACITY entries are waiting already.
+    /// Returns whether the entry got queued.
+    fn enqueue(&mut self, dht_data: DhtData) -> bool {
+        if self.pending.len() >= STORE_QUEUE_CAPACITY {
+            return false;
+        }
+        self.pending.push(dht_data);
+        true
+    }
+}
 
 /// The network requests us to store (i.e. hold) the given entry.
+/// The entry gets queued and stored on a separate thread, so validation and its retries
+/// don't block the network handler. Entries that arrive while earlier ones are still being
+/// stored are stored together as the next batch, see handle_store_dht_batch().
+/// Entries arriving while the queue is full are dropped, they get stored when they are
+/// gossiped again.
 pub fn handle_store_dht(dht_data: DhtData, context: Arc<Context>) {
+    let queue = context.store_queue();
+    let address = dht_data.address.clone();
+    let start_draining = {
+        let mut queue = queue.lock().unwrap();
+        if !queue.enqueue(dht_data) {
+            context.log(format!("Store queue is full, dropping entry {}", address));
+            return;
+        }
+        !mem::replace(&mut queue.draining, true)
+    };
+    if start_draining {
+        thread::spawn(move || loop {
+            let batch = {
+                let mut queue = queue.lock().unwrap();
+                if queue.pending.is_empty() {
+                    queue.draining = false;
+                    return;
+                }
+                mem::replace(&mut queue.pending, Vec::new())
+            };
+            handle_store_dht_batch(batch, context.clone());
+        });
+    }
+}
+
+/// Stores a burst of gossiped entries.
+/// Entries that don't hash to the address they were sent under are dropped.
+/// The validation package definition is looked up once per entry type, and the packages
+/// of all entries whose validation needs more than their header are fetched from their
+/// authors in one concurrent round.
+/// Authors build packages of chain data from their whole source chain and custom packages
+/// only from the definition, so entries of the same author and package definition share
+/// one package, which gets fetched once and handed to each entry with its own header.
+/// App entries that fail validation permanently are quarantined, transient failures are
+/// retried according to the context's retry policy. Entries whose package could not be
+/// fetched are not held, they get stored when they are gossiped again.
+pub fn handle_store_dht_batch(batch: Vec<DhtData>, context: Arc<Context>) {
+    let fetch_context = context.clone();
+    handle_store_dht_batch_with(batch, context, move |headers| {
+        get_validation_packages(headers, &fetch_context)
+    });
+}
+
+fn handle_store_dht_batch_with<F>(batch: Vec<DhtData>, context: Arc<Context>, fetch_packages: F)
+where
+    F: Fn(&[ChainHeader]) -> Vec<HcResult<Option<ValidationPackage>>>,
+{
+    let mut definitions: HashMap<EntryType, ValidationPackageDefinition> = HashMap::new();
+    // Entries sharing a package, by package definition and author
+    let mut needing_package: Vec<(ValidationPackageDefinition, Vec<EntryWithHeader>)> = Vec::new();
+    for dht_data in batch {
+        let entry_with_header = match receive_entry(dht_data, &context) {
+            Some(entry_with_header) => entry_with_header,
+            None => continue,
+        };
+        let entry_type = entry_with_header.entry_body.entry_type();
+        let definition = definitions
+            .entry(entry_type.clone())
+            .or_insert_with(|| {
+                validation_package_definition_for(&entry_type, context.clone())
+                    .unwrap_or(ValidationPackageDefinition::Entry)
+            })
+            .clone();
+        match definition {
+            ValidationPackageDefinition::Entry => {
+                let package = ValidationPackage::only_header(entry_with_header.header.clone());
+                let result = validate_received_entry(&entry_with_header, package, &context);
+                hold_or_quarantine(entry_with_header, result, &context);
+            }
+            definition => {
+                let shared = needing_package
+                    .iter_mut()
+                    .find(|(group_definition, group)| {
+                        *group_definition == definition
+                            && group[0].header.sources() == entry_with_header.header.sources()
+                    });
+                match shared {
+                    Some((_, group)) => group.push(entry_with_header),
+                    None => needing_package.push((definition, vec![entry_with_header])),
+                }
+            }
+        }
+    }
+
+    let headers: Vec<ChainHeader> = needing_package
+        .iter()
+        .map(|(_, group)| group[0].header.clone())
+        .collect();
+    for ((_, group), maybe_package) in needing_package.into_iter().zip(fetch_packages(&headers)) {
+        for entry_with_header in group {
+            match maybe_package {
+                Ok(Some(ref package)) => {
+                    let package = ValidationPackage {
+                        chain_header: Some(entry_with_header.header.clone()),
+                        ..package.clone()
+                    };
+                    let result = validate_received_entry(&entry_with_header, package, &context);
+                    hold_or_quarantine(entry_with_header, result, &context);
+                }
+                Ok(None) => context.log(format!(
+                    "No validation package for entry {}, not holding it",
+                    entry_with_header.entry_body.address()
+                )),
+                Err(ref error) => context.log(format!(
+                    "Could not get validation package for entry {}, not holding it: {}",
+                    entry_with_header.entry_body.address(),
+                    error
+                )),
+            }
+        }
+    }
+}
+
+/// Parses a gossiped entry, dropping it if it doesn't hash to the address it was sent
+/// under or has been quarantined before.
+fn receive_entry(dht_data: DhtData, context: &Arc<Context>) -> Option<EntryWithHeader> {
     let entry_with_header: EntryWithHeader =
         serde_json::from_str(&serde_json::to_string(&dht_data.content).unwrap()).unwrap();
-    let _ = block_on(hold_entry(&entry_with_header.entry_body, &context.clone()));
+    let claimed_address = Address::from(dht_data.address.clone());
+    if let Err(error) = verify_entry_address(&entry_with_header.entry_body, &claimed_address) {
+        context.log(format!("Refusing to hold entry: {}", error));
+        return None;
+    }
+    if context
+        .state()
+        .unwrap()
+        .dht()
+        .is_quarantined(&claimed_address)
+    {
+        context.log(format!("Ignoring quarantined entry {}", claimed_address));
+        return None;
+    }
+    Some(entry_with_header)
+}
+
+/// Holds a validated entry, quarantines a permanently invalid one.
+fn hold_or_quarantine(
+    entry_with_header: EntryWithHeader,
+    validation_result: Result<(), HolochainError>,
+    context: &Arc<Context>,
+) {
+    let address = entry_with_header.entry_body.address();
+    match validation_result {
+        Ok(()) => {
+            let _ = block_on(hold_entry(&entry_with_header.entry_body, &context.clone()));
+        }
+        Err(HolochainError::ValidationFailed(reason)) => {
+            context.log(format!("Quarantining entry {}: {}", address, reason));
+            dispatch_action_and_wait(
+                &context.action_channel,
+                &context.observer_channel,
+                ActionWrapper::new(Action::Quarantine((address, reason))),
+            );
+        }
+        Err(error) => {
+            context.log(format!(
+                "Could not validate entry {}, not holding it: {}",
+                address, error
+            ));
+        }
+    }
+}
+
+/// Validates an entry received through gossip, retrying all failures but definite ones
+/// (HolochainError::ValidationFailed) with the backoff of the context's retry policy.
+/// Entries of app entry types the DNA doesn't define are retried as well, since they
+/// don't prove the entry invalid.
+/// Only app entries are validated, all other entry types pass. Entries of encrypted types
+/// are validated in their encrypted form, without the checks of their content.
+fn validate_received_entry(
+    entry_with_header: &EntryWithHeader,
+    package: ValidationPackage,
+    context: &Arc<Context>,
+) -> Result<(), HolochainError> {
+    let entry_type = entry_with_header.entry_body.entry_type();
+    match entry_type {
+        EntryType::App(_) => (),
+        _ => return Ok(()),
+    }
+    let validation_data = ValidationData {
+        sources: entry_with_header.header.sources().clone(),
+        package,
+        lifecycle: EntryLifecycle::Dht,
+        action: EntryAction::Create,
+    };
+    let validate_once = || {
+        if !is_known_entry_type(&entry_type, context) {
+            return Err(HolochainError::ValidationPending(format!(
+                "Unknown entry type {}",
+                entry_type
+            )));
+        }
+        block_on(validate_entry(
+            entry_with_header.entry_body.clone(),
+            validation_data.clone(),
+            context,
+        ))
+    };
+    let retry_policy = context.retry_policy();
+    let mut attempt = 1;
+    loop {
+        let result = validate_once();
+        let definite = match result {
+            Ok(_) | Err(HolochainError::ValidationFailed(_)) => true,
+            Err(_) => false,
+        };
+        if definite || attempt >= retry_policy.max_attempts {
+            return result.map(|_| ());
+        }
+        sleep(retry_policy.delay_after(attempt));
+        attempt += 1;
+    }
+}
+
+fn is_known_entry_type(entry_type: &EntryType, context: &Arc<Context>) -> bool {
+    match entry_type {
+        EntryType::App(app_entry_type) => context
+            .get_dna()
+            .and_then(|dna| {
+                dna.get_zome_name_for_app_entry_type(app_entry_type)
+                    .unwrap_or(None)
+            })
+            .is_some(),
+        _ => true,
+    }
 }
 
 /// The network requests us to store meta information (links/CRUD/etc) for an
@@ -55,3 +323,342 @@ pub fn handle_store_dht_meta(dht_meta_data: DhtMetaData, context: Arc<Context>)
         _ => {}
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        network::retry::RetryPolicy,
+        nucleus::{
+            actions::tests::{instance_by_name, test_dna},
+            state::ValidationResult,
+        },
+        workflows::author_entry::author_entry,
+    };
+    use holochain_core_types::{
+        cas::content::AddressableContent,
+        chain_header::test_sources,
+        dna::zome::entry_types::Sharing,
+        entry::{entry_type::test_app_entry_type, test_entry},
+        json::JsonString,
+        time::test_iso_8601,
+    };
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::{Duration, Instant},
+    };
+
+    fn dht_data(entry: &Entry) -> DhtData {
+        dht_data_from(entry, test_sources())
+    }
+
+    fn dht_data_from(entry: &Entry, sources: Vec<Address>) -> DhtData {
+        let header = ChainHeader::new(
+            &entry.entry_type(),
+            &entry.address(),
+            &sources,
+            &Vec::new(),
+            &None,
+            &None,
+            &None,
+            &test_iso_8601(),
+        );
+        let entry_with_header = EntryWithHeader::new(entry.clone(), header);
+        DhtData {
+            msg_id: String::from("test"),
+            dna_hash: String::from("dna"),
+            agent_id: String::from("agent"),
+            address: entry.address().to_string(),
+            content: serde_json::from_str(&serde_json::to_string(&entry_with_header).unwrap())
+                .unwrap(),
+        }
+    }
+
+    fn holds(context: &Arc<Context>, address: &Address) -> bool {
+        context
+            .state()
+            .unwrap()
+            .dht()
+            .content_storage()
+            .read()
+            .unwrap()
+            .contains(address)
+            .unwrap()
+    }
+
+    #[test]
+    fn permanently_invalid_entry_is_quarantined() {
+        let (_instance, context) = instance_by_name("jill", test_dna());
+        context.register_native_validator(
+            EntryType::App(test_app_entry_type()),
+            Box::new(|_: &Entry, _: &ValidationData| {
+                ValidationResult::permanent_failure("always invalid")
+            }),
+        );
+
+        let entry = test_entry();
+        handle_store_dht_batch(vec![dht_data(&entry)], context.clone());
+
+        assert!(!holds(&context, &entry.address()));
+        let state = context.state().unwrap();
+        assert!(state.dht().is_quarantined(&entry.address()));
+        assert_eq!(
+            Some(&String::from("always invalid")),
+            state.dht().quarantine().get(&entry.address())
+        );
+    }
+
+    #[test]
+    fn transiently_invalid_entry_is_retried() {
+        let (_instance, context) = instance_by_name("jack", test_dna());
+        let mut context_with_retries = (*context).clone();
+        context_with_retries.set_retry_policy(RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            ..Default::default()
+        });
+        let context = Arc::new(context_with_retries);
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let validator_calls = calls.clone();
+        context.register_native_validator(
+            EntryType::App(test_app_entry_type()),
+            Box::new(move |_: &Entry, _: &ValidationData| {
+                if validator_calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                    ValidationResult::transient_failure("dependency not received yet")
+                } else {
+                    ValidationResult::Pass
+                }
+            }),
+        );
+
+        let entry = test_entry();
+        handle_store_dht_batch(vec![dht_data(&entry)], context.clone());
+
+        assert_eq!(3, calls.load(Ordering::SeqCst));
+        assert!(holds(&context, &entry.address()));
+        assert!(!context
+            .state()
+            .unwrap()
+            .dht()
+            .is_quarantined(&entry.address()));
+    }
+
+    #[test]
+    fn entry_of_encrypted_type_is_validated() {
+        let mut dna = test_dna();
+        dna.zomes_mut()
+            .get_mut("test_zome")
+            .unwrap()
+            .entry_types
+            .get_mut(&EntryType::App(test_app_entry_type()))
+            .unwrap()
+            .sharing = Sharing::Encrypted;
+        let (_instance, context) = instance_by_name("jane", dna);
+        context.register_native_validator(
+            EntryType::App(test_app_entry_type()),
+            Box::new(|_: &Entry, _: &ValidationData| {
+                ValidationResult::permanent_failure("always invalid")
+            }),
+        );
+
+        let entry = test_entry();
+        handle_store_dht_batch(vec![dht_data(&entry)], context.clone());
+
+        assert!(!holds(&context, &entry.address()));
+        assert!(context
+            .state()
+            .unwrap()
+            .dht()
+            .is_quarantined(&entry.address()));
+    }
+
+    #[test]
+    fn entry_is_not_checked_against_local_chain() {
+        let (_instance, context) = instance_by_name("joan", test_dna());
+        context.register_native_validator(
+            EntryType::App(test_app_entry_type()),
+            Box::new(|_: &Entry, _: &ValidationData| ValidationResult::Pass),
+        );
+        // the local chain head is younger than the gossiped entry
+        block_on(author_entry(&test_entry(), None, &context)).unwrap();
+
+        let entry = Entry::App(
+            test_app_entry_type(),
+            JsonString::from("{\"created_at\":\"2018-10-12T00:00:00+00:00\"}"),
+        );
+        handle_store_dht_batch(vec![dht_data(&entry)], context.clone());
+
+        assert!(holds(&context, &entry.address()));
+    }
+
+    #[test]
+    fn entry_of_unknown_type_is_not_quarantined() {
+        let (_instance, context) = instance_by_name("jean", test_dna());
+        let mut context_with_retries = (*context).clone();
+        context_with_retries.set_retry_policy(RetryPolicy {
+            max_attempts: 2,
+            base_delay: Duration::from_millis(1),
+            ..Default::default()
+        });
+        let context = Arc::new(context_with_retries);
+
+        let entry = Entry::App("unknown_type".into(), JsonString::from("{}"));
+        handle_store_dht_batch(vec![dht_data(&entry)], context.clone());
+
+        assert!(!holds(&context, &entry.address()));
+        assert!(!context
+            .state()
+            .unwrap()
+            .dht()
+            .is_quarantined(&entry.address()));
+    }
+
+    fn test_entries(count: usize) -> Vec<Entry> {
+        (0..count)
+            .map(|i| {
+                Entry::App(
+                    test_app_entry_type(),
+                    JsonString::from(format!("entry {}", i)),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn queued_entries_get_stored() {
+        let (_instance, context) = instance_by_name("jim", test_dna());
+        context.register_native_validator(
+            EntryType::App(test_app_entry_type()),
+            Box::new(|_: &Entry, _: &ValidationData| ValidationResult::Pass),
+        );
+
+        let entries = test_entries(3);
+        for entry in entries.iter() {
+            handle_store_dht(dht_data(entry), context.clone());
+        }
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !entries
+            .iter()
+            .all(|entry| holds(&context, &entry.address()))
+        {
+            assert!(Instant::now() < deadline, "queued entries were not stored");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn store_queue_is_bounded() {
+        let mut queue = StoreQueue::default();
+        let entry = test_entry();
+        for _ in 0..STORE_QUEUE_CAPACITY {
+            assert!(queue.enqueue(dht_data(&entry)));
+        }
+        assert!(!queue.enqueue(dht_data(&entry)));
+        assert_eq!(STORE_QUEUE_CAPACITY, queue.pending.len());
+    }
+
+    #[test]
+    fn batch_shares_package_per_author() {
+        let (_instance, context) = instance_by_name("jane", test_dna());
+        context.cache_validation_package_definition(
+            &test_app_entry_type(),
+            ValidationPackageDefinition::ChainFull,
+        );
+        let validated = Arc::new(AtomicUsize::new(0));
+        let validator_calls = validated.clone();
+        context.register_native_validator(
+            EntryType::App(test_app_entry_type()),
+            Box::new(move |entry: &Entry, validation_data: &ValidationData| {
+                validator_calls.fetch_add(1, Ordering::SeqCst);
+                let package = &validation_data.package;
+                let own_header = package
+                    .chain_header
+                    .as_ref()
+                    .map(|header| header.entry_address() == &entry.address())
+                    .unwrap_or(false);
+                match package.custom {
+                    Some(ref author)
+                        if own_header
+                            && validation_data.sources == vec![Address::from(author.clone())] =>
+                    {
+                        ValidationResult::Pass
+                    }
+                    _ => ValidationResult::permanent_failure("package of another entry"),
+                }
+            }),
+        );
+
+        let entries = test_entries(5);
+        let other_author = vec![Address::from("other author")];
+        let batch = entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| match index {
+                0 | 1 | 2 => dht_data(entry),
+                _ => dht_data_from(entry, other_author.clone()),
+            })
+            .collect();
+        let rounds = AtomicUsize::new(0);
+        let fetched = AtomicUsize::new(0);
+        handle_store_dht_batch_with(batch, context.clone(), |headers| {
+            rounds.fetch_add(1, Ordering::SeqCst);
+            fetched.fetch_add(headers.len(), Ordering::SeqCst);
+            headers
+                .iter()
+                .map(|header| {
+                    Ok(Some(ValidationPackage {
+                        custom: Some(header.sources()[0].to_string()),
+                        ..ValidationPackage::only_header(header.clone())
+                    }))
+                })
+                .collect()
+        });
+
+        assert_eq!(1, rounds.load(Ordering::SeqCst));
+        // one package per author
+        assert_eq!(2, fetched.load(Ordering::SeqCst));
+        assert_eq!(5, validated.load(Ordering::SeqCst));
+        for entry in entries.iter() {
+            assert!(holds(&context, &entry.address()));
+        }
+    }
+
+    #[test]
+    fn batch_entry_without_package_is_not_quarantined() {
+        let (_instance, context) = instance_by_name("joe", test_dna());
+        context.cache_validation_package_definition(
+            &test_app_entry_type(),
+            ValidationPackageDefinition::ChainFull,
+        );
+        context.register_native_validator(
+            EntryType::App(test_app_entry_type()),
+            Box::new(|_: &Entry, _: &ValidationData| {
+                ValidationResult::permanent_failure("validated without package")
+            }),
+        );
+
+        let entries = test_entries(2);
+        handle_store_dht_batch_with(
+            vec![
+                dht_data(&entries[0]),
+                dht_data_from(&entries[1], vec![Address::from("other author")]),
+            ],
+            context.clone(),
+            |headers| {
+                assert_eq!(2, headers.len());
+                vec![Err(HolochainError::Timeout), Ok(None)]
+            },
+        );
+
+        for entry in entries.iter() {
+            assert!(!holds(&context, &entry.address()));
+            assert!(!context
+                .state()
+                .unwrap()
+                .dht()
+                .is_quarantined(&entry.address()));
+        }
+    }
+}
@@ -1,29 +1,66 @@
 pub mod actions;
+pub mod bandwidth;
 pub mod direct_message;
 pub mod entry_with_header;
 pub mod handler;
+pub mod pause;
+pub mod peer_rotation;
 pub mod reducers;
+pub mod request_signer;
+pub mod retry;
 pub mod state;
 
 #[cfg(test)]
 pub mod tests {
     use crate::{
-        instance::tests::test_instance_and_context_by_name,
-        network::actions::{get_entry::get_entry, get_validation_package::get_validation_package},
+        action::{Action, ActionWrapper, DirectMessageData},
+        context::Context,
+        instance::{
+            dispatch_action,
+            tests::{test_context, test_context_with_channels, test_instance_and_context_by_name},
+            Instance,
+        },
+        network::{
+            actions::{
+                get_entry::{
+                    get_entries, get_entries_with_options, get_entry, get_entry_with_options,
+                    GetEntryOptions,
+                },
+                get_validation_package::{get_validation_package, get_validation_packages},
+                initialize_network::initialize_network,
+                publish::publish,
+            },
+            direct_message::DirectMessage,
+            handler::send::handle_send,
+            peer_rotation::DEGRADED_AFTER_FAILURES,
+            request_signer::tests::TestSigner,
+        },
+        nucleus::actions::initialize::initialize_application,
+        state::State,
         workflows::author_entry::author_entry,
     };
-    use futures::executor::block_on;
+    use futures::{executor::block_on, stream::StreamExt};
     use holochain_core_types::{
-        cas::content::AddressableContent,
+        cas::content::{Address, AddressableContent},
+        chain_header::test_chain_header,
         crud_status::{create_crud_status_eav, CrudStatus},
-        entry::test_entry,
+        dna::{zome::entry_types::Sharing, Dna},
+        entry::{test_entry, test_entry_b, test_entry_c},
+        entry_acl::create_acl_reader_eav,
+        error::HolochainError,
+    };
+    use holochain_net_connection::protocol_wrapper::MessageData;
+    use std::{
+        sync::{mpsc::sync_channel, Arc, RwLock},
+        thread,
+        time::{Duration, Instant},
     };
     use test_utils::*;
 
     #[test]
     fn get_entry_roundtrip() {
         let mut dna = create_test_dna_with_wat("test_zome", "test_cap", None);
-        dna.uuid = String::from("get_entry_roundtrip");
+        dna.set_uuid(String::from("get_entry_roundtrip"));
         let (_, context1) = test_instance_and_context_by_name(dna.clone(), "alice1").unwrap();
         let (_, context2) = test_instance_and_context_by_name(dna.clone(), "bob1").unwrap();
 
@@ -45,10 +82,133 @@ pub mod tests {
         assert_eq!(entry_with_meta.crud_status, CrudStatus::LIVE);
     }
 
+    /// Initializes an instance with the given DNA and context and returns the instance's context.
+    fn start_instance(dna: &Dna, context: Arc<Context>) -> Arc<Context> {
+        let mut instance = Instance::new(context.clone());
+        instance.start_action_loop(context.clone());
+        let context = instance.initialize_context(context);
+        block_on(
+            async {
+                await!(initialize_application(dna.clone(), &context))?;
+                await!(initialize_network(&context))
+            },
+        )
+        .unwrap();
+        context
+    }
+
+    #[test]
+    fn dna_hash_is_hash_of_committed_dna() {
+        let mut dna = create_test_dna_with_wat("test_zome", "test_cap", None);
+        dna.set_uuid(String::from("dna_hash_is_hash_of_committed_dna"));
+        dna.zomes_mut()
+            .get_mut("test_zome")
+            .unwrap()
+            .capabilities
+            .insert(String::from("test_cap"), create_test_cap_with_fn_name("*"));
+        // test_instance_and_context_by_name() expects the running DNA to equal the given one
+        let context = start_instance(&dna, test_context("carla1"));
+
+        // the running DNA has the wildcard expanded, the network still uses the original
+        assert_ne!(dna, context.get_dna().unwrap());
+        assert_eq!(
+            Some(base64::encode(&dna.multihash().unwrap())),
+            context.state().unwrap().network().dna_hash
+        );
+    }
+
+    #[test]
+    fn get_entries_roundtrip() {
+        let mut dna = create_test_dna_with_wat("test_zome", "test_cap", None);
+        dna.set_uuid(String::from("get_entries_roundtrip"));
+        let (_, context1) = test_instance_and_context_by_name(dna.clone(), "alice8").unwrap();
+        let (_, context2) = test_instance_and_context_by_name(dna.clone(), "bob8").unwrap();
+
+        let stored = vec![test_entry(), test_entry_b()];
+        for entry in stored.iter() {
+            context1.file_storage.write().unwrap().add(entry).unwrap();
+            let status_eav = create_crud_status_eav(&entry.address(), CrudStatus::LIVE);
+            context1
+                .eav_storage
+                .write()
+                .unwrap()
+                .add_eav(&status_eav)
+                .unwrap();
+        }
+
+        // the missing entry gets a None in between
+        let addresses = vec![
+            stored[1].address(),
+            test_entry_c().address(),
+            stored[0].address(),
+        ];
+        let results = block_on(get_entries(&context2, &addresses)).unwrap();
+        assert_eq!(
+            vec![Some(stored[1].clone()), None, Some(stored[0].clone())],
+            results
+                .into_iter()
+                .map(|result| result.map(|entry_with_meta| entry_with_meta.entry))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn get_entry_respects_acl() {
+        let mut dna = create_test_dna_with_wat("test_zome", "test_cap", None);
+        dna.set_uuid(String::from("get_entry_respects_acl"));
+        dna.zomes_mut()
+            .get_mut("test_zome")
+            .unwrap()
+            .entry_types
+            .get_mut(&test_entry().entry_type())
+            .unwrap()
+            .sharing = Sharing::Private;
+        let (_, context1) = test_instance_and_context_by_name(dna.clone(), "alice5").unwrap();
+        let (_, context2) = test_instance_and_context_by_name(dna.clone(), "bob5").unwrap();
+        let (_, context3) = test_instance_and_context_by_name(dna.clone(), "carol5").unwrap();
+        for context in vec![&context1, &context2, &context3] {
+            context.set_request_signer(Arc::new(TestSigner(Address::from(
+                context.agent_id.key.clone(),
+            ))));
+        }
+
+        // alice holds the entry and lets only bob read it
+        let entry = test_entry();
+        // ... and holds another one nobody is allowed to read
+        let unlisted_entry = test_entry_b();
+        context1.file_storage.write().unwrap().add(&entry).unwrap();
+        context1
+            .file_storage
+            .write()
+            .unwrap()
+            .add(&unlisted_entry)
+            .unwrap();
+        let bob = Address::from(context2.agent_id.key.clone());
+        for eav in vec![
+            create_crud_status_eav(&entry.address(), CrudStatus::LIVE),
+            create_crud_status_eav(&unlisted_entry.address(), CrudStatus::LIVE),
+            create_acl_reader_eav(&entry.address(), &bob),
+        ] {
+            context1.eav_storage.write().unwrap().add_eav(&eav).unwrap();
+        }
+
+        let result = block_on(get_entry(&context2, &entry.address())).unwrap();
+        assert_eq!(
+            Some(entry.clone()),
+            result.map(|entry_with_meta| entry_with_meta.entry)
+        );
+
+        let result = block_on(get_entry(&context3, &entry.address())).unwrap();
+        assert!(result.is_none());
+
+        let result = block_on(get_entry(&context2, &unlisted_entry.address())).unwrap();
+        assert!(result.is_none());
+    }
+
     #[test]
     fn get_non_existant_entry() {
         let mut dna = create_test_dna_with_wat("test_zome", "test_cap", None);
-        dna.uuid = String::from("get_non_existant_entry");
+        dna.set_uuid(String::from("get_non_existant_entry"));
         let (_, _) = test_instance_and_context_by_name(dna.clone(), "alice2").unwrap();
         let (_, context2) = test_instance_and_context_by_name(dna.clone(), "bob2").unwrap();
 
@@ -63,7 +223,7 @@ pub mod tests {
     #[test]
     fn get_when_alone() {
         let mut dna = create_test_dna_with_wat("test_zome", "test_cap", None);
-        dna.uuid = String::from("get_when_alone");
+        dna.set_uuid(String::from("get_when_alone"));
         let (_, context1) = test_instance_and_context_by_name(dna.clone(), "bob3").unwrap();
 
         let entry = test_entry();
@@ -75,8 +235,206 @@ pub mod tests {
     }
 
     #[test]
-    fn get_validation_package_roundtrip() {
-        let wat = r#"
+    fn get_entry_with_options_times_out() {
+        let mut dna = create_test_dna_with_wat("test_zome", "test_cap", None);
+        dna.set_uuid(String::from("get_entry_with_options_times_out"));
+        let (_, context) = test_instance_and_context_by_name(dna.clone(), "dave1").unwrap();
+
+        let options = GetEntryOptions {
+            timeout: Duration::from_millis(0),
+            retries: 1,
+        };
+        let result = block_on(get_entry_with_options(
+            &context,
+            &test_entry().address(),
+            options,
+        ));
+        assert_eq!(Err(HolochainError::Timeout), result);
+    }
+
+    #[test]
+    fn get_entry_retries_with_another_peer() {
+        let mut dna = create_test_dna_with_wat("test_zome", "test_cap", None);
+        dna.set_uuid(String::from("get_entry_retries_with_another_peer"));
+        let (_, context1) = test_instance_and_context_by_name(dna.clone(), "alice10").unwrap();
+        let (_, context2) = test_instance_and_context_by_name(dna.clone(), "bob10").unwrap();
+        let (_, context3) = test_instance_and_context_by_name(dna.clone(), "carol10").unwrap();
+
+        let entry = test_entry();
+        for context in vec![&context1, &context2] {
+            context.file_storage.write().unwrap().add(&entry).unwrap();
+            let status_eav = create_crud_status_eav(&entry.address(), CrudStatus::LIVE);
+            context
+                .eav_storage
+                .write()
+                .unwrap()
+                .add_eav(&status_eav)
+                .unwrap();
+        }
+        // alice holds the request without answering
+        context1.pause_network();
+        for context in vec![&context1, &context2] {
+            context3
+                .peer_rotation()
+                .lock()
+                .unwrap()
+                .add_peer(&context.agent_id.key);
+        }
+
+        let options = GetEntryOptions {
+            timeout: Duration::from_millis(500),
+            retries: 1,
+        };
+        let result = block_on(get_entry_with_options(
+            &context3,
+            &entry.address(),
+            options.clone(),
+        ))
+        .unwrap();
+        assert_eq!(Some(entry.clone()), result.map(|result| result.entry));
+
+        // alice is asked first again until failing too often
+        let alice = context1.agent_id.key.clone();
+        for _ in 1..DEGRADED_AFTER_FAILURES {
+            block_on(get_entry_with_options(
+                &context3,
+                &entry.address(),
+                options.clone(),
+            ))
+            .unwrap();
+        }
+        assert!(context3.peer_rotation().lock().unwrap().is_degraded(&alice));
+        let start = Instant::now();
+        block_on(get_entry_with_options(&context3, &entry.address(), options)).unwrap();
+        assert!(start.elapsed() < Duration::from_millis(500));
+    }
+
+    #[test]
+    fn get_entries_retries_timed_out_lookups_together() {
+        let mut dna = create_test_dna_with_wat("test_zome", "test_cap", None);
+        dna.set_uuid(String::from(
+            "get_entries_retries_timed_out_lookups_together",
+        ));
+        let (_, context1) = test_instance_and_context_by_name(dna.clone(), "alice12").unwrap();
+        let (_, context2) = test_instance_and_context_by_name(dna.clone(), "bob12").unwrap();
+        let (_, context3) = test_instance_and_context_by_name(dna.clone(), "carol12").unwrap();
+
+        let stored = vec![test_entry(), test_entry_b()];
+        for context in vec![&context1, &context2] {
+            for entry in stored.iter() {
+                context.file_storage.write().unwrap().add(entry).unwrap();
+                let status_eav = create_crud_status_eav(&entry.address(), CrudStatus::LIVE);
+                context
+                    .eav_storage
+                    .write()
+                    .unwrap()
+                    .add_eav(&status_eav)
+                    .unwrap();
+            }
+        }
+        // alice holds the requests without answering
+        context1.pause_network();
+        for context in vec![&context1, &context2] {
+            context3
+                .peer_rotation()
+                .lock()
+                .unwrap()
+                .add_peer(&context.agent_id.key);
+        }
+        let addresses: Vec<Address> = stored.iter().map(|entry| entry.address()).collect();
+
+        // both look-ups time out with alice and go to bob in the second round
+        let options = GetEntryOptions {
+            timeout: Duration::from_millis(500),
+            retries: 1,
+        };
+        let results = block_on(get_entries_with_options(&context3, &addresses, options)).unwrap();
+        assert_eq!(
+            vec![Some(stored[0].clone()), Some(stored[1].clone())],
+            results
+                .into_iter()
+                .map(|result| result.map(|entry_with_meta| entry_with_meta.entry))
+                .collect::<Vec<_>>()
+        );
+
+        // without retries the time-outs with alice fail the batch
+        let options = GetEntryOptions {
+            timeout: Duration::from_millis(500),
+            retries: 0,
+        };
+        assert_eq!(
+            Err(HolochainError::Timeout),
+            block_on(get_entries_with_options(&context3, &addresses, options))
+        );
+    }
+
+    #[test]
+    fn queued_gossip_gets_flushed_without_further_actions() {
+        let mut dna = create_test_dna_with_wat("test_zome", "test_cap", None);
+        dna.set_uuid(String::from(
+            "queued_gossip_gets_flushed_without_further_actions",
+        ));
+        let mut context = (*test_context("alice9")).clone();
+        // lets a single message per second through
+        context.set_gossip_bandwidth_limit(Some(1));
+        let context = start_instance(&dna, Arc::new(context));
+
+        // publishing sends the entry and its meta data, which exceeds the budget
+        block_on(author_entry(&test_entry(), None, &context)).unwrap();
+        let queued = || {
+            context
+                .state()
+                .unwrap()
+                .network()
+                .bandwidth_usage()
+                .queued_messages
+        };
+        assert!(queued() > 0);
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while queued() > 0 {
+            assert!(Instant::now() < deadline, "queued gossip was not flushed");
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    #[test]
+    fn offline_never_dispatches_network_actions() {
+        let (action_sender, action_receiver) = sync_channel(Context::default_channel_buffer_size());
+        let (observer_sender, _) = sync_channel(Context::default_channel_buffer_size());
+        let mut context =
+            (*test_context_with_channels("offline", &action_sender, &observer_sender)).clone();
+        context.set_network_enabled(false);
+        let state = Arc::new(RwLock::new(State::new(Arc::new(context.clone()))));
+        context.set_state(state);
+        let context = Arc::new(context);
+
+        let entry = test_entry();
+        context.file_storage.write().unwrap().add(&entry).unwrap();
+        let status_eav = create_crud_status_eav(&entry.address(), CrudStatus::LIVE);
+        context.eav_storage.write().unwrap().add_eav(&status_eav).unwrap();
+
+        assert_eq!(Ok(()), block_on(initialize_network(&context)));
+
+        let entry_with_meta = block_on(get_entry(&context, &entry.address()))
+            .unwrap()
+            .expect("entry should be served from local storage");
+        assert_eq!(entry, entry_with_meta.entry);
+
+        let result = block_on(get_validation_package(test_chain_header(), &context));
+        assert_eq!(Ok(None), result);
+
+        assert_eq!(
+            Ok(entry.address()),
+            block_on(publish(entry.address(), &context))
+        );
+
+        assert!(action_receiver.try_recv().is_err());
+    }
+
+    /// WAT of a zome that accepts all entries and links and asks for "Entry" validation packages
+    fn validation_package_wat() -> &'static str {
+        r#"
 (module
 
     (memory 1)
@@ -141,10 +499,15 @@ pub mod tests {
         (i32.const 0)
     )
 )
-                "#;
+                "#
+    }
+
+    #[test]
+    fn get_validation_package_roundtrip() {
+        let wat = validation_package_wat();
 
         let mut dna = create_test_dna_with_wat("test_zome", "test_cap", Some(wat));
-        dna.uuid = String::from("get_validation_package_roundtrip");
+        dna.set_uuid(String::from("get_validation_package_roundtrip"));
         let (_, context1) = test_instance_and_context_by_name(dna.clone(), "alice1").unwrap();
 
         let entry = test_entry();
@@ -166,4 +529,118 @@ pub mod tests {
         let validation_package = maybe_validation_package.unwrap();
         assert_eq!(validation_package.chain_header, Some(header));
     }
+
+    #[test]
+    fn get_validation_packages_in_header_order() {
+        let mut dna =
+            create_test_dna_with_wat("test_zome", "test_cap", Some(validation_package_wat()));
+        dna.set_uuid(String::from("get_validation_packages_in_header_order"));
+        let (_, context1) = test_instance_and_context_by_name(dna.clone(), "alice4").unwrap();
+
+        for entry in vec![test_entry(), test_entry_b(), test_entry_c()] {
+            block_on(author_entry(&entry, None, &context1)).expect("Could not author entry");
+        }
+        let agent1_state = context1.state().unwrap().agent();
+        let headers: Vec<_> = agent1_state
+            .chain()
+            .iter_type(&agent1_state.top_chain_header(), &test_entry().entry_type())
+            .collect();
+        assert_eq!(3, headers.len());
+
+        let (_, context2) = test_instance_and_context_by_name(dna.clone(), "bob4").unwrap();
+        // from the network for bob, built locally for alice
+        for context in vec![context2, context1] {
+            let chain_headers: Vec<_> = get_validation_packages(&headers, &context)
+                .into_iter()
+                .map(|result| result.unwrap().unwrap().chain_header)
+                .collect();
+            assert_eq!(
+                headers.iter().cloned().map(Some).collect::<Vec<_>>(),
+                chain_headers
+            );
+        }
+    }
+
+    #[test]
+    fn direct_message_stream_yields_messages_in_order() {
+        let mut dna = create_test_dna_with_wat("test_zome", "test_cap", None);
+        dna.set_uuid(String::from(
+            "direct_message_stream_yields_messages_in_order",
+        ));
+        let (_, context1) = test_instance_and_context_by_name(dna.clone(), "alice6").unwrap();
+        let (_, context2) = test_instance_and_context_by_name(dna.clone(), "bob6").unwrap();
+        let stream = context2.direct_message_stream();
+
+        let messages: Vec<DirectMessage> = vec!["one", "two", "three"]
+            .into_iter()
+            .map(|text| DirectMessage::Custom(String::from(text)))
+            .collect();
+        for (index, message) in messages.iter().enumerate() {
+            let direct_message_data = DirectMessageData {
+                address: Address::from(context2.agent_id.key.clone()),
+                message: message.clone(),
+                msg_id: format!("direct_message_stream_{}", index),
+                is_response: false,
+            };
+            dispatch_action(
+                &context1.action_channel,
+                ActionWrapper::new(Action::SendDirectMessage(direct_message_data)),
+            );
+        }
+
+        let received: Vec<DirectMessage> = block_on(stream.take(3).collect());
+        assert_eq!(messages, received);
+    }
+
+    #[test]
+    fn only_custom_direct_messages_are_published() {
+        let context = test_context("alice11");
+        let stream = context.direct_message_stream();
+
+        let messages = vec![
+            DirectMessage::ValidationPackage(None),
+            DirectMessage::Custom(String::from("custom")),
+        ];
+        for (index, message) in messages.into_iter().enumerate() {
+            let message_data = MessageData {
+                msg_id: format!("only_custom_{}", index),
+                dna_hash: String::new(),
+                to_agent_id: context.agent_id.key.clone(),
+                from_agent_id: String::from("bob"),
+                data: serde_json::to_value(&message).unwrap(),
+            };
+            handle_send(message_data, context.clone());
+        }
+
+        let received: Vec<DirectMessage> = block_on(stream.take(1).collect());
+        assert_eq!(
+            vec![DirectMessage::Custom(String::from("custom"))],
+            received
+        );
+    }
+
+    #[test]
+    fn full_direct_message_stream_does_not_block() {
+        let context = test_context("alice7");
+        let stream = context.direct_message_stream();
+        let buffer_size = Context::default_channel_buffer_size();
+
+        for index in 0..buffer_size {
+            assert_eq!(
+                Ok(()),
+                context.publish_direct_message(&DirectMessage::Custom(index.to_string()))
+            );
+        }
+        assert!(context
+            .publish_direct_message(&DirectMessage::Custom(buffer_size.to_string()))
+            .is_err());
+
+        // the message that didn't fit into the buffer did not get delivered
+        let received: Vec<DirectMessage> = block_on(stream.take(buffer_size).collect());
+        assert_eq!(buffer_size, received.len());
+        assert_eq!(
+            DirectMessage::Custom((buffer_size - 1).to_string()),
+            received[buffer_size - 1]
+        );
+    }
 }
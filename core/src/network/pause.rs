@@ -0,0 +1,73 @@
+use holochain_net_connection::protocol_wrapper::ProtocolWrapper;
+
+/// Holds network syncing while the instance is paused through `Context::pause_network()`.
+/// Inbound messages received in the meantime are queued here and handled on
+/// `Context::resume_network()`. Outbound messages are deferred by the network reducers
+/// in `NetworkState::deferred_messages` and sent once the network gets resumed.
+/// Local operations like commits are not affected.
+#[derive(Default)]
+pub struct NetworkPause {
+    paused: bool,
+    inbound: Vec<ProtocolWrapper>,
+}
+
+impl NetworkPause {
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Unpauses and returns the inbound messages queued while paused, oldest first.
+    pub fn resume(&mut self) -> Vec<ProtocolWrapper> {
+        self.paused = false;
+        self.inbound.drain(..).collect()
+    }
+
+    /// Queues the given inbound message if paused.
+    /// Hands it back if not, so the caller can handle it right away.
+    pub fn hold(&mut self, protocol_wrapper: ProtocolWrapper) -> Option<ProtocolWrapper> {
+        if self.paused {
+            self.inbound.push(protocol_wrapper);
+            None
+        } else {
+            Some(protocol_wrapper)
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use holochain_net_connection::protocol_wrapper::GetDhtData;
+
+    fn test_message(address: &str) -> ProtocolWrapper {
+        ProtocolWrapper::GetDht(GetDhtData {
+            msg_id: String::from("test"),
+            dna_hash: String::from("dna"),
+            from_agent_id: String::from("agent"),
+            address: String::from(address),
+            signature: String::new(),
+            to_agent_id: String::new(),
+        })
+    }
+
+    #[test]
+    fn holds_inbound_messages_only_while_paused() {
+        let mut pause = NetworkPause::default();
+        assert!(!pause.is_paused());
+        assert_eq!(Some(test_message("a")), pause.hold(test_message("a")));
+
+        pause.pause();
+        assert!(pause.is_paused());
+        assert_eq!(None, pause.hold(test_message("b")));
+        assert_eq!(None, pause.hold(test_message("c")));
+
+        assert_eq!(vec![test_message("b"), test_message("c")], pause.resume());
+        assert!(!pause.is_paused());
+        assert_eq!(Some(test_message("d")), pause.hold(test_message("d")));
+        assert!(pause.resume().is_empty());
+    }
+}
@@ -0,0 +1,138 @@
+//! Peer rotation for network look-ups.
+//!
+//! When a peer fails to answer a look-up in time, the retry should go to a different peer.
+//! PeerRotation remembers which peers were already asked for an address and counts
+//! consecutive failures per peer. Peers that keep failing get marked as degraded and are
+//! only picked once no healthy peer is left.
+//!
+//! Peers become known through the network (see network::handler). As long as no peer is
+//! known, or all known peers were asked, the look-up is not addressed to a specific peer
+//! and the network backend picks one.
+use holochain_core_types::cas::content::Address;
+use std::collections::{HashMap, HashSet};
+
+/// Number of consecutive failures after which a peer counts as degraded.
+pub const DEGRADED_AFTER_FAILURES: u32 = 3;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PeerRotation {
+    known_peers: Vec<String>,
+    consecutive_failures: HashMap<String, u32>,
+    tried: HashMap<Address, HashSet<String>>,
+}
+
+impl PeerRotation {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a peer look-ups can be sent to. Peers are asked in the order they became known.
+    pub fn add_peer(&mut self, peer: &str) {
+        if !self.known_peers.iter().any(|known| known == peer) {
+            self.known_peers.push(peer.to_string());
+        }
+    }
+
+    pub fn known_peers(&self) -> &[String] {
+        &self.known_peers
+    }
+
+    pub fn is_degraded(&self, peer: &str) -> bool {
+        self.consecutive_failures.get(peer).cloned().unwrap_or(0) >= DEGRADED_AFTER_FAILURES
+    }
+
+    /// Picks a known peer that has not been asked for this address yet,
+    /// preferring healthy peers over degraded ones.
+    pub fn next_peer(&mut self, address: &Address) -> Option<String> {
+        let tried = self
+            .tried
+            .entry(address.clone())
+            .or_insert_with(HashSet::new);
+        let untried: Vec<&String> = self
+            .known_peers
+            .iter()
+            .filter(|peer| !tried.contains(*peer))
+            .collect();
+        let consecutive_failures = &self.consecutive_failures;
+        let peer = untried
+            .iter()
+            .find(|peer| {
+                consecutive_failures.get(**peer).cloned().unwrap_or(0) < DEGRADED_AFTER_FAILURES
+            })
+            .or_else(|| untried.first())
+            .map(|peer| (*peer).clone())?;
+        tried.insert(peer.clone());
+        Some(peer)
+    }
+
+    pub fn record_failure(&mut self, peer: &str) {
+        *self
+            .consecutive_failures
+            .entry(peer.to_string())
+            .or_insert(0) += 1;
+    }
+
+    /// A successful answer resets the peer's failure count.
+    pub fn record_success(&mut self, peer: &str) {
+        self.consecutive_failures.remove(peer);
+    }
+
+    /// Forgets which peers were asked for the address once its look-up is over,
+    /// so the next look-up starts over with the healthiest peer.
+    pub fn finish(&mut self, address: &Address) {
+        self.tried.remove(address);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn rotation() -> PeerRotation {
+        let mut rotation = PeerRotation::new();
+        rotation.add_peer("flaky");
+        rotation.add_peer("good");
+        rotation.add_peer("flaky");
+        rotation
+    }
+
+    #[test]
+    fn next_peer_skips_peers_already_asked() {
+        let mut rotation = rotation();
+        let address = Address::from("address");
+
+        assert_eq!(Some(String::from("flaky")), rotation.next_peer(&address));
+        assert_eq!(Some(String::from("good")), rotation.next_peer(&address));
+        assert_eq!(None, rotation.next_peer(&address));
+        // other look-ups are not affected
+        assert_eq!(
+            Some(String::from("flaky")),
+            rotation.next_peer(&Address::from("other"))
+        );
+
+        rotation.finish(&address);
+        assert_eq!(Some(String::from("flaky")), rotation.next_peer(&address));
+    }
+
+    #[test]
+    fn persistently_failing_peer_gets_degraded() {
+        let mut rotation = rotation();
+        let address = Address::from("address");
+        for _ in 0..DEGRADED_AFTER_FAILURES {
+            let peer = rotation.next_peer(&address).unwrap();
+            rotation.record_failure(&peer);
+            let peer = rotation.next_peer(&address).unwrap();
+            rotation.record_success(&peer);
+            rotation.finish(&address);
+        }
+        assert!(rotation.is_degraded("flaky"));
+        assert!(!rotation.is_degraded("good"));
+
+        // degraded peers are asked last
+        assert_eq!(Some(String::from("good")), rotation.next_peer(&address));
+        assert_eq!(Some(String::from("flaky")), rotation.next_peer(&address));
+
+        rotation.record_success("flaky");
+        assert!(!rotation.is_degraded("flaky"));
+    }
+}
@@ -1,35 +1,44 @@
 use crate::{
     action::ActionWrapper,
     context::Context,
-    network::{reducers::send, state::NetworkState},
+    network::{reducers::send, request_signer::sign_get_request, state::NetworkState},
 };
 use holochain_core_types::{cas::content::Address, error::HolochainError};
 use holochain_net_connection::protocol_wrapper::{GetDhtData, ProtocolWrapper};
 use std::sync::Arc;
 
-fn inner(network_state: &mut NetworkState, address: &Address) -> Result<(), HolochainError> {
+fn inner(
+    context: &Arc<Context>,
+    network_state: &mut NetworkState,
+    address: &Address,
+    peer: &Option<String>,
+) -> Result<(), HolochainError> {
     network_state.initialized()?;
 
+    let get_dht_data = GetDhtData {
+        // unique per request, so a signature can't be replayed for another request
+        msg_id: snowflake::ProcessUniqueId::new().to_string(),
+        dna_hash: network_state.dna_hash.clone().unwrap(),
+        from_agent_id: network_state.agent_id.clone().unwrap(),
+        address: address.to_string(),
+        signature: String::new(),
+        to_agent_id: peer.clone().unwrap_or_default(),
+    };
     send(
         network_state,
-        ProtocolWrapper::GetDht(GetDhtData {
-            msg_id: "?".to_string(),
-            dna_hash: network_state.dna_hash.clone().unwrap(),
-            from_agent_id: network_state.agent_id.clone().unwrap(),
-            address: address.to_string(),
-        }),
+        ProtocolWrapper::GetDht(sign_get_request(get_dht_data, context)),
     )
 }
 
 pub fn reduce_get_entry(
-    _context: Arc<Context>,
+    context: Arc<Context>,
     network_state: &mut NetworkState,
     action_wrapper: &ActionWrapper,
 ) {
     let action = action_wrapper.action();
-    let address = unwrap_to!(action => crate::action::Action::GetEntry);
+    let (address, peer) = unwrap_to!(action => crate::action::Action::GetEntry);
 
-    let result = match inner(network_state, &address) {
+    let result = match inner(&context, network_state, address, peer) {
         Ok(()) => None,
         Err(err) => Some(Err(err)),
     };
@@ -88,7 +97,7 @@ mod tests {
         let store = test_store(context.clone());
 
         let entry = test_entry();
-        let action_wrapper = ActionWrapper::new(Action::GetEntry(entry.address()));
+        let action_wrapper = ActionWrapper::new(Action::GetEntry((entry.address(), None)));
 
         let store = store.reduce(context.clone(), action_wrapper);
         let maybe_get_entry_result = store
@@ -119,7 +128,7 @@ mod tests {
         let store = store.reduce(context.clone(), action_wrapper);
 
         let entry = test_entry();
-        let action_wrapper = ActionWrapper::new(Action::GetEntry(entry.address()));
+        let action_wrapper = ActionWrapper::new(Action::GetEntry((entry.address(), None)));
 
         let store = store.reduce(context.clone(), action_wrapper);
         let maybe_get_entry_result = store
@@ -150,7 +159,7 @@ mod tests {
         }
 
         let entry = test_entry();
-        let action_wrapper = ActionWrapper::new(Action::GetEntry(entry.address()));
+        let action_wrapper = ActionWrapper::new(Action::GetEntry((entry.address(), None)));
 
         {
             let mut new_store = store.write().unwrap();
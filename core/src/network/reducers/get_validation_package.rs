@@ -6,7 +6,11 @@ use crate::{
 use holochain_core_types::{chain_header::ChainHeader, error::HolochainError};
 use std::sync::Arc;
 
-fn inner(network_state: &mut NetworkState, header: &ChainHeader) -> Result<(), HolochainError> {
+fn inner(
+    network_state: &mut NetworkState,
+    msg_id: &str,
+    header: &ChainHeader,
+) -> Result<(), HolochainError> {
     network_state.initialized()?;
 
     let source_address = header
@@ -17,7 +21,12 @@ fn inner(network_state: &mut NetworkState, header: &ChainHeader) -> Result<(), H
         ))?;
     let direct_message = DirectMessage::RequestValidationPackage(header.entry_address().clone());
 
-    send_message(network_state, source_address, direct_message)
+    send_message(
+        network_state,
+        source_address,
+        direct_message,
+        msg_id.to_string(),
+    )
 }
 
 pub fn reduce_get_validation_package(
@@ -26,15 +35,100 @@ pub fn reduce_get_validation_package(
     action_wrapper: &ActionWrapper,
 ) {
     let action = action_wrapper.action();
-    let header = unwrap_to!(action => crate::action::Action::GetValidationPackage);
-    let entry_address = header.entry_address().clone();
+    let (msg_id, header) = unwrap_to!(action => crate::action::Action::GetValidationPackage);
 
-    let result = match inner(network_state, header) {
+    let result = match inner(network_state, msg_id, header) {
         Ok(()) => None,
         Err(err) => Some(Err(err)),
     };
 
     network_state
         .get_validation_package_results
-        .insert(entry_address, result);
+        .insert(msg_id.clone(), result);
+}
+
+pub fn reduce_clear_validation_package_result(
+    _context: Arc<Context>,
+    network_state: &mut NetworkState,
+    action_wrapper: &ActionWrapper,
+) {
+    let action = action_wrapper.action();
+    let msg_id = unwrap_to!(action => crate::action::Action::ClearValidationPackageResult);
+
+    network_state.get_validation_package_results.remove(msg_id);
+    network_state.direct_message_connections.remove(msg_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        action::{Action, ActionWrapper, NetworkSettings},
+        context::mock_network_config,
+        instance::tests::test_context,
+        state::test_store,
+    };
+    use holochain_core_types::{chain_header::test_chain_header, validation::ValidationPackage};
+
+    #[test]
+    pub fn responses_are_matched_to_their_request() {
+        let context = test_context("alice");
+        let store = test_store(context.clone());
+
+        let action_wrapper = ActionWrapper::new(Action::InitNetwork(NetworkSettings {
+            config: mock_network_config(),
+            dna_hash: String::from("abcd"),
+            agent_id: String::from("abcd"),
+        }));
+        let mut store = store.reduce(context.clone(), action_wrapper);
+
+        // two requests for the package of the same entry in flight
+        let header = test_chain_header();
+        for msg_id in vec!["first", "second"] {
+            let action_wrapper = ActionWrapper::new(Action::GetValidationPackage((
+                String::from(msg_id),
+                header.clone(),
+            )));
+            store = store.reduce(context.clone(), action_wrapper);
+        }
+        assert!(store
+            .network()
+            .direct_message_connections
+            .contains_key("first"));
+        assert!(store
+            .network()
+            .direct_message_connections
+            .contains_key("second"));
+
+        let package = ValidationPackage::only_header(header.clone());
+        let action_wrapper = ActionWrapper::new(Action::HandleGetValidationPackage((
+            String::from("second"),
+            Some(package.clone()),
+        )));
+        let store = store.reduce(context.clone(), action_wrapper);
+
+        let results = store.network().get_validation_package_results.clone();
+        assert_eq!(results.get("first"), Some(&None));
+        assert_eq!(
+            results.get("second"),
+            Some(&Some(Ok(Some(package.clone()))))
+        );
+
+        // giving up on a request forgets it, and a late response does not bring it back
+        let action_wrapper =
+            ActionWrapper::new(Action::ClearValidationPackageResult(String::from("first")));
+        let store = store.reduce(context.clone(), action_wrapper);
+        let action_wrapper = ActionWrapper::new(Action::HandleGetValidationPackage((
+            String::from("first"),
+            Some(package),
+        )));
+        let store = store.reduce(context.clone(), action_wrapper);
+        assert!(!store
+            .network()
+            .get_validation_package_results
+            .contains_key("first"));
+        assert!(!store
+            .network()
+            .direct_message_connections
+            .contains_key("first"));
+    }
 }
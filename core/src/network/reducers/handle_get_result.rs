@@ -1,5 +1,9 @@
 use crate::{action::ActionWrapper, context::Context, network::state::NetworkState};
-use holochain_core_types::{cas::content::Address, entry::EntryWithMeta, error::HolochainError};
+use holochain_core_types::{
+    cas::content::Address,
+    entry::{verify_entry_address, EntryWithMeta},
+    error::HolochainError,
+};
 use holochain_net_connection::protocol_wrapper::DhtData;
 use std::sync::Arc;
 
@@ -15,7 +19,15 @@ fn inner(
             "Failed to deserialize EntryWithMeta from HandleGetResult action argument".to_string(),
         ));
     }
-    Ok(res.unwrap())
+    let maybe_entry_with_meta: Option<EntryWithMeta> = res.unwrap();
+    // a peer answering with content that does not match the requested address is not trusted
+    if let Some(ref entry_with_meta) = maybe_entry_with_meta {
+        verify_entry_address(
+            &entry_with_meta.entry,
+            &Address::from(dht_data.address.clone()),
+        )?;
+    }
+    Ok(maybe_entry_with_meta)
 }
 
 pub fn reduce_handle_get_result(
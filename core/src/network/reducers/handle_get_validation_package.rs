@@ -7,10 +7,11 @@ pub fn reduce_handle_get_validation_package(
     action_wrapper: &ActionWrapper,
 ) {
     let action = action_wrapper.action();
-    let (address, maybe_validation_package) =
+    let (msg_id, maybe_validation_package) =
         unwrap_to!(action => crate::action::Action::HandleGetValidationPackage);
 
-    network_state
-        .get_validation_package_results
-        .insert(address.clone(), Some(Ok(maybe_validation_package.clone())));
+    // late responses to requests nobody waits for anymore are dropped
+    if let Some(result) = network_state.get_validation_package_results.get_mut(msg_id) {
+        *result = Some(Ok(maybe_validation_package.clone()));
+    }
 }
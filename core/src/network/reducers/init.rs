@@ -1,7 +1,7 @@
 use crate::{
     action::{Action, ActionWrapper},
     context::Context,
-    network::{handler::create_handler, state::NetworkState},
+    network::{bandwidth::BandwidthThrottle, handler::create_handler, state::NetworkState},
 };
 use holochain_net::p2p_network::P2pNetwork;
 use holochain_net_connection::{
@@ -31,6 +31,7 @@ pub fn reduce_init(
             state.network = Some(Arc::new(Mutex::new(network)));
             state.dna_hash = Some(network_settings.dna_hash.clone());
             state.agent_id = Some(network_settings.agent_id.clone());
+            state.bandwidth = BandwidthThrottle::new(context.gossip_bandwidth_limit());
             Ok(())
         });
 }
@@ -6,22 +6,27 @@ pub mod init;
 pub mod publish;
 pub mod resolve_direct_connection;
 pub mod respond_get;
+pub mod resume_network;
 pub mod send_direct_message;
 
 use crate::{
     action::{Action, ActionWrapper, NetworkReduceFn},
     context::Context,
     network::{
+        bandwidth::MessagePriority,
         direct_message::DirectMessage,
         reducers::{
             get_entry::{reduce_get_entry, reduce_get_entry_timeout},
-            get_validation_package::reduce_get_validation_package,
+            get_validation_package::{
+                reduce_clear_validation_package_result, reduce_get_validation_package,
+            },
             handle_get_result::reduce_handle_get_result,
             handle_get_validation_package::reduce_handle_get_validation_package,
             init::reduce_init,
             publish::reduce_publish,
             resolve_direct_connection::reduce_resolve_direct_connection,
             respond_get::reduce_respond_get,
+            resume_network::reduce_resume_network,
             send_direct_message::reduce_send_direct_message,
         },
         state::NetworkState,
@@ -32,26 +37,37 @@ use holochain_net_connection::{
     net_connection::NetConnection,
     protocol_wrapper::{MessageData, ProtocolWrapper},
 };
-use snowflake::ProcessUniqueId;
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
 /// maps incoming action to the correct handler
 fn resolve_reducer(action_wrapper: &ActionWrapper) -> Option<NetworkReduceFn> {
     match action_wrapper.action() {
         Action::GetEntry(_) => Some(reduce_get_entry),
         Action::GetEntryTimeout(_) => Some(reduce_get_entry_timeout),
+        Action::FlushGossip => Some(reduce_flush_gossip),
         Action::GetValidationPackage(_) => Some(reduce_get_validation_package),
+        Action::ClearValidationPackageResult(_) => Some(reduce_clear_validation_package_result),
         Action::HandleGetResult(_) => Some(reduce_handle_get_result),
         Action::HandleGetValidationPackage(_) => Some(reduce_handle_get_validation_package),
         Action::InitNetwork(_) => Some(reduce_init),
         Action::Publish(_) => Some(reduce_publish),
         Action::ResolveDirectConnection(_) => Some(reduce_resolve_direct_connection),
         Action::RespondGet(_) => Some(reduce_respond_get),
+        Action::ResumeNetwork => Some(reduce_resume_network),
         Action::SendDirectMessage(_) => Some(reduce_send_direct_message),
         _ => None,
     }
 }
 
+/// Queued gossip gets flushed by reduce() after every network action,
+/// so there is nothing else to do here.
+fn reduce_flush_gossip(
+    _context: Arc<Context>,
+    _network_state: &mut NetworkState,
+    _action_wrapper: &ActionWrapper,
+) {
+}
+
 pub fn reduce(
     context: Arc<Context>,
     old_state: Arc<NetworkState>,
@@ -61,7 +77,12 @@ pub fn reduce(
     match handler {
         Some(f) => {
             let mut new_state: NetworkState = (*old_state).clone();
-            f(context, &mut new_state, &action_wrapper);
+            new_state.paused = context.network_paused();
+            f(context.clone(), &mut new_state, &action_wrapper);
+            // gossip held back by the bandwidth budget goes out with later network actions
+            if let Err(error) = flush_gossip(&mut new_state) {
+                context.log(format!("Error sending queued gossip: {:?}", error));
+            }
             Arc::new(new_state)
         }
         None => old_state,
@@ -70,10 +91,59 @@ pub fn reduce(
 
 /// Sends the given ProtocolWrapper over the network using the network proxy instance
 /// that lives in the NetworkState.
+/// This is meant for interactive messages which are never throttled.
 pub fn send(
     network_state: &mut NetworkState,
     protocol_wrapper: ProtocolWrapper,
 ) -> Result<(), HolochainError> {
+    let released = network_state.bandwidth.admit(
+        protocol_wrapper,
+        MessagePriority::Interactive,
+        Instant::now(),
+    );
+    send_all(network_state, released)
+}
+
+/// Sends the given ProtocolWrapper as gossip, i.e. subject to the outbound bandwidth budget.
+/// If the budget is used up the message gets queued and sent by a later network action.
+pub fn send_gossip(
+    network_state: &mut NetworkState,
+    protocol_wrapper: ProtocolWrapper,
+) -> Result<(), HolochainError> {
+    let released =
+        network_state
+            .bandwidth
+            .admit(protocol_wrapper, MessagePriority::Gossip, Instant::now());
+    send_all(network_state, released)
+}
+
+/// Sends queued gossip messages as far as the bandwidth budget allows.
+pub fn flush_gossip(network_state: &mut NetworkState) -> Result<(), HolochainError> {
+    if network_state.network.is_none() {
+        return Ok(());
+    }
+    let released = network_state.bandwidth.drain(Instant::now());
+    send_all(network_state, released)
+}
+
+pub(crate) fn send_all(
+    network_state: &mut NetworkState,
+    protocol_wrappers: Vec<ProtocolWrapper>,
+) -> Result<(), HolochainError> {
+    for protocol_wrapper in protocol_wrappers {
+        send_unthrottled(network_state, protocol_wrapper)?;
+    }
+    Ok(())
+}
+
+fn send_unthrottled(
+    network_state: &mut NetworkState,
+    protocol_wrapper: ProtocolWrapper,
+) -> Result<(), HolochainError> {
+    if network_state.paused {
+        network_state.deferred_messages.push(protocol_wrapper);
+        return Ok(());
+    }
     network_state
         .network
         .as_mut()
@@ -93,13 +163,13 @@ pub fn send(
 /// This creates a transient connection as every node-to-node communication follows a
 /// request-response pattern. This function therefore logs the open connection
 /// (expecting a response) in network_state.direct_message_connections.
+/// The response will carry the given message ID.
 pub fn send_message(
     network_state: &mut NetworkState,
     to_agent_id: &Address,
     message: DirectMessage,
+    id: String,
 ) -> Result<(), HolochainError> {
-    let id = ProcessUniqueId::new().to_string();
-
     let data = MessageData {
         msg_id: id.clone(),
         dna_hash: network_state.dna_hash.clone().unwrap(),
@@ -4,7 +4,7 @@ use crate::{
     network::{
         actions::ActionResponse,
         entry_with_header::{fetch_entry_with_header, EntryWithHeader},
-        reducers::send,
+        reducers::send_gossip,
         state::NetworkState,
     },
     nucleus::actions::get_entry::get_entry_crud_meta_from_dht,
@@ -24,7 +24,7 @@ fn publish_entry(
 ) -> Result<(), HolochainError> {
     //let entry_with_header = util::EntryWithHeader::from((entry.clone(), header.clone()));
 
-    send(
+    send_gossip(
         network_state,
         ProtocolWrapper::PublishDht(DhtData {
             msg_id: "?".to_string(),
@@ -44,7 +44,7 @@ fn publish_crud_meta(
     crud_link: Option<Address>,
 ) -> Result<(), HolochainError> {
     // publish crud-status
-    send(
+    send_gossip(
         network_state,
         ProtocolWrapper::PublishDhtMeta(DhtMetaData {
             msg_id: "?".to_string(),
@@ -60,7 +60,7 @@ fn publish_crud_meta(
     if crud_link.is_none() {
         return Ok(());
     }
-    send(
+    send_gossip(
         network_state,
         ProtocolWrapper::PublishDhtMeta(DhtMetaData {
             msg_id: "?".to_string(),
@@ -90,7 +90,7 @@ fn publish_link_meta(
     };
     let link = link_add_entry.link().clone();
 
-    send(
+    send_gossip(
         network_state,
         ProtocolWrapper::PublishDhtMeta(DhtMetaData {
             msg_id: "?".to_string(),
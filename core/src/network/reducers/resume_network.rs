@@ -0,0 +1,21 @@
+use crate::{
+    action::ActionWrapper,
+    context::Context,
+    network::{reducers::send_all, state::NetworkState},
+};
+use std::sync::Arc;
+
+/// Sends everything that got deferred while the network was paused, in the original order.
+pub fn reduce_resume_network(
+    context: Arc<Context>,
+    network_state: &mut NetworkState,
+    _action_wrapper: &ActionWrapper,
+) {
+    if network_state.paused || network_state.network.is_none() {
+        return;
+    }
+    let deferred = network_state.deferred_messages.drain(..).collect();
+    if let Err(error) = send_all(network_state, deferred) {
+        context.log(format!("Error sending deferred messages: {:?}", error));
+    }
+}
@@ -0,0 +1,123 @@
+//! Signing of the get requests we send and verification of the ones we receive, so that
+//! responders can tell which agent really asked for an entry.
+//! The agent id a request claims to come from can not be trusted on its own since any peer
+//! can put any agent id into its requests.
+use crate::context::Context;
+use holochain_core_types::cas::content::Address;
+use holochain_net_connection::protocol_wrapper::GetDhtData;
+use std::sync::Arc;
+
+/// Pluggable signatures for network requests.
+pub trait RequestSigner: Send + Sync {
+    /// Signs the given payload as our own agent.
+    fn sign(&self, payload: &str) -> String;
+
+    /// Checks that the signature over the given payload was made by the given agent.
+    fn verify(&self, agent_id: &Address, payload: &str, signature: &str) -> bool;
+}
+
+/// The part of a get request that gets signed: everything but the signature itself.
+/// The msg_id is unique per request, which makes every signed payload unique.
+fn signed_payload(get_dht_data: &GetDhtData) -> String {
+    format!(
+        "{}:{}:{}:{}",
+        get_dht_data.msg_id,
+        get_dht_data.dna_hash,
+        get_dht_data.from_agent_id,
+        get_dht_data.address
+    )
+}
+
+/// Signs the request with the context's RequestSigner. Requests stay unsigned without one.
+pub fn sign_get_request(get_dht_data: GetDhtData, context: &Arc<Context>) -> GetDhtData {
+    match context.request_signer() {
+        Some(signer) => GetDhtData {
+            signature: signer.sign(&signed_payload(&get_dht_data)),
+            ..get_dht_data
+        },
+        None => get_dht_data,
+    }
+}
+
+/// The agent that sent the request, if its signature proves it.
+/// Without a RequestSigner no request can be authenticated.
+pub fn authenticated_requester(
+    get_dht_data: &GetDhtData,
+    context: &Arc<Context>,
+) -> Option<Address> {
+    let signer = context.request_signer()?;
+    let agent_id = Address::from(get_dht_data.from_agent_id.clone());
+    if !get_dht_data.signature.is_empty()
+        && signer.verify(
+            &agent_id,
+            &signed_payload(get_dht_data),
+            &get_dht_data.signature,
+        )
+    {
+        Some(agent_id)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::instance::tests::test_context;
+
+    /// Not secure at all, the signature just names the signing agent.
+    pub struct TestSigner(pub Address);
+
+    impl RequestSigner for TestSigner {
+        fn sign(&self, payload: &str) -> String {
+            format!("{}/{}", self.0, payload)
+        }
+
+        fn verify(&self, agent_id: &Address, payload: &str, signature: &str) -> bool {
+            signature == format!("{}/{}", agent_id, payload)
+        }
+    }
+
+    fn get_request(from_agent_id: &str) -> GetDhtData {
+        GetDhtData {
+            msg_id: String::from("test"),
+            dna_hash: String::from("dna"),
+            from_agent_id: String::from(from_agent_id),
+            address: String::from("address"),
+            signature: String::new(),
+            to_agent_id: String::new(),
+        }
+    }
+
+    #[test]
+    fn only_signed_requests_are_authenticated() {
+        let bob_context = test_context("bob");
+        bob_context.set_request_signer(Arc::new(TestSigner(Address::from("bob"))));
+        let context = test_context("alice");
+
+        let signed = sign_get_request(get_request("bob"), &bob_context);
+        // nothing can be verified without a signer
+        assert_eq!(None, authenticated_requester(&signed, &context));
+
+        context.set_request_signer(Arc::new(TestSigner(Address::from("alice"))));
+        assert_eq!(
+            Some(Address::from("bob")),
+            authenticated_requester(&signed, &context)
+        );
+        assert_eq!(None, authenticated_requester(&get_request("bob"), &context));
+
+        // a signature is only valid for the request it was made for
+        let replayed = GetDhtData {
+            msg_id: String::from("another request"),
+            ..signed.clone()
+        };
+        assert_eq!(None, authenticated_requester(&replayed, &context));
+
+        // claiming to be somebody else doesn't work with one's own signature
+        let spoofed = GetDhtData {
+            from_agent_id: String::from("carol"),
+            ..signed
+        };
+        assert_eq!(None, authenticated_requester(&spoofed, &context));
+    }
+}
@@ -0,0 +1,284 @@
+//! Retry behaviour for network look-ups, shared by get_entry and get_validation_package
+//! (which is a direct message round trip to the entry's author).
+//! The policy is configured once per instance through `Context::set_retry_policy()`.
+use futures::{
+    future::Future,
+    task::{LocalWaker, Poll},
+};
+use holochain_core_types::error::{HcResult, HolochainError};
+use std::{
+    pin::{Pin, Unpin},
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+/// How the delay between two attempts grows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backoff {
+    /// Always wait base_delay.
+    Constant,
+    /// Wait base_delay times the number of failed attempts.
+    Linear,
+    /// Double the delay after every failed attempt, starting with base_delay.
+    Exponential,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// Total number of attempts including the first one.
+    /// At least one attempt is always made.
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    /// Upper bound for a single delay, regardless of backoff.
+    pub max_delay: Duration,
+    pub backoff: Backoff,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            backoff: Backoff::Exponential,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that gives up after the first failure.
+    pub fn no_retry() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Delay to wait after the given (1-based) attempt failed.
+    pub fn delay_after(&self, failed_attempt: u32) -> Duration {
+        let factor = match self.backoff {
+            Backoff::Constant => Some(1),
+            Backoff::Linear => Some(failed_attempt.max(1)),
+            Backoff::Exponential => 1u32.checked_shl(failed_attempt.max(1) - 1),
+        };
+        factor
+            .and_then(|factor| self.base_delay.checked_mul(factor))
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+
+    /// All delays between attempts, in order.
+    pub fn schedule(&self) -> Vec<Duration> {
+        (1..self.max_attempts)
+            .map(|attempt| self.delay_after(attempt))
+            .collect()
+    }
+
+    /// Only timeouts are worth retrying. Other errors (like an uninitialized network
+    /// or a malformed response) would fail the same way again.
+    pub fn should_retry(&self, failed_attempt: u32, error: &HolochainError) -> bool {
+        failed_attempt < self.max_attempts && *error == HolochainError::Timeout
+    }
+
+    /// Calls f until it succeeds, fails with an error that should not be retried,
+    /// or max_attempts is reached. Sleeps according to the backoff between attempts.
+    pub fn retry<T, F>(&self, mut f: F) -> HcResult<T>
+    where
+        F: FnMut() -> HcResult<T>,
+    {
+        let mut attempt = 1;
+        loop {
+            match f() {
+                Err(ref error) if self.should_retry(attempt, error) => {
+                    sleep(self.delay_after(attempt));
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Future version of RetryPolicy::retry(): awaits the futures returned by make_future
+/// until one succeeds, fails with an error that should not be retried, or the policy's
+/// max_attempts is reached.
+/// Waits according to the backoff between attempts without blocking the executor thread.
+pub async fn retry_future<F, Fut, T>(mut make_future: F, policy: RetryPolicy) -> HcResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = HcResult<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match await!(make_future()) {
+            Err(ref error) if policy.should_retry(attempt, error) => {
+                await!(Delay::new(policy.delay_after(attempt)));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Future that resolves once the given duration has passed.
+pub struct Delay {
+    deadline: Instant,
+}
+
+impl Delay {
+    pub fn new(duration: Duration) -> Self {
+        Delay {
+            deadline: Instant::now() + duration,
+        }
+    }
+}
+
+impl Unpin for Delay {}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        if Instant::now() >= self.deadline {
+            Poll::Ready(())
+        } else {
+            //
+            // TODO: connect the waker to a timer instead of polling
+            // See: https://github.com/holochain/holochain-rust/issues/314
+            //
+            lw.wake();
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use futures::{executor::block_on, future};
+
+    fn policy(backoff: Backoff, max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            backoff,
+        }
+    }
+
+    fn millis(schedule: Vec<Duration>) -> Vec<u64> {
+        schedule
+            .iter()
+            .map(|delay| delay.as_secs() * 1000 + u64::from(delay.subsec_millis()))
+            .collect()
+    }
+
+    #[test]
+    fn computes_backoff_schedule() {
+        assert_eq!(
+            vec![100, 200, 400, 800, 1000, 1000],
+            millis(policy(Backoff::Exponential, 7).schedule())
+        );
+        assert_eq!(
+            vec![100, 200, 300],
+            millis(policy(Backoff::Linear, 4).schedule())
+        );
+        assert_eq!(
+            vec![100, 100],
+            millis(policy(Backoff::Constant, 3).schedule())
+        );
+        assert!(RetryPolicy::no_retry().schedule().is_empty());
+        assert_eq!(
+            Duration::from_secs(1),
+            policy(Backoff::Exponential, 100).delay_after(99)
+        );
+    }
+
+    #[test]
+    fn retry_honors_max_attempts() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(0),
+            ..policy(Backoff::Exponential, 4)
+        };
+
+        let mut calls = 0;
+        let result: HcResult<()> = policy.retry(|| {
+            calls += 1;
+            Err(HolochainError::Timeout)
+        });
+        assert_eq!(Err(HolochainError::Timeout), result);
+        assert_eq!(4, calls);
+
+        let mut calls = 0;
+        let result = policy.retry(|| {
+            calls += 1;
+            if calls < 3 {
+                Err(HolochainError::Timeout)
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(Ok(3), result);
+
+        let mut calls = 0;
+        let result: HcResult<()> = policy.retry(|| {
+            calls += 1;
+            Err(HolochainError::ErrorGeneric(String::from("not transient")))
+        });
+        assert!(result.is_err());
+        assert_eq!(1, calls);
+    }
+
+    #[test]
+    fn delay_resolves_after_duration() {
+        let start = Instant::now();
+        block_on(Delay::new(Duration::from_millis(50)));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn retry_future_retries_transient_errors() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(0),
+            ..policy(Backoff::Constant, 4)
+        };
+
+        let mut calls = 0;
+        let result = block_on(retry_future(
+            || {
+                calls += 1;
+                future::ready(if calls < 3 {
+                    Err(HolochainError::Timeout)
+                } else {
+                    Ok(calls)
+                })
+            },
+            policy.clone(),
+        ));
+        assert_eq!(Ok(3), result);
+
+        let mut calls = 0;
+        let result: HcResult<()> = block_on(retry_future(
+            || {
+                calls += 1;
+                future::ready(Err(HolochainError::Timeout))
+            },
+            policy.clone(),
+        ));
+        assert_eq!(Err(HolochainError::Timeout), result);
+        assert_eq!(4, calls);
+
+        let mut calls = 0;
+        let result: HcResult<()> = block_on(retry_future(
+            || {
+                calls += 1;
+                future::ready(Err(HolochainError::ErrorGeneric(String::from(
+                    "not transient",
+                ))))
+            },
+            policy,
+        ));
+        assert!(result.is_err());
+        assert_eq!(1, calls);
+    }
+}
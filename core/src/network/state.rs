@@ -1,17 +1,24 @@
 use boolinator::*;
 use crate::{
     action::ActionWrapper,
-    network::{actions::ActionResponse, direct_message::DirectMessage},
+    network::{
+        actions::ActionResponse,
+        bandwidth::{BandwidthThrottle, BandwidthUsage},
+        direct_message::DirectMessage,
+    },
 };
 use holochain_core_types::{
-    cas::content::Address, entry::EntryWithMeta, error::HolochainError,
+    cas::content::Address, entry::EntryWithMeta, error::HolochainError, json::JsonString,
     validation::ValidationPackage,
 };
 use holochain_net::p2p_network::P2pNetwork;
+use holochain_net_connection::protocol_wrapper::ProtocolWrapper;
 use snowflake;
 use std::{
     collections::HashMap,
+    convert::TryFrom,
     sync::{Arc, Mutex},
+    time::Instant,
 };
 
 type Actions = HashMap<ActionWrapper, ActionResponse>;
@@ -31,6 +38,22 @@ type GetEntryWithMetaResult = Option<Result<Option<EntryWithMeta>, HolochainErro
 /// Some(Ok(Some(entry))): we have it
 type GetValidationPackageResult = Option<Result<Option<ValidationPackage>, HolochainError>>;
 
+/// Counters of the network processes in flight, for diagnostics.
+/// Created with NetworkState::snapshot().
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, DefaultJson)]
+pub struct NetworkStateSnapshot {
+    pub initialized: bool,
+    pub paused: bool,
+    /// get_entry look-ups waiting for a response
+    pub pending_get_entries: usize,
+    /// Validation package requests waiting for a response
+    pub pending_validation_packages: usize,
+    /// Direct messages sent and waiting for a response
+    pub open_direct_messages: usize,
+    /// Outbound messages held back while the network is paused
+    pub deferred_messages: usize,
+}
+
 #[derive(Clone, Debug)]
 pub struct NetworkState {
     /// every action and the result of that action
@@ -45,14 +68,25 @@ pub struct NetworkState {
     /// None means that we are still waiting for a result from the network.
     pub get_entry_with_meta_results: HashMap<Address, GetEntryWithMetaResult>,
 
-    /// Here we store the results of get validation package processes.
+    /// Here we store the results of get validation package processes by the message ID
+    /// of their request.
     /// None means that we are still waiting for a result from the network.
-    pub get_validation_package_results: HashMap<Address, GetValidationPackageResult>,
+    /// Entries get removed through Action::ClearValidationPackageResult.
+    pub get_validation_package_results: HashMap<String, GetValidationPackageResult>,
 
     /// This stores every open (= waiting for response) node-to-node messages.
-    /// Entries get removed when we receive an answer through Action::ResolveDirectConnection.
+    /// Entries get removed when we receive an answer through Action::ResolveDirectConnection
+    /// or give up waiting through Action::ClearValidationPackageResult.
     pub direct_message_connections: HashMap<String, DirectMessage>,
 
+    /// Outbound bandwidth budget for gossip and the traffic sent within the last second.
+    pub bandwidth: BandwidthThrottle,
+
+    /// Set from Context::network_paused() for each network action that gets reduced.
+    /// While paused, outbound messages go to deferred_messages instead of the network.
+    pub paused: bool,
+    pub deferred_messages: Vec<ProtocolWrapper>,
+
     id: snowflake::ProcessUniqueId,
 }
 
@@ -74,6 +108,11 @@ impl NetworkState {
             get_validation_package_results: HashMap::new(),
             direct_message_connections: HashMap::new(),
 
+            bandwidth: BandwidthThrottle::default(),
+
+            paused: false,
+            deferred_messages: Vec::new(),
+
             id: snowflake::ProcessUniqueId::new(),
         }
     }
@@ -82,9 +121,74 @@ impl NetworkState {
         self.actions.clone()
     }
 
+    pub fn bandwidth_usage(&self) -> BandwidthUsage {
+        self.bandwidth.usage(Instant::now())
+    }
+
     pub fn initialized(&self) -> Result<(), HolochainError> {
         (self.network.is_some() && self.dna_hash.is_some() & self.agent_id.is_some()).ok_or(
             HolochainError::ErrorGeneric("Network not initialized".to_string()),
         )
     }
+
+    /// Counts the network processes in flight without copying the state's maps.
+    /// Peers are not part of it, they are kept by the context's PeerRotation.
+    pub fn snapshot(&self) -> NetworkStateSnapshot {
+        NetworkStateSnapshot {
+            initialized: self.initialized().is_ok(),
+            paused: self.paused,
+            pending_get_entries: self
+                .get_entry_with_meta_results
+                .values()
+                .filter(|result| result.is_none())
+                .count(),
+            pending_validation_packages: self
+                .get_validation_package_results
+                .values()
+                .filter(|result| result.is_none())
+                .count(),
+            open_direct_messages: self.direct_message_connections.len(),
+            deferred_messages: self.deferred_messages.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_counts_pending_processes() {
+        let mut state = NetworkState::new();
+        state
+            .get_entry_with_meta_results
+            .insert(Address::from("pending"), None);
+        state
+            .get_entry_with_meta_results
+            .insert(Address::from("done"), Some(Ok(None)));
+        state
+            .get_validation_package_results
+            .insert(String::from("request"), None);
+        state.direct_message_connections.insert(
+            String::from("request"),
+            DirectMessage::RequestValidationPackage(Address::from("entry")),
+        );
+
+        let snapshot = state.snapshot();
+        assert_eq!(
+            NetworkStateSnapshot {
+                initialized: false,
+                paused: false,
+                pending_get_entries: 1,
+                pending_validation_packages: 1,
+                open_direct_messages: 1,
+                deferred_messages: 0,
+            },
+            snapshot,
+        );
+        assert_eq!(
+            Ok(snapshot.clone()),
+            NetworkStateSnapshot::try_from(JsonString::from(snapshot)),
+        );
+    }
 }
@@ -13,6 +13,7 @@ use futures::{
     task::{LocalWaker, Poll},
 };
 use holochain_core_types::{
+    cas::content::{Address, AddressableContent},
     chain_header::ChainHeader,
     entry::{entry_type::EntryType, Entry},
     error::HolochainError,
@@ -31,22 +32,26 @@ pub fn build_validation_package(entry: &Entry, context: &Arc<Context>) -> Valida
 
     match entry.entry_type() {
         EntryType::App(app_entry_type) => {
-            if context
+            let maybe_zome_name = context
                 .state()
                 .unwrap()
                 .nucleus()
                 .dna()
                 .unwrap()
-                .get_zome_name_for_app_entry_type(&app_entry_type)
-                .is_none()
-            {
+                .get_zome_name_for_app_entry_type(&app_entry_type);
+            let error = match maybe_zome_name {
+                Ok(Some(_)) => None,
+                Ok(None) => Some(HolochainError::ValidationFailed(format!(
+                    "Unknown app entry type '{}'",
+                    String::from(app_entry_type),
+                ))),
+                Err(error) => Some(error.into()),
+            };
+            if error.is_some() {
                 return ValidationPackageFuture {
                     context: context.clone(),
                     key: id,
-                    error: Some(HolochainError::ValidationFailed(format!(
-                        "Unknown app entry type '{}'",
-                        String::from(app_entry_type),
-                    ))),
+                    error,
                 };
             }
         }
@@ -91,45 +96,9 @@ pub fn build_validation_package(entry: &Entry, context: &Arc<Context>) -> Valida
         );
 
         thread::spawn(move || {
-            let maybe_callback_result = get_validation_package_definition(&entry, context.clone());
-            let maybe_validation_package = maybe_callback_result
-                .and_then(|callback_result| match callback_result {
-                    CallbackResult::Fail(error_string) => {
-                        Err(HolochainError::ErrorGeneric(error_string))
-                    }
-                    CallbackResult::ValidationPackageDefinition(def) => Ok(def),
-                    CallbackResult::NotImplemented => Err(HolochainError::ErrorGeneric(format!(
-                        "ValidationPackage callback not implemented for {:?}",
-                        entry.entry_type().clone()
-                    ))),
-                    _ => unreachable!(),
-                })
-                .and_then(|package_definition| {
-                    Ok(match package_definition {
-                        Entry => ValidationPackage::only_header(entry_header),
-                        ChainEntries => {
-                            let mut package = ValidationPackage::only_header(entry_header);
-                            package.source_chain_entries = Some(all_public_chain_entries(&context));
-                            package
-                        }
-                        ChainHeaders => {
-                            let mut package = ValidationPackage::only_header(entry_header);
-                            package.source_chain_headers = Some(all_public_chain_headers(&context));
-                            package
-                        }
-                        ChainFull => {
-                            let mut package = ValidationPackage::only_header(entry_header);
-                            package.source_chain_entries = Some(all_public_chain_entries(&context));
-                            package.source_chain_headers = Some(all_public_chain_headers(&context));
-                            package
-                        }
-                        Custom(string) => {
-                            let mut package = ValidationPackage::only_header(entry_header);
-                            package.custom = Some(string);
-                            package
-                        }
-                    })
-                });
+            let chain_top = context.state().unwrap().agent().top_chain_header();
+            let maybe_validation_package =
+                build_validation_package_at(&entry, entry_header, &chain_top, &context);
 
             context
                 .action_channel
@@ -148,31 +117,176 @@ pub fn build_validation_package(entry: &Entry, context: &Arc<Context>) -> Valida
     }
 }
 
-fn all_public_chain_entries(context: &Arc<Context>) -> Vec<Entry> {
-    let chain = context.state().unwrap().agent().chain();
+/// Builds the validation package for an entry with the given header as it looks when the
+/// source chain ends with `chain_top`, i.e. only chain data up to and including `chain_top`
+/// gets included.
+/// This is used to build packages for new commits (with the current top) and to re-validate
+/// entries at their position in the chain (with the header preceding the entry).
+pub(crate) fn build_validation_package_at(
+    entry: &Entry,
+    entry_header: ChainHeader,
+    chain_top: &Option<ChainHeader>,
+    context: &Arc<Context>,
+) -> Result<ValidationPackage, HolochainError> {
+    let package_definition =
+        get_validation_package_definition(entry, context.clone()).and_then(|callback_result| {
+            match callback_result {
+                CallbackResult::Fail(error_string) => {
+                    Err(HolochainError::ErrorGeneric(error_string))
+                }
+                CallbackResult::ValidationPackageDefinition(def) => Ok(def),
+                CallbackResult::NotImplemented => Err(HolochainError::ErrorGeneric(format!(
+                    "ValidationPackage callback not implemented for {:?}",
+                    entry.entry_type().clone()
+                ))),
+                _ => unreachable!(),
+            }
+        })?;
+
+    let mut package = ValidationPackage::only_header(entry_header);
+    match package_definition {
+        Entry => {}
+        ChainEntries => {
+            package.source_chain_entries = Some(all_public_chain_entries(context, chain_top)?);
+        }
+        ChainHeaders => {
+            package.source_chain_headers = Some(all_public_chain_headers(context, chain_top)?);
+        }
+        ChainFull => {
+            package.source_chain_entries = Some(all_public_chain_entries(context, chain_top)?);
+            package.source_chain_headers = Some(all_public_chain_headers(context, chain_top)?);
+        }
+        Custom(string) => {
+            package.custom = Some(string);
+        }
+        ChainSubset {
+            entry_types,
+            include_headers,
+        } => {
+            let chain_headers = public_chain_headers_of_types(context, chain_top, &entry_types)?;
+            package.source_chain_entries = Some(
+                chain_headers
+                    .iter()
+                    .map(|chain_header| entry_for_chain_header(context, chain_header))
+                    .collect(),
+            );
+            if include_headers {
+                package.source_chain_headers = Some(chain_headers);
+            }
+        }
+    }
+    Ok(package)
+}
+
+/// Builds a validation package that only covers the window of the source chain between the
+/// given chain headers, both inclusive.
+/// `start` is the older and `end` the newer header of the range.
+/// The package carries the range bounds so validators know its scope.
+pub fn build_validation_package_range(
+    start: &Address,
+    end: &Address,
+    context: &Arc<Context>,
+) -> Result<ValidationPackage, HolochainError> {
     let top_header = context.state().unwrap().agent().top_chain_header();
-    chain
+    let mut headers = Vec::new();
+    let mut found_start = false;
+    for chain_header in context
+        .state()
+        .unwrap()
+        .agent()
+        .chain()
         .iter(&top_header)
-        .filter(|ref chain_header| chain_header.entry_type().can_publish())
-        .map(|chain_header| {
-            let storage = chain.content_storage().clone();
-            let json = (*storage.read().unwrap())
-                .fetch(chain_header.entry_address())
-                .expect("Could not fetch from CAS");
-            json.expect("Could not find CAS for existing chain header")
-                .try_into()
-                .expect("Could not convert to serialized entry")
-        })
-        .collect::<Vec<_>>()
+        .skip_while(|chain_header| &chain_header.address() != end)
+    {
+        let is_start = &chain_header.address() == start;
+        headers.push(chain_header);
+        if is_start {
+            found_start = true;
+            break;
+        }
+    }
+
+    if headers.is_empty() || !found_start {
+        return Err(HolochainError::ErrorGeneric(format!(
+            "Could not find chain range from {} to {} in source chain",
+            start, end
+        )));
+    }
+
+    let public_headers = headers
+        .into_iter()
+        .filter(|chain_header| chain_header.entry_type().can_publish())
+        .collect::<Vec<_>>();
+    let entries = public_headers
+        .iter()
+        .map(|chain_header| entry_for_chain_header(context, chain_header))
+        .collect::<Vec<_>>();
+
+    Ok(ValidationPackage {
+        chain_header: None,
+        source_chain_entries: Some(entries),
+        source_chain_headers: Some(public_headers),
+        custom: None,
+        chain_range: Some((start.clone(), end.clone())),
+    })
+}
+
+pub(crate) fn entry_for_chain_header(context: &Arc<Context>, chain_header: &ChainHeader) -> Entry {
+    let storage = context.state().unwrap().agent().chain().content_storage();
+    let json = (*storage.read().unwrap())
+        .fetch(chain_header.entry_address())
+        .expect("Could not fetch from CAS");
+    json.expect("Could not find CAS for existing chain header")
+        .try_into()
+        .expect("Could not convert to serialized entry")
+}
+
+fn all_public_chain_entries(
+    context: &Arc<Context>,
+    chain_top: &Option<ChainHeader>,
+) -> Result<Vec<Entry>, HolochainError> {
+    Ok(all_public_chain_headers(context, chain_top)?
+        .iter()
+        .map(|chain_header| entry_for_chain_header(context, chain_header))
+        .collect::<Vec<_>>())
 }
 
-fn all_public_chain_headers(context: &Arc<Context>) -> Vec<ChainHeader> {
+/// The public chain headers from `chain_top` back to the start of the chain, newest first.
+/// Fails with HolochainError::ValidationPackageTooLarge instead of walking through
+/// more chain headers than Context::max_traversal_depth() allows.
+fn all_public_chain_headers(
+    context: &Arc<Context>,
+    chain_top: &Option<ChainHeader>,
+) -> Result<Vec<ChainHeader>, HolochainError> {
     let chain = context.state().unwrap().agent().chain();
-    let top_header = context.state().unwrap().agent().top_chain_header();
-    chain
-        .iter(&top_header)
-        .filter(|ref chain_header| chain_header.entry_type().can_publish())
-        .collect::<Vec<_>>()
+    let max_depth = context.max_traversal_depth();
+    let mut chain_headers = Vec::new();
+    for (depth, chain_header) in chain.iter(chain_top).enumerate() {
+        if let Some(max_depth) = max_depth {
+            if depth >= max_depth {
+                return Err(HolochainError::ValidationPackageTooLarge(format!(
+                    "Source chain is longer than the maximum traversal depth of {}",
+                    max_depth
+                )));
+            }
+        }
+        if chain_header.entry_type().can_publish() {
+            chain_headers.push(chain_header);
+        }
+    }
+    Ok(chain_headers)
+}
+
+/// The public chain headers of entries of the given types, newest first.
+fn public_chain_headers_of_types(
+    context: &Arc<Context>,
+    chain_top: &Option<ChainHeader>,
+    entry_types: &[EntryType],
+) -> Result<Vec<ChainHeader>, HolochainError> {
+    Ok(all_public_chain_headers(context, chain_top)?
+        .into_iter()
+        .filter(|chain_header| entry_types.contains(chain_header.entry_type()))
+        .collect())
 }
 
 /// ValidationPackageFuture resolves to the ValidationPackage or a HolochainError.
@@ -214,7 +328,7 @@ mod tests {
     use crate::nucleus::actions::tests::*;
 
     use futures::executor::block_on;
-    use holochain_core_types::validation::ValidationPackage;
+    use holochain_core_types::{entry::entry_type::AppEntryType, validation::ValidationPackage};
 
     #[test]
     fn test_building_validation_package_entry() {
@@ -239,6 +353,7 @@ mod tests {
             source_chain_entries: None,
             source_chain_headers: None,
             custom: None,
+            chain_range: None,
         };
 
         assert_eq!(maybe_validation_package.unwrap(), expected);
@@ -261,11 +376,13 @@ mod tests {
         ));
         assert!(maybe_validation_package.is_ok());
 
+        let chain_top = context.state().unwrap().agent().top_chain_header();
         let expected = ValidationPackage {
             chain_header: Some(chain_header),
-            source_chain_entries: Some(all_public_chain_entries(&context)),
+            source_chain_entries: Some(all_public_chain_entries(&context, &chain_top).unwrap()),
             source_chain_headers: None,
             custom: None,
+            chain_range: None,
         };
 
         assert_eq!(maybe_validation_package.unwrap(), expected);
@@ -288,11 +405,13 @@ mod tests {
         ));
         assert!(maybe_validation_package.is_ok());
 
+        let chain_top = context.state().unwrap().agent().top_chain_header();
         let expected = ValidationPackage {
             chain_header: Some(chain_header),
             source_chain_entries: None,
-            source_chain_headers: Some(all_public_chain_headers(&context)),
+            source_chain_headers: Some(all_public_chain_headers(&context, &chain_top).unwrap()),
             custom: None,
+            chain_range: None,
         };
 
         assert_eq!(maybe_validation_package.unwrap(), expected);
@@ -315,13 +434,144 @@ mod tests {
         ));
         assert!(maybe_validation_package.is_ok());
 
+        let chain_top = context.state().unwrap().agent().top_chain_header();
         let expected = ValidationPackage {
             chain_header: Some(chain_header),
-            source_chain_entries: Some(all_public_chain_entries(&context)),
-            source_chain_headers: Some(all_public_chain_headers(&context)),
+            source_chain_entries: Some(all_public_chain_entries(&context, &chain_top).unwrap()),
+            source_chain_headers: Some(all_public_chain_headers(&context, &chain_top).unwrap()),
             custom: None,
+            chain_range: None,
         };
 
         assert_eq!(maybe_validation_package.unwrap(), expected);
     }
+
+    #[test]
+    fn test_building_validation_package_chain_subset() {
+        let (_instance, context) = instance();
+        context.cache_validation_package_definition(
+            &AppEntryType::from("package_entry"),
+            ChainSubset {
+                entry_types: vec![EntryType::App(AppEntryType::from("package_chain_entries"))],
+                include_headers: true,
+            },
+        );
+
+        let header_a = commit(test_entry_package_chain_entries(), &context);
+        commit(test_entry_package_chain_full(), &context);
+        let header_b = commit(test_entry_package_chain_entries(), &context);
+        let chain_header = commit(test_entry_package_entry(), &context);
+
+        let package = block_on(build_validation_package(
+            &test_entry_package_entry(),
+            &context.clone(),
+        ))
+        .expect("validation package should be built");
+
+        let expected = ValidationPackage {
+            chain_header: Some(chain_header),
+            source_chain_entries: Some(vec![
+                test_entry_package_chain_entries(),
+                test_entry_package_chain_entries(),
+            ]),
+            source_chain_headers: Some(vec![header_b, header_a]),
+            custom: None,
+            chain_range: None,
+        };
+        assert_eq!(package, expected);
+
+        context.cache_validation_package_definition(
+            &AppEntryType::from("package_entry"),
+            ChainSubset {
+                entry_types: vec![EntryType::App(AppEntryType::from("package_chain_full"))],
+                include_headers: false,
+            },
+        );
+        let package = block_on(build_validation_package(
+            &test_entry_package_entry(),
+            &context.clone(),
+        ))
+        .expect("validation package should be built");
+        assert_eq!(
+            package.source_chain_entries,
+            Some(vec![test_entry_package_chain_full()]),
+        );
+        assert_eq!(package.source_chain_headers, None);
+    }
+
+    #[test]
+    fn test_building_validation_package_beyond_max_traversal_depth() {
+        let (_instance, context) = instance();
+
+        commit(test_entry_package_entry(), &context);
+        commit(test_entry_package_chain_entries(), &context);
+        commit(test_entry_package_chain_full(), &context);
+
+        // genesis entries plus three commits make a chain of five
+        let mut limited_context = (*context).clone();
+        limited_context.set_max_traversal_depth(Some(4));
+        let limited_context = Arc::new(limited_context);
+
+        match block_on(build_validation_package(
+            &test_entry_package_chain_full(),
+            &limited_context,
+        )) {
+            Err(HolochainError::ValidationPackageTooLarge(_)) => (),
+            other => panic!("expected ValidationPackageTooLarge, got {:?}", other),
+        }
+
+        // packages that don't walk the chain are not affected
+        assert!(block_on(build_validation_package(
+            &test_entry_package_entry(),
+            &limited_context
+        ))
+        .is_ok());
+
+        let mut context = (*context).clone();
+        context.set_max_traversal_depth(Some(5));
+        assert!(block_on(build_validation_package(
+            &test_entry_package_chain_full(),
+            &Arc::new(context)
+        ))
+        .is_ok());
+    }
+
+    #[test]
+    fn test_building_validation_package_range() {
+        let (_instance, context) = instance();
+
+        let header_a = commit(test_entry_package_entry(), &context);
+        let header_b = commit(test_entry_package_chain_entries(), &context);
+        let header_c = commit(test_entry_package_chain_headers(), &context);
+        let header_d = commit(test_entry_package_chain_full(), &context);
+
+        let package =
+            build_validation_package_range(&header_b.address(), &header_c.address(), &context)
+                .expect("range should be found");
+
+        assert_eq!(
+            package.source_chain_headers,
+            Some(vec![header_c.clone(), header_b.clone()]),
+        );
+        assert_eq!(
+            package.source_chain_entries,
+            Some(vec![
+                test_entry_package_chain_headers(),
+                test_entry_package_chain_entries()
+            ]),
+        );
+        assert_eq!(
+            package.chain_range,
+            Some((header_b.address(), header_c.address())),
+        );
+        let headers = package.source_chain_headers.unwrap();
+        assert!(!headers.contains(&header_a));
+        assert!(!headers.contains(&header_d));
+
+        // bounds given in the wrong order can not be found
+        assert!(
+            build_validation_package_range(&header_c.address(), &header_b.address(), &context)
+                .is_err()
+        );
+    }
 }
@@ -1,10 +1,12 @@
 extern crate serde_json;
 use crate::context::Context;
 use holochain_core_types::{
-    cas::content::Address,
-    crud_status::{CrudStatus, LINK_NAME, STATUS_NAME},
+    cas::content::{Address, AddressableContent},
+    crud_status::{CrudStatus, DELETION_NAME, LINK_NAME, STATUS_NAME},
+    dna::zome::entry_types::Sharing,
     eav::EntityAttributeValue,
-    entry::{Entry, EntryWithMeta},
+    entry::{entry_type::EntryType, Entry, EntryWithMeta},
+    entry_acl::ACL_READER_NAME,
     error::HolochainError,
 };
 
@@ -27,6 +29,35 @@ pub(crate) fn get_entry_crud_meta_from_dht(
     context: &Arc<Context>,
     address: Address,
 ) -> Result<Option<(CrudStatus, Option<Address>)>, HolochainError> {
+    let crud_status = match get_entry_crud_status_from_dht(context, address.clone())? {
+        Some(crud_status) => crud_status,
+        None => return Ok(None),
+    };
+    let dht = context.state().unwrap().dht().meta_storage();
+    let storage = &dht.clone();
+    // Get crud-link
+    let mut maybe_crud_link = None;
+    let link_eavs =
+        (*storage.read().unwrap()).fetch_eav(Some(address), Some(LINK_NAME.to_string()), None)?;
+    assert!(
+        link_eavs.len() <= 1,
+        "link_eavs.len() = {}",
+        link_eavs.len()
+    );
+    if link_eavs.len() == 1 {
+        maybe_crud_link = Some(link_eavs.iter().next().unwrap().value());
+    }
+    // Done
+    Ok(Some((crud_status, maybe_crud_link)))
+}
+
+/// The crud-status of the entry at the given address, None if it has none.
+/// Unlike get_entry_crud_meta_from_dht() this does not look at crud-links, so it works
+/// for entries that were updated concurrently as well.
+pub(crate) fn get_entry_crud_status_from_dht(
+    context: &Arc<Context>,
+    address: Address,
+) -> Result<Option<CrudStatus>, HolochainError> {
     let dht = context.state().unwrap().dht().meta_storage();
     let storage = &dht.clone();
     // Get crud-status
@@ -60,28 +91,45 @@ pub(crate) fn get_entry_crud_meta_from_dht(
             crud_status = CrudStatus::MODIFIED;
         }
     }
-    // Get crud-link
-    let mut maybe_crud_link = None;
-    let link_eavs =
-        (*storage.read().unwrap()).fetch_eav(Some(address), Some(LINK_NAME.to_string()), None)?;
-    assert!(
-        link_eavs.len() <= 1,
-        "link_eavs.len() = {}",
-        link_eavs.len()
-    );
-    if link_eavs.len() == 1 {
-        maybe_crud_link = Some(link_eavs.iter().next().unwrap().value());
-    }
-    // Done
-    Ok(Some((crud_status, maybe_crud_link)))
+    Ok(Some(crud_status))
+}
+
+/// Addresses of all deletion entries held in the local DHT shard that delete the entry
+/// with the given address, in address order.
+pub fn get_deletions_for(
+    address: &Address,
+    context: &Arc<Context>,
+) -> Result<Vec<Address>, HolochainError> {
+    let dht = context.state().unwrap().dht().meta_storage();
+    let storage = &dht.clone();
+    let mut deletions: Vec<Address> = (*storage.read().unwrap())
+        .fetch_eav(Some(address.clone()), Some(DELETION_NAME.to_string()), None)?
+        .into_iter()
+        .map(|eav| eav.value())
+        .collect();
+    deletions.sort();
+    Ok(deletions)
 }
 
 /// GetEntry Action Creator
 ///
 /// Returns a future that resolves to an Ok(ActionWrapper) or an Err(error_message:String).
+/// Reads as our own agent, see get_entry_with_meta_for().
 pub fn get_entry_with_meta<'a>(
     context: &'a Arc<Context>,
     address: Address,
+) -> Result<Option<EntryWithMeta>, HolochainError> {
+    let own_agent = Address::from(context.agent_id.key.clone());
+    get_entry_with_meta_for(context, address, Some(&own_agent))
+}
+
+/// Like get_entry_with_meta() on behalf of the given reader, None for readers that
+/// could not be authenticated.
+/// Entries the reader may not read (see entry_readable_by()) are not found.
+pub fn get_entry_with_meta_for<'a>(
+    context: &'a Arc<Context>,
+    address: Address,
+    reader: Option<&Address>,
 ) -> Result<Option<EntryWithMeta>, HolochainError> {
     // 1. try to get the entry
     let entry = match get_entry_from_dht(context, address.clone()) {
@@ -89,6 +137,9 @@ pub fn get_entry_with_meta<'a>(
         Ok(None) => return Ok(None),
         Ok(Some(entry)) => entry,
     };
+    if !entry_readable_by(context, &entry, reader)? {
+        return Ok(None);
+    }
     // 2. try to get the entry's metadata
     let maybe_meta = get_entry_crud_meta_from_dht(context, address.clone());
     if let Err(err) = maybe_meta {
@@ -105,10 +156,224 @@ pub fn get_entry_with_meta<'a>(
     Ok(Some(item))
 }
 
+/// Checks whether the agent with the given key may read the entry.
+/// Entries of types that are not shared publicly can only be read by the agents on the
+/// list of readers stored as acl-reader meta data on the entry, and by our own agent.
+/// Unauthenticated readers (None) and agents missing from the list, or entries without
+/// a list, are denied.
+pub fn entry_readable_by(
+    context: &Arc<Context>,
+    entry: &Entry,
+    agent_key: Option<&Address>,
+) -> Result<bool, HolochainError> {
+    let app_entry_type = match entry.entry_type() {
+        EntryType::App(app_entry_type) => app_entry_type,
+        _ => return Ok(true),
+    };
+    if agent_key == Some(&Address::from(context.agent_id.key.clone())) {
+        return Ok(true);
+    }
+    let sharing = match context.get_dna() {
+        Some(dna) => dna
+            .get_entry_type_def(&String::from(app_entry_type))?
+            .map(|entry_type_def| entry_type_def.sharing.clone())
+            .unwrap_or_default(),
+        None => Sharing::default(),
+    };
+    if sharing == Sharing::Public {
+        return Ok(true);
+    }
+    let agent_key = match agent_key {
+        Some(agent_key) => agent_key,
+        None => return Ok(false),
+    };
+    let meta_storage = context.state().unwrap().dht().meta_storage();
+    let readers = meta_storage.read().unwrap().fetch_eav(
+        Some(entry.address()),
+        Some(ACL_READER_NAME.to_string()),
+        None,
+    )?;
+    Ok(readers.iter().any(|eav| eav.value() == *agent_key))
+}
+
+/// Follows all crud-links of the entry at the given address in the local DHT shard and
+/// returns the tips of the update chain, i.e. the entries that have not been modified further.
+///
+/// Concurrent updates fork the chain into several live tips. In that case the conflict
+/// resolver registered on the context, if any, gets to pick the winner.
+/// Without a resolver, or if it can not decide, all tips are returned so the caller decides.
+pub fn get_entry_latest(
+    context: &Arc<Context>,
+    address: Address,
+) -> Result<Vec<EntryWithMeta>, HolochainError> {
+    let mut tips = Vec::new();
+    let mut visited = HashSet::new();
+    let mut pending = vec![address];
+    while let Some(address) = pending.pop() {
+        if !visited.insert(address.clone()) {
+            continue;
+        }
+        let entry = match get_entry_from_dht(context, address.clone())? {
+            Some(entry) => entry,
+            None => continue,
+        };
+        let crud_status = match get_entry_crud_status_from_dht(context, address.clone())? {
+            Some(crud_status) => crud_status,
+            None => continue,
+        };
+        let crud_links = get_entry_crud_links_from_dht(context, address)?;
+        if crud_status == CrudStatus::DELETED || crud_links.is_empty() {
+            if crud_status != CrudStatus::DELETED {
+                tips.push(EntryWithMeta {
+                    entry,
+                    crud_status,
+                    maybe_crud_link: None,
+                });
+            }
+            continue;
+        }
+        pending.extend(crud_links);
+    }
+
+    if tips.len() > 1 {
+        if let Some(resolver) = context.conflict_resolver() {
+            if let Some(winner) = resolver(&tips) {
+                return Ok(vec![winner]);
+            }
+        }
+    }
+    Ok(tips)
+}
+
+/// What get_entry_with_policy() returns for an entry that has been modified.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModifiedPolicy {
+    /// The modified entry itself, with MODIFIED status. Following the update is up
+    /// to the caller.
+    ReturnOriginal,
+    /// The latest version of the entry, as found by get_entry_latest().
+    /// If concurrent updates left several latest versions, the modified entry is
+    /// returned together with their addresses, as with ReturnBoth.
+    FollowToLatest,
+    /// The modified entry together with the addresses of its latest versions.
+    ReturnBoth,
+}
+
+impl Default for ModifiedPolicy {
+    fn default() -> Self {
+        ModifiedPolicy::ReturnOriginal
+    }
+}
+
+/// An entry as returned by get_entry_with_policy().
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntryWithLatest {
+    pub entry_with_meta: EntryWithMeta,
+    /// Addresses of the latest versions of a modified entry if the policy asks for them,
+    /// empty otherwise.
+    pub latest_addresses: Vec<Address>,
+}
+
+/// Gets the entry at the given address from the local DHT shard, handling modified
+/// entries according to the given policy.
+/// Entries that were updated concurrently have no single crud-link, so their
+/// maybe_crud_link is None and the latest versions are found by following all links.
+pub fn get_entry_with_policy(
+    context: &Arc<Context>,
+    address: Address,
+    policy: ModifiedPolicy,
+) -> Result<Option<EntryWithLatest>, HolochainError> {
+    let entry = match get_entry_from_dht(context, address.clone())? {
+        Some(entry) => entry,
+        None => return Ok(None),
+    };
+    let crud_status = get_entry_crud_status_from_dht(context, address.clone())?
+        .expect("Entry should have crud-status metadata");
+    let crud_links = get_entry_crud_links_from_dht(context, address.clone())?;
+    let entry_with_meta = EntryWithMeta {
+        entry,
+        crud_status,
+        maybe_crud_link: if crud_links.len() == 1 {
+            crud_links.into_iter().next()
+        } else {
+            None
+        },
+    };
+    if entry_with_meta.crud_status != CrudStatus::MODIFIED
+        || policy == ModifiedPolicy::ReturnOriginal
+    {
+        return Ok(Some(EntryWithLatest {
+            entry_with_meta,
+            latest_addresses: Vec::new(),
+        }));
+    }
+    let mut latest = get_entry_latest(context, address)?;
+    if policy == ModifiedPolicy::FollowToLatest && latest.len() == 1 {
+        return Ok(Some(EntryWithLatest {
+            entry_with_meta: latest.remove(0),
+            latest_addresses: Vec::new(),
+        }));
+    }
+    Ok(Some(EntryWithLatest {
+        entry_with_meta,
+        latest_addresses: latest.iter().map(|tip| tip.entry.address()).collect(),
+    }))
+}
+
+/// Resolves a (base58) address prefix, as shown by tools that truncate addresses, to the
+/// addresses of all entries held in the local DHT shard that start with it.
+/// More than one address in the result means the prefix is ambiguous.
+/// This only looks at local storage and never goes to the network.
+pub fn get_entry_by_prefix(
+    prefix: &str,
+    context: &Arc<Context>,
+) -> Result<Vec<Address>, HolochainError> {
+    if prefix.is_empty() {
+        return Err(HolochainError::ErrorGeneric(String::from(
+            "Address prefix must not be empty",
+        )));
+    }
+    let dht = context.state().unwrap().dht().content_storage();
+    let addresses = (*dht.read().unwrap()).addresses()?;
+    let mut matches: Vec<Address> = addresses
+        .into_iter()
+        .filter(|address| String::from(address.clone()).starts_with(prefix))
+        .collect();
+    matches.sort();
+    Ok(matches)
+}
+
+/// All crud-links stored for the given address.
+/// More than one link means the entry was updated concurrently.
+fn get_entry_crud_links_from_dht(
+    context: &Arc<Context>,
+    address: Address,
+) -> Result<Vec<Address>, HolochainError> {
+    let dht = context.state().unwrap().dht().meta_storage();
+    let storage = &dht.clone();
+    let link_eavs =
+        (*storage.read().unwrap()).fetch_eav(Some(address), Some(LINK_NAME.to_string()), None)?;
+    Ok(link_eavs.iter().map(|eav| eav.value()).collect())
+}
+
 #[cfg(test)]
 pub mod tests {
-    use crate::instance::tests::test_context_with_state;
-    use holochain_core_types::{cas::content::AddressableContent, entry::test_entry};
+    use super::ModifiedPolicy;
+    use crate::{
+        agent::actions::commit::commit_entry,
+        instance::tests::test_context_with_state,
+        nucleus::actions::tests::{instance_by_name, test_dna},
+    };
+    use futures::executor::block_on;
+    use holochain_core_types::{
+        cas::content::{Address, AddressableContent},
+        crud_status::{create_crud_link_eav, create_crud_status_eav, CrudStatus},
+        entry::{
+            deletion_entry::DeletionEntry, test_entry, test_entry_a, test_entry_b, test_entry_c,
+            Entry, EntryWithMeta,
+        },
+    };
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn get_entry_from_dht_cas() {
@@ -121,4 +386,210 @@ pub mod tests {
         let result = super::get_entry_from_dht(&context, entry.address());
         assert_eq!(Ok(Some(entry.clone())), result);
     }
+
+    #[test]
+    fn get_entry_by_prefix_test() {
+        let context = test_context_with_state();
+        let entries = vec![test_entry_a(), test_entry_b(), test_entry_c()];
+        {
+            let storage = &context.state().unwrap().dht().content_storage().clone();
+            for entry in entries.iter() {
+                (*storage.write().unwrap()).add(entry).unwrap();
+            }
+        }
+
+        // shortest prefix of the first address that no other address shares
+        let address = String::from(entries[0].address());
+        let unique_prefix = (1..=address.len())
+            .map(|length| &address[..length])
+            .find(|prefix| {
+                entries[1..]
+                    .iter()
+                    .all(|entry| !String::from(entry.address()).starts_with(prefix))
+            })
+            .unwrap();
+        assert_eq!(
+            Ok(vec![entries[0].address()]),
+            super::get_entry_by_prefix(unique_prefix, &context)
+        );
+
+        // all multihash addresses start with "Qm"
+        let mut all: Vec<_> = entries.iter().map(|entry| entry.address()).collect();
+        all.sort();
+        assert_eq!(Ok(all), super::get_entry_by_prefix("Qm", &context));
+
+        assert_eq!(Ok(vec![]), super::get_entry_by_prefix("Qx", &context));
+        assert!(super::get_entry_by_prefix("", &context).is_err());
+    }
+
+    #[test]
+    fn get_entry_latest_forked() {
+        let context = test_context_with_state();
+        let original = test_entry_a();
+        let update_b = test_entry_b();
+        let update_c = test_entry_c();
+        {
+            let content_storage = &context.state().unwrap().dht().content_storage().clone();
+            let meta_storage = &context.state().unwrap().dht().meta_storage().clone();
+            for entry in vec![&original, &update_b, &update_c] {
+                (*content_storage.write().unwrap()).add(entry).unwrap();
+            }
+            let eavs = vec![
+                create_crud_status_eav(&original.address(), CrudStatus::MODIFIED),
+                create_crud_status_eav(&update_b.address(), CrudStatus::LIVE),
+                create_crud_status_eav(&update_c.address(), CrudStatus::LIVE),
+                create_crud_link_eav(&original.address(), &update_b.address()),
+                create_crud_link_eav(&original.address(), &update_c.address()),
+            ];
+            for eav in eavs {
+                (*meta_storage.write().unwrap()).add_eav(&eav).unwrap();
+            }
+        }
+
+        // without a resolver both tips are returned
+        let tips = super::get_entry_latest(&context, original.address()).unwrap();
+        assert_eq!(2, tips.len());
+        assert!(tips.iter().any(|tip| tip.entry == update_b));
+        assert!(tips.iter().any(|tip| tip.entry == update_c));
+
+        // the resolver is invoked with both tips and picks the winner
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_by_resolver = seen.clone();
+        let winner = update_c.clone();
+        context.set_conflict_resolver(Arc::new(move |tips: &Vec<EntryWithMeta>| {
+            *seen_by_resolver.lock().unwrap() = tips.clone();
+            tips.iter().find(|tip| tip.entry == winner).cloned()
+        }));
+        let tips = super::get_entry_latest(&context, original.address()).unwrap();
+        assert_eq!(2, seen.lock().unwrap().len());
+        assert_eq!(1, tips.len());
+        assert_eq!(update_c, tips[0].entry);
+    }
+
+    #[test]
+    fn get_entry_with_policy_test() {
+        let context = test_context_with_state();
+        let original = test_entry_a();
+        let update = test_entry_b();
+        let live = test_entry_c();
+        {
+            let content_storage = &context.state().unwrap().dht().content_storage().clone();
+            let meta_storage = &context.state().unwrap().dht().meta_storage().clone();
+            for entry in vec![&original, &update, &live] {
+                (*content_storage.write().unwrap()).add(entry).unwrap();
+            }
+            let eavs = vec![
+                create_crud_status_eav(&original.address(), CrudStatus::MODIFIED),
+                create_crud_status_eav(&update.address(), CrudStatus::LIVE),
+                create_crud_status_eav(&live.address(), CrudStatus::LIVE),
+                create_crud_link_eav(&original.address(), &update.address()),
+            ];
+            for eav in eavs {
+                (*meta_storage.write().unwrap()).add_eav(&eav).unwrap();
+            }
+        }
+        let get = |address: Address, policy| {
+            super::get_entry_with_policy(&context, address, policy)
+                .unwrap()
+                .expect("entry should be found")
+        };
+
+        assert_eq!(ModifiedPolicy::ReturnOriginal, ModifiedPolicy::default());
+        let result = get(original.address(), ModifiedPolicy::ReturnOriginal);
+        assert_eq!(original, result.entry_with_meta.entry);
+        assert_eq!(CrudStatus::MODIFIED, result.entry_with_meta.crud_status);
+        assert!(result.latest_addresses.is_empty());
+
+        let result = get(original.address(), ModifiedPolicy::FollowToLatest);
+        assert_eq!(update, result.entry_with_meta.entry);
+        assert_eq!(CrudStatus::LIVE, result.entry_with_meta.crud_status);
+        assert!(result.latest_addresses.is_empty());
+
+        let result = get(original.address(), ModifiedPolicy::ReturnBoth);
+        assert_eq!(original, result.entry_with_meta.entry);
+        assert_eq!(vec![update.address()], result.latest_addresses);
+
+        // entries that were not modified are returned as they are under every policy
+        for policy in vec![
+            ModifiedPolicy::ReturnOriginal,
+            ModifiedPolicy::FollowToLatest,
+            ModifiedPolicy::ReturnBoth,
+        ] {
+            let result = get(live.address(), policy);
+            assert_eq!(live, result.entry_with_meta.entry);
+            assert!(result.latest_addresses.is_empty());
+        }
+    }
+
+    #[test]
+    fn get_entry_with_policy_forked() {
+        let context = test_context_with_state();
+        let original = test_entry_a();
+        let update_b = test_entry_b();
+        let update_c = test_entry_c();
+        {
+            let content_storage = &context.state().unwrap().dht().content_storage().clone();
+            let meta_storage = &context.state().unwrap().dht().meta_storage().clone();
+            for entry in vec![&original, &update_b, &update_c] {
+                (*content_storage.write().unwrap()).add(entry).unwrap();
+            }
+            let eavs = vec![
+                create_crud_status_eav(&original.address(), CrudStatus::MODIFIED),
+                create_crud_status_eav(&update_b.address(), CrudStatus::LIVE),
+                create_crud_status_eav(&update_c.address(), CrudStatus::LIVE),
+                create_crud_link_eav(&original.address(), &update_b.address()),
+                create_crud_link_eav(&original.address(), &update_c.address()),
+            ];
+            for eav in eavs {
+                (*meta_storage.write().unwrap()).add_eav(&eav).unwrap();
+            }
+        }
+        let mut tip_addresses = vec![update_b.address(), update_c.address()];
+        tip_addresses.sort();
+
+        let result = super::get_entry_with_policy(
+            &context,
+            original.address(),
+            ModifiedPolicy::ReturnOriginal,
+        )
+        .unwrap()
+        .expect("entry should be found");
+        assert_eq!(original, result.entry_with_meta.entry);
+        assert_eq!(None, result.entry_with_meta.maybe_crud_link);
+
+        // without a resolver there is no single latest version to follow to
+        for policy in vec![ModifiedPolicy::FollowToLatest, ModifiedPolicy::ReturnBoth] {
+            let result = super::get_entry_with_policy(&context, original.address(), policy)
+                .unwrap()
+                .expect("entry should be found");
+            assert_eq!(original, result.entry_with_meta.entry);
+            let mut latest_addresses = result.latest_addresses.clone();
+            latest_addresses.sort();
+            assert_eq!(tip_addresses, latest_addresses);
+        }
+    }
+
+    #[test]
+    fn get_deletions_for_test() {
+        let (_instance, context) = instance_by_name("jill", test_dna());
+        let entry = test_entry();
+        let address = block_on(commit_entry(entry.clone(), None, &context)).unwrap();
+        assert_eq!(Ok(vec![]), super::get_deletions_for(&address, &context));
+
+        let deletion = Entry::Deletion(DeletionEntry::new(address.clone()));
+        block_on(commit_entry(
+            deletion.clone(),
+            Some(address.clone()),
+            &context,
+        ))
+        .unwrap();
+        assert_eq!(
+            Ok(vec![deletion.address()]),
+            super::get_deletions_for(&address, &context)
+        );
+        assert_eq!(
+            Ok(vec![]),
+            super::get_deletions_for(&test_entry_b().address(), &context)
+        );
+    }
 }
@@ -5,7 +5,10 @@ use crate::{
     context::Context,
     instance::dispatch_action_and_wait,
     nucleus::{
-        ribosome::callback::{genesis::genesis, CallbackParams, CallbackResult},
+        ribosome::{
+            callback::{genesis::genesis, init::init, CallbackParams, CallbackResult},
+            wasm_cache::expand_wildcard_capabilities,
+        },
         state::NucleusStatus,
     },
 };
@@ -29,7 +32,7 @@ const INITIALIZATION_TIMEOUT: u64 = 30;
 /// instance. It creates both InitApplication and ReturnInitializationResult actions asynchronously.
 ///
 /// Returns a future that resolves to an Ok(NucleusStatus) or an Err(String) which carries either
-/// the Dna error or errors from the genesis callback.
+/// the Dna error or errors from the genesis or __hdk_init callbacks.
 ///
 /// Use futures::executor::block_on to wait for an initialized instance.
 pub async fn initialize_application(
@@ -42,9 +45,19 @@ pub async fn initialize_application(
         ));
     }
 
+    // Entries get routed to zomes by their type's name, which has to be unambiguous
+    dna.check_entry_type_names().map_err(HolochainError::Dna)?;
+
+    // Parse all zomes' wasm up front if the context asks to fail fast
+    context.wasm_cache().write().unwrap().preload(&dna)?;
+
     let context_clone = context.clone();
 
-    let action_wrapper = ActionWrapper::new(Action::InitApplication(dna.clone()));
+    // The running DNA has wildcard capabilities resolved to the zomes' exported functions,
+    // the DNA committed to the chain stays as it was written.
+    let mut running_dna = dna.clone();
+    expand_wildcard_capabilities(&mut running_dna);
+    let action_wrapper = ActionWrapper::new(Action::InitApplication(running_dna));
     dispatch_action_and_wait(
         &context_clone.action_channel,
         &context_clone.observer_channel,
@@ -89,7 +102,7 @@ pub async fn initialize_application(
 
     // map genesis across every zome
     let results: Vec<_> = dna
-        .zomes
+        .zomes()
         .keys()
         .map(|zome_name| genesis(context_clone.clone(), zome_name, &CallbackParams::Genesis))
         .collect();
@@ -104,6 +117,20 @@ pub async fn initialize_application(
         _ => None,
     });
 
+    // run the optional __hdk_init callback of every zome once genesis passed,
+    // stopping at the first zome that fails to initialize
+    let maybe_error = maybe_error.or_else(|| {
+        dna.zome_names().find_map(|zome_name| {
+            match init(context_clone.clone(), zome_name, &CallbackParams::Init) {
+                CallbackResult::Fail(error_string) => Some(format!(
+                    "Initialization (__hdk_init) of zome \"{}\" failed: {}",
+                    zome_name, error_string
+                )),
+                _ => None,
+            }
+        })
+    });
+
     context_clone
         .action_channel
         .send(ActionWrapper::new(Action::ReturnInitializationResult(
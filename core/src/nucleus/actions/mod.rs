@@ -2,6 +2,7 @@ pub mod build_validation_package;
 pub mod get_entry;
 pub mod initialize;
 pub mod validate;
+pub mod validate_and_commit;
 
 #[cfg(test)]
 pub mod tests {
@@ -36,22 +37,22 @@ pub mod tests {
 
         let mut dna = create_test_dna_with_cap("test_zome", "test_cap", &Capability::new(), &wasm);
 
-        dna.zomes
+        dna.zomes_mut()
             .get_mut("test_zome")
             .unwrap()
             .entry_types
             .insert("package_entry".into(), EntryTypeDef::new());
-        dna.zomes
+        dna.zomes_mut()
             .get_mut("test_zome")
             .unwrap()
             .entry_types
             .insert("package_chain_entries".into(), EntryTypeDef::new());
-        dna.zomes
+        dna.zomes_mut()
             .get_mut("test_zome")
             .unwrap()
             .entry_types
             .insert("package_chain_headers".into(), EntryTypeDef::new());
-        dna.zomes
+        dna.zomes_mut()
             .get_mut("test_zome")
             .unwrap()
             .entry_types
@@ -2,24 +2,37 @@ extern crate futures;
 extern crate serde_json;
 use crate::{
     action::{Action, ActionWrapper},
+    agent::entry_crypto::is_encrypted_type,
     context::Context,
-    nucleus::ribosome::callback::{self, CallbackResult},
+    nucleus::{
+        actions::build_validation_package::{build_validation_package_at, entry_for_chain_header},
+        ribosome::callback::{self, CallbackResult},
+        state::ValidationResult,
+    },
+    workflows::get_entry_history::get_entry_with_meta_workflow,
 };
+use chrono::DateTime;
 use futures::{
+    executor::block_on,
     future::{self, Future, FutureObj},
     task::{LocalWaker, Poll},
 };
 use holochain_core_types::{
-    cas::content::AddressableContent,
+    cas::content::{Address, AddressableContent},
+    chain_header::ChainHeader,
+    dna::zome::entry_types::EntryTypeDef,
     entry::{entry_type::EntryType, Entry},
-    error::HolochainError,
+    error::{DnaError, HolochainError},
     hash::HashString,
-    validation::ValidationData,
+    validation::{EntryAction, EntryLifecycle, ValidationData, ValidationPackage},
 };
 use snowflake;
 use std::{
     pin::{Pin, Unpin},
-    sync::Arc,
+    sync::{
+        mpsc::{channel, Receiver},
+        Arc,
+    },
     thread,
 };
 
@@ -35,24 +48,49 @@ pub fn validate_entry<'a>(
 ) -> FutureObj<'a, Result<HashString, HolochainError>> {
     let id = snowflake::ProcessUniqueId::new();
     let address = entry.address();
+    // Entries of encrypted types reach DHT nodes as ciphertext (see agent::entry_crypto),
+    // so checks of their content can only be done by their author.
+    let content_is_readable = match validation_data.lifecycle {
+        EntryLifecycle::Dht => !is_encrypted_type(&entry.entry_type(), context),
+        _ => true,
+    };
 
     match entry.entry_type() {
         EntryType::App(app_entry_type) => {
-            if context
-                .state()
-                .unwrap()
-                .nucleus()
-                .dna()
-                .unwrap()
-                .get_zome_name_for_app_entry_type(&app_entry_type)
-                .is_none()
-            {
-                return FutureObj::new(Box::new(future::err(HolochainError::ValidationFailed(
-                    format!(
-                        "Attempted to validate unknown app entry type {:?}",
-                        app_entry_type,
-                    ),
-                ))));
+            let dna = context.state().unwrap().nucleus().dna().unwrap();
+            match dna.get_zome_name_for_app_entry_type(&app_entry_type) {
+                Ok(Some(_)) => (),
+                Ok(None) => {
+                    return FutureObj::new(Box::new(future::err(
+                        HolochainError::ValidationFailed(format!(
+                            "Attempted to validate unknown app entry type {:?}",
+                            app_entry_type,
+                        )),
+                    )));
+                }
+                Err(error) => return FutureObj::new(Box::new(future::err(error.into()))),
+            }
+
+            let previous_header = author_previous_header(&validation_data.package, context);
+            if let Err(error) = validate_created_at(&entry, &previous_header) {
+                return FutureObj::new(Box::new(future::err(error)));
+            }
+
+            let maybe_entry_type_def = match dna.get_entry_type_def(&String::from(app_entry_type)) {
+                Ok(maybe_entry_type_def) => maybe_entry_type_def.cloned(),
+                Err(error) => return FutureObj::new(Box::new(future::err(error.into()))),
+            };
+            if let Some(entry_type_def) = maybe_entry_type_def {
+                let required_fields_result = if content_is_readable {
+                    validate_required_fields(&entry, &entry_type_def)
+                } else {
+                    Ok(())
+                };
+                if let Err(error) = required_fields_result.and_then(|_| {
+                    validate_mutability(&entry, &entry_type_def, &validation_data.action)
+                }) {
+                    return FutureObj::new(Box::new(future::err(error)));
+                }
             }
         }
 
@@ -61,7 +99,17 @@ pub fn validate_entry<'a>(
         }
 
         EntryType::Deletion => {
-            // FIXME
+            // The mutability of the deleted entry's type is checked on the validation thread
+        }
+
+        EntryType::Unknown(type_name) => {
+            // Entry types from newer versions can't be validated by this build.
+            // Skip them instead of failing so chains containing them still load.
+            context.log(format!(
+                "warning: skipping validation of entry {} with unknown entry type {}",
+                address, type_name,
+            ));
+            return FutureObj::new(Box::new(future::ok(address)));
         }
 
         _ => {
@@ -80,23 +128,39 @@ pub fn validate_entry<'a>(
         let entry = entry.clone();
         let context = context.clone();
         thread::spawn(move || {
-            let maybe_validation_result = callback::validate_entry::validate_entry(
-                entry.clone(),
-                validation_data.clone(),
-                context.clone(),
-            );
+            // References and deleted entries are checked here since fetching them blocks
+            let references_result = if content_is_readable {
+                validate_references(&entry, &context)
+            } else {
+                Ok(())
+            };
+            let maybe_validation_result = references_result
+                .and_then(|_| validate_deletion(&entry, &context))
+                .and_then(|_| {
+                    callback::validate_entry::validate_entry(
+                        entry.clone(),
+                        validation_data.clone(),
+                        context.clone(),
+                    )
+                });
 
             let result = match maybe_validation_result {
                 Ok(validation_result) => match validation_result {
-                    CallbackResult::Fail(error_string) => Err(error_string),
-                    CallbackResult::Pass => Ok(()),
-                    CallbackResult::NotImplemented => Err(format!(
+                    CallbackResult::Fail(error_string) => {
+                        ValidationResult::permanent_failure(error_string)
+                    }
+                    CallbackResult::Pass => ValidationResult::Pass,
+                    CallbackResult::NotImplemented => ValidationResult::permanent_failure(format!(
                         "Validation callback not implemented for {:?}",
                         entry.entry_type().clone()
                     )),
                     _ => unreachable!(),
                 },
-                Err(error) => Err(error.to_string()),
+                Err(HolochainError::ValidationFailed(reason)) => {
+                    ValidationResult::permanent_failure(reason)
+                }
+                // Lookup, network and callback errors don't prove the entry invalid
+                Err(error) => ValidationResult::transient_failure(error.to_string()),
             };
 
             context
@@ -115,7 +179,311 @@ pub fn validate_entry<'a>(
     }))
 }
 
-/// ValidationFuture resolves to an Ok(ActionWrapper) or an Err(error_message:String).
+/// Checks that the JSON content of an app entry contains all top-level fields that its
+/// entry type definition declares as required.
+pub fn validate_required_fields(
+    entry: &Entry,
+    entry_type_def: &EntryTypeDef,
+) -> Result<(), HolochainError> {
+    if entry_type_def.required_fields.is_empty() {
+        return Ok(());
+    }
+    let content = match entry {
+        Entry::App(_, content) => content,
+        _ => return Ok(()),
+    };
+    let value: serde_json::Value = serde_json::from_str(&String::from(content.clone()))?;
+    match entry_type_def
+        .required_fields
+        .iter()
+        .find(|field| value.get(field.as_str()).is_none())
+    {
+        Some(field) => Err(HolochainError::ValidationFailed(format!(
+            "Entry of type {} is missing required field {}",
+            entry.entry_type(),
+            field,
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Checks that the fields the entry's type declares as references hold the address of an
+/// entry of the expected type, which may be defined in another zome.
+/// Referenced entries that can't be found or looked up yet fail with
+/// HolochainError::ValidationPending.
+/// Blocks on fetching the referenced entries, so must not be called from within a future.
+pub fn validate_references(entry: &Entry, context: &Arc<Context>) -> Result<(), HolochainError> {
+    let (app_entry_type, content) = match entry {
+        Entry::App(app_entry_type, content) => (app_entry_type, content),
+        _ => return Ok(()),
+    };
+    let dna = match context.get_dna() {
+        Some(dna) => dna,
+        None => return Ok(()),
+    };
+    let references = match dna.get_entry_type_def(&String::from(app_entry_type.clone()))? {
+        Some(entry_type_def) if !entry_type_def.references.is_empty() => {
+            entry_type_def.references.clone()
+        }
+        _ => return Ok(()),
+    };
+    let value: serde_json::Value = serde_json::from_str(&String::from(content.clone()))?;
+    for reference in references {
+        let address = match value.get(reference.field.as_str()) {
+            None => continue,
+            Some(serde_json::Value::String(address)) => Address::from(address.clone()),
+            Some(_) => {
+                return Err(HolochainError::ValidationFailed(format!(
+                    "Field {} of entry of type {} must hold an address",
+                    reference.field,
+                    app_entry_type.to_string(),
+                )));
+            }
+        };
+        let referenced_entry = block_on(get_entry_with_meta_workflow(context, &address))
+            .map_err(|error| {
+                HolochainError::ValidationPending(format!(
+                    "Could not look up entry {} referenced by field {}: {}",
+                    address, reference.field, error,
+                ))
+            })?
+            .ok_or_else(|| {
+                HolochainError::ValidationPending(format!(
+                    "Entry {} referenced by field {} not found",
+                    address, reference.field,
+                ))
+            })?
+            .entry;
+        let expected_entry_type = EntryType::from(reference.expected_entry_type.clone());
+        if referenced_entry.entry_type() != expected_entry_type {
+            return Err(HolochainError::ValidationFailed(format!(
+                "Field {} of entry of type {} must reference an entry of type {}, found {}",
+                reference.field,
+                app_entry_type.to_string(),
+                expected_entry_type,
+                referenced_entry.entry_type(),
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the mutability of the entry type allows updating or deleting the given entry.
+/// Creating entries is always allowed.
+pub fn validate_mutability(
+    entry: &Entry,
+    entry_type_def: &EntryTypeDef,
+    action: &EntryAction,
+) -> Result<(), HolochainError> {
+    let (allowed, verb) = match action {
+        EntryAction::Create => (true, "created"),
+        EntryAction::Modify(_) => (entry_type_def.mutability.can_update(), "updated"),
+        EntryAction::Delete => (entry_type_def.mutability.can_delete(), "deleted"),
+    };
+    if allowed {
+        Ok(())
+    } else {
+        Err(HolochainError::ValidationFailed(format!(
+            "Entry of type {} is {:?} and can not be {}",
+            entry.entry_type(),
+            entry_type_def.mutability,
+            verb,
+        )))
+    }
+}
+
+/// Looks up the definition of the given app entry's type in the DNA and checks that its
+/// mutability allows the given action. System entries are not restricted.
+pub fn validate_entry_mutability(
+    entry: &Entry,
+    action: &EntryAction,
+    context: &Arc<Context>,
+) -> Result<(), HolochainError> {
+    if let EntryType::App(app_entry_type) = entry.entry_type() {
+        let dna = match context.get_dna() {
+            Some(dna) => dna,
+            None => return Ok(()),
+        };
+        if let Some(entry_type_def) = dna.get_entry_type_def(&String::from(app_entry_type))? {
+            return validate_mutability(entry, entry_type_def, action);
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the type of the entry a deletion entry removes can be deleted.
+/// Deleted entries that can't be found yet fail with HolochainError::ValidationPending.
+/// Blocks on fetching the deleted entry, so must not be called from within a future.
+pub fn validate_deletion(entry: &Entry, context: &Arc<Context>) -> Result<(), HolochainError> {
+    let deleted_entry_address = match entry {
+        Entry::Deletion(deletion_entry) => deletion_entry.clone().deleted_entry_address(),
+        _ => return Ok(()),
+    };
+    let deleted_entry = block_on(get_entry_with_meta_workflow(
+        context,
+        &deleted_entry_address,
+    ))?
+    .ok_or_else(|| {
+        HolochainError::ValidationPending(format!(
+            "Deleted entry {} not found",
+            deleted_entry_address
+        ))
+    })?
+    .entry;
+    validate_entry_mutability(&deleted_entry, &EntryAction::Delete, context)
+}
+
+/// Progress event of validate_chain_with_progress():
+/// the position of the entry in the chain (0 is the oldest) and its validation result.
+pub type ValidationProgress = (usize, Result<Address, HolochainError>);
+
+/// Validates all entries of the agent's source chain, oldest first, on a separate thread.
+/// Every processed entry emits a ValidationProgress event on the returned receiver so callers
+/// can report progress. Validation stops when the receiver gets dropped and, with fail_fast,
+/// after the first failure. Otherwise all entries get validated and reported.
+/// Every entry gets validated with the validation package it had at its position in the chain.
+/// System entries (DNA, agent ID...) are not validated and always pass.
+pub fn validate_chain_with_progress(
+    context: &Arc<Context>,
+    fail_fast: bool,
+) -> Receiver<ValidationProgress> {
+    let (sender, receiver) = channel();
+    let context = context.clone();
+    thread::spawn(move || {
+        let chain_headers = {
+            let state = context.state().unwrap();
+            let agent = state.agent();
+            let mut chain_headers: Vec<ChainHeader> =
+                agent.chain().iter(&agent.top_chain_header()).collect();
+            chain_headers.reverse();
+            chain_headers
+        };
+
+        let mut previous_header: Option<ChainHeader> = None;
+        for (index, chain_header) in chain_headers.into_iter().enumerate() {
+            let entry = entry_for_chain_header(&context, &chain_header);
+            // Each entry gets validated against the chain as it was when it got committed,
+            // i.e. with the preceding header as the top of the chain
+            let maybe_package = match entry.entry_type() {
+                EntryType::App(_) | EntryType::LinkAdd | EntryType::Deletion => {
+                    Some(build_validation_package_at(
+                        &entry,
+                        chain_header.clone(),
+                        &previous_header,
+                        &context,
+                    ))
+                }
+                EntryType::Unknown(_) => {
+                    Some(Ok(ValidationPackage::only_header(chain_header.clone())))
+                }
+                _ => None,
+            };
+            let result = match maybe_package {
+                Some(Ok(package)) => {
+                    let validation_data = ValidationData {
+                        sources: chain_header.sources().clone(),
+                        package,
+                        lifecycle: EntryLifecycle::Chain,
+                        action: EntryAction::Create,
+                    };
+                    block_on(validate_entry(entry, validation_data, &context))
+                }
+                Some(Err(error)) => Err(error),
+                None => Ok(entry.address()),
+            };
+            previous_header = Some(chain_header);
+
+            let stop = fail_fast && result.is_err();
+            if sender.send((index, result)).is_err() || stop {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// Looks up the header preceding the validated entry's header in its author's source chain,
+/// either in the validation package or, since headers are content addressed, in the CAS.
+/// Returns None for the first entry of a chain and if the header is not available.
+pub fn author_previous_header(
+    package: &ValidationPackage,
+    context: &Arc<Context>,
+) -> Option<ChainHeader> {
+    let previous_address = package.chain_header.as_ref()?.link()?;
+    package
+        .source_chain_headers
+        .iter()
+        .flatten()
+        .find(|chain_header| chain_header.address() == previous_address)
+        .cloned()
+        .or_else(|| {
+            let content = context
+                .file_storage
+                .read()
+                .ok()?
+                .fetch(&previous_address)
+                .ok()??;
+            ChainHeader::try_from_content(&content).ok()
+        })
+}
+
+/// Checks that the creation time an app entry asserts through `created_at` is not earlier
+/// than the timestamp of the previous header in the author's chain.
+/// Entries without `created_at` pass, as do entries whose previous header is unknown or
+/// carries no parsable timestamp.
+pub fn validate_created_at(
+    entry: &Entry,
+    top_chain_header: &Option<ChainHeader>,
+) -> Result<(), HolochainError> {
+    let created_at = match entry.created_at() {
+        Some(created_at) => String::from(created_at),
+        None => return Ok(()),
+    };
+    let entry_time = DateTime::parse_from_rfc3339(&created_at).map_err(|_| {
+        HolochainError::ValidationFailed(format!("Invalid created_at timestamp {}", created_at))
+    })?;
+
+    let maybe_head_time = top_chain_header.as_ref().and_then(|chain_header| {
+        DateTime::parse_from_rfc3339(&String::from(chain_header.timestamp().clone())).ok()
+    });
+
+    match maybe_head_time {
+        Some(head_time) if entry_time < head_time => {
+            Err(HolochainError::ValidationFailed(format!(
+                "created_at {} is earlier than the chain head timestamp {}",
+                entry_time.to_rfc3339(),
+                head_time.to_rfc3339(),
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Runs the test vectors shipped with the instance's DNA through the zomes' validation
+/// callbacks and reports the first vector whose outcome differs from the expected one.
+pub fn run_test_vectors(context: &Arc<Context>) -> Result<(), HolochainError> {
+    let dna = context.get_dna().ok_or(HolochainError::DnaMissing)?;
+    dna.run_test_vectors(|entry| {
+        match callback::validate_entry::validate_entry(
+            entry.clone(),
+            ValidationData::default(),
+            context.clone(),
+        ) {
+            Ok(CallbackResult::Pass) => Ok(()),
+            Ok(CallbackResult::Fail(error_string)) => Err(error_string),
+            Ok(_) => Err(format!(
+                "Validation callback not implemented for {:?}",
+                entry.entry_type()
+            )),
+            Err(error) => Err(error.to_string()),
+        }
+    })
+    .map_err(|error: DnaError| HolochainError::Dna(error))
+}
+
+/// ValidationFuture resolves to the validated entry's address, to
+/// Err(HolochainError::ValidationFailed) for permanent failures or to
+/// Err(HolochainError::ValidationPending) for transient ones.
 /// Tracks the state for ValidationResults.
 pub struct ValidationFuture {
     context: Arc<Context>,
@@ -135,8 +503,14 @@ impl Future for ValidationFuture {
         lw.wake();
         if let Some(state) = self.context.state() {
             match state.nucleus().validation_results.get(&self.key) {
-                Some(Ok(())) => Poll::Ready(Ok(self.key.1.clone())),
-                Some(Err(e)) => Poll::Ready(Err(HolochainError::ValidationFailed(e.clone()))),
+                Some(ValidationResult::Pass) => Poll::Ready(Ok(self.key.1.clone())),
+                Some(ValidationResult::Fail {
+                    permanent: true,
+                    reason,
+                }) => Poll::Ready(Err(HolochainError::ValidationFailed(reason.clone()))),
+                Some(ValidationResult::Fail { reason, .. }) => {
+                    Poll::Ready(Err(HolochainError::ValidationPending(reason.clone())))
+                }
                 None => Poll::Pending,
             }
         } else {
@@ -144,3 +518,453 @@ impl Future for ValidationFuture {
         }
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        agent::actions::commit::commit_entry,
+        context::NativeValidator,
+        instance::{tests::test_context, Instance},
+        nucleus::actions::{
+            initialize::initialize_application,
+            tests::{instance_by_name, test_dna},
+        },
+        workflows::author_entry::author_entry,
+    };
+    use holochain_core_types::{
+        chain_header::{test_chain_header, test_sources},
+        dna::{
+            zome::entry_types::{EntryReference, Mutability},
+            Dna, TestVector,
+        },
+        entry::{
+            deletion_entry::DeletionEntry, entry_type::test_app_entry_type, test_entry,
+            test_entry_b,
+        },
+        json::JsonString,
+        time::Iso8601,
+        validation::ValidationPackageDefinition,
+    };
+
+    fn entry_created_at(created_at: &str) -> Entry {
+        Entry::App(
+            test_app_entry_type(),
+            JsonString::from(format!("{{\"created_at\":\"{}\"}}", created_at)),
+        )
+    }
+
+    #[test]
+    fn validate_created_at_in_order() {
+        // test_chain_header() is timestamped 2018-10-11T03:23:38+00:00
+        let top_chain_header = Some(test_chain_header());
+
+        assert!(validate_created_at(
+            &entry_created_at("2018-10-11T03:23:38+00:00"),
+            &top_chain_header
+        )
+        .is_ok());
+        assert!(validate_created_at(
+            &entry_created_at("2018-10-12T00:00:00+00:00"),
+            &top_chain_header
+        )
+        .is_ok());
+        assert!(validate_created_at(&entry_created_at("2018-10-11T03:23:38+00:00"), &None).is_ok());
+    }
+
+    #[test]
+    fn validate_created_at_out_of_order() {
+        let top_chain_header = Some(test_chain_header());
+
+        assert_eq!(
+            Err(HolochainError::ValidationFailed(String::from(
+                "created_at 2018-10-10T00:00:00+00:00 is earlier than the chain head timestamp 2018-10-11T03:23:38+00:00"
+            ))),
+            validate_created_at(&entry_created_at("2018-10-10T00:00:00+00:00"), &top_chain_header),
+        );
+
+        assert!(
+            validate_created_at(&entry_created_at("not a timestamp"), &top_chain_header).is_err()
+        );
+    }
+
+    #[test]
+    fn author_previous_header_from_package() {
+        let context = test_context("bob");
+        let previous_header = test_chain_header();
+        let entry = entry_created_at("2018-10-10T00:00:00+00:00");
+        let entry_header = ChainHeader::new(
+            &entry.entry_type(),
+            &entry.address(),
+            &test_sources(),
+            &Vec::new(),
+            &Some(previous_header.address()),
+            &None,
+            &None,
+            &Iso8601::from("2018-10-12T00:00:00+00:00"),
+        );
+
+        let mut package = ValidationPackage::only_header(entry_header);
+        // neither in the package nor in the CAS
+        assert_eq!(None, author_previous_header(&package, &context));
+
+        package.source_chain_headers = Some(vec![previous_header.clone()]);
+        let found = author_previous_header(&package, &context);
+        assert_eq!(Some(previous_header), found);
+        assert!(validate_created_at(&entry, &found).is_err());
+
+        // the first header of a chain has no predecessor
+        let package = ValidationPackage::only_header(test_chain_header());
+        assert_eq!(None, author_previous_header(&package, &context));
+    }
+
+    fn entry_type_def_requiring(fields: Vec<&str>) -> EntryTypeDef {
+        let mut entry_type_def = EntryTypeDef::new();
+        entry_type_def.required_fields = fields.into_iter().map(String::from).collect();
+        entry_type_def
+    }
+
+    #[test]
+    fn validate_required_fields_missing() {
+        let entry = Entry::App(
+            test_app_entry_type(),
+            JsonString::from("{\"title\":\"foo\"}"),
+        );
+        assert_eq!(
+            Err(HolochainError::ValidationFailed(String::from(
+                "Entry of type testEntryType is missing required field body"
+            ))),
+            validate_required_fields(&entry, &entry_type_def_requiring(vec!["title", "body"])),
+        );
+    }
+
+    #[test]
+    fn validate_required_fields_present() {
+        let entry = Entry::App(
+            test_app_entry_type(),
+            JsonString::from("{\"title\":\"foo\",\"body\":\"bar\"}"),
+        );
+        assert_eq!(
+            Ok(()),
+            validate_required_fields(&entry, &entry_type_def_requiring(vec!["title", "body"])),
+        );
+        assert_eq!(
+            Ok(()),
+            validate_required_fields(&test_entry(), &entry_type_def_requiring(vec![])),
+        );
+    }
+
+    fn entry_type_def_with(mutability: Mutability) -> EntryTypeDef {
+        let mut entry_type_def = EntryTypeDef::new();
+        entry_type_def.mutability = mutability;
+        entry_type_def
+    }
+
+    #[test]
+    fn validate_mutability_test() {
+        let entry = test_entry();
+        let modify = EntryAction::Modify(entry.address());
+        for (mutability, can_update, can_delete) in vec![
+            (Mutability::Full, true, true),
+            (Mutability::AppendOnly, true, false),
+            (Mutability::Immutable, false, false),
+        ] {
+            let entry_type_def = entry_type_def_with(mutability);
+            assert_eq!(
+                Ok(()),
+                validate_mutability(&entry, &entry_type_def, &EntryAction::Create)
+            );
+            assert_eq!(
+                can_update,
+                validate_mutability(&entry, &entry_type_def, &modify).is_ok()
+            );
+            assert_eq!(
+                can_delete,
+                validate_mutability(&entry, &entry_type_def, &EntryAction::Delete).is_ok()
+            );
+        }
+        assert_eq!(
+            Err(HolochainError::ValidationFailed(String::from(
+                "Entry of type testEntryType is Immutable and can not be updated"
+            ))),
+            validate_mutability(&entry, &entry_type_def_with(Mutability::Immutable), &modify),
+        );
+    }
+
+    #[test]
+    fn validate_entry_enforces_mutability() {
+        let mut dna = test_dna();
+        dna.zomes_mut()
+            .get_mut("test_zome")
+            .unwrap()
+            .entry_types
+            .insert(
+                "immutable".into(),
+                entry_type_def_with(Mutability::Immutable),
+            );
+        let (_instance, context) = instance_by_name("joan", dna);
+
+        let entry = Entry::App("immutable".into(), JsonString::from("{}"));
+        block_on(commit_entry(entry.clone(), None, &context)).unwrap();
+
+        let modify = ValidationData {
+            action: EntryAction::Modify(entry.address()),
+            ..Default::default()
+        };
+        assert_eq!(
+            Err(HolochainError::ValidationFailed(String::from(
+                "Entry of type immutable is Immutable and can not be updated"
+            ))),
+            block_on(validate_entry(entry.clone(), modify, &context)),
+        );
+
+        let delete = ValidationData {
+            action: EntryAction::Delete,
+            ..Default::default()
+        };
+        assert_eq!(
+            Err(HolochainError::ValidationFailed(String::from(
+                "Entry of type immutable is Immutable and can not be deleted"
+            ))),
+            block_on(validate_entry(
+                Entry::Deletion(DeletionEntry::new(entry.address())),
+                delete,
+                &context,
+            )),
+        );
+    }
+
+    #[test]
+    fn validate_invalid_app_entry_type_name() {
+        let (_instance, context) = instance_by_name("jade", test_dna());
+        let entry = Entry::App("%bogus".into(), JsonString::from("{}"));
+        let invalid_name = || {
+            HolochainError::Dna(DnaError::InvalidAppEntryTypeName(String::from(
+                "'%bogus' is not a valid app entry type name",
+            )))
+        };
+
+        assert_eq!(
+            Err(invalid_name()),
+            block_on(validate_entry(
+                entry.clone(),
+                ValidationData::default(),
+                &context
+            )),
+        );
+        assert_eq!(
+            Err(invalid_name()),
+            validate_entry_mutability(&entry, &EntryAction::Delete, &context),
+        );
+    }
+
+    #[test]
+    fn validate_chain_progress_in_order() {
+        let (_instance, context) = instance_by_name("jill", test_dna());
+        let entry_address = block_on(author_entry(&test_entry(), None, &context)).unwrap();
+
+        let events: Vec<ValidationProgress> = validate_chain_with_progress(&context, false)
+            .iter()
+            .collect();
+
+        // genesis commits the DNA and the agent ID before the test entry
+        assert_eq!(3, events.len());
+        for (expected_index, (index, result)) in events.iter().enumerate() {
+            assert_eq!(expected_index, *index);
+            assert!(result.is_ok());
+        }
+        assert_eq!(Ok(entry_address), events[2].1);
+    }
+
+    #[test]
+    fn validate_chain_progress_multi_entry() {
+        let (_instance, context) = instance_by_name("jules", test_dna());
+        context.cache_validation_package_definition(
+            &test_app_entry_type(),
+            ValidationPackageDefinition::ChainFull,
+        );
+        for value in vec!["first", "second", "third"] {
+            let entry = Entry::App(test_app_entry_type(), JsonString::from(value));
+            block_on(author_entry(&entry, None, &context)).unwrap();
+        }
+        // Passes only if the package holds the chain as it was when the entry got committed
+        context.register_native_validator(
+            EntryType::App(test_app_entry_type()),
+            Box::new(|_: &Entry, validation_data: &ValidationData| {
+                let package = &validation_data.package;
+                let previous_address = package
+                    .source_chain_headers
+                    .as_ref()
+                    .and_then(|chain_headers| chain_headers.first())
+                    .map(|chain_header| chain_header.address());
+                let entry_header = package.chain_header.as_ref().unwrap();
+                if previous_address == entry_header.link() {
+                    ValidationResult::Pass
+                } else {
+                    ValidationResult::permanent_failure("package does not end before the entry")
+                }
+            }),
+        );
+
+        let events: Vec<ValidationProgress> = validate_chain_with_progress(&context, false)
+            .iter()
+            .collect();
+
+        // genesis commits the DNA and the agent ID before the three entries
+        assert_eq!(5, events.len());
+        for (expected_index, (index, result)) in events.iter().enumerate() {
+            assert_eq!(expected_index, *index);
+            assert!(result.is_ok(), "{:?}", result);
+        }
+    }
+
+    #[test]
+    fn validate_chain_progress_fail_fast() {
+        let (_instance, context) = instance_by_name("jack", test_dna());
+        for entry in vec![
+            test_entry(),
+            Entry::App(test_app_entry_type(), JsonString::from("other value")),
+        ] {
+            block_on(commit_entry(entry, None, &context)).unwrap();
+        }
+        context.register_native_validator(
+            EntryType::App(test_app_entry_type()),
+            Box::new(|_: &Entry, _: &ValidationData| {
+                ValidationResult::permanent_failure("always invalid")
+            }),
+        );
+
+        let failures = |events: &Vec<ValidationProgress>| -> Vec<usize> {
+            events
+                .iter()
+                .filter(|(_, result)| result.is_err())
+                .map(|(index, _)| *index)
+                .collect()
+        };
+
+        // genesis commits the DNA and the agent ID before the two failing entries
+        let events: Vec<ValidationProgress> = validate_chain_with_progress(&context, false)
+            .iter()
+            .collect();
+        assert_eq!(4, events.len());
+        assert_eq!(vec![2, 3], failures(&events));
+
+        let events: Vec<ValidationProgress> = validate_chain_with_progress(&context, true)
+            .iter()
+            .collect();
+        assert_eq!(3, events.len());
+        assert_eq!(vec![2], failures(&events));
+    }
+
+    fn dna_with_reference_holder() -> Dna {
+        let mut dna = test_dna();
+        let mut entry_type_def = EntryTypeDef::new();
+        entry_type_def.references = vec![EntryReference::new("target", "testEntryType")];
+        dna.zomes_mut()
+            .get_mut("test_zome")
+            .unwrap()
+            .entry_types
+            .insert("reference_holder".into(), entry_type_def);
+        dna
+    }
+
+    fn reference_holder(target: &Entry) -> Entry {
+        Entry::App(
+            "reference_holder".into(),
+            JsonString::from(format!("{{\"target\":\"{}\"}}", target.address())),
+        )
+    }
+
+    #[test]
+    fn validate_references_test() {
+        let (_instance, context) = instance_by_name("jane", dna_with_reference_holder());
+        context.register_native_validator(
+            EntryType::from("reference_holder"),
+            Box::new(|_: &Entry, _: &ValidationData| ValidationResult::Pass),
+        );
+        // the test zome's WASM doesn't know this entry type
+        context.cache_validation_package_definition(
+            &"reference_holder".into(),
+            ValidationPackageDefinition::Entry,
+        );
+
+        let right_target = test_entry();
+        let wrong_target = Entry::App("package_entry".into(), JsonString::from("{}"));
+        for entry in vec![right_target.clone(), wrong_target.clone()] {
+            block_on(commit_entry(entry, None, &context)).unwrap();
+        }
+
+        let result = block_on(author_entry(
+            &reference_holder(&right_target),
+            None,
+            &context,
+        ));
+        assert!(result.is_ok());
+        assert_eq!(
+            Err(HolochainError::ValidationFailed(String::from(
+                "Field target of entry of type reference_holder must reference an entry of type testEntryType, found package_entry"
+            ))),
+            block_on(author_entry(&reference_holder(&wrong_target), None, &context)),
+        );
+    }
+
+    #[test]
+    fn validate_references_pending_on_lookup_error() {
+        // without an initialized network, looking up entries that are not held locally fails
+        let context = test_context("jake");
+        let mut instance = Instance::new(context.clone());
+        instance.start_action_loop(context.clone());
+        let context = instance.initialize_context(context);
+        block_on(initialize_application(
+            dna_with_reference_holder(),
+            &context,
+        ))
+        .unwrap();
+
+        match validate_references(&reference_holder(&test_entry_b()), &context) {
+            Err(HolochainError::ValidationPending(_)) => (),
+            other => panic!(
+                "lookup errors should leave validation pending, got {:?}",
+                other
+            ),
+        }
+    }
+
+    #[test]
+    fn run_test_vectors_through_validation() {
+        let entry_type = EntryType::App(test_app_entry_type());
+        let forbids_secrets = || -> Box<NativeValidator> {
+            Box::new(|entry: &Entry, _: &ValidationData| match entry {
+                Entry::App(_, value) if value.to_string().contains("secret") => {
+                    ValidationResult::permanent_failure("secrets are not allowed")
+                }
+                _ => ValidationResult::Pass,
+            })
+        };
+
+        let mut dna = test_dna();
+        dna.set_test_vectors(vec![
+            TestVector::new(entry_type.clone(), json!({"stuff": "public"}), true),
+            TestVector::new(entry_type.clone(), json!({"stuff": "secret"}), false),
+        ]);
+        let (_instance, context) = instance_by_name("jill", dna.clone());
+        context.register_native_validator(entry_type.clone(), forbids_secrets());
+        assert_eq!(Ok(()), run_test_vectors(&context));
+
+        dna.set_test_vectors(vec![TestVector::new(
+            entry_type.clone(),
+            json!({"stuff": "secret"}),
+            true,
+        )]);
+        let (_instance, context) = instance_by_name("jim", dna);
+        context.register_native_validator(entry_type, forbids_secrets());
+        assert_eq!(
+            Err(HolochainError::Dna(DnaError::TestVectorFailed(
+                String::from(
+                    "Test vector 0 was expected to pass but failed: secrets are not allowed"
+                )
+            ))),
+            run_test_vectors(&context),
+        );
+    }
+}
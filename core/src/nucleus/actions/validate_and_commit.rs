@@ -0,0 +1,106 @@
+use crate::{
+    agent::actions::commit::commit_entry,
+    context::Context,
+    nucleus::actions::{
+        build_validation_package::build_validation_package, validate::validate_entry,
+    },
+    tracer::{traced, Span},
+};
+use holochain_core_types::{
+    cas::content::Address,
+    entry::Entry,
+    error::HolochainError,
+    validation::{EntryAction, EntryLifecycle, ValidationData},
+};
+use std::sync::Arc;
+
+/// Builds the validation package of the entry, validates it with the agent as source
+/// and commits it to the source chain.
+/// Shared by everything that authors entries (commits, updates and removals),
+/// so they all validate the same way.
+/// Entries of encrypted types are validated as plaintext and get encrypted by
+/// commit_entry (see agent::entry_crypto).
+///
+/// Returns the address the entry got committed under.
+pub async fn validate_and_commit<'a>(
+    entry: &'a Entry,
+    maybe_crud_link: Option<Address>,
+    action: EntryAction,
+    lifecycle: EntryLifecycle,
+    context: &'a Arc<Context>,
+) -> Result<Address, HolochainError> {
+    // 1. Build the context needed for validation of the entry
+    let validation_package = await!(traced(
+        Span::new("build_validation_package", entry),
+        build_validation_package(&entry, &context),
+        context
+    ))?;
+    let validation_data = ValidationData {
+        package: validation_package,
+        sources: vec![context.agent_id.address()],
+        lifecycle,
+        action,
+    };
+    // 2. Validate the entry
+    await!(traced(
+        Span::new("validate", entry),
+        validate_entry(entry.clone(), validation_data, &context),
+        context
+    ))?;
+    // 3. Commit the entry
+    await!(traced(
+        Span::new("commit", entry),
+        commit_entry(entry.clone(), maybe_crud_link, &context),
+        context
+    ))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::nucleus::{
+        actions::tests::{instance_by_name, test_dna},
+        state::ValidationResult,
+    };
+    use futures::executor::block_on;
+    use holochain_core_types::{cas::content::AddressableContent, entry::test_entry};
+    use std::sync::Mutex;
+
+    #[test]
+    fn validates_with_agent_as_source_and_commits() {
+        let (_instance, context) = instance_by_name("jill", test_dna());
+        let seen_sources = Arc::new(Mutex::new(Vec::new()));
+        {
+            let seen_sources = seen_sources.clone();
+            context.register_native_validator(
+                test_entry().entry_type(),
+                Box::new(move |_: &Entry, validation_data: &ValidationData| {
+                    seen_sources
+                        .lock()
+                        .unwrap()
+                        .push(validation_data.sources.clone());
+                    ValidationResult::Pass
+                }),
+            );
+        }
+
+        let address = block_on(validate_and_commit(
+            &test_entry(),
+            None,
+            EntryAction::Create,
+            EntryLifecycle::Chain,
+            &context,
+        ))
+        .unwrap();
+
+        assert_eq!(test_entry().address(), address);
+        assert_eq!(
+            vec![vec![context.agent_id.address()]],
+            *seen_sources.lock().unwrap()
+        );
+        let agent_state = context.state().unwrap().agent();
+        let top_header = agent_state.top_chain_header().unwrap();
+        assert_eq!(&address, top_header.entry_address());
+        assert_eq!(&test_entry().entry_type(), top_header.entry_type());
+    }
+}
@@ -7,7 +7,7 @@ pub mod state;
 use crate::{
     action::{Action, ActionWrapper, NucleusReduceFn},
     context::Context,
-    instance::{dispatch_action_with_observer, Observer},
+    instance::{dispatch_action_with_observer, ActionSender, Observer},
     nucleus::{
         ribosome::api::call::reduce_call,
         state::{NucleusState, NucleusStatus},
@@ -83,7 +83,7 @@ impl EntrySubmission {
 /// Dispatch ExecuteZoneFunction to and block until call has finished.
 pub fn call_zome_and_wait_for_result(
     call: ZomeFnCall,
-    action_channel: &SyncSender<ActionWrapper>,
+    action_channel: &ActionSender,
     observer_channel: &SyncSender<Observer>,
 ) -> Result<JsonString, HolochainError> {
     let call_action_wrapper = ActionWrapper::new(Action::ExecuteZomeFunction(call.clone()));
@@ -212,6 +212,20 @@ fn reduce_init_application(
     }
 }
 
+/// Reduce ReloadDna Action
+/// Swaps the DNA without touching the initialization status.
+#[allow(unknown_lints)]
+#[allow(needless_pass_by_value)]
+fn reduce_reload_dna(
+    _context: Arc<Context>,
+    state: &mut NucleusState,
+    action_wrapper: &ActionWrapper,
+) {
+    let action = action_wrapper.action();
+    let dna = unwrap_to!(action => Action::ReloadDna);
+    state.dna = Some(dna.clone());
+}
+
 pub(crate) fn launch_zome_fn_call(
     context: Arc<Context>,
     zome_call: ZomeFnCall,
@@ -255,7 +269,7 @@ fn reduce_execute_zome_function(
     };
 
     fn dispatch_error_result(
-        action_channel: &SyncSender<ActionWrapper>,
+        action_channel: &ActionSender,
         fn_call: &ZomeFnCall,
         error: HolochainError,
     ) {
@@ -283,7 +297,7 @@ fn reduce_execute_zome_function(
     };
 
     // Get zome
-    let zome = match dna.zomes.get(&fn_call.zome_name) {
+    let zome = match dna.zomes().get(&fn_call.zome_name) {
         None => {
             dispatch_error_result(
                 &context.action_channel,
@@ -338,7 +352,7 @@ fn reduce_execute_zome_function(
         context,
         fn_call,
         &zome.code,
-        state.dna.clone().unwrap().name,
+        state.dna.clone().unwrap().name().to_string(),
     );
 }
 
@@ -387,6 +401,7 @@ fn resolve_reducer(action_wrapper: &ActionWrapper) -> Option<NucleusReduceFn> {
     match action_wrapper.action() {
         Action::ReturnInitializationResult(_) => Some(reduce_return_initialization_result),
         Action::InitApplication(_) => Some(reduce_init_application),
+        Action::ReloadDna(_) => Some(reduce_reload_dna),
         Action::ExecuteZomeFunction(_) => Some(reduce_execute_zome_function),
         Action::ReturnZomeFunctionResult(_) => Some(reduce_return_zome_function_result),
         Action::Call(_) => Some(reduce_call),
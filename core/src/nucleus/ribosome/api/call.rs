@@ -153,7 +153,12 @@ pub(crate) fn reduce_call(
     let code =
         maybe_code.expect("zome not found, Should have failed before when getting capability.");
     state.zome_calls.insert(fn_call.clone(), None);
-    launch_zome_fn_call(context, fn_call, &code, state.dna.clone().unwrap().name);
+    launch_zome_fn_call(
+        context,
+        fn_call,
+        &code,
+        state.dna.clone().unwrap().name().to_string(),
+    );
 }
 
 #[cfg(test)]
@@ -25,9 +25,21 @@ pub fn invoke_commit_app_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> Zom
             return ribosome_error_code!(ArgumentDeserializationFailed);
         }
     };
+    let reservation = match runtime
+        .check_writable()
+        .and_then(|_| runtime.check_commit_entry_type(&entry.entry_type()))
+        .and_then(|_| runtime.reserve_commit())
+    {
+        Ok(reservation) => reservation,
+        Err(error) => return runtime.store_result(Err::<Address, HolochainError>(error)),
+    };
+
     // Wait for future to be resolved
     let task_result: Result<Address, HolochainError> =
         block_on(author_entry(&entry, None, &runtime.context));
+    if task_result.is_err() {
+        runtime.release_commit(reservation);
+    }
 
     runtime.store_result(task_result)
 }
@@ -37,12 +49,22 @@ pub mod tests {
     extern crate test_utils;
     extern crate wabt;
 
-    use crate::nucleus::ribosome::{
-        api::{tests::test_zome_api_function, ZomeApiFunction},
-        Defn,
+    use crate::{
+        instance::tests::test_instance_and_context,
+        nucleus::ribosome::{
+            api::{
+                tests::{
+                    test_capability, test_function_name, test_zome_api_function,
+                    test_zome_api_function_call, test_zome_api_function_wasm, test_zome_name,
+                },
+                ZomeApiFunction,
+            },
+            Defn,
+        },
     };
     use holochain_core_types::{
         cas::content::Address,
+        dna::zome::capabilities::FnDeclaration,
         entry::{test_entry, Entry},
         error::ZomeApiInternalResult,
         json::JsonString,
@@ -73,4 +95,95 @@ pub mod tests {
             ),
         );
     }
+
+    #[test]
+    /// test that a zome function declared read-only can not commit
+    fn test_commit_from_read_only_function() {
+        let wasm = test_zome_api_function_wasm(ZomeApiFunction::CommitAppEntry.as_str());
+        let mut dna = test_utils::create_test_dna_with_wasm(
+            &test_zome_name(),
+            &test_capability(),
+            wasm.clone(),
+        );
+        let mut fn_declaration = FnDeclaration::new();
+        fn_declaration.name = test_function_name();
+        fn_declaration.read_only = true;
+        dna.zomes_mut()
+            .get_mut(&test_zome_name())
+            .unwrap()
+            .capabilities
+            .get_mut(&test_capability())
+            .unwrap()
+            .functions
+            .push(fn_declaration);
+
+        let dna_name = dna.name().to_string();
+        let (instance, context) =
+            test_instance_and_context(dna).expect("Could not create test instance");
+        let call_result = test_zome_api_function_call(
+            &dna_name,
+            context.clone(),
+            &instance,
+            &wasm,
+            test_commit_args_bytes(),
+        );
+
+        let call_result = String::from(call_result);
+        assert!(call_result.contains("ReadOnlyViolation"));
+        assert!(call_result.contains("read-only"));
+        let agent = context.state().unwrap().agent();
+        assert_eq!(
+            None,
+            agent
+                .chain()
+                .iter_type(&agent.top_chain_header(), &test_entry().entry_type())
+                .next()
+        );
+    }
+
+    /// calls the commit test function with test_entry() in a zome with strict commit types
+    /// whose function declares the given entry types
+    fn commit_with_declared_entry_types(entry_types: Vec<String>) -> String {
+        let wasm = test_zome_api_function_wasm(ZomeApiFunction::CommitAppEntry.as_str());
+        let mut dna = test_utils::create_test_dna_with_wasm(
+            &test_zome_name(),
+            &test_capability(),
+            wasm.clone(),
+        );
+        let mut fn_declaration = FnDeclaration::new();
+        fn_declaration.name = test_function_name();
+        fn_declaration.commits_entry_types = entry_types;
+        {
+            let zome = dna.zomes_mut().get_mut(&test_zome_name()).unwrap();
+            zome.config.strict_commit_types = true;
+            zome.capabilities
+                .get_mut(&test_capability())
+                .unwrap()
+                .functions
+                .push(fn_declaration);
+        }
+
+        let dna_name = dna.name().to_string();
+        let (instance, context) =
+            test_instance_and_context(dna).expect("Could not create test instance");
+        String::from(test_zome_api_function_call(
+            &dna_name,
+            context,
+            &instance,
+            &wasm,
+            test_commit_args_bytes(),
+        ))
+    }
+
+    #[test]
+    /// test that under strict commit types only declared entry types can be committed
+    fn test_commit_undeclared_entry_type() {
+        let call_result = commit_with_declared_entry_types(vec![String::from("other")]);
+        assert!(call_result.contains("ValidationFailed"));
+        assert!(call_result.contains("does not declare committing entries of type"));
+
+        let call_result =
+            commit_with_declared_entry_types(vec![String::from(test_entry().entry_type())]);
+        assert!(call_result.contains("Qma6RfzvZRL127UCEVEktPhQ7YSS1inxEFw7SjEsfMJcrq"));
+    }
 }
@@ -17,8 +17,12 @@ pub fn get_entry_type(dna: &Dna, entry_type_name: &str) -> Result<EntryType, Opt
 
     // Check if AppEntry is a valid AppEntryType
     if entry_type.is_app() {
-        let result = dna.get_entry_type_def(entry_type_name);
-        if result.is_none() {
+        let maybe_entry_type_def = dna.get_entry_type_def(entry_type_name).map_err(|_| {
+            Some(RuntimeValue::I32(
+                holochain_core_types::error::RibosomeErrorCode::UnknownEntryType as i32,
+            ))
+        })?;
+        if maybe_entry_type_def.is_none() {
             return Err(Some(RuntimeValue::I32(
                 holochain_core_types::error::RibosomeErrorCode::UnknownEntryType as i32,
             )));
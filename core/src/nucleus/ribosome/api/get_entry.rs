@@ -193,7 +193,7 @@ mod tests {
             test_parameters(),
         );
         let call_result = ribosome::run_dna(
-            &dna.name.to_string(),
+            &dna.name().to_string(),
             Arc::clone(&context),
             wasm.clone(),
             &commit_call,
@@ -217,7 +217,7 @@ mod tests {
             test_parameters(),
         );
         let call_result = ribosome::run_dna(
-            &dna.name.to_string(),
+            &dna.name().to_string(),
             Arc::clone(&context),
             wasm.clone(),
             &get_call,
@@ -269,7 +269,7 @@ mod tests {
             test_parameters(),
         );
         let call_result = ribosome::run_dna(
-            &dna.name.to_string(),
+            &dna.name().to_string(),
             Arc::clone(&context),
             wasm.clone(),
             &get_call,
@@ -84,7 +84,7 @@ pub mod tests {
             wasm.clone(),
         );
 
-        let dna_name = &dna.name.to_string().clone();
+        let dna_name = &dna.name().to_string().clone();
         let instance = test_instance(dna).expect("Could not create test instance");
 
         let (context, _) = test_context_and_logger("joan");
@@ -25,9 +25,8 @@ pub fn invoke_init_globals(runtime: &mut Runtime, _args: &RuntimeArgs) -> ZomeAp
     // Update fields
     if let Some(state) = runtime.context.state() {
         // Update dna_hash
-        if let Some(dna) = state.nucleus().dna() {
-            globals.dna_hash =
-                HashString::encode_from_json_string(JsonString::from(dna), Multihash::SHA2256);
+        if let Some(dna_hash) = state.agent().dna_hash() {
+            globals.dna_hash = dna_hash;
         }
         // Update agent hashes
         let maybe_top = state.agent().top_chain_header();
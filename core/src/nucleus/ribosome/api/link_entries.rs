@@ -25,6 +25,13 @@ pub fn invoke_link_entries(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApi
             return ribosome_error_code!(ArgumentDeserializationFailed);
         }
     };
+    let reservation = match runtime
+        .check_writable()
+        .and_then(|_| runtime.reserve_commit())
+    {
+        Ok(reservation) => reservation,
+        Err(error) => return runtime.store_result(Err::<(), HolochainError>(error)),
+    };
 
     let link = input.to_link();
     let link_add = LinkAdd::from_link(&link);
@@ -33,6 +40,9 @@ pub fn invoke_link_entries(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApi
     // Wait for future to be resolved
     let result: Result<(), HolochainError> =
         block_on(author_entry(&entry, None, &runtime.context)).map(|_| ());
+    if result.is_err() {
+        runtime.release_commit(reservation);
+    }
 
     runtime.store_result(result)
 }
@@ -143,7 +153,7 @@ pub mod tests {
             .expect("Could not commit entry for testing");
 
         let call_result = test_zome_api_function_call(
-            &context.get_dna().unwrap().name.to_string(),
+            &context.get_dna().unwrap().name().to_string(),
             context.clone(),
             &instance,
             &context.get_wasm(&test_zome_name()).unwrap().code,
@@ -166,7 +176,7 @@ pub mod tests {
             .expect("Could not commit entry for testing");
 
         let call_result = test_zome_api_function_call(
-            &context.get_dna().unwrap().name.to_string(),
+            &context.get_dna().unwrap().name().to_string(),
             context.clone(),
             &instance,
             &context.get_wasm(&test_zome_name()).unwrap().code,
@@ -191,7 +201,7 @@ pub mod tests {
             .expect("Could not commit entry for testing");
 
         let call_result = test_zome_api_function_call(
-            &context.get_dna().unwrap().name.to_string(),
+            &context.get_dna().unwrap().name().to_string(),
             context.clone(),
             &instance,
             &context.get_wasm(&test_zome_name()).unwrap().code,
@@ -398,7 +398,7 @@ pub mod tests {
             wasm.clone(),
         );
 
-        let dna_name = &dna.name.to_string().clone();
+        let dna_name = &dna.name().to_string().clone();
         let (instance, context) =
             test_instance_and_context(dna).expect("Could not create test instance");
 
@@ -1,22 +1,17 @@
 use crate::{
-    agent::actions::commit::commit_entry,
     dht::actions::remove_entry::remove_entry,
     nucleus::{
-        actions::{build_validation_package::*, validate::*},
+        actions::{validate::validate_entry_mutability, validate_and_commit::validate_and_commit},
         ribosome::{api::ZomeApiResult, Runtime},
     },
     workflows::get_entry_history::get_entry_history_workflow,
 };
-use futures::{
-    executor::block_on,
-    future::{self, TryFutureExt},
-};
+use futures::{executor::block_on, future::TryFutureExt};
 use holochain_core_types::{
     cas::content::{Address, AddressableContent},
     entry::{deletion_entry::DeletionEntry, Entry},
     error::HolochainError,
-    hash::HashString,
-    validation::{EntryAction, EntryLifecycle, ValidationData},
+    validation::{EntryAction, EntryLifecycle},
 };
 use holochain_wasm_utils::api_serialization::get_entry::*;
 use std::convert::TryFrom;
@@ -25,7 +20,7 @@ use wasmi::{RuntimeArgs, RuntimeValue};
 /// ZomeApiFunction::RemoveEntry function code
 /// args: [0] encoded MemoryAllocation as u32
 /// Expected Address argument
-/// Returns only a RibosomeReturnCode as I32
+/// Returns an HcApiReturnCode as I32
 pub fn invoke_remove_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
     // deserialize args
     let args_str = runtime.load_json_string_from_args(&args);
@@ -39,62 +34,172 @@ pub fn invoke_remove_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApi
         return ribosome_error_code!(ArgumentDeserializationFailed);
     }
     let deleted_entry_address = try_address.unwrap();
+    if let Err(error) = runtime.check_writable() {
+        return runtime.store_result(Err::<(), HolochainError>(error));
+    }
 
     // Get Current entry's latest version
     let get_args = GetEntryArgs {
         address: deleted_entry_address,
         options: GetEntryOptions::default(),
     };
-    let get_entry_history_result =
-        block_on(get_entry_history_workflow(&runtime.context, &get_args));
-    if let Err(_err) = get_entry_history_result {
-        return ribosome_error_code!(Unspecified);
-    }
-    let entry_history = get_entry_history_result.unwrap();
-    if entry_history.entries.is_empty() {
-        return ribosome_error_code!(Unspecified);
+    let entry_history = match block_on(get_entry_history_workflow(&runtime.context, &get_args)) {
+        Ok(entry_history) => entry_history,
+        Err(error) => return runtime.store_result(Err::<(), HolochainError>(error)),
+    };
+    let deleted_entry = match entry_history.entries.iter().next() {
+        Some(deleted_entry) => deleted_entry,
+        None => {
+            return runtime.store_result(Err::<(), HolochainError>(HolochainError::ErrorGeneric(
+                format!("Entry {} to remove not found", get_args.address),
+            )));
+        }
+    };
+    if let Err(error) =
+        validate_entry_mutability(deleted_entry, &EntryAction::Delete, &runtime.context)
+    {
+        return runtime.store_result(Err::<(), HolochainError>(error));
     }
-    let deleted_entry_address = entry_history.entries.iter().next().unwrap().address();
+    let deleted_entry_address = deleted_entry.address();
 
     // Create deletion entry
     let deletion_entry = Entry::Deletion(DeletionEntry::new(deleted_entry_address.clone()));
+    let reservation = match runtime.reserve_commit() {
+        Ok(reservation) => reservation,
+        Err(error) => return runtime.store_result(Err::<(), HolochainError>(error)),
+    };
     // Resolve future
     let result: Result<(), HolochainError> = block_on(
-        // 1. Build the context needed for validation of the entry
-        build_validation_package(&deletion_entry, &runtime.context)
-            .and_then(|validation_package| {
-                future::ready(Ok(ValidationData {
-                    package: validation_package,
-                    sources: vec![HashString::from("<insert your agent key here>")],
-                    lifecycle: EntryLifecycle::Chain,
-                    action: EntryAction::Delete,
-                }))
-            })
-            // 2. Validate the entry
-            .and_then(|validation_data| {
-                validate_entry(deletion_entry.clone(), validation_data, &runtime.context)
-            })
-            // 3. Commit the valid entry to chain and DHT
-            .and_then(|_| {
-                commit_entry(
-                    deletion_entry.clone(),
-                    Some(deleted_entry_address.clone()),
-                    &runtime.context,
-                )
-            })
-            // 4. Remove the entry in DHT metadata
-            .and_then(|_| {
-                remove_entry(
-                    &runtime.context,
-                    &runtime.context.action_channel,
-                    deleted_entry_address.clone(),
-                    deletion_entry.address().clone(),
-                )
-            }),
+        // 1. Validate and commit the deletion entry
+        validate_and_commit(
+            &deletion_entry,
+            Some(deleted_entry_address.clone()),
+            EntryAction::Delete,
+            EntryLifecycle::Chain,
+            &runtime.context,
+        )
+        .map_err(|error| {
+            runtime.release_commit(reservation);
+            error
+        })
+        // 2. Remove the entry in DHT metadata
+        .and_then(|_| {
+            remove_entry(
+                &runtime.context,
+                &runtime.context.action_channel,
+                deleted_entry_address.clone(),
+                deletion_entry.address().clone(),
+            )
+        }),
     );
-    // Done
-    match result {
-        Err(_) => ribosome_error_code!(Unspecified),
-        Ok(_) => ribosome_success!(),
+    runtime.store_result(result)
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+    use crate::{
+        context::Context,
+        instance::tests::test_instance_and_context,
+        nucleus::{
+            ribosome::api::{
+                tests::{
+                    test_capability, test_function_name, test_zome_api_function_call,
+                    test_zome_api_function_wasm, test_zome_name,
+                },
+                ZomeApiFunction,
+            },
+            state::ValidationResult,
+        },
+        workflows::author_entry::author_entry,
+    };
+    use futures::executor::block_on;
+    use holochain_core_types::{
+        cas::content::AddressableContent,
+        dna::zome::capabilities::FnDeclaration,
+        entry::{
+            deletion_entry::DeletionEntry,
+            entry_type::{test_app_entry_type, EntryType},
+            test_entry, Entry,
+        },
+        json::JsonString,
+        validation::{ValidationData, ValidationPackageDefinition},
+    };
+    use std::{sync::Arc, thread, time};
+
+    /// Commits test_entry() and removes it through the zome API, from a function declared
+    /// read-only or not.
+    /// Returns the result of the remove call.
+    fn remove_test_entry(read_only: bool) -> (JsonString, Arc<Context>) {
+        let wasm = test_zome_api_function_wasm(ZomeApiFunction::RemoveEntry.as_str());
+        let mut dna = test_utils::create_test_dna_with_wasm(
+            &test_zome_name(),
+            &test_capability(),
+            wasm.clone(),
+        );
+        let mut fn_declaration = FnDeclaration::new();
+        fn_declaration.name = test_function_name();
+        fn_declaration.read_only = read_only;
+        dna.zomes_mut()
+            .get_mut(&test_zome_name())
+            .unwrap()
+            .capabilities
+            .get_mut(&test_capability())
+            .unwrap()
+            .functions
+            .push(fn_declaration);
+        let dna_name = dna.name().to_string();
+        let (instance, context) =
+            test_instance_and_context(dna).expect("Could not create test instance");
+        context.cache_validation_package_definition(
+            &test_app_entry_type(),
+            ValidationPackageDefinition::Entry,
+        );
+        context.register_native_validator(
+            EntryType::App(test_app_entry_type()),
+            Box::new(|_: &Entry, _: &ValidationData| ValidationResult::Pass),
+        );
+
+        block_on(author_entry(&test_entry(), None, &context)).unwrap();
+        // wait for the entry to be held in our own DHT shard
+        thread::sleep(time::Duration::from_millis(500));
+
+        let call_result = test_zome_api_function_call(
+            &dna_name,
+            context.clone(),
+            &instance,
+            &wasm,
+            JsonString::from(test_entry().address()).into_bytes(),
+        );
+        (call_result, context)
+    }
+
+    #[test]
+    /// test that removing an entry commits a deletion entry for it
+    fn test_remove_entry() {
+        let (call_result, context) = remove_test_entry(false);
+
+        assert!(String::from(call_result).contains("\"ok\":true"));
+        let deletion_entry = Entry::Deletion(DeletionEntry::new(test_entry().address()));
+        let agent_state = context.state().unwrap().agent();
+        assert_eq!(
+            &deletion_entry.address(),
+            agent_state.top_chain_header().unwrap().entry_address()
+        );
+    }
+
+    #[test]
+    /// test that failures are reported as errors instead of an unspecified error code
+    fn test_remove_entry_from_read_only_function() {
+        let (call_result, context) = remove_test_entry(true);
+
+        let call_result = String::from(call_result);
+        assert!(call_result.contains("\"ok\":false"));
+        assert!(call_result.contains("ReadOnlyViolation"));
+        let agent_state = context.state().unwrap().agent();
+        assert_eq!(
+            &test_entry().address(),
+            agent_state.top_chain_header().unwrap().entry_address()
+        );
     }
 }
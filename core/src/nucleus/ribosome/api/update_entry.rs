@@ -1,21 +1,18 @@
 use crate::{
-    agent::actions::{commit::commit_entry, update_entry::update_entry},
+    agent::actions::update_entry::update_entry,
     nucleus::{
-        actions::{build_validation_package::*, validate::*},
+        actions::{validate::validate_entry_mutability, validate_and_commit::validate_and_commit},
         ribosome::{api::ZomeApiResult, Runtime},
     },
+    tracer::{traced, Span},
     workflows::get_entry_history::get_entry_history_workflow,
 };
-use futures::{
-    executor::block_on,
-    future::{self, TryFutureExt},
-};
+use futures::{executor::block_on, future::TryFutureExt};
 use holochain_core_types::{
     cas::content::{Address, AddressableContent},
     entry::Entry,
     error::HolochainError,
-    hash::HashString,
-    validation::{EntryAction, EntryLifecycle, ValidationData},
+    validation::{EntryAction, EntryLifecycle},
 };
 use holochain_wasm_utils::api_serialization::{get_entry::*, UpdateEntryArgs};
 use std::convert::TryFrom;
@@ -39,6 +36,9 @@ pub fn invoke_update_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApi
             return ribosome_error_code!(ArgumentDeserializationFailed);
         }
     };
+    if let Err(error) = runtime.check_writable() {
+        return runtime.store_result(Err::<Address, HolochainError>(error));
+    }
 
     // Get Current entry's latest version
     let get_args = GetEntryArgs {
@@ -55,6 +55,10 @@ pub fn invoke_update_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApi
         return ribosome_error_code!(Unspecified);
     }
     let latest_entry = entry_history.entries.iter().next().unwrap().clone();
+    let modify = EntryAction::Modify(latest_entry.address());
+    if let Err(error) = validate_entry_mutability(&latest_entry, &modify, &runtime.context) {
+        return runtime.store_result(Err::<Address, HolochainError>(error));
+    }
 
     // Get latest entry's ChainHeader
     let agent_state = &runtime.context.state().unwrap().agent();
@@ -68,34 +72,156 @@ pub fn invoke_update_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApi
     // Create Chain Entry
     let entry = Entry::from(entry_args.new_entry.clone());
 
+    let reservation = match runtime.reserve_commit() {
+        Ok(reservation) => reservation,
+        Err(error) => return runtime.store_result(Err::<Address, HolochainError>(error)),
+    };
+
     // Wait for future to be resolved
     let task_result: Result<Address, HolochainError> = block_on(
-        // 1. Build the context needed for validation of the entry
-        build_validation_package(&entry, &runtime.context)
-            .and_then(|validation_package| {
-                future::ready(Ok(ValidationData {
-                    package: validation_package,
-                    sources: vec![HashString::from("<insert your agent key here>")],
-                    lifecycle: EntryLifecycle::Chain,
-                    action: EntryAction::Modify,
-                }))
-            })
-            // 2. Validate the entry
-            .and_then(|validation_data| {
-                validate_entry(entry.clone(), validation_data, &runtime.context)
-            })
-            // 3. Commit the valid entry to chain and DHT
-            .and_then(|_| commit_entry(entry.clone(), Some(chain_header_address), &runtime.context))
-            // 4. Update the entry in DHT metadata
-            .and_then(|new_address| {
+        // 1. Validate and commit the entry
+        validate_and_commit(
+            &entry,
+            Some(chain_header_address),
+            modify,
+            EntryLifecycle::Chain,
+            &runtime.context,
+        )
+        .map_err(|error| {
+            runtime.release_commit(reservation);
+            error
+        })
+        // 2. Update the entry in DHT metadata
+        .and_then(|new_address| {
+            traced(
+                Span::new("update_metadata", &entry),
                 update_entry(
                     &runtime.context,
                     &runtime.context.action_channel,
                     latest_entry.address().clone(),
                     new_address,
-                )
-            }),
+                ),
+                &runtime.context,
+            )
+        }),
     );
 
     runtime.store_result(task_result)
 }
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+    use crate::{
+        context::{Context, NativeValidator},
+        instance::tests::test_instance_and_context,
+        nucleus::{
+            ribosome::api::{
+                tests::{
+                    test_capability, test_zome_api_function_call, test_zome_api_function_wasm,
+                    test_zome_name,
+                },
+                ZomeApiFunction,
+            },
+            state::ValidationResult,
+        },
+        workflows::author_entry::author_entry,
+    };
+    use futures::executor::block_on;
+    use holochain_core_types::{
+        cas::content::AddressableContent,
+        entry::{
+            entry_type::{test_app_entry_type, EntryType},
+            test_entry, Entry,
+        },
+        json::JsonString,
+        validation::{EntryAction, ValidationData, ValidationPackageDefinition},
+    };
+    use holochain_wasm_utils::api_serialization::UpdateEntryArgs;
+    use std::{
+        sync::{Arc, Mutex},
+        thread, time,
+    };
+
+    /// Commits test_entry() and replaces it through the zome API, with the given validator
+    /// for the type of test_entry() in place.
+    /// Returns the result of the update call.
+    fn update_test_entry(validator: Box<NativeValidator>) -> (JsonString, Arc<Context>) {
+        let wasm = test_zome_api_function_wasm(ZomeApiFunction::UpdateEntry.as_str());
+        let dna = test_utils::create_test_dna_with_wasm(
+            &test_zome_name(),
+            &test_capability(),
+            wasm.clone(),
+        );
+        let dna_name = dna.name().to_string();
+        let (instance, context) =
+            test_instance_and_context(dna).expect("Could not create test instance");
+        context.cache_validation_package_definition(
+            &test_app_entry_type(),
+            ValidationPackageDefinition::Entry,
+        );
+        context.register_native_validator(EntryType::App(test_app_entry_type()), validator);
+
+        block_on(author_entry(&test_entry(), None, &context)).unwrap();
+        // wait for the entry to be held in our own DHT shard
+        thread::sleep(time::Duration::from_millis(500));
+
+        let args = UpdateEntryArgs {
+            new_entry: Entry::App(test_app_entry_type(), JsonString::from("updated value")),
+            address: test_entry().address(),
+        };
+        let call_result = test_zome_api_function_call(
+            &dna_name,
+            context.clone(),
+            &instance,
+            &wasm,
+            JsonString::from(args).into_bytes(),
+        );
+        (call_result, context)
+    }
+
+    #[test]
+    /// test that updates get validated as Modify of the replaced entry with the agent as source
+    fn test_update_entry_validates_with_agent_as_source() {
+        let modifications = Arc::new(Mutex::new(Vec::new()));
+        let recorded = modifications.clone();
+        let (call_result, context) = update_test_entry(Box::new(
+            move |_: &Entry, validation_data: &ValidationData| {
+                if let EntryAction::Modify(ref replaced) = validation_data.action {
+                    recorded
+                        .lock()
+                        .unwrap()
+                        .push((replaced.clone(), validation_data.sources.clone()));
+                }
+                ValidationResult::Pass
+            },
+        ));
+
+        assert!(String::from(call_result).contains("\"ok\":true"));
+        assert_eq!(
+            vec![(test_entry().address(), vec![context.agent_id.address()])],
+            *modifications.lock().unwrap()
+        );
+    }
+
+    #[test]
+    /// test that validation can reject updates of entries it accepted commits of
+    fn test_update_entry_rejected_by_validation() {
+        let (call_result, context) =
+            update_test_entry(Box::new(|_: &Entry, validation_data: &ValidationData| {
+                match validation_data.action {
+                    EntryAction::Modify(_) => ValidationResult::permanent_failure("no updates"),
+                    _ => ValidationResult::Pass,
+                }
+            }));
+
+        let call_result = String::from(call_result);
+        assert!(call_result.contains("\"ok\":false"));
+        assert!(call_result.contains("no updates"));
+        let agent_state = context.state().unwrap().agent();
+        assert_eq!(
+            &test_entry().address(),
+            agent_state.top_chain_header().unwrap().entry_address()
+        );
+    }
+}
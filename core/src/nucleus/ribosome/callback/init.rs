@@ -0,0 +1,54 @@
+use super::call;
+use crate::{
+    context::Context,
+    nucleus::ribosome::callback::{Callback, CallbackParams, CallbackResult},
+};
+use std::sync::Arc;
+
+/// Runs the optional `__hdk_init` callback of the given zome.
+/// Zomes that don't export it are reported as NotImplemented.
+pub fn init(
+    context: Arc<Context>,
+    zome: &str,
+    // we ignore params for init
+    params: &CallbackParams,
+) -> CallbackResult {
+    call(context, zome, &Callback::Init, params)
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::init;
+    use crate::{
+        instance::tests::test_context,
+        nucleus::ribosome::{
+            callback::{tests::test_callback_instance, Callback, CallbackParams, CallbackResult},
+            Defn,
+        },
+    };
+
+    #[test]
+    fn pass() {
+        let zome = "test_zome";
+        let instance = test_callback_instance(zome, Callback::Init.as_str(), 0)
+            .expect("Test callback instance could not be initialized");
+        let context = instance.initialize_context(test_context("test"));
+
+        let result = init(context, zome, &CallbackParams::Init);
+
+        assert_eq!(CallbackResult::Pass, result);
+    }
+
+    #[test]
+    fn not_implemented() {
+        let zome = "test_zome";
+        let instance = test_callback_instance(zome, Callback::Genesis.as_str(), 0)
+            .expect("Test callback instance could not be initialized");
+        let context = instance.initialize_context(test_context("test"));
+
+        let result = init(context, zome, &CallbackParams::Init);
+
+        assert_eq!(CallbackResult::NotImplemented, result);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{context::Context, workflows::get_entry_history::get_entry_history_workflow};
 use futures::executor::block_on;
 use holochain_core_types::{
+    dna::Dna,
     entry::{entry_type::EntryType, Entry},
     error::HolochainError,
     link::Link,
@@ -75,49 +76,197 @@ pub fn find_link_definition_in_dna(
     context: &Arc<Context>,
 ) -> Result<LinkDefinitionPath, HolochainError> {
     let dna = context.get_dna().expect("No DNA found?!");
+    find_link_definition(&dna, base_type, tag, target_type)
+}
+
+/// Same as find_link_definition_in_dna but looks at the given DNA.
+pub fn find_link_definition(
+    dna: &Dna,
+    base_type: &EntryType,
+    tag: &String,
+    target_type: &EntryType,
+) -> Result<LinkDefinitionPath, HolochainError> {
     match base_type {
-        EntryType::App(app_entry_type) => dna
-            .get_entry_type_def(&app_entry_type.to_string())
-            .ok_or(HolochainError::ErrorGeneric(String::from(
-                "Unknown entry type",
-            )))?
-            .links_to
-            .iter()
-            .find(|&link_def| {
-                link_def.target_type == String::from(target_type.clone()) && &link_def.tag == tag
-            })
-            .and_then(|link_def| {
-                Some(LinkDefinitionPath {
-                    zome_name: dna.get_zome_name_for_app_entry_type(app_entry_type)?,
-                    entry_type_name: app_entry_type.to_string(),
-                    direction: LinkDirection::To,
-                    tag: link_def.tag.clone(),
+        EntryType::App(app_entry_type) => {
+            let maybe_zome_name = dna.get_zome_name_for_app_entry_type(app_entry_type)?;
+            dna.get_entry_type_def(&app_entry_type.to_string())?
+                .ok_or(HolochainError::ErrorGeneric(String::from(
+                    "Unknown entry type",
+                )))?
+                .links_to
+                .iter()
+                .find(|&link_def| {
+                    link_def.target_type == String::from(target_type.clone())
+                        && &link_def.tag == tag
+                })
+                .and_then(|link_def| {
+                    Some(LinkDefinitionPath {
+                        zome_name: maybe_zome_name?,
+                        entry_type_name: app_entry_type.to_string(),
+                        direction: LinkDirection::To,
+                        tag: link_def.tag.clone(),
+                    })
                 })
-            }),
+        }
         _ => None,
     }
     .or(match target_type {
-        EntryType::App(app_entry_type) => dna
-            .get_entry_type_def(&app_entry_type.to_string())
-            .ok_or(HolochainError::ErrorGeneric(String::from(
-                "Unknown entry type",
-            )))?
-            .linked_from
-            .iter()
-            .find(|&link_def| {
-                link_def.base_type == String::from(base_type.clone()) && &link_def.tag == tag
-            })
-            .and_then(|link_def| {
-                Some(LinkDefinitionPath {
-                    zome_name: dna.get_zome_name_for_app_entry_type(app_entry_type)?,
-                    entry_type_name: app_entry_type.to_string(),
-                    direction: LinkDirection::From,
-                    tag: link_def.tag.clone(),
+        EntryType::App(app_entry_type) => {
+            let maybe_zome_name = dna.get_zome_name_for_app_entry_type(app_entry_type)?;
+            dna.get_entry_type_def(&app_entry_type.to_string())?
+                .ok_or(HolochainError::ErrorGeneric(String::from(
+                    "Unknown entry type",
+                )))?
+                .linked_from
+                .iter()
+                .find(|&link_def| {
+                    link_def.base_type == String::from(base_type.clone()) && &link_def.tag == tag
+                })
+                .and_then(|link_def| {
+                    Some(LinkDefinitionPath {
+                        zome_name: maybe_zome_name?,
+                        entry_type_name: app_entry_type.to_string(),
+                        direction: LinkDirection::From,
+                        tag: link_def.tag.clone(),
+                    })
                 })
-            }),
+        }
         _ => None,
     })
     .ok_or(HolochainError::ErrorGeneric(String::from(
         "Unknown entry type",
     )))
 }
+
+/// Checks that a link uses a tag declared in the DNA, if strict link tags are switched on
+/// (zome config "strict_link_tags") for the zome of the base or the target entry type.
+///
+/// A tag is declared if the base entry type has a matching "links_to" definition or the
+/// target entry type has a matching "linked_from" definition.
+pub fn check_link_tag(
+    dna: &Dna,
+    base_type: &EntryType,
+    tag: &str,
+    target_type: &EntryType,
+) -> Result<(), HolochainError> {
+    let is_strict = |entry_type: &EntryType| -> Result<bool, HolochainError> {
+        Ok(match entry_type {
+            EntryType::App(app_entry_type) => dna
+                .get_zome_name_for_app_entry_type(app_entry_type)?
+                .and_then(|zome_name| dna.zomes().get(&zome_name))
+                .map(|zome| zome.config.strict_link_tags)
+                .unwrap_or(false),
+            _ => false,
+        })
+    };
+    if !is_strict(base_type)? && !is_strict(target_type)? {
+        return Ok(());
+    }
+
+    let declared_in_base = match base_type {
+        EntryType::App(app_entry_type) => dna
+            .get_entry_type_def(&app_entry_type.to_string())?
+            .map(|entry_type_def| {
+                entry_type_def.links_to.iter().any(|link_def| {
+                    link_def.target_type == String::from(target_type.clone()) && link_def.tag == tag
+                })
+            })
+            .unwrap_or(false),
+        _ => false,
+    };
+    let declared_in_target = match target_type {
+        EntryType::App(app_entry_type) => dna
+            .get_entry_type_def(&app_entry_type.to_string())?
+            .map(|entry_type_def| {
+                entry_type_def.linked_from.iter().any(|link_def| {
+                    link_def.base_type == String::from(base_type.clone()) && link_def.tag == tag
+                })
+            })
+            .unwrap_or(false),
+        _ => false,
+    };
+
+    if declared_in_base || declared_in_target {
+        Ok(())
+    } else {
+        Err(HolochainError::ValidationFailed(format!(
+            "Link tag \"{}\" is not declared for links from {} to {}",
+            tag, base_type, target_type
+        )))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use holochain_core_types::entry::entry_type::{test_app_entry_type, test_app_entry_type_b};
+    use test_utils::create_test_dna_with_wat;
+
+    fn test_dna(strict_link_tags: bool) -> Dna {
+        let mut dna = create_test_dna_with_wat("test_zome", "test_cap", None);
+        dna.zomes_mut()
+            .get_mut("test_zome")
+            .unwrap()
+            .config
+            .strict_link_tags = strict_link_tags;
+        dna
+    }
+
+    #[test]
+    fn find_link_definition_directions() {
+        let dna = test_dna(false);
+        let entry_type = EntryType::App(test_app_entry_type());
+        let entry_type_b = EntryType::App(test_app_entry_type_b());
+        let tag = String::from("test-tag");
+
+        let link_definition_path =
+            find_link_definition(&dna, &entry_type, &tag, &entry_type).unwrap();
+        assert_eq!(LinkDirection::To, link_definition_path.direction);
+        assert_eq!("testEntryType", link_definition_path.entry_type_name);
+
+        let link_definition_path =
+            find_link_definition(&dna, &entry_type, &tag, &entry_type_b).unwrap();
+        assert_eq!(LinkDirection::From, link_definition_path.direction);
+        assert_eq!("testEntryTypeB", link_definition_path.entry_type_name);
+
+        assert!(
+            find_link_definition(&dna, &entry_type, &String::from("other"), &entry_type).is_err()
+        );
+    }
+
+    #[test]
+    fn strict_link_tags_allowed_tag() {
+        let dna = test_dna(true);
+        let entry_type = EntryType::App(test_app_entry_type());
+        let entry_type_b = EntryType::App(test_app_entry_type_b());
+
+        // declared in links_to of the base type
+        assert_eq!(
+            Ok(()),
+            check_link_tag(&dna, &entry_type, "test-tag", &entry_type)
+        );
+        // declared in linked_from of the target type
+        assert_eq!(
+            Ok(()),
+            check_link_tag(&dna, &entry_type, "test-tag", &entry_type_b)
+        );
+    }
+
+    #[test]
+    fn strict_link_tags_disallowed_tag() {
+        let entry_type = EntryType::App(test_app_entry_type());
+
+        assert_eq!(
+            Err(HolochainError::ValidationFailed(String::from(
+                "Link tag \"test-tga\" is not declared for links from testEntryType to testEntryType"
+            ))),
+            check_link_tag(&test_dna(true), &entry_type, "test-tga", &entry_type),
+        );
+
+        // without strict mode undeclared tags are left to the validation callbacks
+        assert_eq!(
+            Ok(()),
+            check_link_tag(&test_dna(false), &entry_type, "test-tga", &entry_type)
+        );
+    }
+}
@@ -2,6 +2,7 @@
 //! ZomeCallbacks are functions in a Zome that are callable by the ribosome.
 
 pub mod genesis;
+pub mod init;
 pub mod links_utils;
 pub mod receive;
 pub mod validate_entry;
@@ -12,7 +13,7 @@ use crate::{
     nucleus::{
         ribosome::{
             self,
-            callback::{genesis::genesis, receive::receive},
+            callback::{genesis::genesis, init::init, receive::receive},
             Defn,
         },
         ZomeFnCall,
@@ -50,6 +51,12 @@ pub enum Callback {
 
     /// receive(from: String, message: String) -> String
     Receive,
+
+    /// LifeCycle Capability
+
+    /// __hdk_init() -> bool
+    /// Optional one-time setup of a zome, run once per instance after genesis.
+    Init,
 }
 
 impl FromStr for Callback {
@@ -58,6 +65,7 @@ impl FromStr for Callback {
         match s {
             "genesis" => Ok(Callback::Genesis),
             "receive" => Ok(Callback::Receive),
+            "__hdk_init" => Ok(Callback::Init),
             "" => Ok(Callback::MissingNo),
             _ => Err("Cannot convert string to Callback"),
         }
@@ -80,6 +88,7 @@ impl Callback {
             // @TODO call this from somewhere
             // @see https://github.com/holochain/holochain-rust/issues/201
             Callback::Receive => receive,
+            Callback::Init => init,
         }
     }
 }
@@ -90,6 +99,7 @@ impl Defn for Callback {
             Callback::MissingNo => "",
             Callback::Genesis => "genesis",
             Callback::Receive => "receive",
+            Callback::Init => "__hdk_init",
         }
     }
 
@@ -114,6 +124,7 @@ impl Defn for Callback {
             // @TODO call this from somewhere
             // @see https://github.com/holochain/holochain-rust/issues/201
             Callback::Receive => ReservedCapabilityNames::Communication,
+            Callback::Init => ReservedCapabilityNames::LifeCycle,
         }
     }
 }
@@ -125,6 +136,7 @@ pub enum CallbackParams {
     // @TODO call this from somewhere
     // @see https://github.com/holochain/holochain-rust/issues/201
     Receive,
+    Init,
 }
 
 impl ToString for CallbackParams {
@@ -135,6 +147,7 @@ impl ToString for CallbackParams {
                 String::from(JsonString::from(serialized_entry.to_owned()))
             }
             CallbackParams::Receive => String::new(),
+            CallbackParams::Init => String::new(),
         }
     }
 }
@@ -220,7 +233,7 @@ pub fn call(
             if wasm.code.is_empty() {
                 CallbackResult::NotImplemented
             } else {
-                run_callback(context.clone(), zome_call, wasm, dna.name.clone())
+                run_callback(context.clone(), zome_call, wasm, dna.name().to_string())
             }
         }
     }
@@ -340,6 +353,10 @@ pub mod tests {
             Callback::Receive,
             Callback::from_str("receive").expect("string literal should be valid callback")
         );
+        assert_eq!(
+            Callback::Init,
+            Callback::from_str("__hdk_init").expect("string literal should be valid callback")
+        );
 
         assert_eq!(
             "Cannot convert string to Callback",
@@ -354,12 +371,13 @@ pub mod tests {
             (Callback::MissingNo, ""),
             (Callback::Genesis, "genesis"),
             (Callback::Receive, "receive"),
+            (Callback::Init, "__hdk_init"),
         ] {
             assert_eq!(output, input.as_str());
         }
 
         // str_to_index()
-        for (input, output) in vec![("", 0), ("genesis", 1), ("receive", 2)] {
+        for (input, output) in vec![("", 0), ("genesis", 1), ("receive", 2), ("__hdk_init", 3)] {
             assert_eq!(output, Callback::str_to_index(input));
         }
 
@@ -368,6 +386,7 @@ pub mod tests {
             (0, Callback::MissingNo),
             (1, Callback::Genesis),
             (2, Callback::Receive),
+            (3, Callback::Init),
         ] {
             assert_eq!(output, Callback::from_index(input));
         }
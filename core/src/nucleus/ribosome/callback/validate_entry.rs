@@ -6,6 +6,7 @@ use crate::{
             self,
             callback::{links_utils, CallbackResult},
         },
+        state::ValidationResult,
         ZomeFnCall,
     },
 };
@@ -31,11 +32,26 @@ use std::sync::Arc;
 ///         validation callback
 /// * CallbackResult::NotImplemented if a validation callback is not implemented for the given
 ///         entry's type.
+///
+/// A native validator registered in the context for the entry's type takes precedence
+/// over the WASM callback. Its transient failures are returned as
+/// Err(HolochainError::ValidationPending(message)).
 pub fn validate_entry(
     entry: Entry,
     validation_data: ValidationData,
     context: Arc<Context>,
 ) -> Result<CallbackResult, HolochainError> {
+    if let Some(validator) = context.native_validator(&entry.entry_type()) {
+        return match validator(&entry, &validation_data) {
+            ValidationResult::Pass => Ok(CallbackResult::Pass),
+            ValidationResult::Fail {
+                permanent: true,
+                reason,
+            } => Ok(CallbackResult::Fail(reason)),
+            ValidationResult::Fail { reason, .. } => Err(HolochainError::ValidationPending(reason)),
+        };
+    }
+
     match entry.entry_type() {
         // DNA entries are not validated currently and always valid
         // TODO: Specify when DNA can be commited as an update and how to implement validation of DNA entries then.
@@ -77,6 +93,12 @@ fn validate_link_entry(
     };
     let link = link_add.link().clone();
     let (base, target) = links_utils::get_link_entries(&link, &context)?;
+    links_utils::check_link_tag(
+        &context.get_dna().expect("Callback called without DNA set!"),
+        &base.entry_type(),
+        link.tag(),
+        &target.entry_type(),
+    )?;
     let link_definition_path = links_utils::find_link_definition_in_dna(
         &base.entry_type(),
         link.tag(),
@@ -105,7 +127,7 @@ fn validate_link_entry(
         context.clone(),
         call,
         &wasm,
-        context.get_dna().unwrap().name.clone(),
+        context.get_dna().unwrap().name().to_string(),
     ))
 }
 
@@ -116,7 +138,7 @@ fn validate_app_entry(
     context: Arc<Context>,
 ) -> Result<CallbackResult, HolochainError> {
     let dna = context.get_dna().expect("Callback called without DNA set!");
-    let zome_name = dna.get_zome_name_for_app_entry_type(&app_entry_type);
+    let zome_name = dna.get_zome_name_for_app_entry_type(&app_entry_type)?;
     if zome_name.is_none() {
         return Ok(CallbackResult::NotImplemented);
     }
@@ -134,7 +156,7 @@ fn validate_app_entry(
                 context.clone(),
                 validation_call,
                 &wasm,
-                dna.name.clone(),
+                dna.name().to_string(),
             ))
         }
         None => Ok(CallbackResult::NotImplemented),
@@ -189,3 +211,54 @@ fn run_validation_callback(
         Err(error) => CallbackResult::Fail(error.to_string()),
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::instance::tests::test_context;
+    use holochain_core_types::{
+        entry::{entry_type::test_app_entry_type, test_entry},
+        json::{JsonString, RawString},
+    };
+
+    #[test]
+    fn native_validator_is_enforced() {
+        let context = test_context("bob");
+        context.register_native_validator(
+            EntryType::App(test_app_entry_type()),
+            Box::new(|entry: &Entry, _: &ValidationData| match entry {
+                Entry::App(_, value) if value.to_string().contains("forbidden") => {
+                    ValidationResult::permanent_failure("forbidden content")
+                }
+                Entry::App(_, value) if value.to_string().contains("pending") => {
+                    ValidationResult::transient_failure("waiting for dependency")
+                }
+                _ => ValidationResult::Pass,
+            }),
+        );
+
+        // the test context has no DNA, so these can only pass through the native validator
+        assert_eq!(
+            Ok(CallbackResult::Pass),
+            validate_entry(test_entry(), ValidationData::default(), context.clone())
+        );
+        let forbidden_entry = Entry::App(
+            test_app_entry_type(),
+            JsonString::from(RawString::from("forbidden")),
+        );
+        assert_eq!(
+            Ok(CallbackResult::Fail(String::from("forbidden content"))),
+            validate_entry(forbidden_entry, ValidationData::default(), context.clone())
+        );
+        let pending_entry = Entry::App(
+            test_app_entry_type(),
+            JsonString::from(RawString::from("pending")),
+        );
+        assert_eq!(
+            Err(HolochainError::ValidationPending(String::from(
+                "waiting for dependency"
+            ))),
+            validate_entry(pending_entry, ValidationData::default(), context)
+        );
+    }
+}
@@ -10,7 +10,10 @@ use crate::{
     },
 };
 use holochain_core_types::{
-    entry::{entry_type::EntryType, Entry},
+    entry::{
+        entry_type::{AppEntryType, EntryType},
+        Entry,
+    },
     error::HolochainError,
     json::JsonString,
     validation::ValidationPackageDefinition,
@@ -22,31 +25,9 @@ pub fn get_validation_package_definition(
     entry: &Entry,
     context: Arc<Context>,
 ) -> Result<CallbackResult, HolochainError> {
-    let dna = context.get_dna().expect("Callback called without DNA set!");
     let result = match entry.entry_type().clone() {
         EntryType::App(app_entry_type) => {
-            let zome_name = dna.get_zome_name_for_app_entry_type(&app_entry_type);
-            if zome_name.is_none() {
-                return Ok(CallbackResult::NotImplemented);
-            }
-
-            let zome_name = zome_name.unwrap();
-            let wasm = context
-                .get_wasm(&zome_name)
-                .ok_or(HolochainError::ErrorGeneric(String::from("no wasm found")))?;
-
-            ribosome::run_dna(
-                &dna.name.clone(),
-                context,
-                wasm.code.clone(),
-                &ZomeFnCall::new(
-                    &zome_name,
-                    "no capability, since this is an entry validation call",
-                    "__hdk_get_validation_package_for_entry_type",
-                    app_entry_type.to_string(),
-                ),
-                Some(app_entry_type.to_string().into_bytes()),
-            )?
+            return get_app_entry_validation_package_definition(&app_entry_type, context);
         }
         EntryType::LinkAdd => {
             let link_add = match entry {
@@ -67,9 +48,7 @@ pub fn get_validation_package_definition(
             )
             .map_err(|_| HolochainError::NotImplemented)?;
 
-            let wasm = context
-                .get_wasm(&link_definition_path.zome_name)
-                .expect("Couldn't get WASM for zome");
+            let module = context.get_parsed_module(&link_definition_path.zome_name)?;
 
             let params = LinkValidationPackageArgs {
                 entry_type: link_definition_path.entry_type_name,
@@ -77,6 +56,7 @@ pub fn get_validation_package_definition(
                 direction: link_definition_path.direction,
             };
 
+            let dna = context.get_dna().expect("Callback called without DNA set!");
             let call = ZomeFnCall::new(
                 "",
                 "no capability, since this is an entry validation call",
@@ -84,18 +64,91 @@ pub fn get_validation_package_definition(
                 params,
             );
 
-            ribosome::run_dna(
-                &dna.name.clone(),
+            ribosome::run_parsed_dna(
+                &dna.name().to_string(),
                 context,
-                wasm.code.clone(),
+                &module,
                 &call,
                 Some(call.parameters.into_bytes()),
             )?
         }
         EntryType::Deletion => JsonString::from(ValidationPackageDefinition::ChainFull),
+        // System entries that don't get validated against the source chain
+        EntryType::Dna | EntryType::AgentId | EntryType::ChainHeader | EntryType::ChainMigrate => {
+            return Ok(CallbackResult::NotImplemented);
+        }
         _ => Err(HolochainError::NotImplemented)?,
     };
 
+    package_definition_from_result(result)
+}
+
+/// Asks the zome that defines the given app entry type for its validation package definition.
+/// The definition only depends on the entry type, so it gets cached in the context.
+pub fn get_app_entry_validation_package_definition(
+    app_entry_type: &AppEntryType,
+    context: Arc<Context>,
+) -> Result<CallbackResult, HolochainError> {
+    if let Some(definition) = context.cached_validation_package_definition(app_entry_type) {
+        return Ok(CallbackResult::ValidationPackageDefinition(definition));
+    }
+
+    let dna = context.get_dna().expect("Callback called without DNA set!");
+    let zome_name = dna.get_zome_name_for_app_entry_type(app_entry_type)?;
+    if zome_name.is_none() {
+        return Ok(CallbackResult::NotImplemented);
+    }
+
+    let zome_name = zome_name.unwrap();
+    let module = context.get_parsed_module(&zome_name)?;
+
+    let result = ribosome::run_parsed_dna(
+        &dna.name().to_string(),
+        context.clone(),
+        &module,
+        &ZomeFnCall::new(
+            &zome_name,
+            "no capability, since this is an entry validation call",
+            "__hdk_get_validation_package_for_entry_type",
+            app_entry_type.to_string(),
+        ),
+        Some(app_entry_type.to_string().into_bytes()),
+    )?;
+
+    let callback_result = package_definition_from_result(result)?;
+    if let CallbackResult::ValidationPackageDefinition(ref definition) = callback_result {
+        context.cache_validation_package_definition(app_entry_type, definition.clone());
+    }
+    Ok(callback_result)
+}
+
+/// Resolves which validation package definition the given entry type uses, without building
+/// the package. Meant for debugging validation.
+/// Link definitions depend on the link's base and target, so LinkAdd can not be resolved
+/// from the entry type alone and fails with NotImplemented like other system types.
+pub fn validation_package_definition_for(
+    entry_type: &EntryType,
+    context: Arc<Context>,
+) -> Result<ValidationPackageDefinition, HolochainError> {
+    let callback_result = match entry_type {
+        EntryType::App(app_entry_type) => {
+            get_app_entry_validation_package_definition(app_entry_type, context)?
+        }
+        EntryType::Deletion => {
+            CallbackResult::ValidationPackageDefinition(ValidationPackageDefinition::ChainFull)
+        }
+        _ => CallbackResult::NotImplemented,
+    };
+    match callback_result {
+        CallbackResult::ValidationPackageDefinition(definition) => Ok(definition),
+        CallbackResult::Fail(error) => Err(HolochainError::ErrorGeneric(error)),
+        CallbackResult::NotImplemented | CallbackResult::Pass => {
+            Err(HolochainError::NotImplemented)
+        }
+    }
+}
+
+fn package_definition_from_result(result: JsonString) -> Result<CallbackResult, HolochainError> {
     if result.is_null() {
         Err(HolochainError::SerializationError(String::from(
             "__hdk_get_validation_package_for_entry_type returned empty result",
@@ -109,3 +162,62 @@ pub fn get_validation_package_definition(
         }
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::{get_validation_package_definition, validation_package_definition_for};
+    use crate::nucleus::{actions::tests::instance, ribosome::callback::CallbackResult};
+    use holochain_core_types::{
+        agent::test_agent_id,
+        dna::Dna,
+        entry::{
+            entry_type::{AppEntryType, EntryType},
+            Entry,
+        },
+        error::HolochainError,
+        link::link_remove::LinkRemove,
+        validation::ValidationPackageDefinition,
+    };
+
+    #[test]
+    fn system_entries_need_no_validation_package() {
+        let (_instance, context) = instance();
+
+        assert_eq!(
+            Ok(CallbackResult::NotImplemented),
+            get_validation_package_definition(&Entry::AgentId(test_agent_id()), context.clone()),
+        );
+        assert_eq!(
+            Ok(CallbackResult::NotImplemented),
+            get_validation_package_definition(&Entry::Dna(Dna::new()), context.clone()),
+        );
+        assert_eq!(
+            Err(HolochainError::NotImplemented),
+            get_validation_package_definition(&Entry::LinkRemove(LinkRemove {}), context.clone()),
+        );
+    }
+
+    #[test]
+    fn resolves_validation_package_definitions_by_entry_type() {
+        let (_instance, context) = instance();
+
+        assert_eq!(
+            Ok(ValidationPackageDefinition::ChainFull),
+            validation_package_definition_for(&EntryType::Deletion, context.clone()),
+        );
+        assert_eq!(
+            Ok(ValidationPackageDefinition::ChainEntries),
+            validation_package_definition_for(
+                &EntryType::App(AppEntryType::from("package_chain_entries")),
+                context.clone(),
+            ),
+        );
+        assert_eq!(
+            Ok(ValidationPackageDefinition::Entry),
+            validation_package_definition_for(
+                &EntryType::App(AppEntryType::from("package_entry")),
+                context.clone(),
+            ),
+        );
+    }
+}
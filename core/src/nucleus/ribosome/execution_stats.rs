@@ -0,0 +1,163 @@
+//! Accumulates how often and for how long each zome function ran in the ribosome,
+//! to find the functions that dominate an instance's execution time.
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// Source of the instants the run time of zome functions is measured with.
+pub type Clock = Fn() -> Instant + Send + Sync;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionStats {
+    pub zome: String,
+    pub function: String,
+    pub calls: u64,
+    /// Time spent inside the WASM function, summed over all calls.
+    pub total_duration: Duration,
+}
+
+pub struct ExecutionStats {
+    functions: HashMap<(String, String), (u64, Duration)>,
+    clock: Box<Clock>,
+}
+
+impl Default for ExecutionStats {
+    fn default() -> Self {
+        ExecutionStats {
+            functions: HashMap::new(),
+            clock: Box::new(Instant::now),
+        }
+    }
+}
+
+impl ExecutionStats {
+    /// Replaces the clock, which is Instant::now() by default.
+    /// Meant for tests that need deterministic run times.
+    pub fn set_clock(&mut self, clock: Box<Clock>) {
+        self.clock = clock;
+    }
+
+    pub fn now(&self) -> Instant {
+        (self.clock)()
+    }
+
+    pub fn record(&mut self, zome: &str, function: &str, elapsed: Duration) {
+        let (calls, total_duration) = self
+            .functions
+            .entry((zome.to_string(), function.to_string()))
+            .or_insert((0, Duration::from_secs(0)));
+        *calls += 1;
+        *total_duration += elapsed;
+    }
+
+    /// All functions that ran at least once, sorted by zome and function name.
+    pub fn stats(&self) -> Vec<FunctionStats> {
+        let mut stats: Vec<FunctionStats> = self
+            .functions
+            .iter()
+            .map(
+                |((zome, function), (calls, total_duration))| FunctionStats {
+                    zome: zome.clone(),
+                    function: function.clone(),
+                    calls: *calls,
+                    total_duration: *total_duration,
+                },
+            )
+            .collect();
+        stats.sort_by(|a, b| (&a.zome, &a.function).cmp(&(&b.zome, &b.function)));
+        stats
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate wabt;
+    use self::wabt::Wat2Wasm;
+    use super::*;
+    use crate::{
+        instance::tests::test_context,
+        nucleus::{ribosome::run_dna, ZomeFnCall},
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Exports the functions "main" and "other", which both return right away.
+    fn test_wasm() -> Vec<u8> {
+        Wat2Wasm::new()
+            .canonicalize_lebs(false)
+            .write_debug_names(true)
+            .convert(
+                r#"
+(module
+    (memory 1)
+    (export "memory" (memory 0))
+
+    (func (export "main") (param $allocation i32) (result i32)
+        (i32.const 0)
+    )
+
+    (func (export "other") (param $allocation i32) (result i32)
+        (i32.const 0)
+    )
+)
+"#,
+            )
+            .unwrap()
+            .as_ref()
+            .to_vec()
+    }
+
+    #[test]
+    fn accumulates_calls_and_durations_per_function() {
+        let context = test_context("alice");
+        // every reading of the clock is 10ms after the previous one,
+        // so each call runs for exactly 10ms
+        let start = Instant::now();
+        let readings = AtomicUsize::new(0);
+        context.set_execution_clock(Box::new(move || {
+            start + Duration::from_millis(10 * readings.fetch_add(1, Ordering::SeqCst) as u64)
+        }));
+        let wasm = test_wasm();
+        let call = |function: &str| {
+            let zome_call = ZomeFnCall::new("test_zome", "test_cap", function, "");
+            run_dna("test_dna", context.clone(), wasm.clone(), &zome_call, None)
+        };
+        for _ in 0..3 {
+            call("main").expect("test function should run");
+        }
+        call("other").expect("test function should run");
+        // unknown functions are not recorded
+        assert!(call("missing").is_err());
+
+        let stats = context.execution_stats();
+        assert_eq!(2, stats.len());
+        let (main, other) = (&stats[0], &stats[1]);
+        assert_eq!(
+            ("test_zome", "main", 3),
+            (main.zome.as_str(), main.function.as_str(), main.calls)
+        );
+        assert_eq!(
+            ("test_zome", "other", 1),
+            (other.zome.as_str(), other.function.as_str(), other.calls)
+        );
+        assert_eq!(Duration::from_millis(10), other.total_duration);
+        assert_eq!(other.total_duration * 3, main.total_duration);
+    }
+
+    #[test]
+    fn record_sums_durations() {
+        let mut execution_stats = ExecutionStats::default();
+        execution_stats.record("test_zome", "main", Duration::from_millis(200));
+        execution_stats.record("test_zome", "main", Duration::from_millis(300));
+
+        assert_eq!(
+            vec![FunctionStats {
+                zome: String::from("test_zome"),
+                function: String::from("main"),
+                calls: 2,
+                total_duration: Duration::from_millis(500),
+            }],
+            execution_stats.stats()
+        );
+    }
+}
@@ -2,9 +2,11 @@
 
 pub mod api;
 pub mod callback;
+pub mod execution_stats;
 pub mod memory;
 mod run_dna;
 mod runtime;
+pub mod wasm_cache;
 
 pub use self::{run_dna::*, runtime::*};
 
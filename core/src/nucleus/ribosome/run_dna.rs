@@ -12,7 +12,7 @@ use holochain_core_types::{
 use holochain_wasm_utils::memory_allocation::decode_encoded_allocation;
 use std::{str::FromStr, sync::Arc};
 use wasmi::{
-    self, Error as InterpreterError, FuncInstance, FuncRef, ImportsBuilder, ModuleImportResolver,
+    Error as InterpreterError, FuncInstance, FuncRef, ImportsBuilder, Module, ModuleImportResolver,
     ModuleInstance, NopExternals, RuntimeValue, Signature, ValueType,
 };
 
@@ -26,10 +26,29 @@ pub fn run_dna(
     zome_call: &ZomeFnCall,
     parameters: Option<Vec<u8>>,
 ) -> ZomeFnResult {
-    // Create wasm module from wasm binary
-    let module =
-        wasmi::Module::from_buffer(wasm).map_err(|e| HolochainError::ErrorGeneric(e.into()))?;
+    // Get the wasm module for this zome, parsing the wasm binary only on first use
+    let module = context
+        .wasm_cache()
+        .write()
+        .unwrap()
+        .get_or_load(&zome_call.zome_name, &wasm)?;
+    run_parsed_dna(dna_name, context, &module, zome_call, parameters)
+}
 
+/// Executes an exposed zome function in an already parsed wasm module.
+/// Callers running many functions of the same zome (like validation callbacks) can get
+/// the module once from Context::get_parsed_module(), or parse it themselves with
+/// ParsedModule::parsed_module(), and skip copying the code for every call,
+/// which run_dna() does.
+/// Parsing dominates the cost of short calls, benches/parsed_module.rs measures the
+/// difference for a validation callback.
+pub fn run_parsed_dna(
+    dna_name: &str,
+    context: Arc<Context>,
+    module: &Module,
+    zome_call: &ZomeFnCall,
+    parameters: Option<Vec<u8>>,
+) -> ZomeFnResult {
     // invoke_index and resolve_func work together to enable callable host functions
     // within WASM modules, which is how the core API functions
     // read about the Externals trait for more detail
@@ -82,7 +101,7 @@ pub fn run_dna(
     imports.push_resolver("env", &RuntimeModuleImportResolver);
 
     // Create module instance from wasm module, and start it if start is defined
-    let wasm_instance = ModuleInstance::new(&module, &imports)
+    let wasm_instance = ModuleInstance::new(module, &imports)
         .expect("Failed to instantiate module")
         .run_start(&mut NopExternals)
         .map_err(|_| HolochainError::RibosomeFailed("Module failed to start".to_string()))?;
@@ -124,12 +143,24 @@ pub fn run_dna(
         // invoke function in wasm instance
         // arguments are info for wasm on how to retrieve complex input arguments
         // which have been set in memory module
-        returned_encoded_allocation = wasm_instance
-            .invoke_export(
-                zome_call.fn_name.clone().as_str(),
-                &[RuntimeValue::I32(encoded_allocation_of_input as i32)],
-                mut_runtime,
-            )
+        // only functions the module exports get stats, so calls of unknown function names
+        // don't add entries
+        let is_exported_function = wasm_instance
+            .export_by_name(&zome_call.fn_name)
+            .and_then(|export| export.as_func().cloned())
+            .is_some();
+        let started = mut_runtime.context.execution_clock_now();
+        let invoke_result = wasm_instance.invoke_export(
+            zome_call.fn_name.clone().as_str(),
+            &[RuntimeValue::I32(encoded_allocation_of_input as i32)],
+            mut_runtime,
+        );
+        if is_exported_function {
+            mut_runtime
+                .context
+                .record_execution(&zome_call.zome_name, &zome_call.fn_name, started);
+        }
+        returned_encoded_allocation = invoke_result
             .map_err(|err| HolochainError::RibosomeFailed(err.to_string()))?
             .unwrap()
             .try_into()
@@ -10,11 +10,12 @@ use crate::{
     },
 };
 use holochain_core_types::{
+    entry::entry_type::EntryType,
     error::{HolochainError, RibosomeReturnCode, ZomeApiInternalResult},
     json::JsonString,
 };
 use holochain_wasm_utils::memory_allocation::decode_encoded_allocation;
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 use wasmi::{Externals, RuntimeArgs, RuntimeValue};
 
 /// Object holding data to pass around to invoked Zome API functions
@@ -77,6 +78,113 @@ impl Runtime {
         }
     }
 
+    /// Fails with HolochainError::ReadOnlyViolation if the running zome function is declared
+    /// read-only, for zome API functions that write to the chain or the DHT.
+    pub fn check_writable(&self) -> Result<(), HolochainError> {
+        let zome_call = &self.zome_call;
+        let read_only = self
+            .context
+            .get_dna()
+            .and_then(|dna| {
+                dna.get_capability_with_zome_name(&zome_call.zome_name, &zome_call.cap_name)
+                    .ok()
+                    .and_then(|capability| {
+                        capability
+                            .functions
+                            .iter()
+                            .find(|fn_declaration| fn_declaration.name == zome_call.fn_name)
+                            .map(|fn_declaration| fn_declaration.read_only)
+                    })
+            })
+            .unwrap_or(false);
+        if read_only {
+            Err(HolochainError::ReadOnlyViolation(format!(
+                "Zome function {}/{} is read-only and can not write",
+                zome_call.zome_name, zome_call.fn_name
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Fails with HolochainError::ValidationFailed if the zome has strict commit types
+    /// switched on and the running zome function does not declare the given app entry type
+    /// in its "commits_entry_types".
+    pub fn check_commit_entry_type(&self, entry_type: &EntryType) -> Result<(), HolochainError> {
+        let app_entry_type = match entry_type {
+            EntryType::App(app_entry_type) => app_entry_type,
+            _ => return Ok(()),
+        };
+        let zome_call = &self.zome_call;
+        let dna = match self.context.get_dna() {
+            Some(dna) => dna,
+            None => return Ok(()),
+        };
+        let is_strict = dna
+            .get_zome(&zome_call.zome_name)
+            .map(|zome| zome.config.strict_commit_types)
+            .unwrap_or(false);
+        let declared = dna
+            .commits_entry_types(
+                &zome_call.zome_name,
+                &zome_call.cap_name,
+                &zome_call.fn_name,
+            )
+            .map(|entry_types| entry_types.contains(&app_entry_type.to_string()))
+            .unwrap_or(false);
+        if !is_strict || declared {
+            Ok(())
+        } else {
+            Err(HolochainError::ValidationFailed(format!(
+                "Zome function {}/{} does not declare committing entries of type {}",
+                zome_call.zome_name,
+                zome_call.fn_name,
+                app_entry_type.to_string()
+            )))
+        }
+    }
+
+    /// The running zome's max_commits_per_minute, if it declares one.
+    fn max_commits_per_minute(&self) -> Option<u32> {
+        self.context
+            .get_dna()
+            .and_then(|dna| {
+                dna.get_zome(&self.zome_call.zome_name)
+                    .map(|zome| zome.config.max_commits_per_minute)
+            })
+            .and_then(|max_commits| max_commits)
+    }
+
+    /// Reserves a slot of the running zome's commit rate limit, if it declares one.
+    /// Fails with HolochainError::RateLimited if the limit is reached.
+    /// Zome API functions that commit to the source chain call this right before committing
+    /// and hand the returned reservation to release_commit() if the commit failed.
+    pub fn reserve_commit(&self) -> Result<Option<Instant>, HolochainError> {
+        match self.max_commits_per_minute() {
+            Some(max_commits) => {
+                let now = Instant::now();
+                self.context
+                    .commit_rate_limiter()
+                    .lock()
+                    .unwrap()
+                    .reserve(&self.zome_call.zome_name, max_commits, now)
+                    .map(|_| Some(now))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Hands back a slot reserved by reserve_commit() for a commit that failed.
+    pub fn release_commit(&self, reservation: Option<Instant>) {
+        if let Some(reserved_at) = reservation {
+            self.context
+                .commit_rate_limiter()
+                .lock()
+                .unwrap()
+                .release(&self.zome_call.zome_name, reserved_at);
+        }
+    }
+
     pub fn store_result<J: Into<JsonString>>(
         &mut self,
         result: Result<J, HolochainError>,
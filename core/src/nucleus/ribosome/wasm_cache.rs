@@ -0,0 +1,466 @@
+//! Cache of parsed WASM modules per zome, so a zome's code only gets decoded and
+//! validated once.
+use crate::nucleus::ribosome::callback::Callback;
+use holochain_core_types::{
+    dna::{wasm::DnaWasm, Dna},
+    error::{DnaError, HolochainError},
+    hash::HashString,
+};
+use holochain_wasm_utils::abi::{abi_version_from_export, HDK_ABI_VERSION};
+use multihash::Hash;
+use std::{
+    collections::{HashMap, VecDeque},
+    str::FromStr,
+    sync::Arc,
+};
+use wasmi::Module;
+
+/// When to decode and validate a zome's WASM code.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WasmLoadPolicy {
+    /// Defer parsing until the zome's code is run for the first time.
+    Lazy,
+    /// Parse every zome's code when the DNA is loaded so broken modules fail fast.
+    Eager,
+}
+
+impl Default for WasmLoadPolicy {
+    fn default() -> Self {
+        WasmLoadPolicy::Lazy
+    }
+}
+
+/// Parsing of zome code into a module that can be instantiated any number of times,
+/// e.g. with ribosome::run_parsed_dna().
+/// Parsing is the expensive part of running zome code, so the Ribosome goes through the
+/// WasmModuleCache of its context instead (see Context::get_parsed_module()).
+pub trait ParsedModule {
+    fn parsed_module(&self) -> Result<Module, HolochainError>;
+}
+
+impl ParsedModule for DnaWasm {
+    fn parsed_module(&self) -> Result<Module, HolochainError> {
+        parse_module(&self.code)
+    }
+}
+
+fn parse_module(wasm: &[u8]) -> Result<Module, HolochainError> {
+    Module::from_buffer(wasm).map_err(|e| HolochainError::ErrorGeneric(e.into()))
+}
+
+/// Maximum number of parsed modules a WasmModuleCache keeps.
+/// The module that was parsed first gets dropped to make room for a new one.
+pub const WASM_CACHE_CAPACITY: usize = 64;
+
+#[derive(Default)]
+pub struct WasmModuleCache {
+    policy: WasmLoadPolicy,
+    /// parsed modules by zome name and the hash of the code they were parsed from
+    modules: HashMap<(String, HashString), Arc<Module>>,
+    /// keys of the parsed modules in the order they were parsed
+    parse_order: VecDeque<(String, HashString)>,
+    /// the hash of each zome's code, computed once when the zome gets loaded
+    zome_code_hashes: HashMap<String, HashString>,
+}
+
+impl WasmModuleCache {
+    pub fn new(policy: WasmLoadPolicy) -> Self {
+        WasmModuleCache {
+            policy,
+            modules: HashMap::new(),
+            parse_order: VecDeque::new(),
+            zome_code_hashes: HashMap::new(),
+        }
+    }
+
+    pub fn policy(&self) -> WasmLoadPolicy {
+        self.policy.clone()
+    }
+
+    pub fn set_policy(&mut self, policy: WasmLoadPolicy) {
+        self.policy = policy;
+    }
+
+    /// Returns the parsed module for the given zome, parsing and caching it on first use.
+    /// The zome's code is only hashed the first time, so code changes are not noticed
+    /// until the cache gets cleared.
+    pub fn get_or_load(
+        &mut self,
+        zome_name: &str,
+        wasm: &[u8],
+    ) -> Result<Arc<Module>, HolochainError> {
+        let key = (zome_name.to_string(), self.code_hash(zome_name, wasm));
+        if let Some(module) = self.modules.get(&key) {
+            return Ok(module.clone());
+        }
+        check_abi_version(zome_name, wasm)?;
+        let module = Arc::new(parse_module(wasm)?);
+        if self.modules.len() >= WASM_CACHE_CAPACITY {
+            if let Some(oldest) = self.parse_order.pop_front() {
+                self.modules.remove(&oldest);
+            }
+        }
+        self.parse_order.push_back(key.clone());
+        self.modules.insert(key, module.clone());
+        Ok(module)
+    }
+
+    /// The hash of the zome's code, computed when the zome gets loaded for the first time.
+    fn code_hash(&mut self, zome_name: &str, wasm: &[u8]) -> HashString {
+        self.zome_code_hashes
+            .entry(zome_name.to_string())
+            .or_insert_with(|| HashString::encode_from_bytes(wasm, Hash::SHA2256))
+            .clone()
+    }
+
+    /// Checks the ABI version of every zome in the DNA, and parses their code if the
+    /// policy is eager.
+    pub fn preload(&mut self, dna: &Dna) -> Result<(), HolochainError> {
+        for (zome_name, zome) in dna.zomes() {
+            if zome.code.code.is_empty() {
+                continue;
+            }
+            match self.policy {
+                WasmLoadPolicy::Lazy => {
+                    check_abi_version(zome_name, &zome.code.code)?;
+                    self.code_hash(zome_name, &zome.code.code);
+                }
+                WasmLoadPolicy::Eager => {
+                    self.get_or_load(zome_name, &zome.code.code)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The module the given zome was last loaded with, without looking at its code.
+    pub fn get(&self, zome_name: &str) -> Option<Arc<Module>> {
+        self.zome_code_hashes
+            .get(zome_name)
+            .and_then(|code_hash| {
+                self.modules
+                    .get(&(zome_name.to_string(), code_hash.clone()))
+            })
+            .cloned()
+    }
+
+    pub fn is_loaded(&self, zome_name: &str) -> bool {
+        self.get(zome_name).is_some()
+    }
+
+    /// Drops all parsed modules and code hashes, e.g. after the DNA changed.
+    pub fn clear(&mut self) {
+        self.modules.clear();
+        self.parse_order.clear();
+        self.zome_code_hashes.clear();
+    }
+}
+
+/// Rejects zome code marked as built for another HDK ABI version than the Ribosome's
+/// with DnaError::AbiMismatch. Code without a version marker (e.g. hand written WAT)
+/// is not checked, neither is invalid code, which fails once it gets parsed.
+pub fn check_abi_version(zome_name: &str, wasm: &[u8]) -> Result<(), HolochainError> {
+    let exported_functions = match exported_functions(wasm) {
+        Some(exported_functions) => exported_functions,
+        None => return Ok(()),
+    };
+    match exported_functions
+        .iter()
+        .filter_map(|name| abi_version_from_export(name))
+        .find(|version| *version != HDK_ABI_VERSION)
+    {
+        Some(found) => Err(HolochainError::Dna(DnaError::AbiMismatch {
+            zome: zome_name.to_string(),
+            found,
+            expected: HDK_ABI_VERSION,
+        })),
+        None => Ok(()),
+    }
+}
+
+/// Replaces wildcard function declarations in the DNA's capabilities with the functions
+/// the zome's WASM exports, leaving out callbacks and HDK internals (names starting with "__").
+/// Zomes without (valid) code keep their declarations as they are.
+pub fn expand_wildcard_capabilities(dna: &mut Dna) {
+    for zome in dna.zomes_mut().values_mut() {
+        let zome_functions: Vec<String> = match exported_functions(&zome.code.code) {
+            Some(exported_functions) => exported_functions
+                .into_iter()
+                .filter(|name| !name.starts_with("__") && Callback::from_str(name).is_err())
+                .collect(),
+            None => continue,
+        };
+        zome.expand_wildcard_functions(&zome_functions);
+    }
+}
+
+/// Names of all functions a WASM module exports, read from its export section
+/// without decoding the rest of the module. None if the module is malformed.
+pub fn exported_functions(wasm: &[u8]) -> Option<Vec<String>> {
+    const EXPORT_SECTION: u8 = 7;
+    const FUNCTION_EXPORT: u8 = 0;
+    if wasm.len() < 8 || &wasm[..4] != b"\0asm" {
+        return None;
+    }
+    let mut reader = WasmReader { wasm, position: 8 };
+    let mut names = Vec::new();
+    while reader.position < wasm.len() {
+        let section_id = reader.bytes(1)?[0];
+        let section_size = reader.var_u32()? as usize;
+        if section_id != EXPORT_SECTION {
+            reader.bytes(section_size)?;
+            continue;
+        }
+        let count = reader.var_u32()?;
+        for _ in 0..count {
+            let name_length = reader.var_u32()? as usize;
+            let name = reader.bytes(name_length)?;
+            let kind = reader.bytes(1)?[0];
+            // index of the exported item
+            reader.var_u32()?;
+            if kind == FUNCTION_EXPORT {
+                names.push(String::from_utf8_lossy(name).into_owned());
+            }
+        }
+    }
+    Some(names)
+}
+
+struct WasmReader<'a> {
+    wasm: &'a [u8],
+    position: usize,
+}
+
+impl<'a> WasmReader<'a> {
+    fn bytes(&mut self, length: usize) -> Option<&'a [u8]> {
+        let end = self.position.checked_add(length)?;
+        let bytes = self.wasm.get(self.position..end)?;
+        self.position = end;
+        Some(bytes)
+    }
+
+    /// Reads an unsigned LEB128 encoded integer.
+    fn var_u32(&mut self) -> Option<u32> {
+        let mut value: u32 = 0;
+        for shift in (0..35).step_by(7) {
+            let byte = self.bytes(1)?[0];
+            value |= u32::from(byte & 0x7f).checked_shl(shift)?;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate wabt;
+    use self::wabt::Wat2Wasm;
+    use super::*;
+    use holochain_core_types::dna::zome::{
+        capabilities::{Capability, FnDeclaration},
+        Zome,
+    };
+
+    fn test_wasm() -> Vec<u8> {
+        Wat2Wasm::new()
+            .canonicalize_lebs(false)
+            .write_debug_names(true)
+            .convert(r#"(module (func (export "noop")))"#)
+            .unwrap()
+            .as_ref()
+            .to_vec()
+    }
+
+    fn test_wasm_with_abi_version(version: u32) -> Vec<u8> {
+        Wat2Wasm::new()
+            .canonicalize_lebs(false)
+            .write_debug_names(true)
+            .convert(format!(
+                r#"(module (func (export "noop")) (func (export "__hdk_abi_version_{}")))"#,
+                version
+            ))
+            .unwrap()
+            .as_ref()
+            .to_vec()
+    }
+
+    fn test_dna_with_zomes() -> Dna {
+        let mut dna = Dna::new();
+        for zome_name in vec!["called", "uncalled"] {
+            let mut zome = Zome::default();
+            zome.code = DnaWasm { code: test_wasm() };
+            dna.zomes_mut().insert(zome_name.to_string(), zome);
+        }
+        dna
+    }
+
+    #[test]
+    fn lazy_policy_only_loads_called_zomes() {
+        let dna = test_dna_with_zomes();
+        let mut cache = WasmModuleCache::new(WasmLoadPolicy::Lazy);
+        cache.preload(&dna).unwrap();
+        assert!(!cache.is_loaded("called"));
+        assert!(!cache.is_loaded("uncalled"));
+
+        let code = &dna.get_wasm_from_zome_name("called").unwrap().code;
+        let module = cache.get_or_load("called", code).unwrap();
+        assert!(cache.is_loaded("called"));
+        assert!(!cache.is_loaded("uncalled"));
+        assert!(Arc::ptr_eq(&module, &cache.get("called").unwrap()));
+        assert!(cache.get("uncalled").is_none());
+
+        // second access is served from the cache
+        assert!(Arc::ptr_eq(
+            &module,
+            &cache.get_or_load("called", code).unwrap()
+        ));
+    }
+
+    #[test]
+    fn modules_are_cached_by_zome() {
+        let mut cache = WasmModuleCache::new(WasmLoadPolicy::Lazy);
+        let module = cache.get_or_load("zome", &test_wasm()).unwrap();
+        assert!(!Arc::ptr_eq(
+            &module,
+            &cache.get_or_load("other_zome", &test_wasm()).unwrap()
+        ));
+
+        // the code is only hashed when the zome gets loaded first
+        let changed_code = test_wasm_with_abi_version(HDK_ABI_VERSION);
+        assert!(Arc::ptr_eq(
+            &module,
+            &cache.get_or_load("zome", &changed_code).unwrap()
+        ));
+
+        // changed code gets parsed again once the cache was cleared
+        cache.clear();
+        assert!(!Arc::ptr_eq(
+            &module,
+            &cache.get_or_load("zome", &changed_code).unwrap()
+        ));
+    }
+
+    #[test]
+    fn cache_drops_oldest_modules_beyond_capacity() {
+        let mut cache = WasmModuleCache::new(WasmLoadPolicy::Lazy);
+        for index in 0..=WASM_CACHE_CAPACITY {
+            cache
+                .get_or_load(&format!("zome_{}", index), &test_wasm())
+                .unwrap();
+        }
+        assert!(!cache.is_loaded("zome_0"));
+        assert!(cache.is_loaded("zome_1"));
+        assert!(cache.is_loaded(&format!("zome_{}", WASM_CACHE_CAPACITY)));
+
+        // dropped modules get parsed again on their next use
+        cache.get_or_load("zome_0", &test_wasm()).unwrap();
+        assert!(cache.is_loaded("zome_0"));
+        assert!(!cache.is_loaded("zome_1"));
+    }
+
+    #[test]
+    fn eager_policy_loads_all_zomes() {
+        let dna = test_dna_with_zomes();
+        let mut cache = WasmModuleCache::new(WasmLoadPolicy::Eager);
+        cache.preload(&dna).unwrap();
+        assert!(cache.is_loaded("called"));
+        assert!(cache.is_loaded("uncalled"));
+    }
+
+    #[test]
+    fn rejects_incompatible_abi_version() {
+        let mut cache = WasmModuleCache::new(WasmLoadPolicy::Lazy);
+        assert!(cache
+            .get_or_load("current", &test_wasm_with_abi_version(HDK_ABI_VERSION))
+            .is_ok());
+        // code without a version marker is not checked
+        assert!(cache.get_or_load("unmarked", &test_wasm()).is_ok());
+
+        let incompatible = test_wasm_with_abi_version(HDK_ABI_VERSION + 1);
+        let expected_error = HolochainError::Dna(DnaError::AbiMismatch {
+            zome: String::from("outdated"),
+            found: HDK_ABI_VERSION + 1,
+            expected: HDK_ABI_VERSION,
+        });
+        assert_eq!(
+            Err(expected_error.clone()),
+            cache.get_or_load("outdated", &incompatible).map(|_| ())
+        );
+        assert!(!cache.is_loaded("outdated"));
+
+        // loading the DNA fails even without parsing its code
+        let mut dna = Dna::new();
+        let mut zome = Zome::default();
+        zome.code = DnaWasm { code: incompatible };
+        dna.zomes_mut().insert("outdated".to_string(), zome);
+        assert_eq!(Err(expected_error), cache.preload(&dna));
+        assert!(!cache.is_loaded("outdated"));
+    }
+
+    #[test]
+    fn wildcard_capability_exposes_exported_functions() {
+        let wasm = Wat2Wasm::new()
+            .canonicalize_lebs(false)
+            .write_debug_names(true)
+            .convert(
+                r#"(module
+                    (memory 1)
+                    (export "memory" (memory 0))
+                    (func (export "get_post"))
+                    (func (export "create_post"))
+                    (func (export "genesis"))
+                    (func (export "__hdk_validate_app_entry")))"#,
+            )
+            .unwrap()
+            .as_ref()
+            .to_vec();
+        let mut zome = Zome::default();
+        zome.code = DnaWasm { code: wasm };
+        for (cap_name, fn_name) in vec![("admin", "*"), ("public", "get_post")] {
+            let mut fn_declaration = FnDeclaration::new();
+            fn_declaration.name = String::from(fn_name);
+            let mut capability = Capability::new();
+            capability.functions.push(fn_declaration);
+            zome.capabilities.insert(String::from(cap_name), capability);
+        }
+        let mut dna = Dna::new();
+        dna.zomes_mut().insert("blog".to_string(), zome);
+
+        expand_wildcard_capabilities(&mut dna);
+
+        let fn_names = |cap_name: &str| -> Vec<String> {
+            dna.get_capability_with_zome_name("blog", cap_name)
+                .unwrap()
+                .functions
+                .iter()
+                .map(|f| f.name.clone())
+                .collect()
+        };
+        assert_eq!(vec!["get_post", "create_post"], fn_names("admin"));
+        assert_eq!(vec!["get_post"], fn_names("public"));
+    }
+
+    #[test]
+    fn parsed_module_test() {
+        assert!(DnaWasm { code: test_wasm() }.parsed_module().is_ok());
+        assert!(DnaWasm {
+            code: vec![0, 1, 2, 3]
+        }
+        .parsed_module()
+        .is_err());
+    }
+
+    #[test]
+    fn eager_policy_fails_fast_on_invalid_code() {
+        let mut dna = Dna::new();
+        let mut zome = Zome::default();
+        zome.code = DnaWasm {
+            code: vec![0, 1, 2, 3],
+        };
+        dna.zomes_mut().insert("broken".to_string(), zome);
+        let mut cache = WasmModuleCache::new(WasmLoadPolicy::Eager);
+        assert!(cache.preload(&dna).is_err());
+    }
+}
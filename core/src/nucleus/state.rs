@@ -19,7 +19,31 @@ impl Default for NucleusStatus {
     }
 }
 
-pub type ValidationResult = Result<(), String>;
+/// Outcome of validating an entry.
+/// A permanent failure means the entry is invalid and will stay invalid.
+/// A transient failure means it could not be validated right now (e.g. because something
+/// it depends on has not arrived yet) and might pass when validated again later.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationResult {
+    Pass,
+    Fail { permanent: bool, reason: String },
+}
+
+impl ValidationResult {
+    pub fn permanent_failure<S: Into<String>>(reason: S) -> Self {
+        ValidationResult::Fail {
+            permanent: true,
+            reason: reason.into(),
+        }
+    }
+
+    pub fn transient_failure<S: Into<String>>(reason: S) -> Self {
+        ValidationResult::Fail {
+            permanent: false,
+            reason: reason.into(),
+        }
+    }
+}
 
 /// The state-slice for the Nucleus.
 /// Holds the dynamic parts of the DNA, i.e. zome calls and validation requests.
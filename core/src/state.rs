@@ -10,10 +10,16 @@ use crate::{
     nucleus::state::NucleusState,
 };
 use holochain_core_types::{
-    cas::storage::ContentAddressableStorage,
+    cas::{
+        content::{Address, AddressableContent, Content},
+        storage::ContentAddressableStorage,
+    },
+    chain_header::ChainHeader,
     dna::Dna,
+    eav::EntityAttributeValue,
     entry::{entry_type::EntryType, Entry},
     error::{HcResult, HolochainError},
+    json::*,
 };
 use std::{
     collections::HashSet,
@@ -155,6 +161,40 @@ impl State {
     }
 }
 
+/// Serializable copy of everything an instance keeps in storage: the top of the agent's
+/// source chain and the content of its CAS and EAV storage.
+/// Created by Context::snapshot_state() and restored with Context::restore_state().
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, DefaultJson)]
+pub struct InstanceSnapshot {
+    pub top_chain_header: Option<ChainHeader>,
+    /// Content of the CAS by address, sorted by address
+    pub contents: Vec<(Address, String)>,
+    pub eavs: Vec<EntityAttributeValue>,
+}
+
+/// Content restored from an InstanceSnapshot, stored at the address it was captured from.
+/// Not every content in the CAS is stored under its hash (e.g. the AgentStateSnapshot).
+pub(crate) struct SnapshotContent {
+    pub address: Address,
+    pub content: String,
+}
+
+impl AddressableContent for SnapshotContent {
+    fn content(&self) -> Content {
+        Content::from(self.content.clone())
+    }
+
+    fn try_from_content(_content: &Content) -> Result<Self, HolochainError> {
+        Err(HolochainError::ErrorGeneric(String::from(
+            "The address of snapshot content can not be recovered from the content",
+        )))
+    }
+
+    fn address(&self) -> Address {
+        self.address.clone()
+    }
+}
+
 pub fn test_store(context: Arc<Context>) -> State {
     State::new(context)
 }
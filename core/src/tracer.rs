@@ -0,0 +1,93 @@
+//! Spans around the steps of the commit workflows (building the validation package,
+//! validating, committing, publishing or updating metadata).
+//! A Tracer registered on the Context gets told when each step starts and how long it took,
+//! so that slow or failing steps of a commit can be told apart.
+use crate::context::Context;
+use futures::future::Future;
+use holochain_core_types::{
+    cas::content::{Address, AddressableContent},
+    entry::{entry_type::EntryType, Entry},
+    error::HolochainError,
+};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// One step of a workflow, for one entry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Span {
+    pub name: &'static str,
+    pub entry_address: Address,
+    pub entry_type: EntryType,
+}
+
+impl Span {
+    pub fn new(name: &'static str, entry: &Entry) -> Self {
+        Span {
+            name,
+            entry_address: entry.address(),
+            entry_type: entry.entry_type(),
+        }
+    }
+}
+
+/// Receives the spans of an instance's workflows.
+pub trait Tracer: Send + Sync {
+    /// Called when the step starts.
+    fn enter(&self, span: &Span);
+
+    /// Called when the step is done, with its duration and error if it failed.
+    fn exit(&self, span: &Span, elapsed: Duration, error: Option<&HolochainError>);
+}
+
+/// Awaits the given future within the given span.
+/// Without a Tracer set on the Context this is the same as awaiting the future.
+pub async fn traced<'a, Fut, T>(
+    span: Span,
+    future: Fut,
+    context: &'a Arc<Context>,
+) -> Result<T, HolochainError>
+where
+    Fut: Future<Output = Result<T, HolochainError>>,
+{
+    let maybe_tracer = context.tracer();
+    if let Some(ref tracer) = maybe_tracer {
+        tracer.enter(&span);
+    }
+    let started_at = Instant::now();
+    let result = await!(future);
+    if let Some(ref tracer) = maybe_tracer {
+        tracer.exit(&span, started_at.elapsed(), result.as_ref().err());
+    }
+    result
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Records the names of entered and exited spans, e.g. "enter commit".
+    #[derive(Default)]
+    pub struct RecordingTracer {
+        pub events: Mutex<Vec<String>>,
+    }
+
+    impl Tracer for RecordingTracer {
+        fn enter(&self, span: &Span) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("enter {}", span.name));
+        }
+
+        fn exit(&self, span: &Span, _elapsed: Duration, error: Option<&HolochainError>) {
+            let outcome = if error.is_some() { " (failed)" } else { "" };
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("exit {}{}", span.name, outcome));
+        }
+    }
+}
@@ -1,17 +1,15 @@
 use crate::{
-    agent::actions::commit::commit_entry,
     context::Context,
     network::actions::publish::publish,
-    nucleus::actions::{
-        build_validation_package::build_validation_package, validate::validate_entry,
-    },
+    nucleus::actions::validate_and_commit::validate_and_commit,
+    tracer::{traced, Span},
 };
 
 use holochain_core_types::{
-    cas::content::{Address, AddressableContent},
+    cas::content::Address,
     entry::Entry,
     error::HolochainError,
-    validation::{EntryAction, EntryLifecycle, ValidationData},
+    validation::{EntryAction, EntryLifecycle},
 };
 use std::sync::Arc;
 
@@ -20,29 +18,53 @@ pub async fn author_entry<'a>(
     maybe_crud_link: Option<Address>,
     context: &'a Arc<Context>,
 ) -> Result<Address, HolochainError> {
-    // 1. Build the context needed for validation of the entry
-    let validation_package = await!(build_validation_package(&entry, &context))?;
-    let validation_data = ValidationData {
-        package: validation_package,
-        sources: vec![Address::from("<insert your agent key here>")],
-        lifecycle: EntryLifecycle::Chain,
-        action: EntryAction::Create,
-    };
-    // 2. Validate the entry
-    await!(validate_entry(entry.clone(), validation_data, &context))?;
-    // 3. Commit the entry
-    await!(commit_entry(entry.clone(), maybe_crud_link, &context))?;
-    // 4. Publish the valid entry to DHT. This will call Hold to itself
-    await!(publish(entry.address(), &context))
+    // 1. Validate and commit the entry
+    let address = await!(validate_and_commit(
+        entry,
+        maybe_crud_link,
+        EntryAction::Create,
+        EntryLifecycle::Chain,
+        context
+    ))?;
+    // 2. Publish the valid entry to DHT. This will call Hold to itself
+    await!(traced(
+        Span::new("publish", entry),
+        publish(address, &context),
+        context
+    ))
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::author_entry;
-    use crate::nucleus::actions::tests::*;
+    use crate::{context::Context, nucleus::actions::tests::*, tracer::tests::RecordingTracer};
     use futures::executor::block_on;
     use holochain_core_types::entry::test_entry;
-    use std::{thread, time};
+    use std::{sync::Arc, thread, time};
+
+    #[test]
+    /// test that every step of a commit is traced, in order
+    fn test_commit_emits_spans_in_order() {
+        let (_instance, context) = instance_by_name("jill", test_dna());
+        let tracer = Arc::new(RecordingTracer::default());
+        context.set_tracer(tracer.clone());
+
+        block_on(author_entry(&test_entry(), None, &context)).unwrap();
+
+        assert_eq!(
+            vec![
+                "enter build_validation_package",
+                "exit build_validation_package",
+                "enter validate",
+                "exit validate",
+                "enter commit",
+                "exit commit",
+                "enter publish",
+                "exit publish",
+            ],
+            *tracer.events.lock().unwrap()
+        );
+    }
 
     #[test]
     /// test that a commit will publish and entry to the dht of a connected instance via the mock network
@@ -71,4 +93,53 @@ pub mod tests {
             "{\"App\":[\"testEntryType\",\"\\\"test entry value\\\"\"]}".to_string(),
         );
     }
+
+    #[test]
+    /// test that a commit made while the network is paused gets published on resume
+    fn test_commit_while_network_paused() {
+        let mut dna = test_dna();
+        dna.set_uuid(String::from("test_commit_while_network_paused"));
+        let (_instance1, context1) = instance_by_name("jane", dna.clone());
+        let (_instance2, context2) = instance_by_name("joe", dna);
+
+        context1.pause_network();
+        let entry_address = block_on(author_entry(&test_entry(), None, &context1)).unwrap();
+        thread::sleep(time::Duration::from_millis(1000));
+
+        assert!(context1
+            .file_storage
+            .read()
+            .unwrap()
+            .contains(&entry_address)
+            .unwrap());
+        assert!(!context1
+            .state()
+            .unwrap()
+            .network()
+            .deferred_messages
+            .is_empty());
+        let published = || {
+            context2
+                .state()
+                .unwrap()
+                .dht()
+                .content_storage()
+                .read()
+                .unwrap()
+                .contains(&entry_address)
+                .unwrap()
+        };
+        assert!(!published());
+
+        Context::resume_network(&context1);
+        thread::sleep(time::Duration::from_millis(1000));
+
+        assert!(context1
+            .state()
+            .unwrap()
+            .network()
+            .deferred_messages
+            .is_empty());
+        assert!(published());
+    }
 }
@@ -1,26 +1,33 @@
-use crate::{context::Context, network, nucleus};
+use crate::{agent::entry_crypto::decrypt_entry, context::Context, network, nucleus};
 
 use holochain_core_types::{
     cas::content::Address, crud_status::CrudStatus, entry::EntryWithMeta, error::HolochainError,
 };
 use holochain_wasm_utils::api_serialization::get_entry::{
-    EntryHistory, GetEntryArgs, StatusRequestKind,
+    EntryHistory, GetEntryArgs, GetEntryOptions, StatusRequestKind,
 };
 use std::sync::Arc;
 
 /// Get Entry workflow
+/// Entries of encrypted types get decrypted (see agent::entry_crypto).
 pub async fn get_entry_with_meta_workflow<'a>(
     context: &'a Arc<Context>,
     address: &'a Address,
 ) -> Result<Option<EntryWithMeta>, HolochainError> {
     // 1. Try to get the entry locally (i.e. local DHT shard)
-    let maybe_entry_with_meta =
+    let mut maybe_entry_with_meta =
         nucleus::actions::get_entry::get_entry_with_meta(context, address.clone())?;
-    if maybe_entry_with_meta.is_some() {
-        return Ok(maybe_entry_with_meta);
-    }
     // 2. No result, so try on the network
-    await!(network::actions::get_entry::get_entry(context, &address))
+    if maybe_entry_with_meta.is_none() {
+        maybe_entry_with_meta = await!(network::actions::get_entry::get_entry(context, &address))?;
+    }
+    match maybe_entry_with_meta {
+        Some(mut entry_with_meta) => {
+            entry_with_meta.entry = decrypt_entry(entry_with_meta.entry, context)?;
+            Ok(Some(entry_with_meta))
+        }
+        None => Ok(None),
+    }
 }
 
 /// Get EntryHistory workflow
@@ -57,38 +64,77 @@ pub async fn get_entry_history_workflow<'a>(
     Ok(entry_history)
 }
 
-//#[cfg(test)]
-//pub mod tests {
-//    use crate::instance::tests::test_context_with_state;
-//    use futures::executor::block_on;
-//    use holochain_core_types::{
-//        cas::content::AddressableContent,
-//        crud_status::{create_crud_status_eav, CrudStatus},
-//        entry::test_entry,
-//    };
-//    use holochain_wasm_utils::api_serialization::get_entry::*;
-//
-//    #[test]
-//    fn can_get_entry_history_workflow() {
-//        let entry = test_entry();
-//        let context = test_context_with_state();
-//        let args = GetEntryArgs {
-//            address: entry.address(),
-//            options: GetEntryOptions {
-//                status_request: StatusRequestKind::Latest,
-//            },
-//        };
-//        let maybe_entry_history = block_on(super::get_entry_history_workflow(&context, &args));
-////        assert_eq!(0, maybe_entry_history.unwrap().entries.len());
-////        let content_storage = &context.state().unwrap().dht().content_storage().clone();
-////        (*content_storage.write().unwrap()).add(&entry).unwrap();
-////        let status_eav = create_crud_status_eav(&entry.address(), CrudStatus::LIVE);
-////        let meta_storage = &context.state().unwrap().dht().meta_storage().clone();
-////        (*meta_storage.write().unwrap())
-////            .add_eav(&status_eav)
-////            .unwrap();
-////        let maybe_entry_history = block_on(super::get_entry_history_workflow(&context, &args));
-////        let entry_history = maybe_entry_history.unwrap();
-////        assert_eq!(&entry, entry_history.entries.iter().next().unwrap());
-//    }
-//}
+/// All versions of the entry, starting at the given address and following the crud-links
+/// of update_entry() replacements, each with its address and CrudStatus.
+pub async fn get_entry_history<'a>(
+    context: &'a Arc<Context>,
+    address: &'a Address,
+) -> Result<EntryHistory, HolochainError> {
+    let args = GetEntryArgs {
+        address: address.clone(),
+        options: GetEntryOptions::new(StatusRequestKind::All),
+    };
+    await!(get_entry_history_workflow(context, &args))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::instance::tests::test_context_with_state;
+    use futures::executor::block_on;
+    use holochain_core_types::{
+        cas::content::AddressableContent,
+        crud_status::{create_crud_link_eav, create_crud_status_eav, CrudStatus},
+        entry::{test_entry_a, test_entry_b, test_entry_c},
+    };
+
+    #[test]
+    fn can_get_entry_history() {
+        let context = test_context_with_state();
+        let versions = vec![test_entry_a(), test_entry_b(), test_entry_c()];
+
+        let content_storage = &context.state().unwrap().dht().content_storage().clone();
+        let meta_storage = &context.state().unwrap().dht().meta_storage().clone();
+        for (index, entry) in versions.iter().enumerate() {
+            (*content_storage.write().unwrap()).add(entry).unwrap();
+            let (status, maybe_replacement) = match versions.get(index + 1) {
+                Some(replacement) => (CrudStatus::MODIFIED, Some(replacement.address())),
+                None => (CrudStatus::LIVE, None),
+            };
+            let mut meta_storage = meta_storage.write().unwrap();
+            meta_storage
+                .add_eav(&create_crud_status_eav(&entry.address(), status))
+                .unwrap();
+            if let Some(replacement) = maybe_replacement {
+                meta_storage
+                    .add_eav(&create_crud_link_eav(&entry.address(), &replacement))
+                    .unwrap();
+            }
+        }
+
+        let entry_history =
+            block_on(super::get_entry_history(&context, &versions[0].address())).unwrap();
+        assert_eq!(
+            versions
+                .iter()
+                .map(|entry| entry.address())
+                .collect::<Vec<_>>(),
+            entry_history.addresses
+        );
+        assert_eq!(
+            vec![CrudStatus::MODIFIED, CrudStatus::MODIFIED, CrudStatus::LIVE],
+            entry_history.crud_status
+        );
+        assert_eq!(
+            Some(&versions[2].address()),
+            entry_history.crud_links.get(&versions[1].address())
+        );
+
+        // the history starts at the given version
+        let entry_history =
+            block_on(super::get_entry_history(&context, &versions[1].address())).unwrap();
+        assert_eq!(
+            vec![versions[1].clone(), versions[2].clone()],
+            entry_history.entries
+        );
+    }
+}
@@ -57,6 +57,36 @@ impl KeyBuffer {
     }
 }
 
+/// the public identity key of an agent in its base64url encoded user representation
+/// this is also what the agent is addressed by
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PublicKey(String);
+
+impl PublicKey {
+    /// get the raw key buffer behind this key, applying reed-solomon parity correction
+    pub fn to_buffer(&self) -> Result<KeyBuffer, HolochainError> {
+        KeyBuffer::with_corrected(&self.0)
+    }
+}
+
+impl From<KeyBuffer> for PublicKey {
+    fn from(key_buffer: KeyBuffer) -> Self {
+        PublicKey(key_buffer.render())
+    }
+}
+
+impl From<PublicKey> for String {
+    fn from(public_key: PublicKey) -> Self {
+        public_key.0
+    }
+}
+
+impl From<PublicKey> for Address {
+    fn from(public_key: PublicKey) -> Self {
+        Address::from(public_key.0)
+    }
+}
+
 /// agent data that can be stored in the cas
 /// note thate the "address" of an agent entry is the base64url encoded
 /// public key identity string
@@ -98,6 +128,11 @@ impl AgentId {
         let key = base64::decode(&self.key).expect("corrupt identity key");
         KeyBuffer::with_raw(array_ref![key, 0, 64])
     }
+
+    /// get this agent's public key, fails if the key string is not a valid identity
+    pub fn public_key(&self) -> Result<PublicKey, HolochainError> {
+        Ok(PublicKey::from(KeyBuffer::with_corrected(&self.key)?))
+    }
 }
 
 impl AddressableContent for AgentId {
@@ -139,6 +174,23 @@ mod tests {
         format!("{{\"nick\":\"bob\",\"key\":\"{}\"}}", GOOD_ID).into()
     }
 
+    #[test]
+    fn it_should_provide_the_public_key() {
+        let agent_id = test_agent_id();
+        let public_key = agent_id.public_key().unwrap();
+        assert_eq!(agent_id.address(), Address::from(public_key.clone()));
+        assert_eq!(
+            agent_id.to_buffer().get_sig(),
+            public_key.to_buffer().unwrap().get_sig()
+        );
+
+        let broken = AgentId {
+            nick: String::from("broken"),
+            key: String::from("not a key"),
+        };
+        assert!(broken.public_key().is_err());
+    }
+
     #[test]
     fn it_should_allow_buffer_access() {
         let buf = test_agent_id().to_buffer();
@@ -0,0 +1,117 @@
+//! Capability grants and revocations are stored as EAV meta data on the agent's address,
+//! the same way CRUD status is stored on entries.
+//! Every grant and revocation of a capability carries a sequence number in its value, so
+//! that re-granting a revoked capability stores a new EAV and the latest one decides.
+use crate::{cas::content::Address, eav::EntityAttributeValue, hash::HashString};
+
+pub const GRANT_NAME: &str = "cap-grant";
+pub const REVOKE_NAME: &str = "cap-revoke";
+
+const SEQUENCE_SEPARATOR: char = '#';
+
+/// A capability of a zome that has been granted to an agent.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct CapabilityGrant {
+    pub zome_name: String,
+    pub capability_name: String,
+}
+
+impl CapabilityGrant {
+    pub fn new(zome_name: &str, capability_name: &str) -> Self {
+        CapabilityGrant {
+            zome_name: zome_name.to_string(),
+            capability_name: capability_name.to_string(),
+        }
+    }
+}
+
+impl From<CapabilityGrant> for HashString {
+    fn from(grant: CapabilityGrant) -> HashString {
+        HashString::from(format!("{}/{}", grant.zome_name, grant.capability_name))
+    }
+}
+
+impl From<HashString> for CapabilityGrant {
+    fn from(value: HashString) -> CapabilityGrant {
+        let value = String::from(value);
+        let mut parts = value.splitn(2, '/');
+        let zome_name = parts.next().unwrap_or_default();
+        let capability_name = parts.next().unwrap_or_default();
+        CapabilityGrant::new(zome_name, capability_name)
+    }
+}
+
+fn sequenced_value(grant: &CapabilityGrant, sequence: u64) -> HashString {
+    HashString::from(format!(
+        "{}{}{}",
+        String::from(HashString::from(grant.to_owned())),
+        SEQUENCE_SEPARATOR,
+        sequence
+    ))
+}
+
+/// Splits the value of a grant or revocation EAV into the grant and its sequence number.
+pub fn grant_and_sequence(value: HashString) -> (CapabilityGrant, u64) {
+    let value = String::from(value);
+    match value.rfind(SEQUENCE_SEPARATOR) {
+        Some(index) => (
+            CapabilityGrant::from(HashString::from(&value[..index])),
+            value[index + 1..].parse().unwrap_or(0),
+        ),
+        None => (CapabilityGrant::from(HashString::from(value)), 0),
+    }
+}
+
+pub fn create_capability_grant_eav(
+    agent: &Address,
+    grant: &CapabilityGrant,
+    sequence: u64,
+) -> EntityAttributeValue {
+    EntityAttributeValue::new(
+        agent,
+        &GRANT_NAME.to_string(),
+        &sequenced_value(grant, sequence),
+    )
+}
+
+pub fn create_capability_revocation_eav(
+    agent: &Address,
+    grant: &CapabilityGrant,
+    sequence: u64,
+) -> EntityAttributeValue {
+    EntityAttributeValue::new(
+        agent,
+        &REVOKE_NAME.to_string(),
+        &sequenced_value(grant, sequence),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grant_hash_string_round_trip() {
+        let grant = CapabilityGrant::new("blog", "main");
+        assert_eq!(
+            HashString::from("blog/main"),
+            HashString::from(grant.clone())
+        );
+        assert_eq!(grant, CapabilityGrant::from(HashString::from("blog/main")));
+    }
+
+    #[test]
+    fn grant_eav_test() {
+        let agent = Address::from("agent");
+        let grant = CapabilityGrant::new("blog", "main");
+
+        let eav = create_capability_grant_eav(&agent, &grant, 1);
+        assert_eq!(agent, eav.entity());
+        assert_eq!(GRANT_NAME.to_string(), eav.attribute());
+        assert_eq!((grant.clone(), 1), grant_and_sequence(eav.value()));
+
+        let eav = create_capability_revocation_eav(&agent, &grant, 2);
+        assert_eq!(REVOKE_NAME.to_string(), eav.attribute());
+        assert_eq!((grant, 2), grant_and_sequence(eav.value()));
+    }
+}
@@ -18,7 +18,7 @@ use uuid::Uuid;
 /// content addressable store (CAS)
 /// implements storage in memory or persistently
 /// anything implementing AddressableContent can be added and fetched by address
-/// CAS is append only
+/// CAS is append only, content only ever gets removed by garbage collection
 pub trait ContentAddressableStorage: objekt::Clone + Send + Sync + Debug {
     /// adds AddressableContent to the ContentAddressableStorage by its Address as Content
     fn add(&mut self, content: &AddressableContent) -> Result<(), HolochainError>;
@@ -29,6 +29,11 @@ pub trait ContentAddressableStorage: objekt::Clone + Send + Sync + Debug {
     /// AddressableContent::from_content() can be used to allow the compiler to infer the type
     /// @see the fetch implementation for ExampleCas in the cas module tests
     fn fetch(&self, address: &Address) -> Result<Option<Content>, HolochainError>;
+    /// returns the Addresses of all Content in the Store
+    fn addresses(&self) -> Result<HashSet<Address>, HolochainError>;
+    /// removes the Content at the given Address, does nothing if it is not in the Store
+    /// only meant to be used by garbage collection
+    fn remove(&mut self, address: &Address) -> Result<(), HolochainError>;
     //needed to find a way to compare two different CAS for partialord derives.
     //easiest solution was to just compare two ids which are based on uuids
     fn get_id(&self) -> Uuid;
@@ -78,6 +83,15 @@ impl ContentAddressableStorage for ExampleContentAddressableStorage {
         Ok(self.content.read()?.unthreadable_fetch(address)?)
     }
 
+    fn addresses(&self) -> Result<HashSet<Address>, HolochainError> {
+        Ok(self.content.read()?.storage.keys().cloned().collect())
+    }
+
+    fn remove(&mut self, address: &Address) -> Result<(), HolochainError> {
+        self.content.write()?.storage.remove(address);
+        Ok(())
+    }
+
     fn get_id(&self) -> Uuid {
         Uuid::new_v4()
     }
@@ -184,6 +198,20 @@ where
                 Ok(Some(other_content.clone())),
                 cas.fetch(&other_content.address())
             );
+            let addresses = cas.addresses().expect("could not list cas addresses");
+            assert!(addresses.contains(&content.address()));
+            assert!(addresses.contains(&other_content.address()));
+        }
+
+        // removed content disappears from every view on the data
+        assert_eq!(Ok(()), self.cas.remove(&content.address()));
+        assert_eq!(Ok(()), self.cas.remove(&content.address()));
+
+        for cas in both_cas.iter() {
+            assert_eq!(Ok(false), cas.contains(&content.address()));
+            assert_eq!(Ok(None), cas.fetch(&content.address()));
+            assert_eq!(Ok(true), cas.contains(&other_content.address()));
+            assert!(!cas.addresses().unwrap().contains(&content.address()));
         }
 
         // show consistent view on data across threads
@@ -1,9 +1,9 @@
 use crate::{
-    agent::test_agent_id,
+    agent::{test_agent_id, AgentId},
     cas::content::{Address, AddressableContent, Content},
     entry::{
         entry_type::{test_entry_type, EntryType},
-        test_entry,
+        test_entry, Entry,
     },
     error::HolochainError,
     json::JsonString,
@@ -127,6 +127,64 @@ impl AddressableContent for ChainHeader {
     }
 }
 
+/// Builds the header for committing the given entry by the given agent on top of the given
+/// previous header, outside of a source chain, e.g. for custom publish flows or sync tooling.
+/// Only the first entry of a chain (the DNA) may be built without a previous header.
+///
+/// link_same_type can only be derived from the previous header itself: it is set if the
+/// previous header is of the same entry type and left empty otherwise.
+pub fn build_header(
+    entry: &Entry,
+    previous_header: Option<&ChainHeader>,
+    agent: &AgentId,
+    timestamp: &Iso8601,
+) -> Result<ChainHeader, HolochainError> {
+    let entry_type = entry.entry_type();
+    let link_same_type = previous_header
+        .filter(|previous_header| *previous_header.entry_type() == entry_type)
+        .map(|previous_header| previous_header.address());
+    let header = ChainHeader::new(
+        &entry_type,
+        &entry.address(),
+        &vec![agent.address()],
+        // @TODO signatures
+        &vec![Signature::from("")],
+        &previous_header.map(|previous_header| previous_header.address()),
+        &link_same_type,
+        &None,
+        timestamp,
+    );
+    check_header(&header, entry, previous_header)?;
+    Ok(header)
+}
+
+/// Checks that the header belongs to the given entry and links to the given previous header.
+pub fn check_header(
+    header: &ChainHeader,
+    entry: &Entry,
+    previous_header: Option<&ChainHeader>,
+) -> Result<(), HolochainError> {
+    if *header.entry_address() != entry.address() || *header.entry_type() != entry.entry_type() {
+        return Err(HolochainError::ErrorGeneric(format!(
+            "Header {} does not belong to entry {}",
+            header.address(),
+            entry.address()
+        )));
+    }
+    if header.link() != previous_header.map(|previous_header| previous_header.address()) {
+        return Err(HolochainError::ErrorGeneric(format!(
+            "Header {} does not link to the previous header",
+            header.address()
+        )));
+    }
+    if previous_header.is_none() && entry.entry_type() != EntryType::Dna {
+        return Err(HolochainError::ErrorGeneric(String::from(
+            "Only the DNA entry can start a chain without a previous header",
+        )));
+    }
+    Ok(())
+}
+
 /// returns a dummy header for use in tests
 pub fn test_chain_header() -> ChainHeader {
     ChainHeader::new(
@@ -148,11 +206,13 @@ pub fn test_sources() -> Vec<Address> {
 #[cfg(test)]
 pub mod tests {
     use crate::{
+        agent::test_agent_id,
         cas::content::{Address, AddressableContent},
-        chain_header::{test_chain_header, test_sources, ChainHeader},
+        chain_header::{build_header, check_header, test_chain_header, test_sources, ChainHeader},
+        dna::Dna,
         entry::{
             entry_type::{test_entry_type, test_entry_type_a, test_entry_type_b},
-            test_entry, test_entry_a, test_entry_b,
+            test_entry, test_entry_a, test_entry_b, Entry,
         },
         signature::{test_signature_b, test_signatures},
         time::test_iso_8601,
@@ -252,6 +312,34 @@ pub mod tests {
         assert_ne!(chain_header.address(), Address::new());
     }
 
+    #[test]
+    /// tests for build_header()
+    fn build_header_test() {
+        let agent = test_agent_id();
+        let dna_entry = Entry::Dna(Dna::new());
+        let first = build_header(&dna_entry, None, &agent, &test_iso_8601()).unwrap();
+        assert_eq!(None, first.link());
+        assert!(build_header(&test_entry(), None, &agent, &test_iso_8601()).is_err());
+
+        let entry = test_entry();
+        let second = build_header(&entry, Some(&first), &agent, &test_iso_8601()).unwrap();
+        assert_eq!(&entry.address(), second.entry_address());
+        assert_eq!(&entry.entry_type(), second.entry_type());
+        assert_eq!(&vec![agent.address()], second.sources());
+        assert_eq!(Some(first.address()), second.link());
+        assert_eq!(None, second.link_same_type());
+        assert_eq!(None, second.link_crud());
+        assert_eq!(&test_iso_8601(), second.timestamp());
+        assert_eq!(Ok(()), check_header(&second, &entry, Some(&first)));
+
+        let third = build_header(&entry, Some(&second), &agent, &test_iso_8601()).unwrap();
+        assert_eq!(Some(second.address()), third.link_same_type());
+
+        // a header does not fit another entry or previous header
+        assert!(check_header(&second, &test_entry_a(), Some(&first)).is_err());
+        assert!(check_header(&third, &entry, Some(&first)).is_err());
+    }
+
     #[test]
     /// tests for header.entry_type()
     fn entry_type() {
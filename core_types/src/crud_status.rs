@@ -11,6 +11,8 @@ use std::convert::TryInto;
 // @see https://github.com/holochain/holochain-rust/issues/143
 pub const STATUS_NAME: &str = "crud-status";
 pub const LINK_NAME: &str = "crud-link";
+/// Indexes deletion entries by the address of the entry they delete
+pub const DELETION_NAME: &str = "crud-deletion";
 
 pub fn create_crud_status_eav(address: &Address, status: CrudStatus) -> EntityAttributeValue {
     EntityAttributeValue::new(
@@ -24,6 +26,10 @@ pub fn create_crud_link_eav(from: &Address, to: &Address) -> EntityAttributeValu
     EntityAttributeValue::new(from, &LINK_NAME.to_string(), to)
 }
 
+pub fn create_deletion_eav(deleted: &Address, deletion: &Address) -> EntityAttributeValue {
+    EntityAttributeValue::new(deleted, &DELETION_NAME.to_string(), deletion)
+}
+
 bitflags! {
     #[derive(Default, Serialize, Deserialize, DefaultJson)]
     /// the CRUD status of a Pair is stored as EntryMeta in the hash table, NOT in the entry itself
@@ -15,29 +15,37 @@
 //! let name = String::from("My Holochain DNA");
 //!
 //! let mut dna = Dna::new();
-//! dna.name = name.clone();
+//! dna.set_name(name.clone());
 //!
 //! let json = JsonString::from(dna.clone());
 //!
 //! let dna2 = Dna::try_from(json).expect("could not restore DNA from JSON");
-//! assert_eq!(name, dna2.name);
+//! assert_eq!(name, dna2.name());
 //! ```
 
 pub mod wasm;
 pub mod zome;
 
 use crate::{
-    dna::zome::{capabilities::Capability, entry_types::EntryTypeDef},
-    entry::entry_type::EntryType,
+    dna::zome::{
+        capabilities::{Capability, FnDeclaration, FnParameter},
+        entry_types::{EntryTypeDef, Mutability},
+    },
+    entry::{entry_type::EntryType, Entry},
     error::{DnaError, HolochainError},
-    json::JsonString,
+    hash::{sha2_256_multihash, DigestLength, HashString},
+    json::{canonical_json, JsonString},
 };
 use entry::entry_type::AppEntryType;
 use multihash;
+use rust_base58::ToBase58;
 use serde_json::{self, Value};
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Write},
     hash::{Hash, Hasher},
+    io::{self, Read},
+    sync::{Arc, RwLock},
 };
 use uuid::Uuid;
 
@@ -51,36 +59,285 @@ fn new_uuid() -> String {
     Uuid::new_v4().to_string()
 }
 
+/// deterministic uuid for a seed, the seed is spread over all 16 bytes with splitmix64
+fn seeded_uuid(seed: u64) -> String {
+    let mut state = seed;
+    let mut bytes = [0u8; 16];
+    for chunk in bytes.chunks_mut(8) {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        for (i, byte) in chunk.iter_mut().enumerate() {
+            *byte = (z >> (56 - 8 * i)) as u8;
+        }
+    }
+    Uuid::from_bytes(bytes).to_string()
+}
+
+/// Entry type names coming from zome calls are untrusted, so they get checked
+/// instead of asserted.
+fn check_app_entry_type_name(entry_type_name: &str) -> Result<(), DnaError> {
+    if EntryType::has_valid_app_name(entry_type_name) {
+        Ok(())
+    } else {
+        Err(DnaError::InvalidAppEntryTypeName(format!(
+            "'{}' is not a valid app entry type name",
+            entry_type_name
+        )))
+    }
+}
+
+/// Canonical JSON of a Dna, computed on first use by PartialEq and Hash.
+/// Every method that changes the Dna drops it.
+#[derive(Default)]
+struct CanonicalJsonCache(RwLock<Option<Arc<String>>>);
+
+impl CanonicalJsonCache {
+    fn get_or_compute<F: FnOnce() -> String>(&self, compute: F) -> Arc<String> {
+        if let Some(ref json) = *self.0.read().unwrap() {
+            return json.clone();
+        }
+        let json = Arc::new(compute());
+        *self.0.write().unwrap() = Some(json.clone());
+        json
+    }
+
+    fn invalidate(&self) {
+        *self.0.write().unwrap() = None;
+    }
+}
+
+impl Clone for CanonicalJsonCache {
+    fn clone(&self) -> Self {
+        CanonicalJsonCache(RwLock::new(self.0.read().unwrap().clone()))
+    }
+}
+
+impl fmt::Debug for CanonicalJsonCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CanonicalJsonCache")
+    }
+}
+
+/// An example entry shipped with a DNA together with its expected validation outcome.
+/// Running these lets DNA authors catch regressions in their validation callbacks.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TestVector {
+    /// The app entry type the example entry is committed as.
+    pub entry_type: EntryType,
+
+    /// The example entry's value.
+    pub value: Value,
+
+    /// Whether the entry is expected to pass validation.
+    pub expect_valid: bool,
+}
+
+impl TestVector {
+    pub fn new(entry_type: EntryType, value: Value, expect_valid: bool) -> Self {
+        TestVector {
+            entry_type,
+            value,
+            expect_valid,
+        }
+    }
+
+    /// Builds the entry this test vector describes.
+    pub fn entry(&self) -> Result<Entry, DnaError> {
+        match &self.entry_type {
+            EntryType::App(app_entry_type) => Ok(Entry::App(
+                app_entry_type.to_owned(),
+                JsonString::from(self.value.clone()),
+            )),
+            _ => Err(DnaError::TestVectorFailed(format!(
+                "Test vector entry type {:?} is not an app entry type",
+                self.entry_type,
+            ))),
+        }
+    }
+}
+
+/// Location of a zome function declaration: the zome and the capability declaring it.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FunctionPath {
+    pub zome: String,
+    pub capability: String,
+}
+
+/// A reason why a DNA can not read data written by an older DNA.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DataIncompatibility {
+    /// The entry type does not exist anymore.
+    RemovedEntryType(EntryType),
+    /// The entry type does not require the field anymore.
+    RemovedRequiredField(EntryType, String),
+    /// The entry type requires a field that existing entries may not have.
+    AddedRequiredField(EntryType, String),
+    /// The entry type does not allow updates or deletions it used to allow.
+    TightenedMutability(EntryType, Mutability, Mutability),
+}
+
+/// Result of Dna::is_data_compatible_with().
+#[derive(Clone, Debug, PartialEq)]
+pub enum DataCompatibility {
+    Compatible,
+    Incompatible(Vec<DataIncompatibility>),
+}
+
+impl DataCompatibility {
+    pub fn is_compatible(&self) -> bool {
+        *self == DataCompatibility::Compatible
+    }
+}
+
+/// A difference between two DNAs, as found by Dna::diff().
+#[derive(Clone, Debug, PartialEq)]
+pub enum DnaDifference {
+    /// A top-level field changed: field name, old and new value.
+    FieldChanged(String, String, String),
+    ZomeAdded(String),
+    ZomeRemoved(String),
+    /// The description, config or code of the zome changed: zome and field name.
+    ZomeChanged(String, String),
+    EntryTypeAdded(String, EntryType),
+    EntryTypeRemoved(String, EntryType),
+    EntryTypeChanged(String, EntryType),
+    CapabilityAdded(String, String),
+    CapabilityRemoved(String, String),
+    CapabilityChanged(String, String),
+    /// Properties are identified by their JSON pointer, e.g. "/limits/max_posts".
+    PropertyAdded(String),
+    PropertyRemoved(String),
+    PropertyChanged(String),
+    TestVectorsChanged,
+}
+
+impl fmt::Display for DnaDifference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DnaDifference::FieldChanged(field, old, new) => {
+                write!(f, "{} changed from '{}' to '{}'", field, old, new)
+            }
+            DnaDifference::ZomeAdded(zome) => write!(f, "zome '{}' added", zome),
+            DnaDifference::ZomeRemoved(zome) => write!(f, "zome '{}' removed", zome),
+            DnaDifference::ZomeChanged(zome, field) => {
+                write!(f, "zome '{}' {} changed", zome, field)
+            }
+            DnaDifference::EntryTypeAdded(zome, entry_type) => {
+                write!(f, "zome '{}' entry type '{}' added", zome, entry_type)
+            }
+            DnaDifference::EntryTypeRemoved(zome, entry_type) => {
+                write!(f, "zome '{}' entry type '{}' removed", zome, entry_type)
+            }
+            DnaDifference::EntryTypeChanged(zome, entry_type) => {
+                write!(f, "zome '{}' entry type '{}' changed", zome, entry_type)
+            }
+            DnaDifference::CapabilityAdded(zome, capability) => {
+                write!(f, "zome '{}' capability '{}' added", zome, capability)
+            }
+            DnaDifference::CapabilityRemoved(zome, capability) => {
+                write!(f, "zome '{}' capability '{}' removed", zome, capability)
+            }
+            DnaDifference::CapabilityChanged(zome, capability) => {
+                write!(f, "zome '{}' capability '{}' changed", zome, capability)
+            }
+            DnaDifference::PropertyAdded(path) => write!(f, "property '{}' added", path),
+            DnaDifference::PropertyRemoved(path) => write!(f, "property '{}' removed", path),
+            DnaDifference::PropertyChanged(path) => write!(f, "property '{}' changed", path),
+            DnaDifference::TestVectorsChanged => write!(f, "test vectors changed"),
+        }
+    }
+}
+
+/// Keys only in new, keys only in old and keys in both with different values, each in
+/// key order.
+fn diff_keys<'a, K: Ord, V: PartialEq>(
+    old: &'a BTreeMap<K, V>,
+    new: &'a BTreeMap<K, V>,
+) -> (Vec<&'a K>, Vec<&'a K>, Vec<&'a K>) {
+    let added = new.keys().filter(|key| !old.contains_key(*key)).collect();
+    let removed = old.keys().filter(|key| !new.contains_key(*key)).collect();
+    let changed = old
+        .iter()
+        .filter(|(key, value)| new.get(*key).map(|new_value| new_value != *value) == Some(true))
+        .map(|(key, _)| key)
+        .collect();
+    (added, removed, changed)
+}
+
+/// Collects the differences between two property values, descending into objects.
+/// Arrays and scalars are compared as a whole.
+fn diff_properties(path: &str, old: &Value, new: &Value, differences: &mut Vec<DnaDifference>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+            for key in keys {
+                let key_path = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+                match (old.get(key), new.get(key)) {
+                    (Some(old_value), Some(new_value)) => {
+                        diff_properties(&key_path, old_value, new_value, differences)
+                    }
+                    (None, Some(_)) => differences.push(DnaDifference::PropertyAdded(key_path)),
+                    (Some(_), None) => differences.push(DnaDifference::PropertyRemoved(key_path)),
+                    (None, None) => unreachable!(),
+                }
+            }
+        }
+        (old, new) => {
+            if old != new {
+                differences.push(DnaDifference::PropertyChanged(path.to_string()));
+            }
+        }
+    }
+}
+
 /// Represents the top-level holochain dna object.
+/// The fields are only accessible through methods, so that every change drops the
+/// canonical JSON that equality and hashing cache.
 #[derive(Serialize, Deserialize, Clone, Debug, DefaultJson)]
 pub struct Dna {
     /// The top-level "name" of a holochain application.
     #[serde(default)]
-    pub name: String,
+    name: String,
 
     /// The top-level "description" of a holochain application.
     #[serde(default)]
-    pub description: String,
+    description: String,
 
     /// The semantic version of your holochain application.
     #[serde(default)]
-    pub version: String,
+    version: String,
 
     /// A unique identifier to distinguish your holochain application.
     #[serde(default = "new_uuid")]
-    pub uuid: String,
+    uuid: String,
 
     /// Which version of the holochain dna spec does this represent?
     #[serde(default)]
-    pub dna_spec_version: String,
+    dna_spec_version: String,
 
     /// Any arbitrary application properties can be included in this object.
     #[serde(default = "empty_object")]
-    pub properties: Value,
+    properties: Value,
 
     /// An array of zomes associated with your holochain application.
     #[serde(default)]
-    pub zomes: BTreeMap<String, zome::Zome>,
+    zomes: BTreeMap<String, zome::Zome>,
+
+    /// Example entries with their expected validation outcome, for self-testing the DNA.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    test_vectors: Vec<TestVector>,
+
+    /// Maximum number of entries an agent's source chain may hold, unbounded if not set.
+    /// Commits beyond it fail with HolochainError::ChainFull.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_chain_length: Option<u64>,
+
+    /// Equality and hashing compare the canonical JSON, which is cached here.
+    #[serde(skip)]
+    canonical_json: CanonicalJsonCache,
 }
 
 impl Default for Dna {
@@ -94,6 +351,9 @@ impl Default for Dna {
             dna_spec_version: String::from("2.0"),
             properties: empty_object(),
             zomes: BTreeMap::new(),
+            test_vectors: Vec::new(),
+            max_chain_length: None,
+            canonical_json: CanonicalJsonCache::default(),
         }
     }
 }
@@ -107,13 +367,49 @@ impl Dna {
     /// use holochain_core_types::dna::Dna;
     ///
     /// let dna = Dna::new();
-    /// assert_eq!("", dna.name);
+    /// assert_eq!("", dna.name());
     ///
     /// ```
     pub fn new() -> Self {
         Default::default()
     }
 
+    /// Create a new in-memory dna structure whose uuid is derived from the given seed.
+    /// Meant for tests that need distinct but reproducible DNAs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use holochain_core_types::dna::Dna;
+    ///
+    /// assert_eq!(Dna::new_with_seed(1).uuid(), Dna::new_with_seed(1).uuid());
+    /// assert_ne!(Dna::new_with_seed(1).uuid(), Dna::new_with_seed(2).uuid());
+    ///
+    /// ```
+    pub fn new_with_seed(seed: u64) -> Self {
+        Dna {
+            uuid: seeded_uuid(seed),
+            ..Default::default()
+        }
+    }
+
+    /// Deserializes a DNA straight from a JSON source like a file, without reading it into
+    /// a string first. Invalid JSON is rejected with DnaError::SerializationError.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use holochain_core_types::dna::Dna;
+    ///
+    /// let dna = Dna::from_reader(r#"{"name": "from reader"}"#.as_bytes()).unwrap();
+    /// assert_eq!("from reader", dna.name());
+    ///
+    /// ```
+    pub fn from_reader<R: Read>(reader: R) -> Result<Dna, DnaError> {
+        serde_json::from_reader(reader)
+            .map_err(|error| DnaError::SerializationError(error.to_string()))
+    }
+
     /// Generate a pretty-printed json string from an in-memory dna struct.
     ///
     /// # Examples
@@ -129,11 +425,185 @@ impl Dna {
         serde_json::to_string_pretty(self)
     }
 
+    /// Serializes the DNA as compact JSON straight into a writer like a file,
+    /// without building the JSON string in memory first.
+    pub fn to_writer<W: io::Write>(&self, writer: W) -> serde_json::Result<()> {
+        serde_json::to_writer(writer, self)
+    }
+
+    /// Like to_writer() but pretty-printed, giving the same output as to_json_pretty().
+    pub fn to_writer_pretty<W: io::Write>(&self, writer: W) -> serde_json::Result<()> {
+        serde_json::to_writer_pretty(writer, self)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+        self.invalidate_cached_json();
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn set_description(&mut self, description: String) {
+        self.description = description;
+        self.invalidate_cached_json();
+    }
+
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    pub fn set_version(&mut self, version: String) {
+        self.version = version;
+        self.invalidate_cached_json();
+    }
+
+    pub fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    pub fn set_uuid(&mut self, uuid: String) {
+        self.uuid = uuid;
+        self.invalidate_cached_json();
+    }
+
+    pub fn dna_spec_version(&self) -> &str {
+        &self.dna_spec_version
+    }
+
+    pub fn set_dna_spec_version(&mut self, dna_spec_version: String) {
+        self.dna_spec_version = dna_spec_version;
+        self.invalidate_cached_json();
+    }
+
+    pub fn properties(&self) -> &Value {
+        &self.properties
+    }
+
+    pub fn set_properties(&mut self, properties: Value) {
+        self.properties = properties;
+        self.invalidate_cached_json();
+    }
+
+    pub fn zomes(&self) -> &BTreeMap<String, zome::Zome> {
+        &self.zomes
+    }
+
+    /// Mutable access to the zomes, for adding, removing or changing zomes in place.
+    pub fn zomes_mut(&mut self) -> &mut BTreeMap<String, zome::Zome> {
+        self.invalidate_cached_json();
+        &mut self.zomes
+    }
+
+    pub fn test_vectors(&self) -> &[TestVector] {
+        &self.test_vectors
+    }
+
+    pub fn set_test_vectors(&mut self, test_vectors: Vec<TestVector>) {
+        self.test_vectors = test_vectors;
+        self.invalidate_cached_json();
+    }
+
+    pub fn max_chain_length(&self) -> Option<u64> {
+        self.max_chain_length
+    }
+
+    pub fn set_max_chain_length(&mut self, max_chain_length: Option<u64>) {
+        self.max_chain_length = max_chain_length;
+        self.invalidate_cached_json();
+    }
+
+    /// The canonical JSON that equality and hashing compare, serialized on first use.
+    fn cached_canonical_json(&self) -> Arc<String> {
+        self.canonical_json
+            .get_or_compute(|| canonical_json(self).expect("DNA should serialize"))
+    }
+
+    fn invalidate_cached_json(&self) {
+        self.canonical_json.invalidate();
+    }
+
+    /// Deep-merges the overlay object into the DNA's properties, e.g. to parameterize a base
+    /// DNA per environment. Overlay keys replace base keys, except that nested objects get
+    /// merged recursively. Arrays get replaced as a whole.
+    /// Overlays that are no JSON object get rejected with DnaError::InvalidProperties.
+    pub fn merge_properties(&mut self, overlay: &Value) -> Result<(), DnaError> {
+        fn merge(base: &mut Value, overlay: &Value) {
+            match (base, overlay) {
+                (Value::Object(base), Value::Object(overlay)) => {
+                    for (key, overlay_value) in overlay {
+                        merge(
+                            base.entry(key.clone()).or_insert(Value::Null),
+                            overlay_value,
+                        );
+                    }
+                }
+                (base, overlay) => *base = overlay.clone(),
+            }
+        }
+
+        if !overlay.is_object() {
+            return Err(DnaError::InvalidProperties(format!(
+                "Properties overlay must be a JSON object, got {}",
+                overlay
+            )));
+        }
+        if !self.properties.is_object() {
+            self.properties = empty_object();
+        }
+        merge(&mut self.properties, overlay);
+        self.invalidate_cached_json();
+        Ok(())
+    }
+
     /// Return a Zome
     pub fn get_zome(&self, zome_name: &str) -> Option<&zome::Zome> {
         self.zomes.get(zome_name)
     }
 
+    /// Names of all zomes, in alphabetical order.
+    pub fn zome_names(&self) -> impl Iterator<Item = &String> {
+        self.zomes.keys()
+    }
+
+    /// Names of all capabilities of a zome in alphabetical order, None if there is no such zome.
+    pub fn capability_names(&self, zome_name: &str) -> Option<Vec<String>> {
+        self.get_zome(zome_name)
+            .map(|zome| zome.capabilities.keys().cloned().collect())
+    }
+
+    /// Return a Zome for changing it in place.
+    pub fn get_zome_mut(&mut self, zome_name: &str) -> Option<&mut zome::Zome> {
+        self.zomes_mut().get_mut(zome_name)
+    }
+
+    /// Adds a new capability to a zome.
+    /// Fails with DnaError::ZomeNotFound if there is no such zome and with
+    /// DnaError::DuplicateCapability if the zome already has a capability of that name.
+    pub fn add_capability(
+        &mut self,
+        zome_name: &str,
+        cap_name: &str,
+        cap: Capability,
+    ) -> Result<(), DnaError> {
+        let zome = self
+            .get_zome_mut(zome_name)
+            .ok_or_else(|| DnaError::ZomeNotFound(format!("Zome '{}' not found", zome_name)))?;
+        if zome.capabilities.contains_key(cap_name) {
+            return Err(DnaError::DuplicateCapability(format!(
+                "Capability '{}' already exists in Zome '{}'",
+                cap_name, zome_name
+            )));
+        }
+        zome.capabilities.insert(cap_name.to_string(), cap);
+        Ok(())
+    }
+
     /// Return a Zome's Capability from a Zome and a Capability name.
     pub fn get_capability<'a>(
         &'a self,
@@ -150,6 +620,25 @@ impl Dna {
         Some(&zome.code)
     }
 
+    /// Like get_wasm_from_zome_name() but tells apart a missing zome (ZomeNotFound) from a
+    /// zome without code (EmptyWasm).
+    pub fn get_wasm_from_zome_name_checked<T: Into<String>>(
+        &self,
+        zome_name: T,
+    ) -> Result<&wasm::DnaWasm, DnaError> {
+        let zome_name = zome_name.into();
+        let zome = self
+            .get_zome(&zome_name)
+            .ok_or_else(|| DnaError::ZomeNotFound(format!("Zome '{}' not found", zome_name)))?;
+        if zome.code.code.is_empty() {
+            return Err(DnaError::EmptyWasm(format!(
+                "Zome '{}' has no WASM code",
+                zome_name
+            )));
+        }
+        Ok(&zome.code)
+    }
+
     /// Return a Zome's Capability from a Zome name and Capability name.
     pub fn get_capability_with_zome_name(
         &self,
@@ -177,62 +666,537 @@ impl Dna {
         Ok(cap.unwrap())
     }
 
-    /// Return the name of the zome holding a specified app entry_type
+    /// Return the app entry types a zome function declares it may commit.
+    /// None if the function is not declared in the given capability.
+    pub fn commits_entry_types(
+        &self,
+        zome_name: &str,
+        cap_name: &str,
+        fn_name: &str,
+    ) -> Option<&Vec<String>> {
+        self.get_capability_with_zome_name(zome_name, cap_name)
+            .ok()?
+            .functions
+            .iter()
+            .find(|fn_declaration| fn_declaration.name == fn_name)
+            .map(|fn_declaration| &fn_declaration.commits_entry_types)
+    }
+
+    /// Return every zome and capability declaring a function of the given name,
+    /// ordered by zome and capability name. More than one path means the name alone
+    /// is ambiguous.
+    pub fn find_function(&self, fn_name: &str) -> Vec<FunctionPath> {
+        let mut paths = Vec::new();
+        for (zome_name, zome) in &self.zomes {
+            for (cap_name, capability) in &zome.capabilities {
+                if capability.functions.iter().any(|f| f.name == fn_name) {
+                    paths.push(FunctionPath {
+                        zome: zome_name.clone(),
+                        capability: cap_name.clone(),
+                    });
+                }
+            }
+        }
+        paths
+    }
+
+    /// Return the name of the zome holding a specified app entry_type.
+    /// Relies on entry type names being unique across zomes, see check_entry_type_names().
+    /// Fails with DnaError::InvalidAppEntryTypeName if the name can't be an app entry type.
     pub fn get_zome_name_for_app_entry_type(
         &self,
         app_entry_type: &AppEntryType,
-    ) -> Option<String> {
+    ) -> Result<Option<String>, DnaError> {
         let entry_type_name = String::from(app_entry_type.to_owned());
-        // pre-condition: must be a valid app entry_type name
-        assert!(EntryType::has_valid_app_name(&entry_type_name));
-        // Browse through the zomes
+        check_app_entry_type_name(&entry_type_name)?;
+        let entry_type = EntryType::App(app_entry_type.to_owned());
+        Ok(self
+            .zomes
+            .iter()
+            .find(|(_, zome)| zome.entry_types.contains_key(&entry_type))
+            .map(|(zome_name, _)| zome_name.clone()))
+    }
+
+    /// Return the entry_type definition of a specified app entry_type.
+    /// Relies on entry type names being unique across zomes, see check_entry_type_names().
+    /// Fails with DnaError::InvalidAppEntryTypeName if the name can't be an app entry type.
+    pub fn get_entry_type_def(
+        &self,
+        entry_type_name: &str,
+    ) -> Result<Option<&EntryTypeDef>, DnaError> {
+        check_app_entry_type_name(entry_type_name)?;
+        let entry_type = EntryType::App(AppEntryType::from(entry_type_name.to_string()));
+        Ok(self
+            .zomes
+            .values()
+            .find_map(|zome| zome.entry_types.get(&entry_type)))
+    }
+
+    /// Checks that no app entry type name is defined by more than one zome.
+    /// Entries only carry their type's name, so a name defined twice would make the
+    /// zome validating an entry depend on zome ordering. The first duplicated name
+    /// gets rejected with DnaError::DuplicateEntryType, listing all zomes defining it.
+    pub fn check_entry_type_names(&self) -> Result<(), DnaError> {
+        let mut zomes_by_entry_type: BTreeMap<&EntryType, Vec<String>> = BTreeMap::new();
+        for (zome_name, zome) in &self.zomes {
+            for entry_type in zome.entry_types.keys() {
+                zomes_by_entry_type
+                    .entry(entry_type)
+                    .or_insert_with(Vec::new)
+                    .push(zome_name.clone());
+            }
+        }
+        match zomes_by_entry_type
+            .into_iter()
+            .find(|(_, zomes)| zomes.len() > 1)
+        {
+            Some((entry_type, zomes)) => Err(DnaError::DuplicateEntryType {
+                name: String::from(entry_type.to_owned()),
+                zomes,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that function names are unambiguous within each zome.
+    /// Zome calls are scoped by capability, so several capabilities may expose the same
+    /// function, but all of them end up calling the same WASM export. Declaring that
+    /// function with different inputs or outputs in different capabilities is ambiguous
+    /// and gets rejected with DnaError::DuplicateFunction.
+    pub fn check_function_names(&self) -> Result<(), DnaError> {
+        for (zome_name, zome) in &self.zomes {
+            let mut declarations: BTreeMap<&String, (&String, &FnDeclaration)> = BTreeMap::new();
+            for (cap_name, capability) in &zome.capabilities {
+                for fn_declaration in &capability.functions {
+                    let name = &fn_declaration.name;
+                    match declarations.get(name) {
+                        Some((other_cap_name, other_declaration)) => {
+                            if *other_declaration != fn_declaration {
+                                return Err(DnaError::DuplicateFunction(format!(
+                                    "Function '{}' in Zome '{}' declared differently in '{}' and '{}'",
+                                    name, zome_name, other_cap_name, cap_name
+                                )));
+                            }
+                        }
+                        None => {
+                            declarations.insert(name, (cap_name, fn_declaration));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the structural integrity of the DNA, which deserializing it does not.
+    /// Rejects empty zome names, zome code that is no WebAssembly module, functions declared
+    /// twice within one capability and links_to definitions targeting an app entry type
+    /// no zome defines.
+    /// The first inconsistency found gets returned as DnaError::InvalidStructure.
+    pub fn validate(&self) -> Result<(), DnaError> {
         for (zome_name, zome) in &self.zomes {
-            for (zome_entry_type_name, _) in &zome.entry_types {
-                if *zome_entry_type_name
-                    == EntryType::App(AppEntryType::from(entry_type_name.to_string()))
-                {
-                    return Some(zome_name.clone());
+            if zome_name.trim().is_empty() {
+                return Err(DnaError::InvalidStructure(String::from(
+                    "Zome name must not be empty",
+                )));
+            }
+            zome.code.validate_module().map_err(|error| {
+                DnaError::InvalidStructure(format!(
+                    "WASM of Zome '{}' is invalid: {}",
+                    zome_name, error
+                ))
+            })?;
+            for (cap_name, capability) in &zome.capabilities {
+                let mut fn_names = BTreeSet::new();
+                for fn_declaration in &capability.functions {
+                    if !fn_names.insert(&fn_declaration.name) {
+                        return Err(DnaError::InvalidStructure(format!(
+                            "Function '{}' declared twice in capability '{}' of Zome '{}'",
+                            fn_declaration.name, cap_name, zome_name
+                        )));
+                    }
                 }
             }
+            for (entry_type, entry_type_def) in &zome.entry_types {
+                for links_to in &entry_type_def.links_to {
+                    let target_type = &links_to.target_type;
+                    if EntryType::from(target_type.clone()).is_app()
+                        && self
+                            .get_entry_type_def(target_type)
+                            .unwrap_or(None)
+                            .is_none()
+                    {
+                        return Err(DnaError::InvalidStructure(format!(
+                            "Entry type '{}' of Zome '{}' links to undefined entry type '{}'",
+                            entry_type, zome_name, target_type
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces an existing zome, e.g. to ship new WASM in a staged upgrade.
+    /// Entries committed under the old zome have to stay valid, so the new zome must
+    /// keep all of the old zome's entry types. Removing one gets rejected with
+    /// DnaError::IncompatibleOverride.
+    pub fn override_zome(&mut self, zome_name: &str, new_zome: zome::Zome) -> Result<(), DnaError> {
+        let old_zome = self
+            .zomes
+            .get(zome_name)
+            .ok_or_else(|| DnaError::ZomeNotFound(format!("Zome '{}' not found", zome_name)))?;
+        if let Some(entry_type) = old_zome
+            .entry_types
+            .keys()
+            .find(|entry_type| !new_zome.entry_types.contains_key(entry_type))
+        {
+            return Err(DnaError::IncompatibleOverride(format!(
+                "Override of Zome '{}' removes entry type '{}'",
+                zome_name,
+                String::from(entry_type.clone())
+            )));
         }
-        None
+        self.zomes_mut().insert(zome_name.to_string(), new_zome);
+        Ok(())
     }
 
-    /// Return the entry_type definition of a specified app entry_type
-    pub fn get_entry_type_def(&self, entry_type_name: &str) -> Option<&EntryTypeDef> {
-        // pre-condition: must be a valid app entry_type name
-        assert!(EntryType::has_valid_app_name(entry_type_name));
-        // Browse through the zomes
-        for (_zome_name, zome) in &self.zomes {
-            for (zome_entry_type_name, entry_type_def) in &zome.entry_types {
-                if *zome_entry_type_name
-                    == EntryType::App(AppEntryType::from(entry_type_name.to_string()))
-                {
-                    return Some(entry_type_def);
+    /// Checks whether this DNA can read data written by an older one, e.g. before a
+    /// rolling upgrade. Every entry type of the older DNA has to exist in this one (in any
+    /// zome), require exactly the fields it required, and allow every update and deletion
+    /// it allowed.
+    pub fn is_data_compatible_with(&self, older: &Dna) -> DataCompatibility {
+        let mut incompatibilities = Vec::new();
+        for (entry_type, older_def) in older
+            .zomes
+            .values()
+            .flat_map(|zome| zome.entry_types.iter())
+        {
+            let entry_type_def = match self
+                .zomes
+                .values()
+                .find_map(|zome| zome.entry_types.get(entry_type))
+            {
+                Some(entry_type_def) => entry_type_def,
+                None => {
+                    incompatibilities
+                        .push(DataIncompatibility::RemovedEntryType(entry_type.clone()));
+                    continue;
+                }
+            };
+            for field in older_def.required_fields.iter() {
+                if !entry_type_def.required_fields.contains(field) {
+                    incompatibilities.push(DataIncompatibility::RemovedRequiredField(
+                        entry_type.clone(),
+                        field.clone(),
+                    ));
                 }
             }
+            for field in entry_type_def.required_fields.iter() {
+                if !older_def.required_fields.contains(field) {
+                    incompatibilities.push(DataIncompatibility::AddedRequiredField(
+                        entry_type.clone(),
+                        field.clone(),
+                    ));
+                }
+            }
+            let older_mutability = &older_def.mutability;
+            let mutability = &entry_type_def.mutability;
+            if (older_mutability.can_update() && !mutability.can_update())
+                || (older_mutability.can_delete() && !mutability.can_delete())
+            {
+                incompatibilities.push(DataIncompatibility::TightenedMutability(
+                    entry_type.clone(),
+                    older_mutability.clone(),
+                    mutability.clone(),
+                ));
+            }
+        }
+        if incompatibilities.is_empty() {
+            DataCompatibility::Compatible
+        } else {
+            DataCompatibility::Incompatible(incompatibilities)
         }
-        None
+    }
+
+    /// Lists what changed from this DNA to other, walking both structures field by field:
+    /// top-level fields, zomes with their entry types and capabilities, and properties.
+    /// The order is deterministic, so the output of two runs can be compared as well.
+    pub fn diff(&self, other: &Dna) -> Vec<DnaDifference> {
+        let mut differences = Vec::new();
+        let max_chain_length = |dna: &Dna| {
+            dna.max_chain_length
+                .map(|length| length.to_string())
+                .unwrap_or_else(|| String::from("none"))
+        };
+        for (field, old, new) in vec![
+            ("name", self.name.clone(), other.name.clone()),
+            (
+                "description",
+                self.description.clone(),
+                other.description.clone(),
+            ),
+            ("version", self.version.clone(), other.version.clone()),
+            ("uuid", self.uuid.clone(), other.uuid.clone()),
+            (
+                "dna_spec_version",
+                self.dna_spec_version.clone(),
+                other.dna_spec_version.clone(),
+            ),
+            (
+                "max_chain_length",
+                max_chain_length(self),
+                max_chain_length(other),
+            ),
+        ] {
+            if old != new {
+                differences.push(DnaDifference::FieldChanged(field.to_string(), old, new));
+            }
+        }
+
+        let (added, removed, changed) = diff_keys(&self.zomes, &other.zomes);
+        differences.extend(
+            added
+                .into_iter()
+                .map(|zome_name| DnaDifference::ZomeAdded(zome_name.clone())),
+        );
+        differences.extend(
+            removed
+                .into_iter()
+                .map(|zome_name| DnaDifference::ZomeRemoved(zome_name.clone())),
+        );
+        for zome_name in changed {
+            let (old, new) = (&self.zomes[zome_name], &other.zomes[zome_name]);
+            let zome_changed =
+                |field: &str| DnaDifference::ZomeChanged(zome_name.clone(), field.to_string());
+            if old.description != new.description {
+                differences.push(zome_changed("description"));
+            }
+            if old.config != new.config {
+                differences.push(zome_changed("config"));
+            }
+            if old.code != new.code {
+                differences.push(zome_changed("code"));
+            }
+
+            let (added, removed, changed) = diff_keys(&old.entry_types, &new.entry_types);
+            differences.extend(added.into_iter().map(|entry_type| {
+                DnaDifference::EntryTypeAdded(zome_name.clone(), entry_type.clone())
+            }));
+            differences.extend(removed.into_iter().map(|entry_type| {
+                DnaDifference::EntryTypeRemoved(zome_name.clone(), entry_type.clone())
+            }));
+            differences.extend(changed.into_iter().map(|entry_type| {
+                DnaDifference::EntryTypeChanged(zome_name.clone(), entry_type.clone())
+            }));
+
+            let (added, removed, changed) = diff_keys(&old.capabilities, &new.capabilities);
+            differences.extend(added.into_iter().map(|cap_name| {
+                DnaDifference::CapabilityAdded(zome_name.clone(), cap_name.clone())
+            }));
+            differences.extend(removed.into_iter().map(|cap_name| {
+                DnaDifference::CapabilityRemoved(zome_name.clone(), cap_name.clone())
+            }));
+            differences.extend(changed.into_iter().map(|cap_name| {
+                DnaDifference::CapabilityChanged(zome_name.clone(), cap_name.clone())
+            }));
+        }
+
+        diff_properties("", &self.properties, &other.properties, &mut differences);
+        if self.test_vectors != other.test_vectors {
+            differences.push(DnaDifference::TestVectorsChanged);
+        }
+        differences
+    }
+
+    /// Runs every test vector through the given validation function and compares the result
+    /// with the expected outcome.
+    /// The validation function returns Err(reason) for entries it rejects.
+    pub fn run_test_vectors<F>(&self, validate: F) -> Result<(), DnaError>
+    where
+        F: Fn(Entry) -> Result<(), String>,
+    {
+        for (index, test_vector) in self.test_vectors.iter().enumerate() {
+            let result = validate(test_vector.entry()?);
+            match (test_vector.expect_valid, result) {
+                (true, Err(reason)) => {
+                    return Err(DnaError::TestVectorFailed(format!(
+                        "Test vector {} was expected to pass but failed: {}",
+                        index, reason
+                    )));
+                }
+                (false, Ok(())) => {
+                    return Err(DnaError::TestVectorFailed(format!(
+                        "Test vector {} was expected to fail but passed",
+                        index
+                    )));
+                }
+                _ => (),
+            }
+        }
+        Ok(())
     }
 
     pub fn multihash(&self) -> Result<Vec<u8>, HolochainError> {
+        self.multihash_with(multihash::Hash::SHA2256)
+    }
+
+    /// Multihash of the DNA with the given algorithm, for interop with systems not using
+    /// SHA2-256. The hashed input is the same JSON for every algorithm.
+    pub fn multihash_with(&self, algo: multihash::Hash) -> Result<Vec<u8>, HolochainError> {
         let s = String::from(JsonString::from(self.to_owned()));
-        multihash::encode(multihash::Hash::SHA2256, &s.into_bytes())
+        multihash::encode(algo, &s.into_bytes())
             .map_err(|error| HolochainError::ErrorGeneric(error.to_string()))
     }
+
+    /// Structural schema of the DNA for documentation and client code generation:
+    /// zomes with their entry types (including link definitions) and capabilities
+    /// (including function declarations). Code, properties and uuid are left out.
+    pub fn schema_json(&self) -> JsonString {
+        let zomes: BTreeMap<&String, Value> = self
+            .zomes
+            .iter()
+            .map(|(zome_name, zome)| {
+                let entry_types: BTreeMap<String, &EntryTypeDef> = zome
+                    .entry_types
+                    .iter()
+                    .map(|(entry_type, entry_type_def)| {
+                        (String::from(entry_type.to_owned()), entry_type_def)
+                    })
+                    .collect();
+                (
+                    zome_name,
+                    json!({
+                        "description": zome.description,
+                        "entry_types": entry_types,
+                        "capabilities": zome.capabilities,
+                    }),
+                )
+            })
+            .collect();
+
+        JsonString::from(json!({
+            "name": self.name,
+            "description": self.description,
+            "version": self.version,
+            "dna_spec_version": self.dna_spec_version,
+            "zomes": zomes,
+        }))
+    }
+
+    /// SHA2-256 multihash of the DNA with the given digest length.
+    /// `multihash()` is the same as passing DigestLength::Full.
+    pub fn multihash_with_digest_length(
+        &self,
+        digest_length: DigestLength,
+    ) -> Result<Vec<u8>, HolochainError> {
+        let s = String::from(JsonString::from(self.to_owned()));
+        sha2_256_multihash(&s.into_bytes(), digest_length)
+    }
+
+    /// Human readable report of everything the DNA declares, meant for support requests:
+    /// name, hash and spec version, and per zome its WASM (size and hash), resource limits,
+    /// entry types with their links and capabilities with their functions and membranes.
+    pub fn describe(&self) -> String {
+        let mut report = String::new();
+        let _ = self.write_description(&mut report);
+        report
+    }
+
+    fn write_description(&self, report: &mut String) -> fmt::Result {
+        writeln!(report, "DNA \"{}\" version {}", self.name, self.version)?;
+        if !self.description.is_empty() {
+            writeln!(report, "  description: {}", self.description)?;
+        }
+        writeln!(report, "  dna spec version: {}", self.dna_spec_version)?;
+        writeln!(report, "  uuid: {}", self.uuid)?;
+        match self.multihash() {
+            Ok(hash) => writeln!(report, "  hash: {}", hash.to_base58())?,
+            Err(error) => writeln!(report, "  hash: unavailable ({})", error)?,
+        }
+        writeln!(report, "  zomes: {}", self.zomes.len())?;
+
+        for (zome_name, zome) in self.zomes.iter() {
+            writeln!(report, "zome \"{}\"", zome_name)?;
+            if !zome.description.is_empty() {
+                writeln!(report, "  description: {}", zome.description)?;
+            }
+            let profile = zome.resource_profile();
+            writeln!(
+                report,
+                "  wasm: {} bytes, hash {}",
+                profile.wasm_bytes,
+                HashString::encode_from_bytes(&zome.code.code, multihash::Hash::SHA2256)
+            )?;
+            writeln!(
+                report,
+                "  limits: max memory pages {:?}, gas budget {:?}",
+                profile.max_memory_pages, profile.gas_budget
+            )?;
+
+            for (entry_type, entry_type_def) in zome.entry_types.iter() {
+                writeln!(
+                    report,
+                    "  entry type \"{}\" ({:?}, {:?})",
+                    entry_type, entry_type_def.sharing, entry_type_def.mutability
+                )?;
+                for links_to in entry_type_def.links_to.iter() {
+                    writeln!(
+                        report,
+                        "    links to \"{}\" with tag \"{}\"",
+                        links_to.target_type, links_to.tag
+                    )?;
+                }
+                for linked_from in entry_type_def.linked_from.iter() {
+                    writeln!(
+                        report,
+                        "    linked from \"{}\" with tag \"{}\"",
+                        linked_from.base_type, linked_from.tag
+                    )?;
+                }
+            }
+
+            for (cap_name, capability) in zome.capabilities.iter() {
+                writeln!(
+                    report,
+                    "  capability \"{}\" (membrane {:?})",
+                    cap_name, capability.cap_type.membrane
+                )?;
+                for function in capability.functions.iter() {
+                    let parameters = |parameters: &Vec<FnParameter>| -> String {
+                        parameters
+                            .iter()
+                            .map(|parameter| {
+                                format!("{}: {}", parameter.name, parameter.parameter_type)
+                            })
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    };
+                    writeln!(
+                        report,
+                        "    fn {}({}) -> ({}){}",
+                        function.name,
+                        parameters(&function.inputs),
+                        parameters(&function.outputs),
+                        if function.read_only { " read-only" } else { "" }
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Hash for Dna {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        let s = String::from(JsonString::from(self.to_owned()));
-        s.hash(state);
+        self.cached_canonical_json().hash(state);
     }
 }
 
 impl PartialEq for Dna {
     fn eq(&self, other: &Dna) -> bool {
         // need to guarantee that PartialEq and Hash always agree
-        JsonString::from(self.to_owned()) == JsonString::from(other.to_owned())
+        self.cached_canonical_json() == other.cached_canonical_json()
     }
 }
 
@@ -260,8 +1224,14 @@ pub mod tests {
             .insert(entry_type.into(), entry_type_def.clone());
         dna.zomes.insert("zome".to_string(), zome);
 
-        assert_eq!(None, dna.get_entry_type_def("foo"));
-        assert_eq!(Some(&entry_type_def), dna.get_entry_type_def("bar"));
+        assert_eq!(Ok(None), dna.get_entry_type_def("foo"));
+        assert_eq!(Ok(Some(&entry_type_def)), dna.get_entry_type_def("bar"));
+        for invalid_name in vec!["", "%agent_id"] {
+            match dna.get_entry_type_def(invalid_name) {
+                Err(DnaError::InvalidAppEntryTypeName(_)) => (),
+                other => panic!("expected InvalidAppEntryTypeName, got {:?}", other),
+            }
+        }
     }
 
     #[test]
@@ -349,6 +1319,22 @@ pub mod tests {
         assert_eq!(fixture, serialized);
     }
 
+    #[test]
+    fn new_with_seed_test() {
+        let dna = Dna::new_with_seed(42);
+        assert_eq!(dna, Dna::new_with_seed(42));
+        assert_eq!(
+            dna.multihash().unwrap(),
+            Dna::new_with_seed(42).multihash().unwrap()
+        );
+
+        let other = Dna::new_with_seed(43);
+        assert_ne!(dna.uuid, other.uuid);
+        assert_ne!(dna.multihash().unwrap(), other.multihash().unwrap());
+
+        assert!(Uuid::parse_str(&dna.uuid).is_ok());
+    }
+
     #[test]
     fn default_value_test() {
         let mut dna = Dna {
@@ -396,6 +1382,211 @@ pub mod tests {
         assert_eq!(dna, fixture);
     }
 
+    #[test]
+    fn run_test_vectors_test() {
+        let mut dna = test_dna();
+        let entry_type = EntryType::App(AppEntryType::from("post"));
+        // accepts everything that has a non empty title
+        let validate = |entry: Entry| match entry {
+            Entry::App(_, value) => {
+                let value: Value = serde_json::from_str(&String::from(value)).unwrap();
+                match value["title"].as_str() {
+                    Some(title) if !title.is_empty() => Ok(()),
+                    _ => Err(String::from("missing title")),
+                }
+            }
+            _ => Err(String::from("not an app entry")),
+        };
+
+        dna.test_vectors = vec![
+            TestVector::new(entry_type.clone(), json!({"title": "hello"}), true),
+            TestVector::new(entry_type.clone(), json!({"title": ""}), false),
+        ];
+        assert_eq!(Ok(()), dna.run_test_vectors(validate));
+
+        dna.test_vectors
+            .push(TestVector::new(entry_type.clone(), json!({}), true));
+        assert_eq!(
+            Err(DnaError::TestVectorFailed(String::from(
+                "Test vector 2 was expected to pass but failed: missing title"
+            ))),
+            dna.run_test_vectors(validate),
+        );
+
+        dna.test_vectors = vec![TestVector::new(
+            entry_type.clone(),
+            json!({"title": "hello"}),
+            false,
+        )];
+        assert_eq!(
+            Err(DnaError::TestVectorFailed(String::from(
+                "Test vector 0 was expected to fail but passed"
+            ))),
+            dna.run_test_vectors(validate),
+        );
+    }
+
+    #[test]
+    fn multihash_with_digest_length_test() {
+        let dna = test_dna();
+        let full = dna
+            .multihash_with_digest_length(DigestLength::Full)
+            .unwrap();
+        let truncated = dna
+            .multihash_with_digest_length(DigestLength::Truncated160)
+            .unwrap();
+        assert_eq!(dna.multihash().unwrap(), full);
+        assert_eq!(34, full.len());
+        assert_eq!(25, truncated.len());
+        assert_eq!(full[2..22], truncated[5..]);
+    }
+
+    #[test]
+    fn multihash_with_test() {
+        let dna = test_dna();
+        // DNA hashes identify DNAs on the network, so they stay hashes of the plain JSON
+        let json = String::from(JsonString::from(dna.clone())).into_bytes();
+
+        let sha2_256 = dna.multihash_with(multihash::Hash::SHA2256).unwrap();
+        assert_eq!(dna.multihash().unwrap(), sha2_256);
+        assert_eq!(
+            multihash::encode(multihash::Hash::SHA2256, &json).unwrap(),
+            sha2_256
+        );
+
+        let sha3_256 = dna.multihash_with(multihash::Hash::SHA3256).unwrap();
+        assert_ne!(sha2_256, sha3_256);
+        assert_eq!(
+            multihash::encode(multihash::Hash::SHA3256, &json).unwrap(),
+            sha3_256
+        );
+        assert_eq!(
+            multihash::Hash::SHA3256,
+            multihash::decode(&sha3_256).unwrap().alg
+        );
+    }
+
+    fn hash_of(dna: &Dna) -> u64 {
+        use std::collections::hash_map::DefaultHasher;
+        let mut hasher = DefaultHasher::new();
+        dna.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn equality_and_hash_use_cached_json() {
+        let dna = Dna::new_with_seed(1);
+        let same = Dna::try_from(JsonString::from(dna.clone())).unwrap();
+        assert_eq!(dna, same);
+        assert_eq!(hash_of(&dna), hash_of(&same));
+
+        // setters drop the cached JSON
+        let mut renamed = same.clone();
+        assert_eq!(dna, renamed);
+        renamed.set_name(String::from("renamed"));
+        assert_ne!(dna, renamed);
+        assert_ne!(hash_of(&dna), hash_of(&renamed));
+
+        renamed
+            .zomes_mut()
+            .insert(String::from("zome"), zome::Zome::default());
+        let mut expected = Dna::new_with_seed(1);
+        expected.set_name(String::from("renamed"));
+        expected
+            .zomes_mut()
+            .insert(String::from("zome"), zome::Zome::default());
+        assert_eq!(expected, renamed);
+        assert_eq!(hash_of(&expected), hash_of(&renamed));
+
+        // clones keep the cached JSON until they get changed
+        let mut cloned = dna.clone();
+        cloned.set_uuid(String::from(UNIT_UUID));
+        assert_ne!(dna, cloned);
+        cloned.set_uuid(dna.uuid().to_string());
+        assert_eq!(dna, cloned);
+
+        let mut merged = dna.clone();
+        merged.merge_properties(&json!({"key": "value"})).unwrap();
+        assert_ne!(dna, merged);
+        assert_ne!(hash_of(&dna), hash_of(&merged));
+    }
+
+    #[test]
+    fn merge_properties_test() {
+        let mut dna = Dna::new();
+        dna.set_properties(json!({
+            "network": {"bootstrap": ["a", "b"], "timeout": 10},
+            "name": "base",
+            "debug": false
+        }));
+        let before = dna.clone();
+        assert_eq!(before, dna);
+
+        dna.merge_properties(&json!({
+            "network": {"bootstrap": ["c"], "port": 8888},
+            "debug": true,
+            "extra": {"nested": 1}
+        }))
+        .unwrap();
+
+        assert_eq!(
+            &json!({
+                "network": {"bootstrap": ["c"], "timeout": 10, "port": 8888},
+                "name": "base",
+                "debug": true,
+                "extra": {"nested": 1}
+            }),
+            dna.properties()
+        );
+        assert_ne!(before, dna);
+
+        for overlay in vec![json!(["a"]), json!("string"), json!(null)] {
+            match dna.merge_properties(&overlay) {
+                Err(DnaError::InvalidProperties(_)) => (),
+                other => panic!("expected InvalidProperties, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn from_reader_test() {
+        let mut dna = Dna::new_with_seed(7);
+        dna.set_name(String::from("from reader"));
+        let mut zome = zome::Zome::default();
+        zome.code = wasm::DnaWasm {
+            code: vec![0, 97, 115, 109, 1, 0, 0, 0],
+        };
+        dna.zomes_mut().insert(String::from("zome"), zome);
+        let json = String::from(JsonString::from(dna.clone()));
+
+        assert_eq!(Ok(dna), Dna::from_reader(json.as_bytes()));
+
+        match Dna::from_reader("{\"name\": ".as_bytes()) {
+            Err(DnaError::SerializationError(_)) => (),
+            other => panic!("expected SerializationError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn to_writer_test() {
+        let mut dna = Dna::new_with_seed(7);
+        dna.set_properties(json!({"nested": {"list": [1, 2]}}));
+        dna.zomes_mut()
+            .insert(String::from("zome"), zome::Zome::default());
+
+        let mut pretty = Vec::new();
+        dna.to_writer_pretty(&mut pretty).unwrap();
+        assert_eq!(dna.to_json_pretty().unwrap().into_bytes(), pretty);
+
+        let mut compact = Vec::new();
+        dna.to_writer(&mut compact).unwrap();
+        assert_eq!(
+            String::from(JsonString::from(dna.clone())).into_bytes(),
+            compact
+        );
+        assert_eq!(Ok(dna), Dna::from_reader(&compact[..]));
+    }
+
     #[test]
     fn parse_with_defaults_dna() {
         let dna = Dna::try_from(JsonString::from(
@@ -472,6 +1663,25 @@ pub mod tests {
         assert_eq!(vec![0, 1, 2, 3], dna.zomes.get("zome1").unwrap().code.code);
     }
 
+    #[test]
+    fn validate_wasm_module_test() {
+        let module = |code: Vec<u8>| wasm::DnaWasm { code };
+        assert_eq!(Ok(()), module(WASM_HEADER.to_vec()).validate_module());
+        let mut with_sections = WASM_HEADER.to_vec();
+        with_sections.extend_from_slice(&[0x01, 0x04, 0x01, 0x60, 0x00, 0x00]);
+        assert_eq!(Ok(()), module(with_sections).validate_module());
+
+        // parse_wasm shows that arbitrary bytes deserialize fine
+        assert!(module(vec![0, 1, 2, 3]).validate_module().is_err());
+        assert!(module(vec![]).validate_module().is_err());
+        assert!(module(WASM_HEADER[0..6].to_vec())
+            .validate_module()
+            .is_err());
+        let mut version_2 = WASM_HEADER.to_vec();
+        version_2[4] = 0x02;
+        assert!(module(version_2).validate_module().is_err());
+    }
+
     #[test]
     #[should_panic]
     fn parse_fail_if_bad_type_dna() {
@@ -559,6 +1769,548 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn schema_json_test() {
+        let mut dna = Dna::new();
+        dna.name = String::from("schema test");
+        let mut zome = zome::Zome::default();
+        zome.code = wasm::DnaWasm {
+            code: vec![0xde, 0xad, 0xbe, 0xef],
+        };
+        zome.entry_types
+            .insert("post".into(), zome::entry_types::EntryTypeDef::new());
+        zome.entry_types
+            .insert("comment".into(), zome::entry_types::EntryTypeDef::new());
+        let mut capability = Capability::new();
+        let mut fn_declaration = zome::capabilities::FnDeclaration::new();
+        fn_declaration.name = String::from("create_post");
+        capability.functions.push(fn_declaration);
+        zome.capabilities.insert(String::from("main"), capability);
+        dna.zomes.insert(String::from("blog"), zome);
+
+        let schema: Value =
+            serde_json::from_str(&String::from(dna.schema_json())).expect("schema should be JSON");
+
+        assert_eq!("schema test", schema["name"]);
+        let entry_types = &schema["zomes"]["blog"]["entry_types"];
+        assert!(entry_types.get("post").is_some());
+        assert!(entry_types.get("comment").is_some());
+        assert_eq!(
+            "create_post",
+            schema["zomes"]["blog"]["capabilities"]["main"]["functions"][0]["name"]
+        );
+
+        let schema_string = String::from(dna.schema_json());
+        assert!(!schema_string.contains("\"code\""));
+        assert!(!schema_string.contains(&base64::encode(&[0xde, 0xad, 0xbe, 0xef])));
+        assert!(!schema_string.contains(&dna.uuid));
+    }
+
+    fn entry_type_def(required_fields: &[&str], mutability: Mutability) -> EntryTypeDef {
+        let mut entry_type_def = EntryTypeDef::new();
+        entry_type_def.required_fields = required_fields.iter().map(|f| f.to_string()).collect();
+        entry_type_def.mutability = mutability;
+        entry_type_def
+    }
+
+    fn dna_with_entry_types(entry_types: Vec<(&str, EntryTypeDef)>) -> Dna {
+        let mut dna = Dna::new();
+        let mut zome = zome::Zome::default();
+        for (name, entry_type_def) in entry_types {
+            zome.entry_types.insert(name.into(), entry_type_def);
+        }
+        dna.zomes.insert(String::from("blog"), zome);
+        dna
+    }
+
+    #[test]
+    fn is_data_compatible_with_accepts_compatible_upgrade() {
+        let older = dna_with_entry_types(vec![(
+            "post",
+            entry_type_def(&["title"], Mutability::AppendOnly),
+        )]);
+        // new entry type and looser mutability are fine
+        let newer = dna_with_entry_types(vec![
+            ("post", entry_type_def(&["title"], Mutability::Full)),
+            ("comment", entry_type_def(&["text"], Mutability::Immutable)),
+        ]);
+
+        let compatibility = newer.is_data_compatible_with(&older);
+        assert_eq!(DataCompatibility::Compatible, compatibility);
+        assert!(compatibility.is_compatible());
+        assert!(older.is_data_compatible_with(&older).is_compatible());
+    }
+
+    #[test]
+    fn is_data_compatible_with_rejects_added_required_field() {
+        let older =
+            dna_with_entry_types(vec![("post", entry_type_def(&["title"], Mutability::Full))]);
+        // existing posts have no body
+        let newer = dna_with_entry_types(vec![(
+            "post",
+            entry_type_def(&["title", "body"], Mutability::Full),
+        )]);
+
+        assert_eq!(
+            DataCompatibility::Incompatible(vec![DataIncompatibility::AddedRequiredField(
+                "post".into(),
+                String::from("body")
+            )]),
+            newer.is_data_compatible_with(&older)
+        );
+    }
+
+    #[test]
+    fn is_data_compatible_with_rejects_removed_entry_type() {
+        let older = dna_with_entry_types(vec![
+            ("post", entry_type_def(&["title"], Mutability::Full)),
+            ("comment", entry_type_def(&[], Mutability::Full)),
+        ]);
+        let newer =
+            dna_with_entry_types(vec![("post", entry_type_def(&[], Mutability::AppendOnly))]);
+
+        let compatibility = newer.is_data_compatible_with(&older);
+        assert!(!compatibility.is_compatible());
+        assert_eq!(
+            DataCompatibility::Incompatible(vec![
+                DataIncompatibility::RemovedEntryType("comment".into()),
+                DataIncompatibility::RemovedRequiredField("post".into(), String::from("title")),
+                DataIncompatibility::TightenedMutability(
+                    "post".into(),
+                    Mutability::Full,
+                    Mutability::AppendOnly
+                ),
+            ]),
+            compatibility
+        );
+    }
+
+    #[test]
+    fn describe_test() {
+        let mut dna = Dna::new();
+        dna.name = String::from("describe test");
+        for (zome_name, entry_types, cap_names) in vec![
+            ("blog", vec!["post", "comment"], vec!["main", "admin"]),
+            ("profiles", vec!["profile"], vec!["public"]),
+        ] {
+            let mut zome = zome::Zome::default();
+            zome.code = wasm::DnaWasm {
+                code: vec![0xde, 0xad, 0xbe, 0xef],
+            };
+            for entry_type in entry_types {
+                let mut entry_type_def = zome::entry_types::EntryTypeDef::new();
+                entry_type_def.links_to.push(zome::entry_types::LinksTo {
+                    target_type: String::from("comment"),
+                    tag: String::from("comments"),
+                });
+                zome.entry_types.insert(entry_type.into(), entry_type_def);
+            }
+            for cap_name in cap_names {
+                let mut fn_declaration = zome::capabilities::FnDeclaration::new();
+                fn_declaration.name = format!("{}_fn", cap_name);
+                let mut capability = Capability::new();
+                capability.functions.push(fn_declaration);
+                zome.capabilities.insert(String::from(cap_name), capability);
+            }
+            dna.zomes.insert(String::from(zome_name), zome);
+        }
+
+        let report = dna.describe();
+
+        assert!(report.contains("describe test"));
+        assert!(report.contains(&dna.multihash().unwrap().to_base58()));
+        for expected in vec![
+            "zome \"blog\"",
+            "zome \"profiles\"",
+            "entry type \"post\"",
+            "entry type \"comment\"",
+            "entry type \"profile\"",
+            "links to \"comment\" with tag \"comments\"",
+            "capability \"main\"",
+            "capability \"admin\"",
+            "capability \"public\"",
+            "fn main_fn()",
+            "wasm: 4 bytes",
+        ] {
+            assert!(
+                report.contains(expected),
+                "missing {} in:\n{}",
+                expected,
+                report
+            );
+        }
+    }
+
+    fn zome_with_shared_function(other_output: &str) -> zome::Zome {
+        let mut zome = zome::Zome::default();
+        for (cap_name, output) in vec![("public", "string"), ("admin", other_output)] {
+            let mut fn_declaration = zome::capabilities::FnDeclaration::new();
+            fn_declaration.name = String::from("get_post");
+            fn_declaration
+                .outputs
+                .push(zome::capabilities::FnParameter::new("post", output));
+            let mut capability = Capability::new();
+            capability.functions.push(fn_declaration);
+            zome.capabilities.insert(String::from(cap_name), capability);
+        }
+        zome
+    }
+
+    #[test]
+    fn check_function_names_rejects_conflicting_declarations() {
+        let mut dna = Dna::new();
+        dna.zomes
+            .insert(String::from("blog"), zome_with_shared_function("json"));
+
+        match dna.check_function_names() {
+            Err(DnaError::DuplicateFunction(message)) => assert!(message.contains("get_post")),
+            other => panic!("expected DuplicateFunction, got {:?}", other),
+        }
+    }
+
+    fn zome_with_entry_types(entry_types: Vec<&str>, code: Vec<u8>) -> zome::Zome {
+        let mut zome = zome::Zome::default();
+        for entry_type in entry_types {
+            zome.entry_types.insert(
+                EntryType::App(AppEntryType::from(entry_type.to_string())),
+                EntryTypeDef::new(),
+            );
+        }
+        zome.code = wasm::DnaWasm { code };
+        zome
+    }
+
+    #[test]
+    fn override_zome_keeping_entry_types() {
+        let mut dna = Dna::new();
+        dna.zomes.insert(
+            String::from("blog"),
+            zome_with_entry_types(vec!["post"], vec![1]),
+        );
+
+        let new_zome = zome_with_entry_types(vec!["post", "comment"], vec![2]);
+        assert_eq!(Ok(()), dna.override_zome("blog", new_zome.clone()));
+        assert_eq!(Some(&new_zome), dna.get_zome("blog"));
+
+        match dna.override_zome("chat", new_zome) {
+            Err(DnaError::ZomeNotFound(_)) => (),
+            other => panic!("expected ZomeNotFound, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn override_zome_rejects_removed_entry_type() {
+        let mut dna = Dna::new();
+        let old_zome = zome_with_entry_types(vec!["post", "comment"], vec![1]);
+        dna.zomes.insert(String::from("blog"), old_zome.clone());
+
+        match dna.override_zome("blog", zome_with_entry_types(vec!["post"], vec![2])) {
+            Err(DnaError::IncompatibleOverride(message)) => assert!(message.contains("comment")),
+            other => panic!("expected IncompatibleOverride, got {:?}", other),
+        }
+        assert_eq!(Some(&old_zome), dna.get_zome("blog"));
+    }
+
+    #[test]
+    fn check_entry_type_names_rejects_duplicates() {
+        let mut dna = Dna::new();
+        for zome_name in vec!["blog", "comments", "profiles"] {
+            let mut zome = zome::Zome::default();
+            zome.entry_types.insert(
+                EntryType::from(format!("{}_entry", zome_name)),
+                EntryTypeDef::new(),
+            );
+            dna.zomes.insert(String::from(zome_name), zome);
+        }
+        assert_eq!(Ok(()), dna.check_entry_type_names());
+
+        for zome_name in vec!["blog", "profiles"] {
+            dna.zomes
+                .get_mut(zome_name)
+                .unwrap()
+                .entry_types
+                .insert("post".into(), EntryTypeDef::new());
+        }
+
+        assert_eq!(
+            Err(DnaError::DuplicateEntryType {
+                name: String::from("post"),
+                zomes: vec![String::from("blog"), String::from("profiles")],
+            }),
+            dna.check_entry_type_names()
+        );
+    }
+
+    #[test]
+    fn check_function_names_allows_shared_declarations() {
+        let mut dna = Dna::new();
+        dna.zomes
+            .insert(String::from("blog"), zome_with_shared_function("string"));
+        // the same function may also appear in other zomes
+        dna.zomes
+            .insert(String::from("wiki"), zome_with_shared_function("string"));
+
+        assert_eq!(Ok(()), dna.check_function_names());
+    }
+
+    /// magic bytes and version of an empty WASM module
+    static WASM_HEADER: [u8; 8] = [0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+
+    fn dna_linking_to(target_type: &str) -> Dna {
+        let mut dna = Dna::new();
+        let mut zome = zome::Zome::default();
+        zome.code = wasm::DnaWasm {
+            code: WASM_HEADER.to_vec(),
+        };
+        let mut post = EntryTypeDef::new();
+        let mut links_to = zome::entry_types::LinksTo::new();
+        links_to.target_type = String::from(target_type);
+        links_to.tag = String::from("comments");
+        post.links_to.push(links_to);
+        zome.entry_types
+            .insert(EntryType::App(AppEntryType::from("post")), post);
+        zome.entry_types.insert(
+            EntryType::App(AppEntryType::from("comment")),
+            EntryTypeDef::new(),
+        );
+        let mut capability = Capability::new();
+        let mut fn_declaration = FnDeclaration::new();
+        fn_declaration.name = String::from("get_post");
+        capability.functions.push(fn_declaration);
+        zome.capabilities.insert(String::from("public"), capability);
+        dna.zomes.insert(String::from("blog"), zome);
+        dna
+    }
+
+    fn invalid_structure_message(dna: &Dna) -> String {
+        match dna.validate() {
+            Err(DnaError::InvalidStructure(message)) => message,
+            other => panic!("expected InvalidStructure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn diff_test() {
+        let old = dna_linking_to("comment");
+        assert_eq!(Vec::<DnaDifference>::new(), old.diff(&old.clone()));
+
+        let mut new = old.clone();
+        new.set_version(String::from("1.1"));
+        new.set_properties(json!({"limits": {"max_posts": 10}, "title": "blog"}));
+        new.zomes_mut()
+            .insert(String::from("admin"), zome::Zome::default());
+        {
+            let blog = new.get_zome_mut("blog").unwrap();
+            blog.description = String::from("posts and comments");
+            blog.entry_types
+                .remove(&EntryType::App(AppEntryType::from("comment")));
+            blog.capabilities
+                .insert(String::from("private"), Capability::new());
+            blog.capabilities
+                .get_mut("public")
+                .unwrap()
+                .functions
+                .clear();
+        }
+
+        let differences = old.diff(&new);
+        let descriptions: Vec<String> = differences.iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            vec![
+                "version changed from '' to '1.1'",
+                "zome 'admin' added",
+                "zome 'blog' description changed",
+                "zome 'blog' entry type 'comment' removed",
+                "zome 'blog' capability 'private' added",
+                "zome 'blog' capability 'public' changed",
+                "property '/limits' added",
+                "property '/title' added",
+            ],
+            descriptions
+        );
+
+        let mut newer = new.clone();
+        newer.set_properties(json!({"limits": {"max_posts": 20}, "title": "blog"}));
+        assert_eq!(
+            vec![DnaDifference::PropertyChanged(String::from(
+                "/limits/max_posts"
+            ))],
+            new.diff(&newer)
+        );
+        // the output is deterministic
+        assert_eq!(differences, old.clone().diff(&new.clone()));
+    }
+
+    #[test]
+    fn validate_test() {
+        assert_eq!(Ok(()), Dna::new().validate());
+        assert_eq!(Ok(()), dna_linking_to("comment").validate());
+        // links to system entry types don't need a definition
+        assert_eq!(Ok(()), dna_linking_to("%agent_id").validate());
+
+        assert!(invalid_structure_message(&dna_linking_to("reply")).contains("'reply'"));
+
+        let mut dna = dna_linking_to("comment");
+        let zome = dna.zomes.remove("blog").unwrap();
+        dna.zomes.insert(String::new(), zome);
+        assert!(invalid_structure_message(&dna).contains("Zome name"));
+
+        let mut dna = dna_linking_to("comment");
+        dna.get_zome_mut("blog").unwrap().code = wasm::DnaWasm {
+            code: vec![0, 1, 2, 3],
+        };
+        assert!(invalid_structure_message(&dna).contains("WASM of Zome 'blog'"));
+
+        let mut dna = dna_linking_to("comment");
+        let functions = &mut dna
+            .zomes
+            .get_mut("blog")
+            .unwrap()
+            .capabilities
+            .get_mut("public")
+            .unwrap()
+            .functions;
+        let duplicate = functions[0].clone();
+        functions.push(duplicate);
+        assert!(invalid_structure_message(&dna).contains("'get_post'"));
+    }
+
+    #[test]
+    fn zome_and_capability_names_test() {
+        let mut dna = Dna::new();
+        assert_eq!(0, dna.zome_names().count());
+        for zome_name in vec!["wiki", "blog"] {
+            dna.zomes
+                .insert(String::from(zome_name), zome_with_shared_function("string"));
+        }
+
+        assert_eq!(
+            vec!["blog", "wiki"],
+            dna.zome_names().map(String::as_str).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some(vec![String::from("admin"), String::from("public")]),
+            dna.capability_names("blog")
+        );
+        assert_eq!(None, dna.capability_names("missing"));
+    }
+
+    #[test]
+    fn add_capability_test() {
+        let mut dna = Dna::new();
+        dna.zomes
+            .insert(String::from("blog"), zome::Zome::default());
+
+        dna.get_zome_mut("blog").unwrap().description = String::from("changed");
+        assert_eq!("changed", dna.get_zome("blog").unwrap().description);
+        assert!(dna.get_zome_mut("missing").is_none());
+
+        let mut capability = Capability::new();
+        capability.description = String::from("first");
+        assert_eq!(
+            Ok(()),
+            dna.add_capability("blog", "public", capability.clone())
+        );
+        assert_eq!(
+            Some(vec![String::from("public")]),
+            dna.capability_names("blog")
+        );
+
+        match dna.add_capability("missing", "public", capability.clone()) {
+            Err(DnaError::ZomeNotFound(_)) => (),
+            other => panic!("expected ZomeNotFound, got {:?}", other),
+        }
+
+        let mut other = Capability::new();
+        other.description = String::from("second");
+        match dna.add_capability("blog", "public", other) {
+            Err(DnaError::DuplicateCapability(message)) => assert!(message.contains("public")),
+            other => panic!("expected DuplicateCapability, got {:?}", other),
+        }
+        assert_eq!(
+            "first",
+            dna.get_zome("blog").unwrap().capabilities["public"].description
+        );
+    }
+
+    #[test]
+    fn find_function_test() {
+        let mut dna = Dna::new();
+        dna.zomes
+            .insert(String::from("blog"), zome_with_shared_function("string"));
+        let mut fn_declaration = zome::capabilities::FnDeclaration::new();
+        fn_declaration.name = String::from("create_post");
+        dna.zomes
+            .get_mut("blog")
+            .unwrap()
+            .capabilities
+            .get_mut("admin")
+            .unwrap()
+            .functions
+            .push(fn_declaration);
+
+        let path = |zome: &str, capability: &str| FunctionPath {
+            zome: String::from(zome),
+            capability: String::from(capability),
+        };
+        assert_eq!(
+            vec![path("blog", "admin")],
+            dna.find_function("create_post")
+        );
+        assert_eq!(
+            vec![path("blog", "admin"), path("blog", "public")],
+            dna.find_function("get_post")
+        );
+        assert!(dna.find_function("delete_post").is_empty());
+    }
+
+    #[test]
+    fn commits_entry_types_test() {
+        let dna = Dna::try_from(JsonString::from(
+            r#"{
+                "zomes": {
+                    "blog": {
+                        "capabilities": {
+                            "main": {
+                                "capability": {
+                                    "membrane": "public"
+                                },
+                                "functions": [
+                                    {
+                                        "name": "create_post",
+                                        "inputs": [],
+                                        "outputs": [],
+                                        "commits_entry_types": ["post", "tag"]
+                                    },
+                                    {
+                                        "name": "get_post",
+                                        "inputs": [],
+                                        "outputs": []
+                                    }
+                                ]
+                            }
+                        }
+                    }
+                }
+            }"#,
+        ))
+        .unwrap();
+
+        assert_eq!(
+            Some(&vec![String::from("post"), String::from("tag")]),
+            dna.commits_entry_types("blog", "main", "create_post")
+        );
+        assert_eq!(
+            Some(&Vec::new()),
+            dna.commits_entry_types("blog", "main", "get_post")
+        );
+        assert_eq!(None, dna.commits_entry_types("blog", "main", "delete_post"));
+        assert_eq!(
+            None,
+            dna.commits_entry_types("blog", "admin", "create_post")
+        );
+    }
+
     #[test]
     fn get_wasm_from_zome_name() {
         let dna = Dna::try_from(JsonString::from(
@@ -607,6 +2359,30 @@ pub mod tests {
 
         let fail = dna.get_wasm_from_zome_name("non existant zome");
         assert_eq!(None, fail);
+
+        assert_eq!(
+            Ok(wasm.unwrap()),
+            dna.get_wasm_from_zome_name_checked("test zome")
+        );
+        assert_eq!(
+            Err(DnaError::ZomeNotFound(String::from(
+                "Zome 'non existant zome' not found"
+            ))),
+            dna.get_wasm_from_zome_name_checked("non existant zome")
+        );
+
+        let mut dna = dna.clone();
+        dna.get_zome_mut("test zome").unwrap().code = wasm::DnaWasm::new();
+        assert_eq!(
+            Some(&wasm::DnaWasm::new()),
+            dna.get_wasm_from_zome_name("test zome")
+        );
+        assert_eq!(
+            Err(DnaError::EmptyWasm(String::from(
+                "Zome 'test zome' has no WASM code"
+            ))),
+            dna.get_wasm_from_zome_name_checked("test zome")
+        );
     }
 
     #[test]
@@ -651,12 +2427,17 @@ pub mod tests {
 
         assert_eq!(
             dna.get_zome_name_for_app_entry_type(&AppEntryType::from("test type"))
+                .unwrap()
                 .unwrap(),
             "test zome".to_string()
         );
-        assert!(
+        assert_eq!(
+            Ok(None),
             dna.get_zome_name_for_app_entry_type(&AppEntryType::from("non existant entry type"))
-                .is_none()
         );
+        match dna.get_zome_name_for_app_entry_type(&AppEntryType::from("")) {
+            Err(DnaError::InvalidAppEntryTypeName(_)) => (),
+            other => panic!("expected InvalidAppEntryTypeName, got {:?}", other),
+        }
     }
 }
@@ -3,12 +3,18 @@
 //!  - and serialized to json
 
 use base64;
+use error::HolochainError;
 use serde::{
     self,
     de::{Deserializer, Visitor},
     ser::Serializer,
 };
 
+/// Every WebAssembly binary starts with the magic bytes "\0asm"...
+const WASM_MAGIC: [u8; 4] = [0x00, 0x61, 0x73, 0x6d];
+/// ...followed by the binary format version as a little endian u32.
+const WASM_VERSION: [u8; 4] = [0x01, 0x00, 0x00, 0x00];
+
 /// Private helper for converting binary WebAssembly into base64 serialized string.
 fn _vec_u8_to_b64_str<S>(data: &[u8], s: S) -> Result<S::Ok, S::Error>
 where
@@ -75,4 +81,22 @@ impl DnaWasm {
     pub fn new() -> Self {
         Default::default()
     }
+
+    /// Checks that the code starts with the WebAssembly magic bytes and binary format
+    /// version 1, so DNAs carrying anything else get rejected before the Ribosome runs them.
+    /// The rest of the module is not parsed.
+    pub fn validate_module(&self) -> Result<(), HolochainError> {
+        if self.code.len() < 8 || self.code[0..4] != WASM_MAGIC {
+            return Err(HolochainError::ErrorGeneric(String::from(
+                "Code is not a WebAssembly module (missing \\0asm header)",
+            )));
+        }
+        if self.code[4..8] != WASM_VERSION {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "Unsupported WebAssembly binary version {:?}",
+                &self.code[4..8]
+            )));
+        }
+        Ok(())
+    }
 }
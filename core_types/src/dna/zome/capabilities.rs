@@ -108,6 +108,11 @@ impl FnParameter {
     }
 }
 
+/// Name of a function declaration that stands for all functions the zome's WASM exports.
+/// Gets expanded into concrete declarations when the DNA is loaded,
+/// see Zome::expand_wildcard_functions().
+pub const WILDCARD_FN_NAME: &str = "*";
+
 /// Represents a zome "fn_declarations" object.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Hash)]
 pub struct FnDeclaration {
@@ -116,6 +121,14 @@ pub struct FnDeclaration {
     pub name: String,
     pub inputs: Vec<FnParameter>,
     pub outputs: Vec<FnParameter>,
+    /// Read-only functions are pure queries: any attempt to commit, update, remove
+    /// or link entries from within them is rejected.
+    #[serde(default, skip_serializing_if = "super::is_false")]
+    pub read_only: bool,
+    /// App entry types this function may commit, as declared by the author.
+    /// Only enforced for zomes with "strict_commit_types" switched on.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commits_entry_types: Vec<String>,
 }
 
 impl Default for FnDeclaration {
@@ -125,6 +138,8 @@ impl Default for FnDeclaration {
             name: String::new(),
             inputs: Vec::new(),
             outputs: Vec::new(),
+            read_only: false,
+            commits_entry_types: Vec::new(),
         }
     }
 }
@@ -146,6 +161,10 @@ pub struct Capability {
     /// "fn_declarations" array
     #[serde(default)]
     pub functions: Vec<FnDeclaration>,
+
+    /// Human readable explanation of what this capability grants, shown in permission prompts.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
 }
 
 impl Default for Capability {
@@ -154,6 +173,7 @@ impl Default for Capability {
         Capability {
             cap_type: CapabilityType::new(),
             functions: Vec::new(),
+            description: String::new(),
         }
     }
 }
@@ -236,4 +256,55 @@ mod tests {
 
         assert_eq!(fixture, cap);
     }
+
+    #[test]
+    fn read_only_round_trip() {
+        let fn_declaration: FnDeclaration = serde_json::from_str(
+            r#"{
+                "name": "list_posts",
+                "inputs": [],
+                "outputs": [],
+                "read_only": true
+            }"#,
+        )
+        .unwrap();
+        assert!(fn_declaration.read_only);
+
+        let serialized = serde_json::to_string(&fn_declaration).unwrap();
+        let round_tripped: FnDeclaration = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(fn_declaration, round_tripped);
+        assert!(!FnDeclaration::new().read_only);
+    }
+
+    #[test]
+    fn description_round_trip() {
+        let cap: Capability = serde_json::from_str(
+            r#"{
+                "capability": {
+                    "membrane": "public"
+                },
+                "functions": [],
+                "description": "read access to posts"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!("read access to posts", cap.description);
+
+        let serialized = serde_json::to_string(&cap).unwrap();
+        let round_tripped: Capability = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(cap, round_tripped);
+    }
+
+    #[test]
+    fn description_defaults_to_empty() {
+        let cap: Capability = serde_json::from_str(
+            r#"{
+                "capability": {
+                    "membrane": "public"
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(String::new(), cap.description);
+    }
 }
@@ -36,6 +36,49 @@ impl Default for Sharing {
     }
 }
 
+/// Enum for Zome EntryType "mutability" property:
+/// which changes entries of the type allow once they are committed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Hash)]
+pub enum Mutability {
+    #[serde(rename = "full")]
+    Full,
+    #[serde(rename = "append_only")]
+    AppendOnly,
+    #[serde(rename = "immutable")]
+    Immutable,
+}
+
+impl Mutability {
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    pub fn can_update(&self) -> bool {
+       match self {
+           Mutability::Full       => true,
+           Mutability::AppendOnly => true,
+           Mutability::Immutable  => false,
+       }
+    }
+
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    pub fn can_delete(&self) -> bool {
+       match self {
+           Mutability::Full       => true,
+           Mutability::AppendOnly => false,
+           Mutability::Immutable  => false,
+       }
+    }
+
+    pub fn is_full(&self) -> bool {
+        *self == Mutability::Full
+    }
+}
+
+impl Default for Mutability {
+    /// Default zome entry_type mutability is "full"
+    fn default() -> Self {
+        Mutability::Full
+    }
+}
+
 /// An individual object in a "links_to" array.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Hash)]
 pub struct LinksTo {
@@ -95,6 +138,28 @@ impl LinkedFrom {
     }
 }
 
+/// Declares that a field of the entry type holds the address of an entry of another
+/// (possibly another zome's) entry type.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Hash, Default)]
+pub struct EntryReference {
+    /// The top-level field holding the referenced entry's address
+    #[serde(default)]
+    pub field: String,
+
+    /// The app entry type the referenced entry must have
+    #[serde(default)]
+    pub expected_entry_type: String,
+}
+
+impl EntryReference {
+    pub fn new(field: &str, expected_entry_type: &str) -> Self {
+        EntryReference {
+            field: field.to_string(),
+            expected_entry_type: expected_entry_type.to_string(),
+        }
+    }
+}
+
 pub fn serialize_entry_types<S>(
     entry_types: &ZomeEntryTypes,
     serializer: S,
@@ -140,6 +205,22 @@ pub struct EntryTypeDef {
     /// An array of link definitions for links pointing to entries of this type
     #[serde(default)]
     pub linked_from: Vec<LinkedFrom>,
+
+    /// Top-level fields every entry of this type has to contain
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_fields: Vec<String>,
+
+    /// Whether entries of this type can be updated or deleted (full, append_only, immutable).
+    #[serde(default, skip_serializing_if = "Mutability::is_full")]
+    pub mutability: Mutability,
+
+    /// Fields that reference entries of other types by address
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<EntryReference>,
+
+    /// Top-level fields whose value no two live entries of this type may share
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unique_fields: Vec<String>,
 }
 
 impl EntryTypeDef {
@@ -160,6 +241,21 @@ mod tests {
         assert!(!Sharing::Private.can_publish());
     }
 
+    #[test]
+    fn mutability() {
+        assert!(Mutability::Full.can_update());
+        assert!(Mutability::Full.can_delete());
+        assert!(Mutability::AppendOnly.can_update());
+        assert!(!Mutability::AppendOnly.can_delete());
+        assert!(!Mutability::Immutable.can_update());
+        assert!(!Mutability::Immutable.can_delete());
+
+        let fixture: EntryTypeDef =
+            serde_json::from_str(r#"{"mutability": "append_only"}"#).unwrap();
+        assert_eq!(Mutability::AppendOnly, fixture.mutability);
+        assert_eq!(Mutability::Full, EntryTypeDef::new().mutability);
+    }
+
     #[test]
     fn build_and_compare() {
         let fixture: EntryTypeDef = serde_json::from_str(
@@ -198,4 +294,56 @@ mod tests {
 
         assert_eq!(fixture, entry);
     }
+
+    #[test]
+    fn required_fields_test() {
+        let fixture: EntryTypeDef = serde_json::from_str(
+            r#"{
+                "description": "test",
+                "required_fields": ["title", "body"]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            vec![String::from("title"), String::from("body")],
+            fixture.required_fields,
+        );
+
+        let fixture: EntryTypeDef = serde_json::from_str(r#"{"description": "test"}"#).unwrap();
+        assert!(fixture.required_fields.is_empty());
+    }
+
+    #[test]
+    fn references_test() {
+        let fixture: EntryTypeDef = serde_json::from_str(
+            r#"{
+                "description": "test",
+                "references": [
+                    {
+                        "field": "author",
+                        "expected_entry_type": "profile"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            vec![EntryReference::new("author", "profile")],
+            fixture.references,
+        );
+        assert!(EntryTypeDef::new().references.is_empty());
+    }
+
+    #[test]
+    fn unique_fields_test() {
+        let fixture: EntryTypeDef = serde_json::from_str(
+            r#"{
+                "description": "test",
+                "unique_fields": ["username"]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(vec![String::from("username")], fixture.unique_fields);
+        assert!(EntryTypeDef::new().unique_fields.is_empty());
+    }
 }
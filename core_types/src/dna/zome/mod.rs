@@ -9,6 +9,11 @@ use crate::{
 use dna::zome::entry_types::{deserialize_entry_types, serialize_entry_types, EntryTypeDef};
 use std::collections::BTreeMap;
 
+/// serde helper, leaves switched off flags out of the DNA so they don't change its hash
+pub(crate) fn is_false(flag: &bool) -> bool {
+    !flag
+}
+
 /// Enum for "zome" "config" "error_handling" property.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Hash)]
 pub enum ErrorHandling {
@@ -29,6 +34,27 @@ pub struct Config {
     /// How errors should be handled within this zome.
     #[serde(default)]
     pub error_handling: ErrorHandling,
+
+    /// Maximum number of 64KiB wasm memory pages this zome declares it needs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_memory_pages: Option<u32>,
+
+    /// Gas budget declared for a single call into this zome.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_budget: Option<u64>,
+
+    /// How many entries this zome may commit to the source chain per minute.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_commits_per_minute: Option<u32>,
+
+    /// Reject links of this zome's entry types whose tag is not declared in the DNA.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub strict_link_tags: bool,
+
+    /// Reject commits of app entry types that the committing function does not list
+    /// in its "commits_entry_types".
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub strict_commit_types: bool,
 }
 
 impl Default for Config {
@@ -36,6 +62,11 @@ impl Default for Config {
     fn default() -> Self {
         Config {
             error_handling: ErrorHandling::ThrowErrors,
+            max_memory_pages: None,
+            gas_budget: None,
+            max_commits_per_minute: None,
+            strict_link_tags: false,
+            strict_commit_types: false,
         }
     }
 }
@@ -47,6 +78,18 @@ impl Config {
     }
 }
 
+/// Summary of the resources a zome declares or requires, used for capacity planning
+/// before a DNA is activated.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Hash, DefaultJson)]
+pub struct ResourceProfile {
+    /// Declared maximum wasm memory pages, if any.
+    pub max_memory_pages: Option<u32>,
+    /// Declared gas budget per call, if any.
+    pub gas_budget: Option<u64>,
+    /// Size of the zome's wasm code in bytes.
+    pub wasm_bytes: usize,
+}
+
 pub type ZomeEntryTypes = BTreeMap<EntryType, EntryTypeDef>;
 pub type ZomeCapabilities = BTreeMap<String, capabilities::Capability>;
 
@@ -110,6 +153,51 @@ impl Zome {
             code: code.clone(),
         }
     }
+
+    /// Aggregates the declared resource limits from the config with the size of the wasm code.
+    pub fn resource_profile(&self) -> ResourceProfile {
+        ResourceProfile {
+            max_memory_pages: self.config.max_memory_pages,
+            gas_budget: self.config.gas_budget,
+            wasm_bytes: self.code.code.len(),
+        }
+    }
+
+    /// Looks up the declaration of the given function in all capabilities of the zome.
+    /// Capabilities are searched in the order of their names, so if several capabilities
+    /// declare the function the declaration of the first one is returned.
+    pub fn find_fn_declaration(&self, fn_name: &str) -> Option<&capabilities::FnDeclaration> {
+        self.capabilities.values().find_map(|capability| {
+            capability
+                .functions
+                .iter()
+                .find(|fn_declaration| fn_declaration.name == fn_name)
+        })
+    }
+
+    /// Replaces the wildcard declaration ("*") of every capability with a declaration for
+    /// each of the given functions that the capability does not declare explicitly.
+    /// The expanded declarations take their other fields (like read_only) from the wildcard.
+    pub fn expand_wildcard_functions(&mut self, exported_functions: &[String]) {
+        let is_wildcard = |fn_declaration: &capabilities::FnDeclaration| {
+            fn_declaration.name == capabilities::WILDCARD_FN_NAME
+        };
+        for capability in self.capabilities.values_mut() {
+            let wildcard = match capability.functions.iter().position(is_wildcard) {
+                Some(index) => capability.functions.remove(index),
+                None => continue,
+            };
+            for fn_name in exported_functions {
+                if capability.functions.iter().any(|f| &f.name == fn_name) {
+                    continue;
+                }
+                capability.functions.push(capabilities::FnDeclaration {
+                    name: fn_name.clone(),
+                    ..wildcard.clone()
+                });
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -123,6 +211,57 @@ pub mod tests {
         Zome::default()
     }
 
+    #[test]
+    fn expand_wildcard_functions_test() {
+        let mut zome = Zome::default();
+        for (cap_name, fn_names) in vec![("admin", vec!["*"]), ("public", vec!["get_post"])] {
+            let mut capability = capabilities::Capability::new();
+            for fn_name in fn_names {
+                let mut fn_declaration = capabilities::FnDeclaration::new();
+                fn_declaration.name = String::from(fn_name);
+                capability.functions.push(fn_declaration);
+            }
+            zome.capabilities.insert(String::from(cap_name), capability);
+        }
+
+        let exported = vec![String::from("get_post"), String::from("create_post")];
+        zome.expand_wildcard_functions(&exported);
+
+        let fn_names = |cap_name: &str| -> Vec<String> {
+            zome.capabilities[cap_name]
+                .functions
+                .iter()
+                .map(|f| f.name.clone())
+                .collect()
+        };
+        assert_eq!(exported, fn_names("admin"));
+        assert_eq!(vec![String::from("get_post")], fn_names("public"));
+    }
+
+    #[test]
+    fn find_fn_declaration_test() {
+        let mut zome = Zome::default();
+        for (cap_name, input_type) in vec![("public", "string"), ("admin", "json")] {
+            let mut fn_declaration = capabilities::FnDeclaration::new();
+            fn_declaration.name = String::from("get_post");
+            fn_declaration
+                .inputs
+                .push(capabilities::FnParameter::new("post_address", input_type));
+            let mut capability = capabilities::Capability::new();
+            capability.functions.push(fn_declaration);
+            zome.capabilities.insert(String::from(cap_name), capability);
+        }
+
+        let fn_declaration = zome
+            .find_fn_declaration("get_post")
+            .expect("get_post should be declared");
+        assert_eq!(
+            vec![capabilities::FnParameter::new("post_address", "json")],
+            fn_declaration.inputs,
+        );
+        assert_eq!(None, zome.find_fn_declaration("create_post"));
+    }
+
     #[test]
     fn build_and_compare() {
         let fixture: Zome = serde_json::from_str(
@@ -162,4 +301,31 @@ pub mod tests {
 
         assert_eq!(zome, Zome::try_from(JsonString::from(expected)).unwrap(),);
     }
+
+    #[test]
+    fn resource_profile_test() {
+        let mut zome = Zome::default();
+        assert_eq!(
+            ResourceProfile {
+                max_memory_pages: None,
+                gas_budget: None,
+                wasm_bytes: 0,
+            },
+            zome.resource_profile(),
+        );
+
+        zome.config.max_memory_pages = Some(16);
+        zome.config.gas_budget = Some(1_000_000);
+        zome.code = DnaWasm {
+            code: vec![0, 1, 2, 3],
+        };
+        assert_eq!(
+            ResourceProfile {
+                max_memory_pages: Some(16),
+                gas_budget: Some(1_000_000),
+                wasm_bytes: 4,
+            },
+            zome.resource_profile(),
+        );
+    }
 }
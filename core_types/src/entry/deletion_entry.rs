@@ -7,24 +7,42 @@ use crate::{cas::content::Address, error::HolochainError, json::JsonString};
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, DefaultJson)]
 pub struct DeletionEntry {
     deleted_entry_address: Address,
+
+    /// Why the entry got deleted, for audit trails.
+    /// Not serialized when unset, so deletions without a reason keep their address.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
 }
 
 impl DeletionEntry {
     pub fn new(deleted_entry_address: Address) -> Self {
         DeletionEntry {
             deleted_entry_address,
+            reason: None,
+        }
+    }
+
+    pub fn new_with_reason(deleted_entry_address: Address, reason: String) -> Self {
+        DeletionEntry {
+            deleted_entry_address,
+            reason: Some(reason),
         }
     }
 
     pub fn deleted_entry_address(self) -> Address {
         self.deleted_entry_address
     }
+
+    pub fn reason(&self) -> Option<&String> {
+        self.reason.as_ref()
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
     use crate::{cas::content::AddressableContent, entry::test_entry_a};
+    use std::convert::TryFrom;
 
     pub fn test_deletion_entry() -> DeletionEntry {
         let entry = test_entry_a();
@@ -38,4 +56,32 @@ pub mod tests {
             test_deletion_entry().deleted_entry_address()
         );
     }
+
+    #[test]
+    fn deletion_entry_reason_test() {
+        assert_eq!(None, test_deletion_entry().reason());
+
+        let deletion_entry =
+            DeletionEntry::new_with_reason(test_entry_a().address(), String::from("spam"));
+        assert_eq!(Some(&String::from("spam")), deletion_entry.reason());
+
+        let json = JsonString::from(deletion_entry.clone());
+        assert_eq!(
+            deletion_entry,
+            DeletionEntry::try_from(json).expect("could not restore DeletionEntry")
+        );
+    }
+
+    #[test]
+    fn deletion_entry_without_reason_json_test() {
+        // JSON written before the reason field existed still deserializes,
+        // and deletions without reason serialize as before
+        let json = JsonString::from(format!(
+            "{{\"deleted_entry_address\":\"{}\"}}",
+            test_entry_a().address()
+        ));
+        let deletion_entry = DeletionEntry::try_from(json.clone()).unwrap();
+        assert_eq!(test_deletion_entry(), deletion_entry);
+        assert_eq!(json, JsonString::from(deletion_entry));
+    }
 }
@@ -0,0 +1,279 @@
+//! Computes the changes between two revisions of an app entry as an RFC 6902 JSON Patch.
+use crate::{
+    entry::Entry,
+    error::{HcResult, HolochainError},
+    json::JsonString,
+};
+use serde_json::{self, Map, Value};
+
+/// A single RFC 6902 operation. Only add, remove and replace are ever generated.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, DefaultJson)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+/// The JSON Patch turning the content of an old entry revision into the new one.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, DefaultJson)]
+pub struct EntryDiff(pub Vec<PatchOperation>);
+
+impl EntryDiff {
+    pub fn operations(&self) -> &Vec<PatchOperation> {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Applies the patch to the given JSON document.
+    pub fn apply(&self, document: &Value) -> HcResult<Value> {
+        let mut document = document.clone();
+        for operation in &self.0 {
+            match operation {
+                PatchOperation::Add { path, value } => {
+                    let (parent, key) = parent_mut(&mut document, path)?;
+                    match parent {
+                        Value::Object(map) => {
+                            map.insert(key, value.clone());
+                        }
+                        Value::Array(array) => {
+                            let index = if key == "-" {
+                                array.len()
+                            } else {
+                                array_index(&key, array.len() + 1)?
+                            };
+                            array.insert(index, value.clone());
+                        }
+                        _ => return Err(invalid_path(path)),
+                    }
+                }
+                PatchOperation::Remove { path } => {
+                    let (parent, key) = parent_mut(&mut document, path)?;
+                    match parent {
+                        Value::Object(map) => {
+                            map.remove(&key).ok_or_else(|| invalid_path(path))?;
+                        }
+                        Value::Array(array) => {
+                            let index = array_index(&key, array.len())?;
+                            array.remove(index);
+                        }
+                        _ => return Err(invalid_path(path)),
+                    }
+                }
+                PatchOperation::Replace { path, value } => {
+                    if path.is_empty() {
+                        document = value.clone();
+                    } else {
+                        let target = document
+                            .pointer_mut(path)
+                            .ok_or_else(|| invalid_path(path))?;
+                        *target = value.clone();
+                    }
+                }
+            }
+        }
+        Ok(document)
+    }
+}
+
+/// Computes the JSON Patch between the contents of two revisions of an app entry.
+/// Both entries must be app entries of the same type holding JSON values.
+pub fn entry_diff(old: &Entry, new: &Entry) -> HcResult<EntryDiff> {
+    match (old, new) {
+        (Entry::App(old_type, old_value), Entry::App(new_type, new_value)) => {
+            if old_type != new_type {
+                return Err(HolochainError::ErrorGeneric(format!(
+                    "Can not diff entries of different types {:?} and {:?}",
+                    old_type, new_type
+                )));
+            }
+            let mut operations = Vec::new();
+            diff_values(
+                "",
+                &parse_json(old_value)?,
+                &parse_json(new_value)?,
+                &mut operations,
+            );
+            Ok(EntryDiff(operations))
+        }
+        _ => Err(HolochainError::ErrorGeneric(String::from(
+            "Can only diff app entries",
+        ))),
+    }
+}
+
+fn parse_json(value: &JsonString) -> HcResult<Value> {
+    Ok(serde_json::from_str(&String::from(value.to_owned()))?)
+}
+
+/// Escapes a key for use as a JSON pointer reference token.
+fn escape_key(key: &str) -> String {
+    key.replace("~", "~0").replace("/", "~1")
+}
+
+fn unescape_key(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+fn diff_values(path: &str, old: &Value, new: &Value, operations: &mut Vec<PatchOperation>) {
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            diff_objects(path, old_map, new_map, operations)
+        }
+        (Value::Array(old_array), Value::Array(new_array)) => {
+            let common = old_array.len().min(new_array.len());
+            for index in 0..common {
+                diff_values(
+                    &format!("{}/{}", path, index),
+                    &old_array[index],
+                    &new_array[index],
+                    operations,
+                );
+            }
+            // remove from the back so earlier indices stay valid
+            for index in (common..old_array.len()).rev() {
+                operations.push(PatchOperation::Remove {
+                    path: format!("{}/{}", path, index),
+                });
+            }
+            for (index, value) in new_array.iter().enumerate().skip(common) {
+                operations.push(PatchOperation::Add {
+                    path: format!("{}/{}", path, index),
+                    value: value.clone(),
+                });
+            }
+        }
+        _ => {
+            if old != new {
+                operations.push(PatchOperation::Replace {
+                    path: path.to_string(),
+                    value: new.clone(),
+                });
+            }
+        }
+    }
+}
+
+fn diff_objects(
+    path: &str,
+    old_map: &Map<String, Value>,
+    new_map: &Map<String, Value>,
+    operations: &mut Vec<PatchOperation>,
+) {
+    for (key, old_value) in old_map {
+        let child_path = format!("{}/{}", path, escape_key(key));
+        match new_map.get(key) {
+            Some(new_value) => diff_values(&child_path, old_value, new_value, operations),
+            None => operations.push(PatchOperation::Remove { path: child_path }),
+        }
+    }
+    for (key, new_value) in new_map {
+        if !old_map.contains_key(key) {
+            operations.push(PatchOperation::Add {
+                path: format!("{}/{}", path, escape_key(key)),
+                value: new_value.clone(),
+            });
+        }
+    }
+}
+
+fn invalid_path(path: &str) -> HolochainError {
+    HolochainError::ErrorGeneric(format!("Invalid JSON Patch path '{}'", path))
+}
+
+fn array_index(token: &str, upper_bound: usize) -> HcResult<usize> {
+    token
+        .parse::<usize>()
+        .ok()
+        .filter(|index| *index < upper_bound)
+        .ok_or_else(|| invalid_path(token))
+}
+
+/// Resolves the parent of the value a path points to, together with the last reference token.
+fn parent_mut<'a>(document: &'a mut Value, path: &str) -> HcResult<(&'a mut Value, String)> {
+    let split = path.rfind('/').ok_or_else(|| invalid_path(path))?;
+    let (parent_path, key) = path.split_at(split);
+    let parent = document
+        .pointer_mut(parent_path)
+        .ok_or_else(|| invalid_path(path))?;
+    Ok((parent, unescape_key(&key[1..])))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        cas::content::AddressableContent,
+        entry::{
+            deletion_entry::DeletionEntry,
+            entry_type::{test_app_entry_type, test_app_entry_type_b},
+            test_entry_a,
+        },
+    };
+
+    fn app_entry(value: Value) -> Entry {
+        Entry::App(test_app_entry_type(), JsonString::from(value))
+    }
+
+    #[test]
+    fn entry_diff_round_trip() {
+        let old_value = json!({
+            "title": "hello",
+            "tags": ["a", "b", "c"],
+            "meta": {"draft": true, "a/b": 1},
+            "removed": 1
+        });
+        let new_value = json!({
+            "title": "hello world",
+            "tags": ["a", "x"],
+            "meta": {"draft": false, "a/b": 2, "published": "today"},
+            "added": [1, 2]
+        });
+
+        let diff = entry_diff(&app_entry(old_value.clone()), &app_entry(new_value.clone()))
+            .expect("diff should succeed");
+
+        assert!(diff.operations().contains(&PatchOperation::Replace {
+            path: String::from("/title"),
+            value: json!("hello world"),
+        }));
+        assert!(diff.operations().contains(&PatchOperation::Replace {
+            path: String::from("/meta/a~1b"),
+            value: json!(2),
+        }));
+        assert!(diff.operations().contains(&PatchOperation::Remove {
+            path: String::from("/removed"),
+        }));
+        assert_eq!(new_value, diff.apply(&old_value).unwrap());
+
+        let unchanged = entry_diff(&app_entry(old_value.clone()), &app_entry(old_value)).unwrap();
+        assert!(unchanged.is_empty());
+    }
+
+    #[test]
+    fn entry_diff_serializes_as_json_patch() {
+        let diff = EntryDiff(vec![PatchOperation::Remove {
+            path: String::from("/foo"),
+        }]);
+        assert_eq!(
+            JsonString::from("[{\"op\":\"remove\",\"path\":\"/foo\"}]"),
+            JsonString::from(diff),
+        );
+    }
+
+    #[test]
+    fn entry_diff_errors() {
+        let entry = app_entry(json!({"title": "hello"}));
+        let other_type = Entry::App(test_app_entry_type_b(), JsonString::from(json!({})));
+        assert!(entry_diff(&entry, &other_type).is_err());
+
+        let not_json = Entry::App(test_app_entry_type(), JsonString::from("not json"));
+        assert!(entry_diff(&entry, &not_json).is_err());
+
+        let deletion = Entry::Deletion(DeletionEntry::new(test_entry_a().address()));
+        assert!(entry_diff(&entry, &deletion).is_err());
+    }
+}
@@ -1,5 +1,7 @@
 use error::HolochainError;
 use json::JsonString;
+use serde::{de::Error as DeError, Deserialize, Deserializer};
+use serde_json::{self, Value};
 use std::{
     convert::TryFrom,
     fmt::{Display, Formatter, Result as FmtResult},
@@ -42,9 +44,7 @@ impl ToString for AppEntryType {
 
 // Enum for listing all System Entry Types
 // Variant `Data` is for user defined entry types
-#[derive(
-    Debug, Clone, PartialEq, Hash, Serialize, Deserialize, DefaultJson, PartialOrd, Ord, Eq,
-)]
+#[derive(Debug, Clone, PartialEq, Hash, Serialize, DefaultJson, PartialOrd, Ord, Eq)]
 pub enum EntryType {
     App(AppEntryType),
 
@@ -56,6 +56,52 @@ pub enum EntryType {
     LinkList,
     ChainHeader,
     ChainMigrate,
+
+    /// A system entry type this build does not know about, e.g. one introduced by a newer
+    /// version. Keeps the original name so DNAs and chains referencing it still load.
+    /// Entries of unknown type are skipped by validation.
+    /// The name is kept without the system prefix, which String::from() adds back.
+    Unknown(String),
+}
+
+impl<'de> Deserialize<'de> for EntryType {
+    /// Same format as the derived implementation, but maps variants this build does not know
+    /// to EntryType::Unknown instead of failing.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(match Value::deserialize(deserializer)? {
+            Value::String(variant) => match variant.as_ref() {
+                "Dna" => EntryType::Dna,
+                "AgentId" => EntryType::AgentId,
+                "Deletion" => EntryType::Deletion,
+                "LinkAdd" => EntryType::LinkAdd,
+                "LinkRemove" => EntryType::LinkRemove,
+                "LinkList" => EntryType::LinkList,
+                "ChainHeader" => EntryType::ChainHeader,
+                "ChainMigrate" => EntryType::ChainMigrate,
+                _ => EntryType::Unknown(variant),
+            },
+            Value::Object(map) => {
+                let (variant, value) = map
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| D::Error::custom("empty EntryType object"))?;
+                if variant == "App" {
+                    EntryType::App(serde_json::from_value(value).map_err(D::Error::custom)?)
+                } else {
+                    match value {
+                        Value::String(name) if variant == "Unknown" => EntryType::Unknown(name),
+                        _ => EntryType::Unknown(variant),
+                    }
+                }
+            }
+            other => {
+                return Err(D::Error::custom(format!("invalid EntryType: {}", other)));
+            }
+        })
+    }
 }
 
 impl From<AppEntryType> for EntryType {
@@ -78,6 +124,13 @@ impl TryFrom<EntryType> for AppEntryType {
 }
 
 impl EntryType {
+    pub fn is_unknown(&self) -> bool {
+        match self {
+            EntryType::Unknown(_) => true,
+            _ => false,
+        }
+    }
+
     pub fn is_app(&self) -> bool {
         match self {
             EntryType::App(_) => true,
@@ -110,6 +163,7 @@ impl EntryType {
             */
         match self {
             EntryType::Dna => false,
+            EntryType::Unknown(_) => false,
             _ => true,
         }
     }
@@ -137,6 +191,9 @@ impl FromStr for EntryType {
             sys_prefix!("link_remove") => EntryType::LinkRemove,
             sys_prefix!("link_list") => EntryType::LinkList,
             sys_prefix!("chain_migrate") => EntryType::ChainMigrate,
+            _ if s.starts_with(sys_prefix!("")) => {
+                EntryType::Unknown(s[sys_prefix!("").len()..].into())
+            }
             _ => EntryType::App(AppEntryType(s.into())),
         })
     }
@@ -154,6 +211,7 @@ impl From<EntryType> for String {
             EntryType::LinkRemove => sys_prefix!("link_remove"),
             EntryType::LinkList => sys_prefix!("link_list"),
             EntryType::ChainMigrate => sys_prefix!("chain_migrate"),
+            EntryType::Unknown(ref name) => return format!("{}{}", sys_prefix!(""), name),
         })
     }
 }
@@ -221,6 +279,7 @@ pub mod tests {
             EntryType::LinkList,
             EntryType::ChainHeader,
             EntryType::ChainMigrate,
+            EntryType::Unknown(String::from("future_type")),
         ]
     }
 
@@ -272,8 +331,56 @@ pub mod tests {
         for t in test_types() {
             match t {
                 EntryType::Dna => assert!(!t.can_publish()),
+                EntryType::Unknown(_) => assert!(!t.can_publish()),
                 _ => assert!(t.can_publish()),
             }
         }
     }
+
+    #[test]
+    fn unknown_entry_type_test() {
+        assert_eq!(
+            EntryType::Unknown(String::from("future_type")),
+            EntryType::from("%future_type"),
+        );
+        assert_eq!(
+            "%future_type",
+            String::from(EntryType::Unknown(String::from("future_type"))),
+        );
+        // names of unknown types read from JSON survive the string round trip
+        let unknown: EntryType = serde_json::from_str("\"FutureSysType\"").unwrap();
+        assert_eq!(unknown.clone(), EntryType::from(String::from(unknown)));
+        for entry_type in test_types() {
+            assert_eq!(
+                entry_type.clone(),
+                EntryType::from(String::from(entry_type))
+            );
+        }
+        assert!(EntryType::from("%future_type").is_unknown());
+        assert!(!EntryType::from("%future_type").is_app());
+    }
+
+    #[test]
+    fn deserialize_unknown_entry_type_test() {
+        let known: EntryType = serde_json::from_str("\"ChainMigrate\"").unwrap();
+        assert_eq!(EntryType::ChainMigrate, known);
+
+        let app: EntryType = serde_json::from_str("{\"App\":\"foo\"}").unwrap();
+        assert_eq!(EntryType::App(AppEntryType::from("foo")), app);
+
+        let unknown: EntryType = serde_json::from_str("\"FutureSysType\"").unwrap();
+        assert_eq!(EntryType::Unknown(String::from("FutureSysType")), unknown);
+
+        let unknown: EntryType = serde_json::from_str("{\"FutureSysType\":{}}").unwrap();
+        assert_eq!(EntryType::Unknown(String::from("FutureSysType")), unknown);
+
+        // round trip
+        for entry_type in test_types() {
+            let json = serde_json::to_string(&entry_type).unwrap();
+            assert_eq!(
+                entry_type,
+                serde_json::from_str::<EntryType>(&json).unwrap()
+            );
+        }
+    }
 }
@@ -1,5 +1,7 @@
 pub mod deletion_entry;
+pub mod diff;
 pub mod entry_type;
+pub mod versioning;
 
 use self::deletion_entry::DeletionEntry;
 use agent::{test_agent_id, AgentId};
@@ -14,11 +16,21 @@ use json::{default_to_json, default_try_from_json, JsonString, RawString};
 use link::{link_add::LinkAdd, link_list::LinkList, link_remove::LinkRemove};
 use multihash::Hash;
 use serde::{ser::SerializeTuple, Deserialize, Deserializer, Serializer};
+use serde_json;
 use snowflake;
 use std::convert::TryFrom;
+use time::Iso8601;
 
 pub type AppEntryValue = JsonString;
 
+/// Key an app entry value may use to assert its own creation time.
+///
+/// Addressing policy: the timestamp is part of the entry value and therefore part of the
+/// entry's address. It is never stripped or normalized, so two entries that only differ in
+/// `created_at` have different addresses. Apps that rely on identical content resolving to
+/// the same address should leave it out.
+pub const CREATED_AT_KEY: &str = "created_at";
+
 fn serialize_app_entry<S>(
     app_entry_type: &AppEntryType,
     app_entry_value: &AppEntryValue,
@@ -92,6 +104,24 @@ impl Entry {
             Entry::ChainMigrate(_) => EntryType::ChainMigrate,
         }
     }
+
+    /// The creation time an app entry asserts through its `created_at` field, if any.
+    /// Always None for system entries and for app entries whose value is not a JSON object.
+    pub fn created_at(&self) -> Option<Iso8601> {
+        match &self {
+            Entry::App(_, app_entry_value) => {
+                serde_json::from_str::<serde_json::Value>(&String::from(app_entry_value.to_owned()))
+                    .ok()
+                    .and_then(|value| {
+                        value
+                            .get(CREATED_AT_KEY)
+                            .and_then(|created_at| created_at.as_str())
+                            .map(|created_at| Iso8601::from(created_at.to_string()))
+                    })
+            }
+            _ => None,
+        }
+    }
 }
 
 impl PartialEq for Entry {
@@ -124,6 +154,20 @@ pub struct EntryWithMeta {
     pub maybe_crud_link: Option<Address>,
 }
 
+/// Checks that the entry's content hashes to the address it was claimed to have,
+/// e.g. by a peer sending it. Fails with HolochainError::AddressMismatch otherwise.
+pub fn verify_entry_address(entry: &Entry, claimed: &Address) -> Result<(), HolochainError> {
+    let address = entry.address();
+    if address == *claimed {
+        Ok(())
+    } else {
+        Err(HolochainError::AddressMismatch(format!(
+            "Entry claimed to have address {} but its content hashes to {}",
+            claimed, address
+        )))
+    }
+}
+
 /// dummy entry value
 #[cfg_attr(tarpaulin, skip)]
 pub fn test_entry_value() -> JsonString {
@@ -220,8 +264,27 @@ pub mod tests {
             storage::{test_content_addressable_storage, ExampleContentAddressableStorage},
         },
         entry::{expected_entry_address, Entry},
+        json::canonical_json,
+        link::link_add::tests::test_link_entry,
     };
 
+    #[test]
+    fn verify_entry_address_test() {
+        let entry = test_entry();
+        assert_eq!(
+            Ok(()),
+            verify_entry_address(&entry, &expected_entry_address())
+        );
+
+        let tampered = test_entry_b();
+        match verify_entry_address(&tampered, &expected_entry_address()) {
+            Err(HolochainError::AddressMismatch(message)) => {
+                assert!(message.contains(&String::from(expected_entry_address())))
+            }
+            other => panic!("expected AddressMismatch, got {:?}", other),
+        }
+    }
+
     #[test]
     /// tests for PartialEq
     fn eq() {
@@ -235,12 +298,51 @@ pub mod tests {
         assert_ne!(entry_a, entry_b);
     }
 
+    #[test]
+    /// show created_at is read from app entry values and is part of the address
+    fn created_at() {
+        assert_eq!(None, test_entry().created_at());
+        assert_eq!(
+            None,
+            Entry::Deletion(DeletionEntry::new(test_entry().address())).created_at()
+        );
+
+        let entry_a = Entry::App(
+            test_app_entry_type(),
+            JsonString::from("{\"created_at\":\"2018-10-11T03:23:38+00:00\"}"),
+        );
+        let entry_b = Entry::App(
+            test_app_entry_type(),
+            JsonString::from("{\"created_at\":\"2018-10-11T03:23:39+00:00\"}"),
+        );
+        assert_eq!(
+            Some(Iso8601::from("2018-10-11T03:23:38+00:00")),
+            entry_a.created_at(),
+        );
+        assert_ne!(entry_a.address(), entry_b.address());
+    }
+
     #[test]
     /// test entry.address() against a known value
     fn known_address() {
         assert_eq!(expected_entry_address(), test_entry().address());
     }
 
+    #[test]
+    /// show that entries stay addressed by their content as stored, not by its canonical
+    /// JSON, so addresses of existing chains don't change
+    fn address_of_content() {
+        let entry = test_link_entry();
+        assert_ne!(
+            String::from(entry.content()),
+            canonical_json(&entry).unwrap()
+        );
+        assert_eq!(
+            Address::encode_from_str(&String::from(entry.content()), Hash::SHA2256),
+            entry.address()
+        );
+    }
+
     #[test]
     /// show From<Entry> for JsonString
     fn json_string_from_entry_test() {
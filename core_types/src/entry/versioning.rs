@@ -0,0 +1,235 @@
+//! Content versions for app entries, so entries committed with an older struct layout
+//! can be upgraded to the current one when they are read.
+//!
+//! A versioned app entry value is stored as `{"__entry_version": n, "content": ...}`.
+//! The version marker is part of the entry value and therefore part of the address:
+//! the same data committed in two versions has two different addresses. Values without
+//! the marker are treated as version 1, which covers entries committed before versioning.
+use crate::{
+    entry::{entry_type::AppEntryType, Entry},
+    error::HolochainError,
+    json::JsonString,
+};
+use serde_json::{self, Value};
+use std::{collections::HashMap, convert::TryFrom};
+
+pub const ENTRY_VERSION_KEY: &str = "__entry_version";
+pub const ENTRY_CONTENT_KEY: &str = "content";
+
+/// Upgrades the content of an entry from one version to the next.
+pub type EntryMigration = fn(JsonString) -> Result<JsonString, HolochainError>;
+
+/// Registry of content migrations per app entry type.
+/// The current version of an entry type is one more than the highest version
+/// a migration is registered for, or 1 if there are none.
+#[derive(Clone, Default)]
+pub struct EntryMigrations {
+    migrations: HashMap<AppEntryType, HashMap<u32, EntryMigration>>,
+}
+
+impl EntryMigrations {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Registers the migration turning content of `from_version` into `from_version + 1`.
+    pub fn register(
+        &mut self,
+        entry_type: AppEntryType,
+        from_version: u32,
+        migration: EntryMigration,
+    ) {
+        self.migrations
+            .entry(entry_type)
+            .or_insert_with(HashMap::new)
+            .insert(from_version, migration);
+    }
+
+    pub fn current_version(&self, entry_type: &AppEntryType) -> u32 {
+        self.migrations
+            .get(entry_type)
+            .and_then(|migrations| migrations.keys().max())
+            .map(|version| version + 1)
+            .unwrap_or(1)
+    }
+
+    /// Wraps content in the version marker of the current version, for committing.
+    /// Fails if the content is not valid JSON.
+    pub fn versioned(
+        &self,
+        entry_type: &AppEntryType,
+        content: JsonString,
+    ) -> Result<JsonString, HolochainError> {
+        let content: Value = serde_json::from_str(&String::from(content))?;
+        Ok(JsonString::from(json!({
+            ENTRY_VERSION_KEY: self.current_version(entry_type),
+            ENTRY_CONTENT_KEY: content,
+        })))
+    }
+
+    /// Removes the version marker and runs all migrations from the stored version
+    /// up to the current one.
+    pub fn upgrade(
+        &self,
+        entry_type: &AppEntryType,
+        value: JsonString,
+    ) -> Result<JsonString, HolochainError> {
+        let (mut version, mut content) = split_version(value)?;
+        let current_version = self.current_version(entry_type);
+        if version > current_version {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "Entry of type {} has version {} but only versions up to {} are known",
+                entry_type.to_string(),
+                version,
+                current_version
+            )));
+        }
+        while version < current_version {
+            let migration = self
+                .migrations
+                .get(entry_type)
+                .and_then(|migrations| migrations.get(&version))
+                .ok_or_else(|| {
+                    HolochainError::ErrorGeneric(format!(
+                        "No migration registered for entry type {} from version {}",
+                        entry_type.to_string(),
+                        version
+                    ))
+                })?;
+            content = migration(content)?;
+            version += 1;
+        }
+        Ok(content)
+    }
+
+    /// Reads an app entry into the current struct for its type, upgrading old content first.
+    pub fn typed_entry<T: TryFrom<JsonString>>(&self, entry: &Entry) -> Result<T, HolochainError> {
+        match entry {
+            Entry::App(entry_type, value) => T::try_from(self.upgrade(entry_type, value.clone())?)
+                .map_err(|_| {
+                    HolochainError::SerializationError(format!(
+                        "Could not read entry of type {} into the current struct",
+                        entry_type.to_string()
+                    ))
+                }),
+            _ => Err(HolochainError::ErrorGeneric(String::from(
+                "Only app entries have typed content",
+            ))),
+        }
+    }
+}
+
+/// Fails for version markers that don't fit a u32 instead of truncating them.
+fn split_version(value: JsonString) -> Result<(u32, JsonString), HolochainError> {
+    let json: Value = serde_json::from_str(&String::from(value.clone()))?;
+    match json {
+        Value::Object(ref map) if map.len() == 2 && map.contains_key(ENTRY_CONTENT_KEY) => {
+            match map.get(ENTRY_VERSION_KEY).and_then(Value::as_u64) {
+                Some(version) if version > u64::from(u32::max_value()) => {
+                    Err(HolochainError::ErrorGeneric(format!(
+                        "Entry version {} is out of range",
+                        version
+                    )))
+                }
+                Some(version) => Ok((
+                    version as u32,
+                    JsonString::from(map[ENTRY_CONTENT_KEY].clone()),
+                )),
+                None => Ok((1, value)),
+            }
+        }
+        _ => Ok((1, value)),
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, DefaultJson, PartialEq)]
+    struct PostV2 {
+        title: String,
+        body: String,
+    }
+
+    fn split_content(content: JsonString) -> Result<JsonString, HolochainError> {
+        let content: Value = serde_json::from_str(&String::from(content))?;
+        let text = content["text"].as_str().unwrap_or_default().to_string();
+        let mut lines = text.splitn(2, '\n');
+        Ok(JsonString::from(json!({
+            "title": lines.next().unwrap_or_default(),
+            "body": lines.next().unwrap_or_default(),
+        })))
+    }
+
+    fn post_migrations() -> EntryMigrations {
+        let mut migrations = EntryMigrations::new();
+        migrations.register(AppEntryType::from("post"), 1, split_content);
+        migrations
+    }
+
+    #[test]
+    fn reads_v1_entry_as_v2() {
+        let migrations = post_migrations();
+        let v1_entry = Entry::App(
+            AppEntryType::from("post"),
+            JsonString::from(json!({"text": "Hello\nWorld"})),
+        );
+
+        let post: PostV2 = migrations.typed_entry(&v1_entry).unwrap();
+        assert_eq!(
+            PostV2 {
+                title: String::from("Hello"),
+                body: String::from("World"),
+            },
+            post
+        );
+    }
+
+    #[test]
+    fn current_version_is_not_migrated() {
+        let migrations = post_migrations();
+        let post_type = AppEntryType::from("post");
+        assert_eq!(2, migrations.current_version(&post_type));
+        assert_eq!(
+            1,
+            migrations.current_version(&AppEntryType::from("comment"))
+        );
+
+        let content = JsonString::from(json!({"title": "Hello", "body": "World"}));
+        let v2_entry = Entry::App(
+            post_type.clone(),
+            migrations.versioned(&post_type, content.clone()).unwrap(),
+        );
+        assert_eq!(
+            PostV2::try_from(content).unwrap(),
+            migrations.typed_entry::<PostV2>(&v2_entry).unwrap()
+        );
+
+        let v3_entry = Entry::App(
+            post_type.clone(),
+            JsonString::from(json!({ENTRY_VERSION_KEY: 3, ENTRY_CONTENT_KEY: {}})),
+        );
+        assert!(migrations.typed_entry::<PostV2>(&v3_entry).is_err());
+    }
+
+    #[test]
+    fn invalid_content_is_not_versioned() {
+        let migrations = post_migrations();
+        assert!(migrations
+            .versioned(&AppEntryType::from("post"), JsonString::from("{not json"))
+            .is_err());
+    }
+
+    #[test]
+    fn out_of_range_version_is_rejected() {
+        let migrations = post_migrations();
+        let post_type = AppEntryType::from("post");
+        // would be version 2 if it got truncated to a u32
+        let value = JsonString::from(json!({
+            ENTRY_VERSION_KEY: (1u64 << 32) + 2,
+            ENTRY_CONTENT_KEY: {"title": "Hello", "body": "World"},
+        }));
+        assert!(migrations.upgrade(&post_type, value).is_err());
+    }
+}
@@ -0,0 +1,27 @@
+//! Entry access control lists are stored as EAV meta data on the entry's address,
+//! one value per agent that may read the entry, the same way CRUD status is stored.
+//! Agents are identified by their agent key as sent along with network requests.
+//! The list is only consulted for entry types that are not shared publicly.
+use crate::{cas::content::Address, eav::EntityAttributeValue};
+
+pub const ACL_READER_NAME: &str = "acl-reader";
+
+pub fn create_acl_reader_eav(entry_address: &Address, reader: &Address) -> EntityAttributeValue {
+    EntityAttributeValue::new(entry_address, &ACL_READER_NAME.to_string(), reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn acl_reader_eav_test() {
+        let entry_address = Address::from("entry");
+        let reader = Address::from("agent");
+
+        let eav = create_acl_reader_eav(&entry_address, &reader);
+        assert_eq!(entry_address, eav.entity());
+        assert_eq!(ACL_READER_NAME.to_string(), eav.attribute());
+        assert_eq!(reader, eav.value());
+    }
+}
@@ -5,6 +5,30 @@ pub enum DnaError {
     ZomeNotFound(String),
     CapabilityNotFound(String),
     ZomeFunctionNotFound(String),
+    TestVectorFailed(String),
+    DuplicateFunction(String),
+    DuplicateCapability(String),
+    IncompatibleOverride(String),
+    /// The DNA deserialized fine but is inconsistent, e.g. links to an undefined entry type.
+    InvalidStructure(String),
+    /// Properties that can't be merged into the DNA's properties, e.g. because they are no object.
+    InvalidProperties(String),
+    /// The name can't be the name of an app entry type (it's empty or has the system prefix).
+    InvalidAppEntryTypeName(String),
+    SerializationError(String),
+    /// The zome exists but carries no WASM code.
+    EmptyWasm(String),
+    /// The app entry type `name` is defined by more than one zome.
+    DuplicateEntryType {
+        name: String,
+        zomes: Vec<String>,
+    },
+    /// The zome's WASM was built for an ABI version the Ribosome does not speak.
+    AbiMismatch {
+        zome: String,
+        found: u32,
+        expected: u32,
+    },
 }
 
 impl Error for DnaError {
@@ -13,6 +37,17 @@ impl Error for DnaError {
             DnaError::ZomeNotFound(err_msg) => &err_msg,
             DnaError::CapabilityNotFound(err_msg) => &err_msg,
             DnaError::ZomeFunctionNotFound(err_msg) => &err_msg,
+            DnaError::TestVectorFailed(err_msg) => &err_msg,
+            DnaError::DuplicateFunction(err_msg) => &err_msg,
+            DnaError::DuplicateCapability(err_msg) => &err_msg,
+            DnaError::IncompatibleOverride(err_msg) => &err_msg,
+            DnaError::InvalidStructure(err_msg) => &err_msg,
+            DnaError::InvalidProperties(err_msg) => &err_msg,
+            DnaError::InvalidAppEntryTypeName(err_msg) => &err_msg,
+            DnaError::SerializationError(err_msg) => &err_msg,
+            DnaError::EmptyWasm(err_msg) => &err_msg,
+            DnaError::DuplicateEntryType { .. } => "Entry type defined in several zomes",
+            DnaError::AbiMismatch { .. } => "Zome built for an incompatible HDK ABI version",
         }
     }
 }
@@ -1,6 +1,6 @@
 use self::HolochainError::*;
 use crate::{
-    error::{DnaError, RibosomeErrorCode},
+    error::{DnaError, RibosomeErrorCode, StorageError},
     json::*,
 };
 use futures::channel::oneshot::Canceled as FutureCanceled;
@@ -96,10 +96,17 @@ pub enum HolochainError {
     InvalidOperationOnSysEntry,
     DoesNotHaveCapabilityToken,
     ValidationFailed(String),
+    ValidationPending(String),
     Ribosome(RibosomeErrorCode),
     RibosomeFailed(String),
     ConfigError(String),
     Timeout,
+    Storage(StorageError),
+    RateLimited(String),
+    ReadOnlyViolation(String),
+    ChainFull,
+    AddressMismatch(String),
+    ValidationPackageTooLarge(String),
 }
 
 pub type HcResult<T> = Result<T, HolochainError>;
@@ -129,10 +136,17 @@ impl Error for HolochainError {
             InvalidOperationOnSysEntry => "operation cannot be done on a system entry type",
             DoesNotHaveCapabilityToken => "Caller does not have Capability to make that call",
             ValidationFailed(fail_msg) => &fail_msg,
+            ValidationPending(fail_msg) => &fail_msg,
             Ribosome(err_code) => err_code.as_str(),
             RibosomeFailed(fail_msg) => &fail_msg,
             ConfigError(err_msg) => &err_msg,
             Timeout => "timeout",
+            Storage(storage_err) => storage_err.description(),
+            RateLimited(err_msg) => &err_msg,
+            ReadOnlyViolation(err_msg) => &err_msg,
+            ChainFull => "source chain has reached its maximum length",
+            AddressMismatch(err_msg) => &err_msg,
+            ValidationPackageTooLarge(err_msg) => &err_msg,
         }
     }
 }
@@ -170,12 +184,24 @@ impl<T> From<::std::sync::PoisonError<T>> for HolochainError {
     }
 }
 
+impl From<DnaError> for HolochainError {
+    fn from(error: DnaError) -> Self {
+        HolochainError::Dna(error)
+    }
+}
+
 impl From<IoError> for HolochainError {
     fn from(error: IoError) -> Self {
         HolochainError::IoError(reason_for_io_error(&error))
     }
 }
 
+impl From<StorageError> for HolochainError {
+    fn from(error: StorageError) -> Self {
+        HolochainError::Storage(error)
+    }
+}
+
 impl From<SerdeError> for HolochainError {
     fn from(error: SerdeError) -> Self {
         HolochainError::SerializationError(error.to_string())
@@ -317,7 +343,29 @@ mod tests {
                 HolochainError::DoesNotHaveCapabilityToken,
                 "Caller does not have Capability to make that call",
             ),
+            (
+                HolochainError::ValidationPending(String::from("foo")),
+                "foo",
+            ),
             (HolochainError::Timeout, "timeout"),
+            (
+                HolochainError::Storage(StorageError::DiskFull(String::from("foo"))),
+                "foo",
+            ),
+            (HolochainError::RateLimited(String::from("foo")), "foo"),
+            (
+                HolochainError::ReadOnlyViolation(String::from("foo")),
+                "foo",
+            ),
+            (
+                HolochainError::ChainFull,
+                "source chain has reached its maximum length",
+            ),
+            (HolochainError::AddressMismatch(String::from("foo")), "foo"),
+            (
+                HolochainError::ValidationPackageTooLarge(String::from("foo")),
+                "foo",
+            ),
         ] {
             assert_eq!(output, input.description());
         }
@@ -1,5 +1,6 @@
 mod dna_error;
 pub mod error;
 mod ribosome_error;
+mod storage_error;
 
-pub use self::{dna_error::*, error::*, ribosome_error::*};
+pub use self::{dna_error::*, error::*, ribosome_error::*, storage_error::*};
@@ -0,0 +1,74 @@
+use libc::ENOSPC;
+use std::{
+    error::Error,
+    fmt,
+    io::{self, Error as IoError},
+};
+
+/// Failures of a CAS or EAV storage backend.
+/// Keeps the kind of the underlying failure so conductors can react to it,
+/// e.g. alert when the disk is full.
+#[derive(Clone, Debug, PartialEq, Hash, Eq, Serialize, Deserialize)]
+pub enum StorageError {
+    NotFound(String),
+    PermissionDenied(String),
+    DiskFull(String),
+    Io(String),
+}
+
+impl Error for StorageError {
+    fn description(&self) -> &str {
+        match self {
+            StorageError::NotFound(err_msg) => &err_msg,
+            StorageError::PermissionDenied(err_msg) => &err_msg,
+            StorageError::DiskFull(err_msg) => &err_msg,
+            StorageError::Io(err_msg) => &err_msg,
+        }
+    }
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl From<IoError> for StorageError {
+    fn from(error: IoError) -> Self {
+        let message = error.to_string();
+        // io::ErrorKind has no variant for a full disk, so check the OS error code
+        if error.raw_os_error() == Some(ENOSPC) {
+            return StorageError::DiskFull(message);
+        }
+        match error.kind() {
+            io::ErrorKind::NotFound => StorageError::NotFound(message),
+            io::ErrorKind::PermissionDenied => StorageError::PermissionDenied(message),
+            _ => StorageError::Io(message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_io_error_test() {
+        assert_eq!(
+            StorageError::NotFound("missing".to_string()),
+            StorageError::from(IoError::new(io::ErrorKind::NotFound, "missing")),
+        );
+        assert_eq!(
+            StorageError::PermissionDenied("denied".to_string()),
+            StorageError::from(IoError::new(io::ErrorKind::PermissionDenied, "denied")),
+        );
+        assert_eq!(
+            StorageError::Io("broken".to_string()),
+            StorageError::from(IoError::new(io::ErrorKind::BrokenPipe, "broken")),
+        );
+        match StorageError::from(IoError::from_raw_os_error(ENOSPC)) {
+            StorageError::DiskFull(_) => (),
+            other => panic!("expected DiskFull, got {:?}", other),
+        }
+    }
+}
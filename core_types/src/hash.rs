@@ -3,9 +3,51 @@ use crate::{
     json::{default_try_from_json, JsonString},
 };
 use multihash::{encode, Hash};
-use rust_base58::ToBase58;
+use rust_base58::{FromBase58, ToBase58};
 use std::{convert::TryFrom, fmt};
 
+/// Multihash code for SHA2-256 digests truncated to 160 bits, as varint bytes.
+/// 0x300001 is taken from the multicodec range reserved for private use, so truncated
+/// digests are self-describing and can never be mistaken for full length SHA2-256 ones.
+const SHA2_256_TRUNCATED_160_CODE: [u8; 4] = [0x81, 0x80, 0xc0, 0x01];
+const TRUNCATED_160_DIGEST_BYTES: usize = 20;
+
+/// Digest length used when hashing content into an address.
+/// Truncated digests make for shorter addresses at the cost of a higher collision risk.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DigestLength {
+    /// Full 256 bit SHA2-256 digest.
+    Full,
+    /// SHA2-256 digest truncated to 160 bits.
+    Truncated160,
+}
+
+impl Default for DigestLength {
+    fn default() -> Self {
+        DigestLength::Full
+    }
+}
+
+/// SHA2-256 multihash of the given bytes with the given digest length.
+pub fn sha2_256_multihash(
+    bytes: &[u8],
+    digest_length: DigestLength,
+) -> Result<Vec<u8>, HolochainError> {
+    let full = encode(Hash::SHA2256, bytes)
+        .map_err(|error| HolochainError::ErrorGeneric(error.to_string()))?;
+    Ok(match digest_length {
+        DigestLength::Full => full,
+        DigestLength::Truncated160 => {
+            // skip the code and length bytes of the full multihash
+            let digest = &full[2..2 + TRUNCATED_160_DIGEST_BYTES];
+            let mut truncated = SHA2_256_TRUNCATED_160_CODE.to_vec();
+            truncated.push(TRUNCATED_160_DIGEST_BYTES as u8);
+            truncated.extend_from_slice(digest);
+            truncated
+        }
+    })
+}
+
 // HashString newtype for String
 #[derive(
     PartialOrd, PartialEq, Eq, Ord, Clone, Debug, Serialize, Deserialize, DefaultJson, Default, Hash,
@@ -58,6 +100,44 @@ impl HashString {
         HashString::encode_from_bytes(s.as_bytes(), hash_type)
     }
 
+    /// convert bytes to a b58 SHA2-256 hashed string with the given digest length
+    pub fn encode_sha2_256_from_bytes(
+        bytes: &[u8],
+        digest_length: DigestLength,
+    ) -> Result<HashString, HolochainError> {
+        Ok(HashString::from(
+            sha2_256_multihash(bytes, digest_length)?.to_base58(),
+        ))
+    }
+
+    /// Decodes a b58 SHA2-256 hashed string back into its digest length and raw digest.
+    pub fn decode_sha2_256(&self) -> Result<(DigestLength, Vec<u8>), HolochainError> {
+        let bytes = self
+            .0
+            .from_base58()
+            .map_err(|error| HolochainError::SerializationError(format!("{:?}", error)))?;
+        let code_length = SHA2_256_TRUNCATED_160_CODE.len();
+        let (digest_length, digest) = if bytes.starts_with(&SHA2_256_TRUNCATED_160_CODE) {
+            (DigestLength::Truncated160, &bytes[code_length..])
+        } else if bytes.first() == Some(&Hash::SHA2256.code()) {
+            (DigestLength::Full, &bytes[1..])
+        } else {
+            return Err(HolochainError::SerializationError(format!(
+                "{} is not a SHA2-256 multihash",
+                self
+            )));
+        };
+        match digest.split_first() {
+            Some((length, digest)) if *length as usize == digest.len() => {
+                Ok((digest_length, digest.to_vec()))
+            }
+            _ => Err(HolochainError::SerializationError(format!(
+                "{} has an invalid digest length",
+                self
+            ))),
+        }
+    }
+
     /// magic all in one fn, take a JsonString + hash type and get a hashed b58 string back
     pub fn encode_from_json_string(json_string: JsonString, hash_type: Hash) -> HashString {
         HashString::encode_from_str(&String::from(json_string), hash_type)
@@ -125,6 +205,33 @@ pub mod tests {
         );
     }
 
+    #[test]
+    /// show truncated and full length addresses are distinguishable and both round trip
+    fn truncated_digest_test() {
+        let full =
+            HashString::encode_sha2_256_from_bytes(b"test data", DigestLength::Full).unwrap();
+        let truncated =
+            HashString::encode_sha2_256_from_bytes(b"test data", DigestLength::Truncated160)
+                .unwrap();
+
+        // default stays the same as the regular full length address
+        assert_eq!(
+            HashString::encode_from_bytes(b"test data", Hash::SHA2256),
+            full
+        );
+        assert_ne!(full, truncated);
+
+        let (full_length, full_digest) = full.decode_sha2_256().unwrap();
+        let (truncated_length, truncated_digest) = truncated.decode_sha2_256().unwrap();
+        assert_eq!(DigestLength::Full, full_length);
+        assert_eq!(DigestLength::Truncated160, truncated_length);
+        assert_eq!(32, full_digest.len());
+        assert_eq!(20, truncated_digest.len());
+        assert_eq!(full_digest[..20].to_vec(), truncated_digest);
+
+        assert!(HashString::from("not a hash").decode_sha2_256().is_err());
+    }
+
     #[test]
     /// known hash for a serializable something
     fn can_serialize_to_b58_hash() {
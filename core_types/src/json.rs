@@ -195,6 +195,36 @@ pub fn default_try_from_json<D: DeserializeOwned>(
     }
 }
 
+/// Serializes the value to JSON with the keys of all objects sorted and without whitespace.
+/// Equal values always give the same string no matter in which order their keys were
+/// inserted or declared.
+/// Entry addresses and DNA hashes are not computed from it: they hash the JSON as it gets
+/// stored, and changing that would change the addresses of existing chains and DNAs.
+pub fn canonical_json<T: Serialize>(value: &T) -> Result<String, HolochainError> {
+    fn sorted(value: serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(map) => {
+                let mut entries: Vec<(String, serde_json::Value)> = map.into_iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                serde_json::Value::Object(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| (key, sorted(value)))
+                        .collect(),
+                )
+            }
+            serde_json::Value::Array(values) => {
+                serde_json::Value::Array(values.into_iter().map(sorted).collect())
+            }
+            other => other,
+        }
+    }
+    let value = serde_json::to_value(value)
+        .map_err(|e| HolochainError::SerializationError(e.to_string()))?;
+    serde_json::to_string(&sorted(value))
+        .map_err(|e| HolochainError::SerializationError(e.to_string()))
+}
+
 pub trait DefaultJson:
     Serialize + DeserializeOwned + TryFrom<JsonString> + Into<JsonString>
 {
@@ -267,7 +297,7 @@ impl TryFrom<JsonString> for RawString {
 pub mod tests {
     use crate::{
         error::HolochainError,
-        json::{JsonString, RawString},
+        json::{canonical_json, JsonString, RawString},
     };
     use serde_json;
     use std::convert::TryFrom;
@@ -388,4 +418,24 @@ pub mod tests {
             String::from(JsonString::from(RawString::from(1))),
         );
     }
+
+    #[test]
+    fn canonical_json_sorts_keys() {
+        let a = json!({"b": [{"d": 1, "c": 2}], "a": {"f": null, "e": "x"}});
+        let b = json!({"a": {"e": "x", "f": null}, "b": [{"c": 2, "d": 1}]});
+        assert_ne!(a.to_string(), b.to_string());
+        assert_eq!(canonical_json(&a).unwrap(), canonical_json(&b).unwrap());
+        assert_eq!(
+            String::from(r#"{"a":{"e":"x","f":null},"b":[{"c":2,"d":1}]}"#),
+            canonical_json(&a).unwrap(),
+        );
+
+        assert_eq!(
+            String::from(r#"{"foo":"bar"}"#),
+            canonical_json(&DeriveTest {
+                foo: String::from("bar")
+            })
+            .unwrap(),
+        );
+    }
 }
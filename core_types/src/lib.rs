@@ -14,6 +14,7 @@
 extern crate arrayref;
 extern crate base64;
 extern crate futures;
+extern crate libc;
 extern crate multihash;
 extern crate reed_solomon;
 extern crate rust_base58;
@@ -30,11 +31,13 @@ extern crate holochain_core_types_derive;
 
 extern crate uuid;
 
+pub mod capability_grant;
 pub mod cas;
 pub mod chain_header;
 pub mod crud_status;
 pub mod eav;
 pub mod entry;
+pub mod entry_acl;
 pub mod error;
 #[macro_use]
 extern crate objekt;
@@ -7,6 +7,18 @@ impl From<&'static str> for Iso8601 {
     }
 }
 
+impl From<String> for Iso8601 {
+    fn from(s: String) -> Iso8601 {
+        Iso8601(s)
+    }
+}
+
+impl From<Iso8601> for String {
+    fn from(iso8601: Iso8601) -> String {
+        iso8601.0
+    }
+}
+
 pub fn test_iso_8601() -> Iso8601 {
     Iso8601::from("2018-10-11T03:23:38+00:00")
 }
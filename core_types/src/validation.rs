@@ -1,6 +1,10 @@
 extern crate serde_json;
 use crate::{
-    chain_header::ChainHeader, entry::Entry, error::HolochainError, hash::HashString,
+    cas::content::{Address, AddressableContent},
+    chain_header::ChainHeader,
+    entry::{entry_type::EntryType, Entry},
+    error::HolochainError,
+    hash::HashString,
     json::JsonString,
 };
 
@@ -10,6 +14,10 @@ pub struct ValidationPackage {
     pub source_chain_entries: Option<Vec<Entry>>,
     pub source_chain_headers: Option<Vec<ChainHeader>>,
     pub custom: Option<String>,
+    /// Addresses of the first and last chain header covered by the package,
+    /// if it only holds a window of the source chain.
+    #[serde(default)]
+    pub chain_range: Option<(Address, Address)>,
 }
 
 impl ValidationPackage {
@@ -19,8 +27,67 @@ impl ValidationPackage {
             source_chain_entries: None,
             source_chain_headers: None,
             custom: None,
+            chain_range: None,
         }
     }
+
+    /// Compares this package with another one, e.g. to find out why two peers disagree
+    /// about the validity of an entry.
+    pub fn diff(&self, other: &ValidationPackage) -> ValidationPackageDiff {
+        let header_addresses = |package: &ValidationPackage| -> Vec<Address> {
+            package
+                .source_chain_headers
+                .iter()
+                .flatten()
+                .map(|header| header.address())
+                .collect()
+        };
+        let entry_addresses = |package: &ValidationPackage| -> Vec<Address> {
+            package
+                .source_chain_entries
+                .iter()
+                .flatten()
+                .map(|entry| entry.address())
+                .collect()
+        };
+        let missing_in = |addresses: &Vec<Address>, others: &Vec<Address>| -> Vec<Address> {
+            addresses
+                .iter()
+                .filter(|address| !others.contains(address))
+                .cloned()
+                .collect()
+        };
+
+        let (headers, other_headers) = (header_addresses(self), header_addresses(other));
+        let (entries, other_entries) = (entry_addresses(self), entry_addresses(other));
+        ValidationPackageDiff {
+            chain_header_differs: self.chain_header.as_ref().map(|header| header.address())
+                != other.chain_header.as_ref().map(|header| header.address()),
+            headers_only_in_self: missing_in(&headers, &other_headers),
+            headers_only_in_other: missing_in(&other_headers, &headers),
+            entries_only_in_self: missing_in(&entries, &other_entries),
+            entries_only_in_other: missing_in(&other_entries, &entries),
+            custom_differs: self.custom != other.custom,
+        }
+    }
+}
+
+/// Result of ValidationPackage::diff, listing the addresses of source chain headers and
+/// entries that are only present in one of the two compared packages.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, DefaultJson, Default)]
+pub struct ValidationPackageDiff {
+    pub chain_header_differs: bool,
+    pub headers_only_in_self: Vec<Address>,
+    pub headers_only_in_other: Vec<Address>,
+    pub entries_only_in_self: Vec<Address>,
+    pub entries_only_in_other: Vec<Address>,
+    pub custom_differs: bool,
+}
+
+impl ValidationPackageDiff {
+    pub fn is_empty(&self) -> bool {
+        self == &ValidationPackageDiff::default()
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, DefaultJson)]
@@ -30,6 +97,12 @@ pub enum ValidationPackageDefinition {
     ChainHeaders,   //sending all source chain headers
     ChainFull,      //sending the whole chain, entries and headers
     Custom(String), //sending something custom
+    /// sending only the source chain entries of the given types,
+    /// and their headers if include_headers is set
+    ChainSubset {
+        entry_types: Vec<EntryType>,
+        include_headers: bool,
+    },
 }
 
 /// This structs carries information contextual for the process
@@ -70,7 +143,8 @@ impl Default for EntryLifecycle {
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub enum EntryAction {
     Create,
-    Modify,
+    /// Replaces the entry at the given address.
+    Modify(Address),
     Delete,
 }
 
@@ -85,3 +159,49 @@ pub enum LinkAction {
     Create,
     Delete,
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        chain_header::{test_chain_header, test_sources},
+        entry::{test_entry, test_entry_b},
+        signature::test_signatures,
+        time::test_iso_8601,
+    };
+
+    #[test]
+    fn diff_finds_missing_header() {
+        let header_a = test_chain_header();
+        let entry_b = test_entry_b();
+        let header_b = ChainHeader::new(
+            &entry_b.entry_type(),
+            &entry_b.address(),
+            &test_sources(),
+            &test_signatures(),
+            &Some(header_a.address()),
+            &None,
+            &None,
+            &test_iso_8601(),
+        );
+
+        let mut package = ValidationPackage::only_header(header_b.clone());
+        package.source_chain_headers = Some(vec![header_b.clone(), header_a.clone()]);
+        package.source_chain_entries = Some(vec![test_entry()]);
+        let mut other = package.clone();
+        other.source_chain_headers = Some(vec![header_a.clone()]);
+
+        assert!(package.diff(&package).is_empty());
+
+        let diff = package.diff(&other);
+        assert!(!diff.is_empty());
+        assert_eq!(vec![header_b.address()], diff.headers_only_in_self);
+        assert!(diff.headers_only_in_other.is_empty());
+        assert!(diff.entries_only_in_self.is_empty());
+        assert!(!diff.chain_header_differs);
+
+        let diff = other.diff(&package);
+        assert_eq!(vec![header_b.address()], diff.headers_only_in_other);
+        assert!(diff.headers_only_in_self.is_empty());
+    }
+}
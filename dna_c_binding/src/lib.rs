@@ -95,7 +95,7 @@ pub extern "C" fn holochain_dna_string_free(s: *mut c_char) {
 /// This macro takes care boilerplate for getting string accessors over ffi.
 /// This is not exported, it is only meant to be used internally.
 macro_rules! _xa_str {
-    ($struct:ident, $prop:ident, $getname:ident, $setname:ident) => {
+    ($struct:ident, $prop:ident, $setter:ident, $getname:ident, $setname:ident) => {
         #[no_mangle]
         pub extern "C" fn $getname(ptr: *const $struct) -> *mut c_char {
             match catch_unwind(|| {
@@ -106,7 +106,7 @@ macro_rules! _xa_str {
                     &*ptr
                 };
 
-                let res = arg.$prop.clone();
+                let res = arg.$prop().to_string();
 
                 let res = match CString::new(res) {
                     Ok(s) => s,
@@ -130,18 +130,25 @@ macro_rules! _xa_str {
                     &mut *ptr
                 };
                 let val = unsafe { CStr::from_ptr(val).to_string_lossy().into_owned() };
-                arg.$prop = val;
+                arg.$setter(val);
             })
             .unwrap_or(());
         }
     };
 }
 
-_xa_str!(Dna, name, holochain_dna_get_name, holochain_dna_set_name);
+_xa_str!(
+    Dna,
+    name,
+    set_name,
+    holochain_dna_get_name,
+    holochain_dna_set_name
+);
 
 _xa_str!(
     Dna,
     description,
+    set_description,
     holochain_dna_get_description,
     holochain_dna_set_description
 );
@@ -149,15 +156,23 @@ _xa_str!(
 _xa_str!(
     Dna,
     version,
+    set_version,
     holochain_dna_get_version,
     holochain_dna_set_version
 );
 
-_xa_str!(Dna, uuid, holochain_dna_get_uuid, holochain_dna_set_uuid);
+_xa_str!(
+    Dna,
+    uuid,
+    set_uuid,
+    holochain_dna_get_uuid,
+    holochain_dna_set_uuid
+);
 
 _xa_str!(
     Dna,
     dna_spec_version,
+    set_dna_spec_version,
     holochain_dna_get_dna_spec_version,
     holochain_dna_set_dna_spec_version
 );
@@ -184,7 +199,7 @@ unsafe fn vec_char_to_cstringvec(vec: Option<Vec<*const c_char>>, string_vec: *m
 
 fn zome_names_as_vec(dna: &Dna) -> Option<Vec<*const c_char>> {
     Some(
-        dna.zomes
+        dna.zomes()
             .keys()
             .map(|zome_name| {
                 let raw = match CString::new(zome_name.to_string()) {
@@ -224,7 +239,7 @@ pub unsafe extern "C" fn holochain_dna_free_zome_names(string_vec: *mut CStringV
 #[cfg_attr(tarpaulin, skip)] //Tested in c_bindings_test by C based test code
 fn capabilities_as_vec(dna: &Dna, zome_name: &str) -> Option<Vec<*const c_char>> {
     let result = dna
-        .zomes
+        .zomes()
         .get(zome_name)?
         .capabilities
         .keys()
@@ -252,6 +267,30 @@ pub unsafe extern "C" fn holochain_dna_get_capabilities_names(
     vec_char_to_cstringvec(capabalities, string_vec);
 }
 
+#[cfg_attr(tarpaulin, skip)] //Tested in c_bindings_test by C based test code
+#[no_mangle]
+pub unsafe extern "C" fn holochain_dna_get_capability_description(
+    ptr: *mut Dna,
+    zome_name: *const c_char,
+    capability_name: *const c_char,
+) -> *mut c_char {
+    let dna = &*ptr;
+
+    let zome_name = CStr::from_ptr(zome_name).to_string_lossy();
+    let capability_name = CStr::from_ptr(capability_name).to_string_lossy();
+
+    let description = dna
+        .zomes()
+        .get(&*zome_name)
+        .and_then(|zome| zome.capabilities.get(&*capability_name))
+        .map(|capability| capability.description.clone());
+
+    match description.map(CString::new) {
+        Some(Ok(s)) => s.into_raw(),
+        _ => std::ptr::null_mut(),
+    }
+}
+
 #[cfg_attr(tarpaulin, skip)] //Tested in c_bindings_test by C based test code
 fn fn_names_as_vec(
     dna: &Dna,
@@ -259,7 +298,7 @@ fn fn_names_as_vec(
     capability_name: &str,
 ) -> Option<Vec<*const c_char>> {
     let result = dna
-        .zomes
+        .zomes()
         .get(zome_name)?
         .capabilities
         .get(capability_name)?
@@ -301,7 +340,7 @@ fn fn_parameters_as_vec(
     function_name: &str,
 ) -> Option<Vec<*const c_char>> {
     let result = dna
-        .zomes
+        .zomes()
         .get(zome_name)?
         .capabilities
         .get(capability_name)?
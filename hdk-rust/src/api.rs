@@ -5,7 +5,7 @@ use crate::{
 use holochain_core_types::{
     cas::content::Address,
     crud_status::CrudStatus,
-    entry::Entry,
+    entry::{versioning::EntryMigrations, Entry},
     error::{CoreError, HolochainError, RibosomeReturnCode, ZomeApiInternalResult},
 };
 pub use holochain_wasm_utils::api_serialization::validation::*;
@@ -24,7 +24,10 @@ use holochain_wasm_utils::{
     memory_serialization::*,
 };
 use serde_json;
-use std::{convert::TryInto, os::raw::c_char};
+use std::{
+    convert::{TryFrom, TryInto},
+    os::raw::c_char,
+};
 
 //--------------------------------------------------------------------------------------------------
 // ZOME API GLOBAL VARIABLES
@@ -501,6 +504,19 @@ pub fn get_entry(address: Address) -> ZomeApiResult<Option<Entry>> {
     Ok(Some(entry.clone()))
 }
 
+/// Same as get_entry but reads the app entry into its current struct, running the registered
+/// migrations on content that was committed with an older version of the entry type.
+/// See [EntryMigrations](../holochain_core_types/entry/versioning/struct.EntryMigrations.html).
+pub fn get_typed_entry<T: TryFrom<JsonString>>(
+    address: Address,
+    migrations: &EntryMigrations,
+) -> ZomeApiResult<Option<T>> {
+    match get_entry(address)? {
+        Some(entry) => Ok(Some(migrations.typed_entry(&entry)?)),
+        None => Ok(None),
+    }
+}
+
 /// Returns the Entry at the exact address specified, whatever its crud-status.
 /// Returns None if no entry exists at the specified address.
 pub fn get_entry_initial(address: Address) -> ZomeApiResult<Option<Entry>> {
@@ -788,13 +804,17 @@ pub fn remove_entry(address: Address) -> ZomeApiResult<()> {
     unsafe {
         encoded_allocation_of_result = hc_remove_entry(allocation_of_input.encode() as u32);
     }
-    let res = check_for_ribosome_error(encoded_allocation_of_result);
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result as u32)?;
     // Free result & input allocations
     mem_stack
         .deallocate(allocation_of_input)
         .expect("deallocate failed");
     // Done
-    res
+    if result.ok {
+        Ok(())
+    } else {
+        Err(ZomeApiError::from(result.error))
+    }
 }
 
 /// Consumes two values, the first of which is the address of an entry, `base`, and the second of which is a string, `tag`,
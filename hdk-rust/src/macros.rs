@@ -199,7 +199,9 @@ macro_rules! define_zome {
                                         $(
                                             FnParameter::new(stringify!($output_param_name), stringify!($output_param_type))
                                         ),*
-                                    ]
+                                    ],
+                                    read_only: false,
+                                    commits_entry_types: Vec::new(),
                                 }
 
                             ),+
@@ -53,6 +53,11 @@ extern "C" {
     fn __list_capabilities() -> ZomeCapabilities;
 }
 
+/// Marks the zome as built for HDK ABI version 1, see holochain_wasm_utils::abi.
+/// Has to be renamed whenever HDK_ABI_VERSION changes.
+#[no_mangle]
+pub extern "C" fn __hdk_abi_version_1() {}
+
 #[no_mangle]
 pub extern "C" fn __hdk_get_validation_package_for_entry_type(
     encoded_allocation_of_input: u32,
@@ -241,6 +246,7 @@ pub mod tests {
         error::HolochainError,
         json::JsonString,
     };
+    use holochain_wasm_utils::abi::{abi_version_from_export, HDK_ABI_VERSION};
     use meta::PartialZome;
     use std::collections::BTreeMap;
 
@@ -252,6 +258,14 @@ pub mod tests {
         BTreeMap::new()
     }
 
+    #[test]
+    fn abi_version_marker_matches_ribosome() {
+        assert_eq!(
+            Some(HDK_ABI_VERSION),
+            abi_version_from_export(stringify!(__hdk_abi_version_1))
+        );
+    }
+
     #[test]
     fn partial_zome_json() {
         #[derive(Serialize, Deserialize, Debug, DefaultJson)]
@@ -145,7 +145,7 @@ fn start_holochain_instance<T: Into<String>>(uuid: T) -> (Holochain, Arc<Mutex<T
         "remove_modified_entry_ok",
     ]);
     let mut dna = create_test_dna_with_cap("test_zome", "test_cap", &capabability, &wasm);
-    dna.uuid = uuid.into();
+    dna.set_uuid(uuid.into());
 
     // TODO: construct test DNA using the auto-generated JSON feature
     // The code below is fragile!
@@ -154,7 +154,7 @@ fn start_holochain_instance<T: Into<String>>(uuid: T) -> (Holochain, Arc<Mutex<T
     // In a production setting, hc would read the auto-generated JSON to make sure the Dna struct
     // matches up. We should do the same in test.
     {
-        let entry_types = &mut dna.zomes.get_mut("test_zome").unwrap().entry_types;
+        let entry_types = &mut dna.zomes_mut().get_mut("test_zome").unwrap().entry_types;
         entry_types.insert(
             EntryType::from("validation_package_tester"),
             EntryTypeDef::new(),
@@ -170,7 +170,7 @@ fn start_holochain_instance<T: Into<String>>(uuid: T) -> (Holochain, Arc<Mutex<T
     }
 
     {
-        let entry_types = &mut dna.zomes.get_mut("test_zome").unwrap().entry_types;
+        let entry_types = &mut dna.zomes_mut().get_mut("test_zome").unwrap().entry_types;
         let mut link_validator = EntryTypeDef::new();
         link_validator.links_to.push(LinksTo {
             target_type: String::from("link_validator"),
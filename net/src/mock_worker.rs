@@ -151,9 +151,21 @@ impl MockSingleton {
     }
 
     /// when someone makes a dht data request,
-    /// this mock module routes it to the first node connected on that dna.
+    /// this mock module routes it to the requested node if there is one,
+    /// or else to the first node connected on that dna.
     /// this works because we also send store requests to all connected nodes.
     fn priv_handle_get_dht(&mut self, msg: &GetDhtData) -> NetResult<()> {
+        if !msg.to_agent_id.is_empty()
+            && self
+                .senders
+                .contains_key(&cat_dna_agent(&msg.dna_hash, &msg.to_agent_id))
+        {
+            return self.priv_send_one(
+                &msg.dna_hash,
+                &msg.to_agent_id,
+                ProtocolWrapper::GetDht(msg.clone()).into(),
+            );
+        }
         match self.senders_by_dna.entry(msg.dna_hash.to_string()) {
             Entry::Occupied(mut e) => {
                 if !e.get().is_empty() {
@@ -421,6 +433,8 @@ mod tests {
                 dna_hash: DNA_HASH.to_string(),
                 from_agent_id: AGENT_ID_2.to_string(),
                 address: "hello".to_string(),
+                signature: String::new(),
+                to_agent_id: String::new(),
             })
             .into(),
         )
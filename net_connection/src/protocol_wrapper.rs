@@ -110,6 +110,18 @@ pub struct GetDhtData {
     pub from_agent_id: String,
 
     pub address: String,
+
+    /// Signature of the requesting agent, empty for unsigned requests
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub signature: String,
+
+    /// Agent that should answer the request, empty to let the network pick one
+    #[serde(
+        rename = "toAgentId",
+        default,
+        skip_serializing_if = "String::is_empty"
+    )]
+    pub to_agent_id: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, DefaultJson)]
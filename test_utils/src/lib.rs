@@ -104,9 +104,9 @@ pub fn create_test_dna_with_wasm(zome_name: &str, cap_name: &str, wasm: Vec<u8>)
     );
 
     // zome.capabilities.push(capability);
-    dna.zomes.insert(zome_name.to_string(), zome);
-    dna.name = "TestApp".into();
-    dna.uuid = "8ed84a02-a0e6-4c8c-a752-34828e302986".into();
+    dna.zomes_mut().insert(zome_name.to_string(), zome);
+    dna.set_name("TestApp".into());
+    dna.set_uuid("8ed84a02-a0e6-4c8c-a752-34828e302986".into());
     dna
 }
 
@@ -149,9 +149,9 @@ pub fn create_test_dna_with_cap(
         },
     );
 
-    dna.zomes.insert(zome_name.to_string(), zome);
-    dna.name = "TestApp".into();
-    dna.uuid = "8ed84a02-a0e6-4c8c-a752-34828e302986".into();
+    dna.zomes_mut().insert(zome_name.to_string(), zome);
+    dna.set_name("TestApp".into());
+    dna.set_uuid("8ed84a02-a0e6-4c8c-a752-34828e302986".into());
     dna
 }
 
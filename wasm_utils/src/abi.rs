@@ -0,0 +1,31 @@
+//! Versioning of the interface between the Ribosome and zome WASM built with an HDK,
+//! i.e. the callbacks a zome exports and the Zome API functions it imports.
+
+/// The ABI version the Ribosome speaks.
+pub const HDK_ABI_VERSION: u32 = 1;
+
+/// Prefix of the export marking which ABI version a zome was built for,
+/// followed by the version number, e.g. `__hdk_abi_version_1`.
+pub const HDK_ABI_VERSION_MARKER: &str = "__hdk_abi_version_";
+
+/// The ABI version declared by the given export name, if it is a version marker.
+pub fn abi_version_from_export(export_name: &str) -> Option<u32> {
+    if export_name.starts_with(HDK_ABI_VERSION_MARKER) {
+        export_name[HDK_ABI_VERSION_MARKER.len()..].parse().ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn abi_version_from_export_test() {
+        assert_eq!(Some(1), abi_version_from_export("__hdk_abi_version_1"));
+        assert_eq!(Some(42), abi_version_from_export("__hdk_abi_version_42"));
+        assert_eq!(None, abi_version_from_export("__hdk_abi_version_"));
+        assert_eq!(None, abi_version_from_export("__hdk_validate_app_entry"));
+    }
+}
@@ -33,3 +33,36 @@ pub struct LinkValidationArgs {
     pub direction: LinkDirection,
     pub validation_data: ValidationData,
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn link_direction_round_trip() {
+        for direction in vec![LinkDirection::To, LinkDirection::From] {
+            let args = LinkValidationPackageArgs {
+                entry_type: String::from("post"),
+                tag: String::from("comments"),
+                direction: direction.clone(),
+            };
+            let json = JsonString::from(args);
+            let args = LinkValidationPackageArgs::try_from(json).unwrap();
+            assert_eq!(direction, args.direction);
+        }
+    }
+
+    #[test]
+    fn link_direction_rejects_unknown_direction() {
+        assert_eq!(
+            JsonString::from("\"To\""),
+            JsonString::from(LinkDirection::To)
+        );
+        assert!(LinkDirection::try_from(JsonString::from("\"Sideways\"")).is_err());
+        assert!(LinkValidationPackageArgs::try_from(JsonString::from(
+            "{\"entry_type\":\"post\",\"tag\":\"comments\",\"direction\":\"sideways\"}"
+        ))
+        .is_err());
+    }
+}
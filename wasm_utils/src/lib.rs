@@ -14,6 +14,7 @@ pub extern crate holochain_core_types_derive;
 #[cfg_attr(tarpaulin, skip)]
 pub mod api_serialization;
 
+pub mod abi;
 pub mod macros;
 pub mod memory_allocation;
 pub mod memory_serialization;